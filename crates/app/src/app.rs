@@ -4,8 +4,10 @@ use crate::pages::*;
 use crate::components::LoadingScreen;
 use crate::theme::CSS;
 use crate::context::AppContext;
-use data::seed_all_data;
-use std::sync::Arc;
+use data::{seed_all_data, seed_essay_revisions};
+use domain::traits::QuestionRepository;
+use services::{AIConfigManager, ModelEvent, ModelStatus};
+use tokio::sync::broadcast::error::RecvError;
 
 #[derive(Routable, Clone, PartialEq)]
 #[rustfmt::skip]
@@ -22,12 +24,20 @@ pub enum Route {
     Questions {},
     #[route("/questao/:id")]
     QuestionDetail { id: String },
+    #[route("/busca")]
+    Search {},
     #[route("/redacoes")]
     Essays {},
     #[route("/redacao/:id")]
     EssayDetail { id: String },
+    #[route("/redacao/:id/replay")]
+    EssayReplay { id: String },
     #[route("/redacao/nova")]
     NewEssay {},
+    #[route("/rascunho/:draft_id")]
+    ResumeDraft { draft_id: String },
+    #[route("/revisao")]
+    ReviewQueue {},
     #[route("/perfil")]
     Profile {},
 }
@@ -35,88 +45,76 @@ pub enum Route {
 #[component]
 pub fn App() -> Element {
     // Initialize context and provide to component tree
-    let ctx = use_context_provider(|| AppContext::new());
+    let ctx = use_context_provider(AppContext::new);
     
-<<<<<<< Local
     // Clone context for different use_effects
     let ctx_for_ai = ctx.clone();
     let ctx_for_seeding = ctx.clone();
     
-    // Track AI model loading state
-    let mut is_loading = use_signal(|| true);
+    // The loading screen is shown only when the model loads on startup
+    let auto_load = use_hook(|| {
+        AIConfigManager::new()
+            .and_then(|manager| manager.load())
+            .map(|config| config.auto_load_on_startup)
+            .unwrap_or(false)
+    });
+    let mut is_loading = use_signal(|| auto_load);
     let mut loading_progress = use_signal(|| 0.0_f32);
     let mut loading_message = use_signal(|| "Initializing AI model...".to_string());
     
-    // Initialize AI model on startup
-=======
-    // Seed test data (async in background)
->>>>>>> Remote
+    // Load the model on startup, following its lifecycle events
     use_effect(move || {
+        if !auto_load {
+            return;
+        }
         let ctx_clone = ctx_for_ai.clone();
         spawn(async move {
-            // Check if model is already loaded
-            if ctx_clone.ai_service.is_initialized().await {
+            let ai_service = ctx_clone.ai_service.clone();
+            let mut events = ai_service.subscribe();
+            if ai_service.status() == ModelStatus::Ready {
                 loading_progress.set(1.0);
                 loading_message.set("Model already loaded!".to_string());
-                // Small delay to show success state
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
                 is_loading.set(false);
                 return;
             }
             
-            // Update loading states
-            loading_message.set("Downloading AI model...".to_string());
-            loading_progress.set(0.2);
-            
-            // Start initialization in background
-            let ai_service = ctx_clone.ai_service.clone();
-            let init_task = tokio::spawn(async move {
-                ai_service.initialize().await
-            });
-            
-            // Simulate progress while initializing
-            let mut progress: f32 = 0.2;
-            while !init_task.is_finished() {
-                tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-                progress = f32::min(progress + 0.05, 0.95);
-                loading_progress.set(progress);
-                
-                if progress < 0.4 {
-                    loading_message.set("Downloading model files...".to_string());
-                } else if progress < 0.7 {
-                    loading_message.set("Loading model weights...".to_string());
-                } else {
-                    loading_message.set("Initializing model...".to_string());
-                }
-            }
+            let loader = ai_service.clone();
+            tokio::spawn(async move { loader.initialize().await });
             
-            // Check result
-            match init_task.await {
-                Ok(Ok(_)) => {
-                    loading_progress.set(1.0);
-                    loading_message.set("Model loaded successfully!".to_string());
-                    // Small delay to show success state
-                    tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
-                    is_loading.set(false);
-                }
-                Ok(Err(e)) => {
-                    let error_msg = format!("{}", e);
-                    tracing::error!("Failed to initialize AI model: {}", error_msg);
-                    loading_message.set("Failed to load model. Continuing without AI...".to_string());
-                    loading_progress.set(1.0);
-                    // Continue anyway after showing error
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                    is_loading.set(false);
-                }
-                Err(e) => {
-                    let error_msg = format!("{}", e);
-                    tracing::error!("Task failed: {}", error_msg);
-                    loading_message.set("Failed to load model. Continuing without AI...".to_string());
-                    loading_progress.set(1.0);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                    is_loading.set(false);
+            loop {
+                let status = match events.recv().await {
+                    Ok(ModelEvent::Progress { fraction, message }) => {
+                        loading_progress.set(fraction);
+                        loading_message.set(message);
+                        continue;
+                    }
+                    Ok(ModelEvent::Status(status)) => status,
+                    // Missed events: carry on from the current status
+                    Err(RecvError::Lagged(_)) => ai_service.status(),
+                    Err(RecvError::Closed) => break,
+                };
+                match status {
+                    ModelStatus::Ready => {
+                        loading_progress.set(1.0);
+                        loading_message.set("Model loaded successfully!".to_string());
+                        // Small delay to show success state
+                        tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
+                        break;
+                    }
+                    ModelStatus::Error(error) => {
+                        tracing::error!("Failed to initialize AI model: {}", error);
+                        loading_message.set("Failed to load model. Continuing without AI...".to_string());
+                        loading_progress.set(1.0);
+                        // Continue anyway after showing error
+                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                        break;
+                    }
+                    // A cancelled download sets the status back to idle
+                    status if status.is_idle() => break,
+                    _ => {}
                 }
             }
+            is_loading.set(false);
         });
     });
     
@@ -124,20 +122,41 @@ pub fn App() -> Element {
     use_effect(move || {
         let ctx = ctx_for_seeding.clone();
         spawn(async move {
-            let _ = seed_all_data(
-                &*ctx.essay_repo,
-                &*ctx.question_repo,
-                &*ctx.user_repo,
-                &*ctx.trail_repo,
-                &*ctx.rubric_repo,
-            ).await;
+            if let Ok(user_id) = seed_all_data(
+                &ctx.essay_repo,
+                &ctx.question_repo,
+                &ctx.user_repo,
+                &ctx.trail_repo,
+                &ctx.rubric_repo,
+            ).await {
+                let _ = seed_essay_revisions(
+                    &ctx.revision_repo,
+                    &ctx.essay_repo,
+                    user_id,
+                ).await;
+            }
+            
+            // Index the question bank for semantic search whenever the model is ready
+            let mut events = ctx.ai_service.subscribe();
+            let mut ready = ctx.ai_service.status() == ModelStatus::Ready;
+            loop {
+                if ready {
+                    let questions = ctx.question_repo.search("").await.unwrap_or_default();
+                    if let Err(e) = ctx.question_search.index_questions(&questions).await {
+                        tracing::warn!("Failed to index questions: {}", e);
+                    }
+                }
+                ready = match events.recv().await {
+                    Ok(ModelEvent::Status(status)) => status == ModelStatus::Ready,
+                    Ok(_) | Err(RecvError::Lagged(_)) => false,
+                    Err(RecvError::Closed) => break,
+                };
+            }
         });
     });
     
-    // Note: AI model initialization has been moved to the Profile/Settings page.
-    // Users configure their HuggingFace token and download the model on-demand.
-    // The model loads lazily when first used for essay evaluation.
-    // This improves startup time and gives users control over AI features.
+    // Without auto-load the model is downloaded and loaded from the
+    // Profile/Settings page, or lazily when first used for essay evaluation.
     
     rsx! {
         style { {CSS} }
@@ -1,24 +1,43 @@
 use dioxus::prelude::*;
 use crate::context::AppContext;
 use crate::components::{NeonInput, NeonButton, NeonProgressBar, ButtonVariant, TokenGuide, ModelStatusIndicator};
-use services::{AIService, AIConfigManager, ModelStatus};
+use services::{AIService, AIConfigManager, FeedbackGenerator, ModelEvent, ModelStatus};
+use tokio::sync::broadcast::error::RecvError;
 
 #[component]
 pub fn AIConfigPanel() -> Element {
     let ctx = use_context::<AppContext>();
     
     // State management
-    let mut token_input = use_signal(|| String::new());
+    let mut token_input = use_signal(String::new);
     let mut token_configured = use_signal(|| false);
     let mut model_cached = use_signal(|| false);
     let mut model_status = use_signal(|| ModelStatus::NotConfigured);
     let mut loading_progress = use_signal(|| 0.0_f32);
-    let mut loading_message = use_signal(|| String::new());
+    let mut loading_message = use_signal(String::new);
     let mut error_message = use_signal(|| Option::<String>::None);
-    let mut cache_info = use_signal(|| String::new());
+    let mut cache_info = use_signal(String::new);
+    let mut feedback_model_input = use_signal(|| {
+        AIConfigManager::new()
+            .and_then(|m| m.load())
+            .ok()
+            .and_then(|c| c.feedback_model_path)
+            .unwrap_or_default()
+    });
+    let mut feedback_model_ready = use_signal(FeedbackGenerator::is_configured);
+    
+    // Clone context for the effect and each handler
+    let ctx_for_init = ctx.clone();
+    let ctx_for_save = ctx.clone();
+    let ctx_for_download = ctx.clone();
+    let ctx_for_unload = ctx.clone();
+    let ctx_for_reload = ctx.clone();
+    let ctx_for_clear = ctx.clone();
+    let ctx_for_feedback = ctx.clone();
     
     // Initialize state on mount
     use_effect(move || {
+        let ctx = ctx_for_init.clone();
         spawn(async move {
             // Check if token is configured
             let ai_service = ctx.ai_service.as_ref();
@@ -32,25 +51,46 @@ pub fn AIConfigPanel() -> Element {
             
             // Get cache info
             if let Ok(info) = ai_service.get_cache_info() {
-                cache_info.set(format!("{}: {}", 
+                let mut text = format!("{}: {}", 
                     ctx.t("profile-ai-info-cache-location"), 
                     info.location
-                ));
+                );
+                if let Some(embeddings) = info.embeddings {
+                    text.push_str(&format!(" · {} {} ({:.1} / {:.0} MB)",
+                        ctx.t("profile-ai-info-embedding-cache"),
+                        embeddings.entries,
+                        embeddings.size_bytes as f64 / 1_048_576.0,
+                        embeddings.max_bytes as f64 / 1_048_576.0
+                    ));
+                }
+                cache_info.set(text);
             }
             
-            // Set initial status
-            if is_configured && model_cached() {
-                model_status.set(ModelStatus::Ready);
-            } else if is_configured {
-                model_status.set(ModelStatus::TokenSaved);
-            } else {
-                model_status.set(ModelStatus::NotConfigured);
+            // Follow the model lifecycle, whoever starts the load
+            let mut events = ai_service.subscribe();
+            model_status.set(ai_service.status());
+            loop {
+                match events.recv().await {
+                    Ok(ModelEvent::Progress { fraction, message }) => {
+                        loading_progress.set(fraction);
+                        loading_message.set(message);
+                    }
+                    Ok(ModelEvent::Status(status)) => {
+                        if status == ModelStatus::Ready {
+                            model_cached.set(true);
+                        }
+                        model_status.set(status);
+                    }
+                    Err(RecvError::Lagged(_)) => model_status.set(ctx.ai_service.status()),
+                    Err(RecvError::Closed) => break,
+                }
             }
         });
     });
     
     // Handle token save
     let handle_save_token = move |_| {
+        let ctx = ctx_for_save.clone();
         let token_value = token_input();
         
         // Validate token format
@@ -61,9 +101,8 @@ pub fn AIConfigPanel() -> Element {
         
         // Save token
         spawn(async move {
-            if let Ok(_) = ctx.ai_service.set_token(&token_value) {
+            if ctx.ai_service.set_token(&token_value).is_ok() {
                 token_configured.set(true);
-                model_status.set(ModelStatus::TokenSaved);
                 error_message.set(None);
                 token_input.set(String::new()); // Clear input for security
             } else {
@@ -72,53 +111,76 @@ pub fn AIConfigPanel() -> Element {
         });
     };
     
-    // Handle model download
-    let handle_download_model = move |_| {
+    // Handle model download; status and progress arrive as lifecycle events
+    let handle_download_model = use_callback(move |_: ()| {
+        let ctx = ctx_for_download.clone();
         error_message.set(None);
-        model_status.set(ModelStatus::Downloading);
         
         let ai_service = ctx.ai_service.clone();
         
         spawn(async move {
-            // Create progress callback
-            let progress_cb = {
-                let loading_progress = loading_progress.clone();
-                let loading_message = loading_message.clone();
-                std::sync::Arc::new(move |progress: f32, message: String| {
-                    loading_progress.set(progress);
-                    loading_message.set(message);
-                }) as services::ProgressCallback
-            };
-            
-            // Initialize with progress
-            match ai_service.initialize_with_progress(Some(progress_cb)).await {
-                Ok(_) => {
-                    model_status.set(ModelStatus::Ready);
-                    model_cached.set(true);
-                    loading_progress.set(1.0);
-                    loading_message.set(ctx.t("profile-ai-progress-complete"));
-                },
+            match ai_service.initialize().await {
+                Ok(_) => loading_message.set(ctx.t("profile-ai-progress-complete")),
                 Err(e) => {
-                    model_status.set(ModelStatus::Error(e.to_string()));
                     error_message.set(Some(format!("{}: {}", 
                         ctx.t("profile-ai-error-unknown"), 
-                        e.to_string()
+                        e
                     )));
                 }
             }
         });
+    });
+    
+    // Handle unload: frees the model's memory, keeping the cached files
+    let handle_unload_model = move |_| {
+        let ctx = ctx_for_unload.clone();
+        spawn(async move {
+            ctx.ai_service.unload().await;
+            loading_progress.set(0.0);
+            loading_message.set(String::new());
+        });
+    };
+    
+    // Handle reload, e.g. after changing the backend or quantization
+    let handle_reload_model = move |_| {
+        let ctx = ctx_for_reload.clone();
+        error_message.set(None);
+        spawn(async move {
+            if let Err(e) = ctx.ai_service.reload(None).await {
+                error_message.set(Some(format!("{}: {}",
+                    ctx.t("profile-ai-error-unknown"),
+                    e
+                )));
+            }
+        });
+    };
+    
+    // Handle feedback model path save
+    let handle_save_feedback_model = move |_| {
+        let path = feedback_model_input().trim().to_string();
+        let result = AIConfigManager::new().and_then(|manager| {
+            let mut config = manager.load()?;
+            config.feedback_model_path = (!path.is_empty()).then_some(path);
+            manager.save(&config)
+        });
+        match result {
+            Ok(_) => {
+                feedback_model_ready.set(FeedbackGenerator::is_configured());
+                error_message.set(None);
+            }
+            Err(e) => error_message.set(Some(format!("{}: {}",
+                ctx_for_feedback.t("profile-ai-error-unknown"),
+                e
+            ))),
+        }
     };
     
     // Handle clear cache
     let handle_clear_cache = move |_| {
+        let ctx = ctx_for_clear.clone();
         spawn(async move {
-            if let Ok(_) = ctx.ai_service.clear_cache() {
+            if ctx.ai_service.clear_cache().await.is_ok() {
                 model_cached.set(false);
-                model_status.set(if token_configured() { 
-                    ModelStatus::TokenSaved 
-                } else { 
-                    ModelStatus::NotConfigured 
-                });
                 loading_progress.set(0.0);
                 loading_message.set(String::new());
             }
@@ -178,15 +240,15 @@ pub fn AIConfigPanel() -> Element {
                             value: token_input(),
                             placeholder: ctx.t("profile-ai-token-placeholder"),
                             input_type: "password",
-                            on_change: move |val| token_input.set(val)
+                            on_input: move |val| token_input.set(val)
                         }
                     }
                     
                     NeonButton {
-                        text: ctx.t("profile-ai-token-save"),
                         variant: ButtonVariant::Primary,
                         on_click: handle_save_token,
-                        disabled: token_input().is_empty()
+                        disabled: token_input().is_empty(),
+                        {ctx.t("profile-ai-token-save")}
                     }
                 }
                 
@@ -198,6 +260,53 @@ pub fn AIConfigPanel() -> Element {
                 }
             }
             
+            // Optional generative feedback model
+            div {
+                class: "feedback-model-section",
+                style: "margin-bottom: 24px;",
+                
+                label {
+                    style: "
+                        display: block;
+                        color: #00ffff;
+                        margin-bottom: 8px;
+                        font-weight: bold;
+                    ",
+                    {ctx.t("profile-ai-feedback-model-label")}
+                }
+                
+                div {
+                    style: "display: flex; gap: 12px; align-items: flex-start;",
+                    
+                    div {
+                        style: "flex: 1;",
+                        NeonInput {
+                            value: feedback_model_input(),
+                            placeholder: ctx.t("profile-ai-feedback-model-placeholder"),
+                            on_input: move |val| feedback_model_input.set(val)
+                        }
+                    }
+                    
+                    NeonButton {
+                        variant: ButtonVariant::Secondary,
+                        on_click: handle_save_feedback_model,
+                        {ctx.t("profile-ai-feedback-model-save")}
+                    }
+                }
+                
+                if feedback_model_ready() {
+                    p {
+                        style: "color: #00ff00; margin-top: 8px; font-size: 0.9em;",
+                        "✓ {ctx.t(\"profile-ai-feedback-model-saved\")}"
+                    }
+                } else if !feedback_model_input().trim().is_empty() {
+                    p {
+                        style: "color: #ffaa00; margin-top: 8px; font-size: 0.9em;",
+                        {ctx.t("profile-ai-feedback-model-missing")}
+                    }
+                }
+            }
+            
             // Error display
             if let Some(err) = error_message() {
                 div {
@@ -231,13 +340,13 @@ pub fn AIConfigPanel() -> Element {
                 }
                 
                 // Progress bar (shown when downloading/loading)
-                if matches!(model_status(), ModelStatus::Downloading | ModelStatus::Loading) {
+                if matches!(model_status(), ModelStatus::Connecting | ModelStatus::Downloading | ModelStatus::Loading) {
                     div {
                         style: "margin-bottom: 16px;",
                         
                         NeonProgressBar {
-                            progress: loading_progress(),
-                            label: Some(loading_message())
+                            progress: (loading_progress() * 100.0) as u8,
+                            label: loading_message()
                         }
                     }
                 }
@@ -249,30 +358,53 @@ pub fn AIConfigPanel() -> Element {
                     // Download button
                     if !model_cached() && token_configured() {
                         NeonButton {
-                            text: ctx.t("profile-ai-download-model"),
                             variant: ButtonVariant::Primary,
                             on_click: handle_download_model,
-                            disabled: matches!(model_status(), ModelStatus::Downloading | ModelStatus::Loading)
+                            disabled: matches!(model_status(), ModelStatus::Downloading | ModelStatus::Loading),
+                            {ctx.t("profile-ai-download-model")}
                         }
                     }
                     
                     // Retry button (on error)
                     if matches!(model_status(), ModelStatus::Error(_)) {
                         NeonButton {
-                            text: ctx.t("profile-ai-retry-download"),
                             variant: ButtonVariant::Primary,
                             on_click: handle_download_model,
-                            disabled: false
+                            disabled: false,
+                            {ctx.t("profile-ai-retry-download")}
+                        }
+                    }
+                    
+                    // Unload / load buttons
+                    if model_status() == ModelStatus::Ready {
+                        NeonButton {
+                            variant: ButtonVariant::Secondary,
+                            on_click: handle_unload_model,
+                            disabled: false,
+                            {ctx.t("profile-ai-unload-model")}
+                        }
+                        NeonButton {
+                            variant: ButtonVariant::Secondary,
+                            on_click: handle_reload_model,
+                            disabled: false,
+                            {ctx.t("profile-ai-reload-model")}
+                        }
+                    } else if model_status() == ModelStatus::Unloaded {
+                        NeonButton {
+                            variant: ButtonVariant::Primary,
+                            on_click: handle_download_model,
+                            disabled: false,
+                            {ctx.t("profile-ai-reload-model")}
                         }
                     }
                     
                     // Clear cache button
                     if model_cached() {
                         NeonButton {
-                            text: ctx.t("profile-ai-clear-cache"),
                             variant: ButtonVariant::Secondary,
                             on_click: handle_clear_cache,
-                            disabled: matches!(model_status(), ModelStatus::Downloading | ModelStatus::Loading)
+                            disabled: matches!(model_status(), ModelStatus::Downloading | ModelStatus::Loading),
+                            {ctx.t("profile-ai-clear-cache")}
                         }
                     }
                 }
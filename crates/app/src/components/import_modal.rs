@@ -3,7 +3,6 @@ use serde_json;
 use uuid::Uuid;
 use domain::{question::Question, knowledge_trail::KnowledgeTrail};
 use crate::context::AppContext;
-use domain::traits::{QuestionRepository, KnowledgeTrailRepository};
 
 #[derive(Clone, PartialEq)]
 pub enum ImportType {
@@ -12,6 +11,7 @@ pub enum ImportType {
 }
 
 #[derive(Clone, PartialEq)]
+#[allow(dead_code)] // Processing and Success wait for file reading (see handle_file_change)
 pub enum ImportStatus {
     Idle,
     Processing,
@@ -173,10 +173,11 @@ pub fn ImportModal(props: ImportModalProps) -> Element {
     }
 }
 
-async fn handle_questions_import(json_text: String, mut status: Signal<ImportStatus>) {
+#[allow(dead_code)] // Called once file reading is implemented
+async fn handle_questions_import(ctx: AppContext, json_text: String, mut status: Signal<ImportStatus>) {
     match serde_json::from_str::<Vec<Question>>(&json_text) {
         Ok(mut questions) => {
-            let ctx = AppContext::new();
+            let mut imported = Vec::new();
             let mut success_count = 0;
             let mut error_count = 0;
             
@@ -189,7 +190,10 @@ async fn handle_questions_import(json_text: String, mut status: Signal<ImportSta
                 // Validate question
                 if validate_question(question) {
                     match ctx.question_repo.insert(question.clone()).await {
-                        Ok(_) => success_count += 1,
+                        Ok(_) => {
+                            success_count += 1;
+                            imported.push(question.clone());
+                        }
                         Err(_) => error_count += 1,
                     }
                 } else {
@@ -197,6 +201,14 @@ async fn handle_questions_import(json_text: String, mut status: Signal<ImportSta
                 }
             }
             
+            // Add the new questions to the semantic index in the background
+            let question_search = ctx.question_search.clone();
+            spawn(async move {
+                if let Err(e) = question_search.index_questions(&imported).await {
+                    tracing::warn!("Failed to index imported questions: {}", e);
+                }
+            });
+            
             if error_count == 0 {
                 status.set(ImportStatus::Success(
                     format!("Successfully imported {} questions", success_count)
@@ -216,10 +228,10 @@ async fn handle_questions_import(json_text: String, mut status: Signal<ImportSta
     }
 }
 
-async fn handle_trails_import(json_text: String, mut status: Signal<ImportStatus>) {
+#[allow(dead_code)] // Called once file reading is implemented
+async fn handle_trails_import(ctx: AppContext, json_text: String, mut status: Signal<ImportStatus>) {
     match serde_json::from_str::<Vec<KnowledgeTrail>>(&json_text) {
         Ok(mut trails) => {
-            let ctx = AppContext::new();
             let mut success_count = 0;
             let mut error_count = 0;
             
@@ -266,6 +278,7 @@ async fn handle_trails_import(json_text: String, mut status: Signal<ImportStatus
     }
 }
 
+#[allow(dead_code)]
 fn validate_question(question: &Question) -> bool {
     // Validate statement is not empty
     if question.statement.trim().is_empty() {
@@ -290,6 +303,7 @@ fn validate_question(question: &Question) -> bool {
     true
 }
 
+#[allow(dead_code)]
 fn validate_trail(trail: &KnowledgeTrail) -> bool {
     // Validate title is not empty
     if trail.title.trim().is_empty() {
@@ -9,13 +9,10 @@ pub mod navigation_menu;
 pub mod brain_icon;
 pub mod toggle;
 pub mod import_modal;
-<<<<<<< Local
 pub mod loading_screen;
-=======
 pub mod model_status_indicator;
 pub mod token_guide;
 pub mod ai_config_panel;
->>>>>>> Remote
 
 pub use neon_button::{NeonButton, ButtonVariant};
 pub use neon_input::NeonInput;
@@ -23,15 +20,10 @@ pub use cyber_card::CyberCard;
 pub use status_bar::StatusBar;
 pub use tab_bar::TabBar;
 pub use neon_progress_bar::NeonProgressBar;
-pub use sidebar::Sidebar;
-pub use navigation_menu::NavigationMenu;
 pub use brain_icon::{BrainIcon, BrainWatermark};
 pub use toggle::Toggle;
 pub use import_modal::ImportModal;
-<<<<<<< Local
 pub use loading_screen::LoadingScreen;
-=======
 pub use model_status_indicator::ModelStatusIndicator;
 pub use token_guide::TokenGuide;
 pub use ai_config_panel::AIConfigPanel;
->>>>>>> Remote
@@ -39,13 +39,19 @@ pub fn ModelStatusIndicator(props: ModelStatusIndicatorProps) -> Element {
             "⟳",
             true,
         ),
+        ModelStatus::Unloaded => (
+            "profile-ai-status-model-cached",
+            "#ffaa00",
+            "○",
+            false,
+        ),
         ModelStatus::Ready => (
             "profile-ai-status-model-ready",
             "#00ff00",
             "✓",
             false,
         ),
-        ModelStatus::Error(err) => (
+        ModelStatus::Error(_) => (
             "profile-ai-status-error",
             "#ff0000",
             "✗",
@@ -91,17 +97,17 @@ pub fn ModelStatusIndicator(props: ModelStatusIndicatorProps) -> Element {
             if let ModelStatus::Error(err) = &props.status {
                 div {
                     style: "margin-left: 8px; color: #ff0000; font-size: 0.85em;",
-                    {err}
+                    "{err}"
                 }
             }
         }
         
         style {
             r#"
-            @keyframes pulse {
-                0%, 100% { opacity: 0.6; transform: scale(1); }
-                50% { opacity: 1; transform: scale(1.1); }
-            }
+            @keyframes pulse {{
+                0%, 100% {{ opacity: 0.6; transform: scale(1); }}
+                50% {{ opacity: 1; transform: scale(1.1); }}
+            }}
             "#
         }
     }
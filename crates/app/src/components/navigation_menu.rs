@@ -57,6 +57,11 @@ pub fn NavigationMenu() -> Element {
                         icon: "❓",
                         label: "Questões"
                     }
+                    MenuLink {
+                        route: Route::Search {},
+                        icon: "🔍",
+                        label: "Busca"
+                    }
                     MenuLink {
                         route: Route::Essays {},
                         icon: "✍️",
@@ -9,6 +9,8 @@ pub struct NeonButtonProps {
     class: String,
     #[props(default)]
     variant: ButtonVariant,
+    #[props(default)]
+    disabled: bool,
 }
 
 #[derive(PartialEq, Clone, Default)]
@@ -16,6 +18,7 @@ pub enum ButtonVariant {
     #[default]
     Primary,
     Secondary,
+    #[allow(dead_code)] // Styled in the theme, not used by any screen yet
     Danger,
 }
 
@@ -30,6 +33,7 @@ pub fn NeonButton(props: NeonButtonProps) -> Element {
     rsx! {
         button {
             class: "neon-button {variant_class} {props.class}",
+            disabled: props.disabled,
             onclick: move |_| props.on_click.call(()),
             {props.children}
         }
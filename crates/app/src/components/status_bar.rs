@@ -1,5 +1,4 @@
 use dioxus::prelude::*;
-use dioxus::prelude::*;
 use super::BrainIcon;
 use crate::context::AppContext;
 
@@ -5,6 +5,7 @@ use crate::context::AppContext;
 pub fn TokenGuide() -> Element {
     let ctx = use_context::<AppContext>();
     let mut expanded = use_signal(|| false);
+    let header_margin = if expanded() { "16px" } else { "0" };
 
     rsx! {
         div {
@@ -23,7 +24,7 @@ pub fn TokenGuide() -> Element {
                     border: 1px solid rgba(0, 255, 255, 0.3);
                     border-radius: 8px;
                     cursor: pointer;
-                    margin-bottom: {if expanded() { \"16px\" } else { \"0\" }};
+                    margin-bottom: {header_margin};
                 ",
                 onclick: move |_| expanded.set(!expanded()),
                 
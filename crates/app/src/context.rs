@@ -3,8 +3,11 @@ use data::{
     InMemoryEssayRepository, InMemoryQuestionRepository,
     InMemoryUserRepository, InMemoryKnowledgeTrailRepository,
     InMemoryExamRubricRepository, InMemoryReadingContentRepository,
+    InMemoryEssayRevisionRepository, InMemoryWritingRecordingRepository,
+    InMemoryEvaluationRecordRepository,
+    SearchIndex,
 };
-use services::AIService;
+use services::{AIService, DraftStore, EvaluationService, QuestionSearch};
 use shared::{Translator, LocaleDetector};
 use uuid::Uuid;
 
@@ -12,12 +15,19 @@ use uuid::Uuid;
 #[derive(Clone)]
 pub struct AppContext {
     pub essay_repo: Arc<InMemoryEssayRepository>,
+    pub revision_repo: Arc<InMemoryEssayRevisionRepository>,
+    pub recording_repo: Arc<InMemoryWritingRecordingRepository>,
     pub question_repo: Arc<InMemoryQuestionRepository>,
     pub user_repo: Arc<InMemoryUserRepository>,
     pub trail_repo: Arc<InMemoryKnowledgeTrailRepository>,
     pub rubric_repo: Arc<InMemoryExamRubricRepository>,
     pub reading_repo: Arc<InMemoryReadingContentRepository>,
+    pub evaluation_record_repo: Arc<InMemoryEvaluationRecordRepository>,
+    pub search_index: SearchIndex,
     pub ai_service: Arc<AIService>,
+    pub evaluation_service: Arc<EvaluationService>,
+    pub question_search: Arc<QuestionSearch>,
+    pub draft_store: Arc<DraftStore>,
     pub current_user_id: Uuid,
     pub translator: Arc<Mutex<Translator>>,
     pub current_locale: Arc<RwLock<String>>,
@@ -25,24 +35,46 @@ pub struct AppContext {
 
 impl AppContext {
     pub fn new() -> Self {
-        let essay_repo = Arc::new(InMemoryEssayRepository::new());
-        let question_repo = Arc::new(InMemoryQuestionRepository::new());
+        // Atualizações de redações entram no histórico de revisões
+        let revision_repo = Arc::new(InMemoryEssayRevisionRepository::new());
+        let essay_repo = Arc::new(InMemoryEssayRepository::with_revisions(revision_repo.clone()));
+        let recording_repo = Arc::new(InMemoryWritingRecordingRepository::new());
+        // Índice de texto compartilhado por questões, leituras e trilhas
+        let search_index = SearchIndex::new();
+        let question_repo = Arc::new(InMemoryQuestionRepository::with_search_index(search_index.clone()));
         let user_repo = Arc::new(InMemoryUserRepository::new());
-        let trail_repo = Arc::new(InMemoryKnowledgeTrailRepository::new());
+        let trail_repo = Arc::new(InMemoryKnowledgeTrailRepository::with_search_index(search_index.clone()));
         let rubric_repo = Arc::new(InMemoryExamRubricRepository::new());
-        let reading_repo = Arc::new(InMemoryReadingContentRepository::new());
+        let reading_repo = Arc::new(InMemoryReadingContentRepository::with_search_index(search_index.clone()));
+        let evaluation_record_repo = Arc::new(InMemoryEvaluationRecordRepository::new());
         
         // Initialize AI service
         let ai_service = Arc::new(
             AIService::new().expect("Failed to create AI service")
         );
         
+        // Correções usam o mesmo modelo e guardam a procedência de cada nota
+        let evaluation_service = Arc::new(
+            EvaluationService::with_ai_service((*ai_service).clone())
+                .with_records(evaluation_record_repo.clone())
+        );
+
+        // Busca semântica de questões com o encoder local
+        let question_search = Arc::new(QuestionSearch::new(ai_service.clone()));
+        
+        // Rascunhos ficam em disco para sobreviver a fechamentos inesperados
+        let draft_store = Arc::new(
+            DraftStore::new().expect("Failed to create draft store")
+        );
+        
         // User ID padrão (do seeder)
         let current_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
             .unwrap();
         
         // Initialize translator with detected system locale
         let locale = LocaleDetector::detect_system_locale();
+        // The context never leaves the UI thread; Arc keeps it cheap to clone
+        #[allow(clippy::arc_with_non_send_sync)]
         let translator = Arc::new(Mutex::new(
             Translator::new(locale.clone(), "./locales")
         ));
@@ -51,12 +83,19 @@ impl AppContext {
         
         Self {
             essay_repo,
+            revision_repo,
+            recording_repo,
             question_repo,
             user_repo,
             trail_repo,
             rubric_repo,
             reading_repo,
+            evaluation_record_repo,
+            search_index,
             ai_service,
+            evaluation_service,
+            question_search,
+            draft_store,
             current_user_id,
             translator,
             current_locale,
@@ -1,23 +1,38 @@
 use dioxus::prelude::*;
+use dioxus_router::Link;
+use crate::app::Route;
+use crate::components::NeonProgressBar;
 use crate::context::AppContext;
-use domain::traits::EssayRepository;
-use domain::essay::EssayStatus;
+use domain::traits::{EssayRepository, EssayRevisionRepository, EvaluationRecordRepository};
+use domain::attribution::SentenceAttribution;
+use domain::essay::{Essay, EssayStatus};
+use domain::provenance::EvaluationRecord;
+use domain::review::ReviewStatus;
+use domain::revision::{DiffOp, EssayRevision};
+use services::{
+    compare_revisions, save_essay_export, CancelToken, ExportFormat, FeedbackEvent, FeedbackGenerator, JobOptions,
+    JobPriority,
+};
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[component]
 pub fn EssayDetail(id: String) -> Element {
-    let mut essay = use_signal(|| None);
+    let ctx = use_context::<AppContext>();
+    let mut essay = use_signal(|| None::<Essay>);
+    let mut export_message = use_signal(|| None::<String>);
+    let mut exporting = use_signal(|| false);
     
     // Carregar redação
     use_effect(move || {
         let id_clone = id.clone();
+        let ctx = ctx.clone();
         spawn(async move {
             let essay_id = match Uuid::parse_str(&id_clone) {
                 Ok(uuid) => uuid,
                 Err(_) => return,
             };
             
-            let ctx = AppContext::new();
             if let Ok(Some(e)) = ctx.essay_repo.find_by_id(essay_id).await {
                 essay.set(Some(e));
             }
@@ -52,17 +67,64 @@ pub fn EssayDetail(id: String) -> Element {
                                     {format!("Score: {}/{}", score, e.max_score)}
                                 }
                             }
+                            if let Some(review) = &e.review {
+                                span {
+                                    class: "review-status",
+                                    style: "color: #ffaa00;",
+                                    {match review.status {
+                                        ReviewStatus::Pendente => "Aguardando revisão do professor",
+                                        ReviewStatus::Confirmada => "Notas confirmadas pelo professor",
+                                        ReviewStatus::Alterada => "Notas revisadas pelo professor",
+                                    }}
+                                }
+                            }
                         }
-                        // Botão de avaliação (temporariamente desabilitado)
-                        if e.status != EssayStatus::Corrigida {
+                        Link {
+                            to: Route::EssayReplay { id: e.id.to_string() },
+                            class: "replay-link",
+                            "Ver replay da escrita"
+                        }
+                        if e.status == EssayStatus::Corrigida {
                             div {
-                                class: "evaluation-actions",
+                                class: "export-actions",
+                                style: "display: flex; gap: 10px; margin-top: 10px;",
+                                for format in [ExportFormat::Pdf, ExportFormat::Docx] {
+                                    button {
+                                        class: "neon-button neon-button-secondary",
+                                        disabled: exporting(),
+                                        onclick: {
+                                            let e = e.clone();
+                                            move |_| {
+                                                let e = e.clone();
+                                                exporting.set(true);
+                                                export_message.set(Some("Exportando...".to_string()));
+                                                // Gerar PDF/DOCX e gravar o arquivo bloqueia: fora da thread da interface
+                                                spawn(async move {
+                                                    let saved = tokio::task::spawn_blocking(move || save_essay_export(&e, format)).await;
+                                                    let message = match saved {
+                                                        Ok(Ok(path)) => format!("Arquivo salvo em {}", path.display()),
+                                                        Ok(Err(err)) => format!("Erro ao exportar: {}", err),
+                                                        Err(err) => format!("Erro ao exportar: {}", err),
+                                                    };
+                                                    export_message.set(Some(message));
+                                                    exporting.set(false);
+                                                });
+                                            }
+                                        },
+                                        {format!("Exportar {}", format.extension().to_uppercase())}
+                                    }
+                                }
+                            }
+                            if let Some(message) = export_message() {
                                 p {
-                                    style: "color: #888; margin-top: 10px;",
-                                    "Avaliação com IA em desenvolvimento"
+                                    style: "color: #aaaaaa; margin-top: 6px;",
+                                    {message}
                                 }
                             }
                         }
+                        EvaluationPanel {
+                            essay: essay,
+                        }
                     }
                     div {
                         class: "essay-content",
@@ -107,6 +169,13 @@ pub fn EssayDetail(id: String) -> Element {
                                             class: "criterion-score",
                                             style: "font-weight: bold; color: #ff00ff; font-size: 1.2em;",
                                             {format!("{} pontos", score)}
+                                            if let Some(interval) = e.confidence.as_ref().and_then(|c| c.get(criterion)) {
+                                                span {
+                                                    style: "color: #888888; font-size: 0.75em; font-weight: normal; margin-left: 6px;",
+                                                    title: "Intervalo de confiança de 90%",
+                                                    {format!("({}–{})", interval.lower, interval.upper)}
+                                                }
+                                            }
                                         }
                                     }
                                     if let Some(detailed) = rubric.detailed_feedback.get(criterion) {
@@ -119,6 +188,37 @@ pub fn EssayDetail(id: String) -> Element {
                             }
                         }
                     }
+                    if let Some(note) = e.review.as_ref().and_then(|r| r.note.clone()) {
+                        div {
+                            class: "essay-feedback",
+                            h3 {
+                                "Comentário do professor:"
+                            }
+                            p {
+                                style: "white-space: pre-wrap;",
+                                {note}
+                            }
+                        }
+                    }
+                    if let (Some(attributions), Some(rubric)) = (&e.attributions, &e.rubric_scores) {
+                        AttributionHeatmap {
+                            content: e.content.clone(),
+                            attributions: attributions.clone(),
+                            criteria: {
+                                let mut names: Vec<String> = rubric.scores.keys().cloned().collect();
+                                names.sort();
+                                names
+                            },
+                        }
+                    }
+                    if e.status == EssayStatus::Corrigida {
+                        GenerativeFeedbackPanel {
+                            essay: essay,
+                        }
+                    }
+                    RevisionHistory {
+                        essay: essay,
+                    }
                     if let Some(corrections) = &e.corrections {
                         if !corrections.is_empty() {
                             div {
@@ -157,3 +257,446 @@ pub fn EssayDetail(id: String) -> Element {
         }
     }
 }
+
+#[derive(Props, PartialEq, Clone)]
+struct EvaluationPanelProps {
+    essay: Signal<Option<Essay>>,
+}
+
+/// Botão de avaliação automática e a procedência da última nota
+#[component]
+fn EvaluationPanel(props: EvaluationPanelProps) -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut essay = props.essay;
+    let mut record = use_signal(|| None::<EvaluationRecord>);
+    let mut evaluating = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+    // Escritos pelo worker de inferência, fora da thread da interface
+    let mut progress = use_signal_sync(|| 0.0_f32);
+    let mut progress_message = use_signal_sync(String::new);
+    let mut cancel = use_signal(CancelToken::new);
+
+    // Recarrega o registro sempre que a redação muda, inclusive após avaliar
+    let ctx_for_load = ctx.clone();
+    use_effect(move || {
+        let essay_id = essay.read().as_ref().map(|e| e.id);
+        let ctx = ctx_for_load.clone();
+        spawn(async move {
+            if let Some(id) = essay_id {
+                if let Ok(latest) = ctx.evaluation_record_repo.latest(id).await {
+                    record.set(latest);
+                }
+            }
+        });
+    });
+
+    let evaluate = move |_| {
+        if let Some(current) = essay() {
+            evaluating.set(true);
+            error.set(None);
+            progress.set(0.0);
+            progress_message.set(String::new());
+
+            // Um token novo por avaliação: o anterior pode ter sido cancelado
+            let token = CancelToken::new();
+            cancel.set(token.clone());
+            let progress_cb = Arc::new(move |fraction: f32, message: String| {
+                let (mut progress, mut progress_message) = (progress, progress_message);
+                progress.set(fraction);
+                progress_message.set(message);
+            }) as services::ProgressCallback;
+            let options = JobOptions {
+                priority: JobPriority::High,
+                progress: Some(progress_cb),
+                cancel: token,
+                ..Default::default()
+            };
+
+            let ctx = ctx.clone();
+            spawn(async move {
+                match ctx.evaluation_service.evaluate_essay_with(current, options).await {
+                    Ok(evaluated) => match ctx.essay_repo.update(evaluated.clone()).await {
+                        Ok(()) => essay.set(Some(evaluated)),
+                        Err(e) => error.set(Some(format!("Erro ao salvar avaliação: {}", e))),
+                    },
+                    Err(_) if cancel.read().is_cancelled() => error.set(Some("Avaliação cancelada".to_string())),
+                    Err(e) => error.set(Some(format!("Erro na avaliação: {}", e))),
+                }
+                evaluating.set(false);
+            });
+        }
+    };
+
+    let corrected = essay.read().as_ref().is_some_and(|e| e.status == EssayStatus::Corrigida);
+
+    rsx! {
+        div {
+            class: "evaluation-actions",
+            style: "margin-top: 10px;",
+            if !corrected {
+                button {
+                    class: "neon-button",
+                    disabled: evaluating(),
+                    onclick: evaluate,
+                    if evaluating() { "Avaliando..." } else { "Avaliar com IA" }
+                }
+                if evaluating() {
+                    button {
+                        class: "neon-button",
+                        style: "margin-left: 8px;",
+                        onclick: move |_| cancel.read().cancel(),
+                        "Cancelar"
+                    }
+                }
+            }
+            if evaluating() && !progress_message().is_empty() {
+                div {
+                    style: "margin-top: 6px;",
+                    NeonProgressBar {
+                        progress: (progress() * 100.0) as u8,
+                        label: progress_message()
+                    }
+                }
+            }
+            if let Some(r) = record() {
+                p {
+                    style: "color: #888888; font-size: 0.85em; margin-top: 6px;",
+                    {format!(
+                        "Avaliada com {} · pontuação {} · rubrica {} · {} ms",
+                        match &r.model {
+                            Some(model) => format!(
+                                "{} ({})",
+                                model.model_id,
+                                model.revision.as_deref().unwrap_or("revisão desconhecida")
+                            ),
+                            None => "heurísticas, sem modelo".to_string(),
+                        },
+                        r.scoring_head_version,
+                        r.rubric_version,
+                        r.duration_ms
+                    )}
+                }
+            }
+            if let Some(message) = error() {
+                p {
+                    style: "color: #ff6464; margin-top: 6px;",
+                    {message}
+                }
+            }
+        }
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct AttributionHeatmapProps {
+    content: String,
+    attributions: Vec<SentenceAttribution>,
+    criteria: Vec<String>,
+}
+
+/// Texto da redação colorido pela influência de cada frase na competência escolhida
+///
+/// Verde indica trechos que aumentaram a nota; vermelho, trechos que a reduziram.
+#[component]
+fn AttributionHeatmap(props: AttributionHeatmapProps) -> Element {
+    let mut selected = use_signal(|| props.criteria.first().cloned().unwrap_or_default());
+    let criterion = selected();
+
+    let chars: Vec<char> = props.content.chars().collect();
+    let slice = |start: usize, end: usize| -> String {
+        chars[start.min(chars.len())..end.min(chars.len())].iter().collect()
+    };
+    let max_weight = props
+        .attributions
+        .iter()
+        .map(|a| a.weight(&criterion).abs())
+        .fold(0.0_f32, f32::max);
+
+    // Trechos em ordem: (texto, peso da frase, se é frase avaliada)
+    let mut segments: Vec<(String, f32, bool)> = Vec::new();
+    let mut cursor = 0;
+    for attribution in &props.attributions {
+        if attribution.start > cursor {
+            segments.push((slice(cursor, attribution.start), 0.0, false));
+        }
+        segments.push((slice(attribution.start, attribution.end), attribution.weight(&criterion), true));
+        cursor = cursor.max(attribution.end);
+    }
+    if cursor < chars.len() {
+        segments.push((slice(cursor, chars.len()), 0.0, false));
+    }
+
+    rsx! {
+        div {
+            class: "attribution-heatmap",
+            style: "margin: 20px 0;",
+            h3 {
+                "Por que essa nota?"
+            }
+            div {
+                style: "display: flex; gap: 8px; flex-wrap: wrap; margin-bottom: 10px;",
+                for name in props.criteria.clone() {
+                    button {
+                        class: if name == criterion { "neon-button" } else { "neon-button neon-button-secondary" },
+                        onclick: {
+                            let name = name.clone();
+                            move |_| selected.set(name.clone())
+                        },
+                        {name.clone()}
+                    }
+                }
+            }
+            if max_weight == 0.0 {
+                p {
+                    style: "color: #888888;",
+                    {format!("Nenhum trecho alterou sozinho a nota de {}.", criterion)}
+                }
+            }
+            p {
+                class: "essay-text",
+                style: "white-space: pre-wrap; line-height: 1.7;",
+                for (text, weight, is_sentence) in segments {
+                    if is_sentence && weight != 0.0 {
+                        span {
+                            style: heatmap_style(weight, max_weight),
+                            title: format!("{:+.0} pontos em {}", weight, criterion),
+                            {text}
+                        }
+                    } else {
+                        span {
+                            {text}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fundo verde (positivo) ou vermelho (negativo) proporcional ao peso da frase
+fn heatmap_style(weight: f32, max_weight: f32) -> String {
+    let alpha = 0.15 + 0.5 * (weight.abs() / max_weight);
+    let (r, g, b) = if weight > 0.0 { (0, 255, 128) } else { (255, 80, 80) };
+    format!("background: rgba({}, {}, {}, {:.2}); border-radius: 3px;", r, g, b, alpha)
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct GenerativeFeedbackPanelProps {
+    essay: Signal<Option<Essay>>,
+}
+
+/// Feedback personalizado gerado pelo modelo local, exibido token a token
+///
+/// Só aparece com um modelo GGUF configurado; sem ele a redação mantém o
+/// feedback de modelo da avaliação.
+#[component]
+fn GenerativeFeedbackPanel(props: GenerativeFeedbackPanelProps) -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut essay = props.essay;
+    let configured = use_signal(FeedbackGenerator::is_configured);
+    let mut streamed = use_signal(String::new);
+    let mut generating = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let generate = move |_| {
+        let Some(current) = essay() else { return };
+        generating.set(true);
+        streamed.set(String::new());
+        error.set(None);
+
+        let ctx = ctx.clone();
+        spawn(async move {
+            let generator = tokio::task::spawn_blocking(FeedbackGenerator::shared)
+                .await
+                .ok()
+                .flatten();
+            let Some(generator) = generator else {
+                error.set(Some("Não foi possível carregar o modelo de feedback".to_string()));
+                generating.set(false);
+                return;
+            };
+
+            let mut events = generator.stream(current.clone());
+            while let Some(event) = events.recv().await {
+                match event {
+                    FeedbackEvent::Token(token) => streamed.write().push_str(&token),
+                    FeedbackEvent::Done(feedback) => {
+                        let mut updated = current.clone();
+                        feedback.apply_to(&mut updated);
+                        updated.updated_at = chrono::Utc::now();
+                        match ctx.essay_repo.update(updated.clone()).await {
+                            Ok(()) => essay.set(Some(updated)),
+                            Err(e) => error.set(Some(format!("Erro ao salvar feedback: {}", e))),
+                        }
+                    }
+                    FeedbackEvent::Error(message) => error.set(Some(message)),
+                }
+            }
+            generating.set(false);
+        });
+    };
+
+    if !configured() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "generative-feedback",
+            style: "margin: 20px 0; padding: 15px; background: rgba(255, 0, 255, 0.08); border-left: 3px solid #ff00ff;",
+            h3 {
+                "Feedback personalizado (IA local)"
+            }
+            button {
+                class: "neon-button neon-button-secondary",
+                disabled: generating(),
+                onclick: generate,
+                if generating() { "Gerando..." } else { "Gerar feedback personalizado" }
+            }
+            if !streamed().is_empty() {
+                pre {
+                    class: "essay-text",
+                    style: "white-space: pre-wrap; margin-top: 10px;",
+                    {streamed()}
+                }
+            }
+            if let Some(message) = error() {
+                p {
+                    style: "color: #ff6464; margin-top: 6px;",
+                    {message}
+                }
+            }
+        }
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct RevisionHistoryProps {
+    essay: Signal<Option<Essay>>,
+}
+
+/// Linha do tempo das revisões com comparação entre duas versões
+#[component]
+fn RevisionHistory(props: RevisionHistoryProps) -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut revisions = use_signal(Vec::<EssayRevision>::new);
+    let mut from_idx = use_signal(|| 0usize);
+    let mut to_idx = use_signal(|| 0usize);
+
+    // Lê a redação dentro do efeito: cada correção ou revisão salva recarrega o histórico
+    let essay = props.essay;
+    use_effect(move || {
+        let Some(essay_id) = essay.read().as_ref().map(|e| e.id) else { return };
+        let ctx = ctx.clone();
+        spawn(async move {
+            if let Ok(history) = ctx.revision_repo.list_by_essay(essay_id).await {
+                let last = history.len().saturating_sub(1);
+                from_idx.set(last.saturating_sub(1));
+                to_idx.set(last);
+                revisions.set(history);
+            }
+        });
+    });
+
+    let history = revisions();
+    if history.len() < 2 {
+        return rsx! {};
+    }
+
+    let from = &history[from_idx().min(history.len() - 1)];
+    let to = &history[to_idx().min(history.len() - 1)];
+    let comparison = compare_revisions(from, to);
+
+    rsx! {
+        div {
+            class: "essay-revisions",
+            h3 {
+                "Histórico de Revisões:"
+            }
+            for (idx, revision) in history.iter().enumerate() {
+                div {
+                    class: "revision-item",
+                    style: "display: flex; gap: 12px; align-items: center; margin: 6px 0;",
+                    span {
+                        style: "color: #00ffff; font-weight: bold;",
+                        {format!("Versão {}", revision.revision_number)}
+                    }
+                    span {
+                        style: "color: #aaaaaa;",
+                        {revision.created_at.format("%d/%m/%Y %H:%M").to_string()}
+                    }
+                    span {
+                        style: "color: #ff00ff;",
+                        {revision.score.map(|s| format!("{}/{}", s, revision.max_score)).unwrap_or_else(|| "Sem nota".to_string())}
+                    }
+                    button {
+                        class: if idx == from_idx() { "tab-button active" } else { "tab-button" },
+                        onclick: move |_| from_idx.set(idx),
+                        "De"
+                    }
+                    button {
+                        class: if idx == to_idx() { "tab-button active" } else { "tab-button" },
+                        onclick: move |_| to_idx.set(idx),
+                        "Para"
+                    }
+                }
+            }
+            div {
+                class: "revision-comparison",
+                style: "margin-top: 15px;",
+                p {
+                    style: "color: #cccccc;",
+                    {format!(
+                        "Versão {} → Versão {}: +{} / -{} palavras",
+                        comparison.from_revision,
+                        comparison.to_revision,
+                        comparison.words_added,
+                        comparison.words_removed,
+                    )}
+                }
+                if let Some(total) = comparison.total_delta {
+                    p {
+                        style: if total >= 0 { "color: #00ff00; font-weight: bold;" } else { "color: #ff6464; font-weight: bold;" },
+                        {format!("Variação da nota: {:+}", total)}
+                    }
+                }
+                for delta in comparison.criterion_deltas.iter() {
+                    div {
+                        class: "criterion-delta",
+                        style: "display: flex; justify-content: space-between; padding: 4px 0;",
+                        span {
+                            style: "color: #00ffff;",
+                            {delta.criterion.clone()}
+                        }
+                        span {
+                            style: if delta.delta > 0 { "color: #00ff00;" } else if delta.delta < 0 { "color: #ff6464;" } else { "color: #888888;" },
+                            {format!(
+                                "{} → {} ({:+})",
+                                delta.before.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string()),
+                                delta.after.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string()),
+                                delta.delta,
+                            )}
+                        }
+                    }
+                }
+                div {
+                    class: "revision-diff",
+                    style: "margin-top: 10px; line-height: 1.6; white-space: pre-wrap;",
+                    for segment in comparison.diff.iter() {
+                        match segment.op {
+                            DiffOp::Equal => rsx! {
+                                span { style: "color: #cccccc;", {format!("{} ", segment.text)} }
+                            },
+                            DiffOp::Insert => rsx! {
+                                ins { style: "color: #00ff00; background: rgba(0, 255, 0, 0.1);", {format!("{} ", segment.text)} }
+                            },
+                            DiffOp::Delete => rsx! {
+                                del { style: "color: #ff6464; background: rgba(255, 100, 100, 0.1);", {format!("{} ", segment.text)} }
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
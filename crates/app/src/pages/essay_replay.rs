@@ -0,0 +1,131 @@
+use dioxus::prelude::*;
+use dioxus_router::Link;
+use crate::context::AppContext;
+use crate::app::Route;
+use domain::traits::WritingRecordingRepository;
+use domain::writing_process::WritingRecording;
+use services::analyze_writing;
+use uuid::Uuid;
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+#[component]
+pub fn EssayReplay(id: String) -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut recording = use_signal(|| None::<WritingRecording>);
+    let mut position_ms = use_signal(|| 0u64);
+    let mut loaded = use_signal(|| false);
+
+    let essay_id = Uuid::parse_str(&id).ok();
+    use_effect(move || {
+        let ctx = ctx.clone();
+        spawn(async move {
+            if let Some(essay_id) = essay_id {
+                if let Ok(Some(rec)) = ctx.recording_repo.find_by_essay(essay_id).await {
+                    position_ms.set(rec.duration_ms());
+                    recording.set(Some(rec));
+                }
+            }
+            loaded.set(true);
+        });
+    });
+
+    rsx! {
+        div {
+            class: "page-container",
+            h1 {
+                class: "page-title",
+                "REPLAY DA ESCRITA"
+            }
+            if let Some(rec) = recording() {
+                {
+                    let analytics = analyze_writing(&rec);
+                    let duration = rec.duration_ms();
+                    let text = rec.text_at(position_ms());
+                    rsx! {
+                        div {
+                            class: "replay-controls",
+                            style: "display: flex; gap: 12px; align-items: center; margin-bottom: 15px;",
+                            input {
+                                r#type: "range",
+                                min: "0",
+                                max: "{duration}",
+                                step: "500",
+                                value: "{position_ms()}",
+                                style: "flex: 1;",
+                                oninput: move |evt| {
+                                    if let Ok(ms) = evt.value().parse() {
+                                        position_ms.set(ms);
+                                    }
+                                },
+                            }
+                            span {
+                                style: "color: #00ffff; font-family: monospace;",
+                                {format!("{} / {}", format_duration(position_ms()), format_duration(duration))}
+                            }
+                        }
+                        pre {
+                            class: "essay-text",
+                            {text}
+                        }
+                        div {
+                            class: "writing-analytics",
+                            h3 {
+                                "Análise do processo:"
+                            }
+                            p {
+                                {format!("Tempo total de escrita: {}", format_duration(analytics.total_duration_ms))}
+                            }
+                            p {
+                                {match analytics.time_to_first_paragraph_ms {
+                                    Some(ms) => format!("Primeiro parágrafo concluído em {}", format_duration(ms)),
+                                    None => "Nenhum parágrafo concluído".to_string(),
+                                }}
+                            }
+                            p {
+                                {format!(
+                                    "Pausas longas: {} (total {})",
+                                    analytics.pauses.len(),
+                                    format_duration(analytics.total_pause_ms),
+                                )}
+                            }
+                            p {
+                                {format!(
+                                    "Texto revisado: {:.0}% ({} caracteres apagados de {} digitados)",
+                                    analytics.revised_ratio * 100.0,
+                                    analytics.chars_deleted,
+                                    analytics.chars_inserted,
+                                )}
+                            }
+                            for (idx, count) in analytics.paragraph_revisions.iter().enumerate() {
+                                if *count > 0 {
+                                    p {
+                                        style: "color: #aaaaaa;",
+                                        {format!("Parágrafo {}: reescrito {} vez(es)", idx + 1, count)}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            } else if loaded() {
+                div {
+                    class: "empty-state",
+                    "Esta redação não tem gravação do processo de escrita."
+                }
+            } else {
+                div {
+                    class: "loading",
+                    "Carregando gravação..."
+                }
+            }
+            Link {
+                to: Route::EssayDetail { id: id.clone() },
+                "Voltar para a redação"
+            }
+        }
+    }
+}
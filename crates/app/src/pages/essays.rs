@@ -3,23 +3,38 @@ use dioxus_router::Link;
 use crate::components::*;
 use crate::context::AppContext;
 use crate::app::Route;
+use domain::draft::EssayDraft;
 use domain::traits::EssayRepository;
 
 #[component]
 pub fn Essays() -> Element {
-    let mut essays = use_signal(|| Vec::new());
+    let ctx = use_context::<AppContext>();
+    let mut essays = use_signal(Vec::new);
+    let mut drafts = use_signal(Vec::<EssayDraft>::new);
     
     // Carregar redações na inicialização
+    let load_ctx = ctx.clone();
     use_effect(move || {
+        let ctx = load_ctx.clone();
         spawn(async move {
-            let ctx = AppContext::new();
             let user_id = ctx.current_user_id;
             if let Ok(user_essays) = ctx.essay_repo.list_by_user(user_id).await {
                 essays.set(user_essays);
             }
+            // Rascunhos ficam fora do repositório de redações
+            if let Ok(user_drafts) = ctx.draft_store.list_by_user(user_id) {
+                drafts.set(user_drafts);
+            }
         });
     });
     
+    // Descartar apaga o rascunho do disco
+    let discard_draft = use_callback(move |id: uuid::Uuid| {
+        if ctx.draft_store.discard(id).is_ok() {
+            drafts.write().retain(|d| d.id != id);
+        }
+    });
+    
     rsx! {
         div {
             class: "app-container",
@@ -42,6 +57,29 @@ pub fn Essays() -> Element {
                                 "Nova Redação"
                             }
                         }
+                        Link {
+                            to: Route::ReviewQueue {},
+                            NeonButton {
+                                variant: crate::components::neon_button::ButtonVariant::Secondary,
+                                "Fila de revisão"
+                            }
+                        }
+                    }
+                    if !drafts().is_empty() {
+                        div {
+                            class: "drafts-list",
+                            h2 {
+                                class: "section-title",
+                                "Continuar rascunho"
+                            }
+                            for draft in drafts().iter() {
+                                DraftCard {
+                                    key: "{draft.id}",
+                                    draft: draft.clone(),
+                                    on_discard: discard_draft,
+                                }
+                            }
+                        }
                     }
                     div {
                         class: "essays-list",
@@ -86,6 +124,66 @@ pub fn Essays() -> Element {
     }
 }
 
+#[derive(Props, PartialEq, Clone)]
+struct DraftCardProps {
+    draft: EssayDraft,
+    on_discard: EventHandler<uuid::Uuid>,
+}
+
+#[component]
+fn DraftCard(props: DraftCardProps) -> Element {
+    let draft_id = props.draft.id;
+    let title = if props.draft.title.trim().is_empty() {
+        "Rascunho sem título".to_string()
+    } else {
+        props.draft.title.clone()
+    };
+    let edited_text = format!(
+        "Editado em {} · {} palavras",
+        props.draft.updated_at.format("%d/%m/%Y %H:%M"),
+        props.draft.content.split_whitespace().count(),
+    );
+
+    rsx! {
+        CyberCard {
+            class: "essay-card draft-card".to_string(),
+            div {
+                class: "essay-header",
+                h3 {
+                    class: "essay-title",
+                    {title}
+                }
+                span {
+                    class: "essay-exam-type",
+                    {props.draft.exam_type.display_name()}
+                }
+            }
+            div {
+                class: "essay-info",
+                span {
+                    class: "essay-status",
+                    {edited_text}
+                }
+            }
+            div {
+                class: "editor-actions",
+                Link {
+                    to: Route::ResumeDraft { draft_id: draft_id.to_string() },
+                    NeonButton {
+                        variant: crate::components::neon_button::ButtonVariant::Primary,
+                        "Continuar"
+                    }
+                }
+                NeonButton {
+                    variant: crate::components::neon_button::ButtonVariant::Secondary,
+                    on_click: move |_| props.on_discard.call(draft_id),
+                    "Descartar"
+                }
+            }
+        }
+    }
+}
+
 #[derive(Props, PartialEq, Clone)]
 struct EssayCardProps {
     id: String,
@@ -1,6 +1,7 @@
 use dioxus::prelude::*;
 use crate::components::*;
 use crate::context::AppContext;
+use domain::question_query::QuestionQuery;
 use domain::traits::{EssayRepository, QuestionRepository, KnowledgeTrailRepository};
 
 #[component]
@@ -9,13 +10,19 @@ pub fn Home() -> Element {
     let mut stats = use_signal(|| (0u32, 0u32, 0u32));
     
     // Carregar estatísticas
+    let ctx_for_load = ctx.clone();
     use_effect(move || {
+        let ctx = ctx_for_load.clone();
         spawn(async move {
-            let ctx = AppContext::new();
             let user_id = ctx.current_user_id;
             
             let essays_count = ctx.essay_repo.list_by_user(user_id).await.unwrap_or_default().len() as u32;
-            let questions_count = ctx.question_repo.search("").await.unwrap_or_default().len() as u32;
+            let questions_count = ctx
+                .question_repo
+                .query(&QuestionQuery { limit: 1, ..Default::default() })
+                .await
+                .map(|page| page.total)
+                .unwrap_or_default() as u32;
             let trails_count = ctx.trail_repo.list_by_user(user_id).await.unwrap_or_default().len() as u32;
             
             stats.set((essays_count, questions_count, trails_count));
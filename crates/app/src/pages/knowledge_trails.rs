@@ -7,12 +7,13 @@ use domain::traits::KnowledgeTrailRepository;
 
 #[component]
 pub fn KnowledgeTrails() -> Element {
-    let mut trails = use_signal(|| Vec::new());
+    let ctx = use_context::<AppContext>();
+    let mut trails = use_signal(Vec::new);
     
     // Carregar trilhas na inicialização
     use_effect(move || {
+        let ctx = ctx.clone();
         spawn(async move {
-            let ctx = AppContext::new();
             let user_id = ctx.current_user_id;
             if let Ok(user_trails) = ctx.trail_repo.list_by_user(user_id).await {
                 trails.set(user_trails);
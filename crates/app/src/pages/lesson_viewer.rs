@@ -88,8 +88,8 @@ pub fn LessonViewer(trail_id: String, module_id: String) -> Element {
     });
     
     // Mark lesson as complete
-    let mut trail_mut = trail.clone();
-    let mut current_module_mut = current_module.clone();
+    let mut trail_mut = trail;
+    let mut current_module_mut = current_module;
     let mark_complete = move |_| {
         let trail_repo = trail_repo_for_mark.clone();
         let trail_id_str = trail_id_for_mark.clone();
@@ -6,7 +6,10 @@ pub mod questions;
 pub mod question_detail;
 pub mod essays;
 pub mod essay_detail;
+pub mod essay_replay;
 pub mod new_essay;
+pub mod review_queue;
+pub mod search;
 pub mod profile;
 
 pub use home::Home;
@@ -17,6 +20,9 @@ pub use questions::Questions;
 pub use question_detail::QuestionDetail;
 pub use essays::Essays;
 pub use essay_detail::EssayDetail;
-pub use new_essay::NewEssay;
+pub use essay_replay::EssayReplay;
+pub use new_essay::{NewEssay, ResumeDraft};
+pub use review_queue::ReviewQueue;
+pub use search::Search;
 pub use profile::Profile;
 
@@ -1,63 +1,314 @@
 use dioxus::prelude::*;
-use dioxus_router::Link;
+use dioxus_router::{Link, navigator};
 use crate::components::*;
 use crate::context::AppContext;
 use crate::app::Route;
-use domain::essay::{ExamType, EssayStatus};
-use domain::traits::EssayRepository;
+use domain::answer_sheet::{AnswerSheet, LineCheck};
+use domain::draft::EssayDraft;
+use domain::essay::ExamType;
+use domain::traits::{EssayRepository, WritingRecordingRepository};
+use services::{Misspelling, SpellChecker};
 use uuid::Uuid;
-use chrono::Utc;
+
+/// Intervalo entre checkpoints completos do rascunho
+const AUTOSAVE_INTERVAL_SECS: u64 = 15;
+/// Intervalo mínimo entre registros no journal; edições no meio dele vão juntas
+const JOURNAL_INTERVAL_MS: u64 = 500;
 
 #[component]
 pub fn NewEssay() -> Element {
-    let mut title = use_signal(|| String::new());
-    let mut content = use_signal(|| String::new());
+    rsx! {
+        EssayEditor {
+            draft_id: None,
+        }
+    }
+}
+
+#[component]
+pub fn ResumeDraft(draft_id: String) -> Element {
+    rsx! {
+        EssayEditor {
+            draft_id: Uuid::parse_str(&draft_id).ok(),
+        }
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct EssayEditorProps {
+    draft_id: Option<Uuid>,
+}
+
+#[component]
+fn EssayEditor(props: EssayEditorProps) -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut title = use_signal(String::new);
+    let mut content = use_signal(String::new);
     let mut exam_type = use_signal(|| ExamType::Enem);
     let mut is_saving = use_signal(|| false);
-    
+    let mut last_autosave = use_signal(|| None::<String>);
+
+    // Modo prova: folha de 30 linhas, cronômetro e tela cheia
+    let mut exam_mode = use_signal(|| false);
+    let mut time_budget_minutes = use_signal(|| 90u16);
+    let mut remaining_secs = use_signal(|| None::<u32>);
+
+    // Corretor ortográfico carregado em segundo plano (dicionário grande)
+    let mut spell_checker = use_signal(|| None::<SpellChecker>);
+    let mut selected_misspelling = use_signal(|| None::<Misspelling>);
+    let user_id = ctx.current_user_id;
+    use_future(move || async move {
+        if let Ok(Some(checker)) =
+            tokio::task::spawn_blocking(move || SpellChecker::for_user(user_id)).await
+        {
+            spell_checker.set(Some(checker));
+        }
+    });
+
+    // Rascunho ativo: retomado do disco ou criado na primeira digitação
+    let draft_ctx = ctx.clone();
+    let mut draft = use_signal(move || {
+        props.draft_id
+            .and_then(|id| draft_ctx.draft_store.recover(id).ok().flatten())
+            .unwrap_or_else(|| EssayDraft::new(draft_ctx.current_user_id, ExamType::Enem))
+    });
+
+    use_hook(|| {
+        let recovered = draft.peek();
+        title.set(recovered.title.clone());
+        content.set(recovered.content.clone());
+        exam_type.set(recovered.exam_type.clone());
+    });
+
+    // Alterações ainda não registradas no journal do rascunho
+    let mut journal_pending = use_signal(|| false);
+    let mut journal_edit = move || journal_pending.set(true);
+
+    // Registra no journal o que os campos têm e o rascunho ainda não
+    let journal_ctx = ctx.clone();
+    let flush_journal = use_callback(move |_: ()| {
+        journal_pending.set(false);
+        let title_val = title.peek().clone();
+        let content_val = content.peek().clone();
+        let exam_type_val = exam_type.peek().clone();
+        if let Err(e) = journal_ctx.draft_store.record_edit(
+            &mut draft.write(),
+            &title_val,
+            &content_val,
+            &exam_type_val,
+        ) {
+            tracing::warn!("Failed to journal draft edit: {}", e);
+        }
+    });
+
+    // Cada registro faz fsync, então a digitação é agrupada em no máximo um por intervalo
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_millis(JOURNAL_INTERVAL_MS)).await;
+            if *journal_pending.peek() {
+                flush_journal.call(());
+            }
+        }
+    });
+
+    // Checkpoint periódico que compacta o journal
+    let autosave_ctx = ctx.clone();
+    use_future(move || {
+        let ctx = autosave_ctx.clone();
+        async move {
+            let mut saved_sequence = draft.peek().sequence;
+            loop {
+                tokio::time::sleep(tokio::time::Duration::from_secs(AUTOSAVE_INTERVAL_SECS)).await;
+                let current = draft.peek().clone();
+                if current.sequence == saved_sequence || current.is_empty() {
+                    continue;
+                }
+                match ctx.draft_store.checkpoint(&current) {
+                    Ok(_) => {
+                        saved_sequence = current.sequence;
+                        last_autosave.set(Some(current.updated_at.format("%H:%M:%S").to_string()));
+                    }
+                    Err(e) => tracing::warn!("Failed to autosave draft: {}", e),
+                }
+            }
+        }
+    });
+
+    // Contagem regressiva do modo prova
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            if !*exam_mode.peek() {
+                continue;
+            }
+            let remaining = *remaining_secs.peek();
+            if let Some(secs) = remaining.filter(|s| *s > 0) {
+                remaining_secs.set(Some(secs - 1));
+            }
+        }
+    });
+
+    let sheet = AnswerSheet::for_exam(&exam_type());
+    let line_count = sheet.estimate_lines(&content());
+    let line_check = sheet.check(line_count);
+    let time_up = exam_mode() && remaining_secs() == Some(0);
+    let can_save = !exam_mode() || line_check != LineCheck::TooShort;
+
+    let save_ctx = ctx.clone();
     let handle_save = move |_| {
-        let title_val = title().clone();
-        let content_val = content().clone();
-        let exam_type_val = exam_type().clone();
-        
-        if title_val.is_empty() || content_val.is_empty() {
+        if title().trim().is_empty() || content().trim().is_empty() || !can_save {
             return;
         }
-        
+
+        // A gravação do processo de escrita precisa das últimas edições
+        flush_journal.call(());
+        // Os campos prevalecem caso o registro no journal tenha falhado
+        let current = EssayDraft {
+            title: title(),
+            content: content(),
+            exam_type: exam_type(),
+            ..draft.peek().clone()
+        };
+
         is_saving.set(true);
-        
+
+        let ctx = save_ctx.clone();
         spawn(async move {
-            let ctx = AppContext::new();
-            let user_id = ctx.current_user_id;
-            
-            let new_essay = domain::essay::Essay {
-                id: Uuid::new_v4(),
-                user_id,
-                title: title_val,
-                content: content_val,
-                exam_type: exam_type_val.clone(),
-                status: EssayStatus::EmProgresso,
-                score: None,
-                max_score: exam_type_val.max_score(),
-                feedback: None,
-                corrections: None,
-                rubric_scores: None,
-                created_at: Utc::now(),
-                updated_at: Utc::now(),
-                submitted_at: None,
-            };
-            
-            let _ = ctx.essay_repo.save(new_essay).await;
+            let new_essay = current.to_essay();
+            let essay_id = new_essay.id;
+            if ctx.essay_repo.save_revision(new_essay).await.is_ok() {
+                // Um registro atrasado no journal recriaria o rascunho descartado
+                journal_pending.set(false);
+                if let Some(mut rec) = current.recording.clone() {
+                    rec.essay_id = Some(essay_id);
+                    let _ = ctx.recording_repo.save(rec).await;
+                }
+                let _ = ctx.draft_store.discard(current.id);
+                navigator().push(Route::EssayDetail { id: essay_id.to_string() });
+            }
             is_saving.set(false);
         });
     };
-    
+
+    let recording_ctx = ctx.clone();
+
+    // Troca a palavra selecionada pela sugestão, registrando a edição
+    let replace_misspelling = use_callback(move |(misspelling, replacement): (Misspelling, String)| {
+        let old_content = content.peek().clone();
+        let new_content: String = old_content
+            .chars()
+            .take(misspelling.position)
+            .chain(replacement.chars())
+            .chain(old_content.chars().skip(misspelling.position + misspelling.word.chars().count()))
+            .collect();
+        content.set(new_content);
+        flush_journal.call(());
+        selected_misspelling.set(None);
+    });
+
+    // Verificado só quando o texto muda, não a cada tique do cronômetro
+    let misspellings = use_memo(move || {
+        if exam_mode() {
+            return Vec::new();
+        }
+        spell_checker
+            .read()
+            .as_ref()
+            .map(|checker| checker.check_text(&content()))
+            .unwrap_or_default()
+    });
+    // Texto dividido em trechos corretos e palavras sublinhadas
+    let spell_segments = {
+        let chars: Vec<char> = content().chars().collect();
+        let mut segments = Vec::new();
+        let mut cursor = 0;
+        for m in misspellings.read().iter() {
+            segments.push((chars[cursor..m.position].iter().collect::<String>(), None));
+            segments.push((m.word.clone(), Some(m.clone())));
+            cursor = m.position + m.word.chars().count();
+        }
+        segments.push((chars[cursor..].iter().collect::<String>(), None));
+        segments
+    };
+    let suggestions = selected_misspelling()
+        .and_then(|m| spell_checker.read().as_ref().map(|c| c.suggest(&m.word)))
+        .unwrap_or_default();
+
+    let line_counter_class = match line_check {
+        LineCheck::TooShort => "line-counter too-short",
+        LineCheck::Within => "line-counter",
+        LineCheck::TooLong => "line-counter too-long",
+    };
+    let timer_text = remaining_secs()
+        .map(|secs| format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60))
+        .unwrap_or_else(|| "--:--:--".to_string());
+
     rsx! {
         div {
-            class: "page-container",
-            h1 {
-                class: "page-title",
-                "NOVA REDAÇÃO"
+            class: if exam_mode() { "page-container exam-mode" } else { "page-container" },
+            if !exam_mode() {
+                h1 {
+                    class: "page-title",
+                    "NOVA REDAÇÃO"
+                }
+            }
+            div {
+                class: "exam-toolbar",
+                Toggle {
+                    label: "Modo prova".to_string(),
+                    checked: exam_mode(),
+                    on_change: move |value: bool| {
+                        exam_mode.set(value);
+                        remaining_secs.set(None);
+                        time_budget_minutes.set(AnswerSheet::for_exam(&exam_type.peek()).time_budget_minutes);
+                    }
+                }
+                Toggle {
+                    label: "Gravar processo de escrita".to_string(),
+                    checked: draft().recording.is_some(),
+                    on_change: move |value: bool| {
+                        // A gravação parte do texto já registrado no rascunho
+                        flush_journal.call(());
+                        if let Err(e) = recording_ctx.draft_store.set_recording(&mut draft.write(), value) {
+                            tracing::warn!("Failed to toggle writing recording: {}", e);
+                        }
+                    }
+                }
+                if exam_mode() {
+                    select {
+                        class: "neon-select",
+                        disabled: remaining_secs().is_some(),
+                        onchange: move |evt| {
+                            if let Ok(minutes) = evt.value().parse() {
+                                time_budget_minutes.set(minutes);
+                            }
+                        },
+                        for minutes in [60u16, 90, 120, 150, 180] {
+                            option {
+                                value: "{minutes}",
+                                selected: time_budget_minutes() == minutes,
+                                {format!("{} min", minutes)}
+                            }
+                        }
+                    }
+                    if remaining_secs().is_none() {
+                        NeonButton {
+                            variant: crate::components::neon_button::ButtonVariant::Primary,
+                            on_click: move |_| remaining_secs.set(Some(time_budget_minutes() as u32 * 60)),
+                            "Iniciar prova"
+                        }
+                    }
+                    span {
+                        class: if time_up { "exam-timer expired" } else { "exam-timer" },
+                        {if time_up { "Tempo esgotado".to_string() } else { timer_text }}
+                    }
+                }
+                span {
+                    class: line_counter_class,
+                    {match sheet.min_lines {
+                        Some(min_lines) => format!("Linhas: {}/{} (mín. {})", line_count, sheet.max_lines, min_lines),
+                        None => format!("Linhas: {}/{}", line_count, sheet.max_lines),
+                    }}
+                }
             }
             div {
                 class: "essay-editor",
@@ -68,6 +319,7 @@ pub fn NewEssay() -> Element {
                         value: title().to_string(),
                         on_input: move |value: String| {
                             title.set(value);
+                            journal_edit();
                         },
                         class: "essay-title-input".to_string()
                     }
@@ -82,6 +334,7 @@ pub fn NewEssay() -> Element {
                                 "unesp" => exam_type.set(ExamType::Unesp),
                                 _ => {}
                             }
+                            journal_edit();
                         },
                         option {
                             value: "enem",
@@ -110,10 +363,94 @@ pub fn NewEssay() -> Element {
                     placeholder: "Digite sua redação aqui...",
                     rows: "20",
                     value: content().to_string(),
+                    readonly: time_up,
                     oninput: move |evt| {
-                        content.set(evt.value());
+                        let mut value = evt.value();
+                        // A folha de resposta não comporta mais que o máximo de linhas:
+                        // digitação ou colagem além da última linha é cortada
+                        if exam_mode() {
+                            let sheet = AnswerSheet::for_exam(&exam_type.peek());
+                            if let Some(within) = sheet.truncate(&value) {
+                                value = within.to_string();
+                            }
+                        }
+                        content.set(value);
+                        journal_edit();
                     },
                 }
+                if !misspellings.is_empty() {
+                    div {
+                        class: "spell-preview",
+                        for (text, misspelling) in spell_segments {
+                            if let Some(m) = misspelling {
+                                span {
+                                    class: if selected_misspelling() == Some(m.clone()) { "misspelled selected" } else { "misspelled" },
+                                    title: "Clique para ver sugestões",
+                                    onclick: move |_| selected_misspelling.set(Some(m.clone())),
+                                    {text}
+                                }
+                            } else {
+                                span {
+                                    {text}
+                                }
+                            }
+                        }
+                    }
+                    if let Some(selected) = selected_misspelling() {
+                        div {
+                            class: "spell-suggestions",
+                            span {
+                                style: "color: #888;",
+                                {format!("\"{}\":", selected.word)}
+                            }
+                            if suggestions.is_empty() {
+                                span {
+                                    style: "color: #888;",
+                                    "Nenhuma sugestão"
+                                }
+                            }
+                            for suggestion in suggestions {
+                                button {
+                                    class: "neon-button neon-button-secondary",
+                                    onclick: {
+                                        let selected = selected.clone();
+                                        let suggestion = suggestion.clone();
+                                        move |_| replace_misspelling.call((selected.clone(), suggestion.clone()))
+                                    },
+                                    {suggestion.clone()}
+                                }
+                            }
+                            button {
+                                class: "neon-button",
+                                onclick: {
+                                    let word = selected.word.clone();
+                                    move |_| {
+                                        if let Some(checker) = spell_checker.write().as_mut() {
+                                            if let Err(e) = checker.add_to_user_dictionary(&word) {
+                                                tracing::warn!("Failed to update user dictionary: {}", e);
+                                            }
+                                        }
+                                        selected_misspelling.set(None);
+                                    }
+                                },
+                                "Adicionar ao dicionário"
+                            }
+                        }
+                    }
+                }
+                if let Some(time) = last_autosave() {
+                    p {
+                        class: "autosave-status",
+                        style: "color: #888; font-size: 0.85em; margin: 5px 0;",
+                        {format!("Rascunho salvo automaticamente às {}", time)}
+                    }
+                }
+                if exam_mode() && line_check == LineCheck::TooShort {
+                    p {
+                        style: "color: #ff6464; font-size: 0.85em; margin: 5px 0;",
+                        {format!("Textos com menos de {} linhas recebem nota zero.", sheet.min_lines.unwrap_or_default())}
+                    }
+                }
                 div {
                     class: "editor-actions",
                     NeonButton {
@@ -122,7 +459,7 @@ pub fn NewEssay() -> Element {
                         if is_saving() {
                             "Salvando..."
                         } else {
-                            "Salvar Rascunho"
+                            "Salvar Redação"
                         }
                     }
                     Link {
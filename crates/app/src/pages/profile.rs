@@ -2,6 +2,134 @@ use dioxus::prelude::*;
 use crate::components::*;
 use crate::context::AppContext;
 use shared::i18n::locale::get_supported_languages;
+use domain::essay::EssayStatus;
+use domain::text_metrics::TextMetrics;
+use domain::traits::EssayRepository;
+use services::compute_text_metrics;
+
+const CHART_WIDTH: f32 = 300.0;
+const CHART_HEIGHT: f32 = 120.0;
+
+/// SVG polyline points for values already normalized to 0–100
+fn chart_points(values: &[f32]) -> String {
+    let step = if values.len() > 1 {
+        CHART_WIDTH / (values.len() - 1) as f32
+    } else {
+        0.0
+    };
+    values
+        .iter()
+        .enumerate()
+        .map(|(idx, v)| {
+            let y = CHART_HEIGHT - v.clamp(0.0, 100.0) / 100.0 * CHART_HEIGHT;
+            format!("{:.1},{:.1}", idx as f32 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Readability and lexical metrics of the corrected essays, oldest first
+#[component]
+fn WritingMetricsChart() -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut history = use_signal(Vec::<TextMetrics>::new);
+
+    let ctx_for_load = ctx.clone();
+    use_effect(move || {
+        let ctx = ctx_for_load.clone();
+        spawn(async move {
+            if let Ok(mut essays) = ctx.essay_repo.list_by_user(ctx.current_user_id).await {
+                essays.retain(|e| e.status == EssayStatus::Corrigida);
+                essays.sort_by_key(|e| e.submitted_at.unwrap_or(e.updated_at));
+                history.set(
+                    essays
+                        .iter()
+                        .map(|e| {
+                            e.text_metrics
+                                .clone()
+                                .unwrap_or_else(|| compute_text_metrics(&e.content))
+                        })
+                        .collect(),
+                );
+            }
+        });
+    });
+
+    let metrics = history();
+    let latest = metrics.last().cloned().unwrap_or_default();
+    let series = [
+        (
+            ctx.t("profile-metrics-readability"),
+            "#00ffff",
+            metrics.iter().map(|m| m.flesch_martins).collect::<Vec<_>>(),
+            format!("{:.0}", latest.flesch_martins),
+        ),
+        (
+            ctx.t("profile-metrics-diversity"),
+            "#ff00ff",
+            // MTLD rarely exceeds 150 in essays; scale it onto the same axis
+            metrics.iter().map(|m| m.mtld / 1.5).collect(),
+            format!("{:.0}", latest.mtld),
+        ),
+        (
+            ctx.t("profile-metrics-ttr"),
+            "#00ff88",
+            metrics.iter().map(|m| m.type_token_ratio * 100.0).collect(),
+            format!("{:.2}", latest.type_token_ratio),
+        ),
+    ];
+
+    rsx! {
+        div {
+            class: "panel-card writing-metrics",
+            h3 {
+                "{ctx.t(\"profile-metrics-title\")}"
+            }
+            if metrics.is_empty() {
+                p {
+                    style: "color: #888888;",
+                    "{ctx.t(\"profile-metrics-empty\")}"
+                }
+            } else {
+                svg {
+                    width: "100%",
+                    height: "{CHART_HEIGHT}",
+                    view_box: "-4 -4 {CHART_WIDTH + 8.0} {CHART_HEIGHT + 8.0}",
+                    preserve_aspect_ratio: "none",
+                    xmlns: "http://www.w3.org/2000/svg",
+                    for (_, color, values, _) in series.iter() {
+                        polyline {
+                            points: chart_points(values),
+                            fill: "none",
+                            stroke: "{color}",
+                            "stroke-width": "2",
+                        }
+                    }
+                }
+                div {
+                    class: "chart-legend",
+                    style: "display: flex; flex-wrap: wrap; gap: 12px; font-size: 0.85em;",
+                    for (label, color, _, current) in series.iter() {
+                        span {
+                            style: "color: {color};",
+                            {format!("{}: {}", label, current)}
+                        }
+                    }
+                }
+                p {
+                    style: "color: #aaaaaa; font-size: 0.85em;",
+                    {format!(
+                        "{}: {:.0}% · {}: {}",
+                        ctx.t("profile-metrics-passive"),
+                        latest.passive_voice_ratio * 100.0,
+                        ctx.t("profile-metrics-informal"),
+                        latest.register.total(),
+                    )}
+                }
+            }
+        }
+    }
+}
 
 #[component]
 pub fn Profile() -> Element {
@@ -9,6 +137,7 @@ pub fn Profile() -> Element {
     let mut notifications_enabled = use_signal(|| true);
     let mut show_import_modal = use_signal(|| false);
     let mut selected_locale = use_signal(|| ctx.current_locale());
+    let mut active_tab = use_signal(|| "preferences".to_string());
     let supported_languages = get_supported_languages();
     
     rsx! {
@@ -75,6 +204,7 @@ pub fn Profile() -> Element {
                                     }
                                 }
                             }
+                            WritingMetricsChart {}
                         }
                         
                         // Right Panel: Settings with Tabs
@@ -100,32 +230,14 @@ pub fn Profile() -> Element {
                                 
                                 button {
                                     class: if active_tab() == "preferences" { "tab-button active" } else { "tab-button" },
-                                    style: "
-                                        padding: 12px 24px;
-                                        background: {if active_tab() == \"preferences\" { \"rgba(0, 255, 255, 0.2)\" } else { \"transparent\" }};
-                                        border: none;
-                                        border-bottom: 2px solid {if active_tab() == \"preferences\" { \"#00ffff\" } else { \"transparent\" }};
-                                        color: {if active_tab() == \"preferences\" { \"#00ffff\" } else { \"#888888\" }};
-                                        cursor: pointer;
-                                        font-size: 1em;
-                                        transition: all 0.3s ease;
-                                    ",
+                                    style: tab_button_style(active_tab() == "preferences", "#00ffff", "rgba(0, 255, 255, 0.2)"),
                                     onclick: move |_| active_tab.set("preferences".to_string()),
                                     "{ctx.t(\"profile-section-preferences\")}"
                                 }
                                 
                                 button {
                                     class: if active_tab() == "ai-config" { "tab-button active" } else { "tab-button" },
-                                    style: "
-                                        padding: 12px 24px;
-                                        background: {if active_tab() == \"ai-config\" { \"rgba(255, 0, 255, 0.2)\" } else { \"transparent\" }};
-                                        border: none;
-                                        border-bottom: 2px solid {if active_tab() == \"ai-config\" { \"#ff00ff\" } else { \"transparent\" }};
-                                        color: {if active_tab() == \"ai-config\" { \"#ff00ff\" } else { \"#888888\" }};
-                                        cursor: pointer;
-                                        font-size: 1em;
-                                        transition: all 0.3s ease;
-                                    ",
+                                    style: tab_button_style(active_tab() == "ai-config", "#ff00ff", "rgba(255, 0, 255, 0.2)"),
                                     onclick: move |_| active_tab.set("ai-config".to_string()),
                                     "🤖 {ctx.t(\"profile-ai-title\")}"
                                 }
@@ -151,7 +263,7 @@ pub fn Profile() -> Element {
                                                 value: "{selected_locale()}",
                                                 onchange: move |evt| {
                                                     let new_locale = evt.value();
-                                                    if let Ok(_) = ctx.set_locale(&new_locale) {
+                                                    if ctx.set_locale(&new_locale).is_ok() {
                                                         selected_locale.set(new_locale.clone());
                                                     }
                                                 },
@@ -194,3 +306,17 @@ pub fn Profile() -> Element {
         }
     }
 }
+
+/// Inline style of a settings tab button, lit in its color while active
+fn tab_button_style(active: bool, color: &str, tint: &str) -> String {
+    let (background, border, text) = if active {
+        (tint, color, color)
+    } else {
+        ("transparent", "transparent", "#888888")
+    };
+    format!(
+        "padding: 12px 24px; background: {background}; border: none; \
+         border-bottom: 2px solid {border}; color: {text}; cursor: pointer; \
+         font-size: 1em; transition: all 0.3s ease;"
+    )
+}
@@ -1,28 +1,47 @@
 use dioxus::prelude::*;
-use crate::components::*;
+use dioxus_router::Link;
+use crate::app::Route;
 use crate::context::AppContext;
+use domain::question::Question;
 use domain::traits::QuestionRepository;
 use uuid::Uuid;
 
+/// Questões semelhantes listadas abaixo da questão
+const SIMILAR_LIMIT: usize = 5;
+
 #[component]
 pub fn QuestionDetail(id: String) -> Element {
+    let ctx = use_context::<AppContext>();
     let mut question = use_signal(|| None);
+    let mut similar = use_signal(Vec::<Question>::new);
     let mut selected_answer = use_signal(|| None::<usize>);
     let mut show_explanation = use_signal(|| false);
+    let ctx_for_answer = ctx.clone();
     
     // Carregar questão
     use_effect(move || {
         let id_clone = id.clone();
+        let ctx = ctx.clone();
         spawn(async move {
             let question_id = match Uuid::parse_str(&id_clone) {
                 Ok(uuid) => uuid,
                 Err(_) => return,
             };
             
-            let ctx = AppContext::new();
             if let Ok(Some(q)) = ctx.question_repo.find_by_id(question_id).await {
                 question.set(Some(q));
             }
+            
+            // Questões semelhantes pelo índice semântico (vazio sem o modelo carregado;
+            // o banco é indexado quando o modelo fica pronto)
+            let neighbours = ctx.question_search.similar(question_id, SIMILAR_LIMIT).await;
+            let mut found = Vec::new();
+            for hit in neighbours {
+                if let Ok(Some(q)) = ctx.question_repo.find_by_id(hit.id).await {
+                    found.push(q);
+                }
+            }
+            similar.set(found);
         });
     });
     
@@ -55,9 +74,21 @@ pub fn QuestionDetail(id: String) -> Element {
                         for (idx, alt) in q.alternatives.iter().enumerate() {
                             div {
                                 class: "alternative-item",
-                                onclick: move |_| {
-                                    selected_answer.set(Some(idx));
-                                    show_explanation.set(true);
+                                onclick: {
+                                    let ctx = ctx_for_answer.clone();
+                                    let question_id = q.id;
+                                    let correct = idx == q.correct_answer;
+                                    move |_| {
+                                        selected_answer.set(Some(idx));
+                                        show_explanation.set(true);
+                                        // Alimenta o filtro de respondidas da lista de questões
+                                        let ctx = ctx.clone();
+                                        spawn(async move {
+                                            if let Err(e) = ctx.question_repo.record_answer(ctx.current_user_id, question_id, correct).await {
+                                                tracing::warn!("Failed to record answer: {}", e);
+                                            }
+                                        });
+                                    }
                                 },
                                 input {
                                     r#type: "radio",
@@ -101,6 +132,27 @@ pub fn QuestionDetail(id: String) -> Element {
                             }
                         }
                     }
+                    if !similar().is_empty() {
+                        div {
+                            class: "similar-questions",
+                            h3 {
+                                "Questões semelhantes:"
+                            }
+                            for other in similar().iter() {
+                                Link {
+                                    to: Route::QuestionDetail { id: other.id.to_string() },
+                                    div {
+                                        class: "similar-question-item",
+                                        span {
+                                            class: "question-subject",
+                                            {other.subject.display_name()}
+                                        }
+                                        {format!(" {}...", other.statement.chars().take(100).collect::<String>())}
+                                    }
+                                }
+                            }
+                        }
+                    }
                 }
             } else {
                 div {
@@ -3,34 +3,118 @@ use dioxus_router::Link;
 use crate::components::*;
 use crate::context::AppContext;
 use crate::app::Route;
+use domain::question::{Difficulty, Question, Subject};
+use domain::question_query::{AnsweredFilter, QuestionCursor, QuestionPage, QuestionQuery, QuestionSort, SemanticScores};
 use domain::traits::QuestionRepository;
+use shared::Error;
+
+/// Pausa na digitação antes de consultar; cada consulta com texto passa pelo codificador
+const SEARCH_DEBOUNCE_MS: u64 = 300;
 
 #[component]
 pub fn Questions() -> Element {
-    let mut search_query = use_signal(|| String::new());
-    let mut questions = use_signal(|| Vec::new());
+    let ctx = use_context::<AppContext>();
+    // O que está no campo de busca; `search_query` só o acompanha quando a digitação para
+    let mut typed = use_signal(String::new);
+    let mut search_query = use_signal(String::new);
+    let mut subject = use_signal(|| None::<Subject>);
+    let mut difficulty = use_signal(|| None::<Difficulty>);
+    // Some(true) para respondidas, Some(false) para não respondidas
+    let mut answered = use_signal(|| None::<bool>);
+    let mut sort = use_signal(QuestionSort::default);
+    let mut questions = use_signal(Vec::<Question>::new);
+    let mut total = use_signal(|| 0usize);
+    let mut next = use_signal(|| None::<QuestionCursor>);
+    // Incrementado a cada nova consulta, para descartar páginas de consultas antigas
+    let mut generation = use_signal(|| 0u64);
+    // Similaridades do último texto buscado; reaproveitadas ao trocar filtros e nas páginas seguintes
+    let mut semantic = use_signal(|| None::<(String, SemanticScores)>);
+    let mut error = use_signal(|| None::<String>);
+    
+    let user_id = ctx.current_user_id;
+    let build_query = move || {
+        let text = search_query();
+        QuestionQuery {
+            subjects: subject().into_iter().collect(),
+            difficulties: difficulty().into_iter().collect(),
+            answered: answered().map(|answered| AnsweredFilter { user_id, answered }),
+            text: (!text.trim().is_empty()).then_some(text),
+            sort: sort(),
+            ..Default::default()
+        }
+    };
     
-    // Carregar questões na inicialização
+    // Primeira página sempre que a busca ou os filtros mudam
+    let ctx_for_load = ctx.clone();
     use_effect(move || {
+        let query = build_query();
+        let ctx = ctx_for_load.clone();
+        let current = *generation.peek() + 1;
+        generation.set(current);
+        error.set(None);
         spawn(async move {
-            let ctx = AppContext::new();
-            if let Ok(all_questions) = ctx.question_repo.search("").await {
-                questions.set(all_questions);
+            let cached = cached_scores(&semantic.peek(), &query);
+            let scores = match (&query.text, cached) {
+                (Some(_), Some(scores)) => Some(scores),
+                (Some(text), None) => semantic_scores(&ctx, text).await,
+                (None, _) => None,
+            };
+            let query = QuestionQuery { semantic: scores.clone(), ..query };
+            let page = load_page(&ctx, &query).await;
+            // Ignore results of a query the user has already changed
+            if *generation.peek() != current {
+                return;
+            }
+            if let (Some(text), Some(scores)) = (query.text, scores) {
+                semantic.set(Some((text, scores)));
+            }
+            if let Some(page) = page {
+                questions.set(page.questions);
+                total.set(page.total);
+                next.set(page.next);
             }
         });
     });
     
-    // Filtrar questões baseado na busca
-    let filtered_questions: Vec<_> = questions()
-        .iter()
-        .filter(|q| {
-            search_query().is_empty() || 
-            q.statement.to_lowercase().contains(&search_query().to_lowercase()) ||
-            q.tags.iter().any(|tag| tag.to_lowercase().contains(&search_query().to_lowercase()))
-        })
-        .take(20)
-        .cloned()
-        .collect();
+    let load_more = move |_| {
+        let cursor = match next() {
+            Some(cursor) => cursor,
+            None => return,
+        };
+        let query = build_query();
+        // Mesmas similaridades da primeira página, para o cursor seguir a mesma ordem
+        let query = QuestionQuery { after: Some(cursor), semantic: cached_scores(&semantic.peek(), &query), ..query };
+        let ctx = ctx.clone();
+        let current = *generation.peek();
+        error.set(None);
+        spawn(async move {
+            let (page, restarted) = match ctx.question_repo.query(&query).await {
+                Ok(page) => (Some(page), false),
+                // Cursor recusado porque o índice mudou: refaz a lista até esta página
+                Err(Error::StaleCursor) => {
+                    let limit = questions.peek().len() + query.limit;
+                    let query = QuestionQuery { after: None, limit, ..query };
+                    (load_page(&ctx, &query).await, true)
+                }
+                Err(e) => {
+                    error.set(Some(format!("Erro ao carregar mais questões: {}", e)));
+                    return;
+                }
+            };
+            if let Some(page) = page {
+                // A página só continua a lista se a consulta não mudou enquanto carregava
+                if *generation.peek() == current && *next.peek() == Some(cursor) {
+                    if restarted {
+                        questions.set(page.questions);
+                        total.set(page.total);
+                    } else {
+                        questions.write().extend(page.questions);
+                    }
+                    next.set(page.next);
+                }
+            }
+        });
+    };
     
     rsx! {
         div {
@@ -48,22 +132,92 @@ pub fn Questions() -> Element {
                         class: "search-section",
                         NeonInput {
                             placeholder: "Buscar questões...".to_string(),
-                            value: search_query().to_string(),
+                            value: typed(),
                             on_input: move |value: String| {
-                                search_query.set(value);
+                                typed.set(value.clone());
+                                spawn(async move {
+                                    tokio::time::sleep(tokio::time::Duration::from_millis(SEARCH_DEBOUNCE_MS)).await;
+                                    if *typed.peek() == value && *search_query.peek() != value {
+                                        search_query.set(value);
+                                    }
+                                });
+                            },
+                        }
+                    }
+                    div {
+                        class: "question-filters",
+                        select {
+                            class: "neon-select",
+                            onchange: move |evt| {
+                                subject.set(evt.value().parse::<usize>().ok().and_then(|i| Subject::ALL.get(i).cloned()));
+                            },
+                            option { value: "", "Todas as matérias" }
+                            for (i, s) in Subject::ALL.iter().enumerate() {
+                                option {
+                                    value: "{i}",
+                                    selected: subject().as_ref() == Some(s),
+                                    {s.display_name()}
+                                }
+                            }
+                        }
+                        select {
+                            class: "neon-select",
+                            onchange: move |evt| {
+                                difficulty.set(evt.value().parse::<usize>().ok().and_then(|i| Difficulty::ALL.get(i).cloned()));
+                            },
+                            option { value: "", "Todas as dificuldades" }
+                            for (i, d) in Difficulty::ALL.iter().enumerate() {
+                                option {
+                                    value: "{i}",
+                                    selected: difficulty().as_ref() == Some(d),
+                                    {d.display_name()}
+                                }
+                            }
+                        }
+                        select {
+                            class: "neon-select",
+                            onchange: move |evt| {
+                                answered.set(match evt.value().as_str() {
+                                    "answered" => Some(true),
+                                    "unanswered" => Some(false),
+                                    _ => None,
+                                });
+                            },
+                            option { value: "", "Respondidas ou não" }
+                            option { value: "unanswered", selected: answered() == Some(false), "Não respondidas" }
+                            option { value: "answered", selected: answered() == Some(true), "Respondidas" }
+                        }
+                        select {
+                            class: "neon-select",
+                            onchange: move |evt| {
+                                if let Some(s) = evt.value().parse::<usize>().ok().and_then(|i| QuestionSort::ALL.get(i)) {
+                                    sort.set(*s);
+                                }
                             },
+                            for (i, s) in QuestionSort::ALL.iter().enumerate() {
+                                option {
+                                    value: "{i}",
+                                    selected: sort() == *s,
+                                    {s.display_name()}
+                                }
+                            }
                         }
                     }
+                    p {
+                        class: "question-count",
+                        {format!("{} questões", total())}
+                    }
                     div {
                         class: "questions-list",
-                        if filtered_questions.is_empty() {
+                        if questions().is_empty() {
                             div {
                                 class: "empty-state",
                                 "Nenhuma questão encontrada"
                             }
                         } else {
-                            for question in filtered_questions.iter() {
+                            for question in questions().iter() {
                                 Link {
+                                    key: "{question.id}",
                                     to: Route::QuestionDetail { id: question.id.to_string() },
                                     QuestionCard {
                                         id: question.id.to_string(),
@@ -75,6 +229,19 @@ pub fn Questions() -> Element {
                             }
                         }
                     }
+                    if let Some(message) = error() {
+                        p {
+                            style: "color: #ff6464; margin-top: 6px;",
+                            {message}
+                        }
+                    }
+                    if next().is_some() {
+                        NeonButton {
+                            variant: ButtonVariant::Secondary,
+                            on_click: load_more,
+                            "Carregar mais"
+                        }
+                    }
                 }
             }
             TabBar {}
@@ -82,6 +249,35 @@ pub fn Questions() -> Element {
     }
 }
 
+/// Similaridades guardadas, se forem do texto de `query`
+fn cached_scores(cache: &Option<(String, SemanticScores)>, query: &QuestionQuery) -> Option<SemanticScores> {
+    match (cache, &query.text) {
+        (Some((text, scores)), Some(query_text)) if text == query_text => Some(scores.clone()),
+        _ => None,
+    }
+}
+
+/// Similaridade de `text` com o banco; `None` sem o modelo carregado
+async fn semantic_scores(ctx: &AppContext, text: &str) -> Option<SemanticScores> {
+    match ctx.question_search.semantic_scores(text).await {
+        Ok(scores) => scores,
+        Err(e) => {
+            tracing::warn!("Question search failed: {}", e);
+            None
+        }
+    }
+}
+
+async fn load_page(ctx: &AppContext, query: &QuestionQuery) -> Option<QuestionPage> {
+    match ctx.question_repo.query(query).await {
+        Ok(page) => Some(page),
+        Err(e) => {
+            tracing::warn!("Question query failed: {}", e);
+            None
+        }
+    }
+}
+
 #[derive(Props, PartialEq, Clone)]
 struct QuestionCardProps {
     id: String,
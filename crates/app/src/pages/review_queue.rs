@@ -0,0 +1,233 @@
+use dioxus::prelude::*;
+use dioxus_router::Link;
+use crate::app::Route;
+use crate::components::*;
+use crate::context::AppContext;
+use domain::essay::Essay;
+use domain::traits::EssayRepository;
+use services::{confirm_review, override_scores, review_queue};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Correções automáticas incertas aguardando confirmação do professor
+#[component]
+pub fn ReviewQueue() -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut queue = use_signal(Vec::<Essay>::new);
+
+    use_effect(move || {
+        let ctx = ctx.clone();
+        spawn(async move {
+            if let Ok(pending) = ctx.essay_repo.list_pending_review().await {
+                queue.set(review_queue(pending));
+            }
+        });
+    });
+
+    rsx! {
+        div {
+            class: "app-container",
+            StatusBar {}
+            main {
+                class: "main-content",
+                div {
+                    class: "page-container",
+                    div {
+                        class: "page-header",
+                        h1 {
+                            class: "page-title",
+                            "FILA DE REVISÃO"
+                        }
+                    }
+                    p {
+                        style: "color: #aaaaaa; margin-bottom: 20px;",
+                        "Redações com notas incertas ou no limite entre níveis, da mais incerta para a menos incerta."
+                    }
+                    if queue().is_empty() {
+                        div {
+                            class: "empty-state",
+                            "Nenhuma correção aguardando revisão."
+                        }
+                    } else {
+                        for essay in queue() {
+                            ReviewCard {
+                                key: "{essay.id}",
+                                essay_id: essay.id,
+                                queue: queue,
+                            }
+                        }
+                    }
+                }
+            }
+            TabBar {}
+        }
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct ReviewCardProps {
+    essay_id: Uuid,
+    queue: Signal<Vec<Essay>>,
+}
+
+/// Notas, intervalos e motivos de uma redação, com confirmação ou ajuste das notas
+#[component]
+fn ReviewCard(props: ReviewCardProps) -> Element {
+    let ctx = use_context::<AppContext>();
+    let queue = props.queue;
+    let essay_id = props.essay_id;
+    let mut edits = use_signal(HashMap::<String, String>::new);
+    let mut note = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let Some(essay) = queue().into_iter().find(|e| e.id == essay_id) else {
+        return rsx! {};
+    };
+
+    let mut criteria: Vec<(String, u16)> = essay
+        .rubric_scores
+        .as_ref()
+        .map(|r| r.scores.iter().map(|(k, v)| (k.clone(), *v)).collect())
+        .unwrap_or_default();
+    criteria.sort();
+    let reasons = essay.review.as_ref().map(|r| r.reasons.clone()).unwrap_or_default();
+    let confidence = essay.confidence.clone().unwrap_or_default();
+
+    let on_confirm = {
+        let essay = essay.clone();
+        let ctx = ctx.clone();
+        move |_| {
+            let mut reviewed = essay.clone();
+            match confirm_review(&mut reviewed, Some(note())) {
+                Ok(()) => save_review(ctx.clone(), reviewed, queue, error),
+                Err(e) => error.set(Some(e.to_string())),
+            }
+        }
+    };
+
+    let on_override = {
+        let essay = essay.clone();
+        let ctx = ctx.clone();
+        move |_| {
+            let mut scores = HashMap::new();
+            for (criterion, value) in edits().iter() {
+                match value.trim().parse::<u16>() {
+                    Ok(score) => {
+                        scores.insert(criterion.clone(), score);
+                    }
+                    Err(_) => {
+                        error.set(Some(format!("Nota inválida em {}", criterion)));
+                        return;
+                    }
+                }
+            }
+            let mut reviewed = essay.clone();
+            match override_scores(&mut reviewed, scores, Some(note())) {
+                Ok(()) => save_review(ctx.clone(), reviewed, queue, error),
+                Err(e) => error.set(Some(e.to_string())),
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: "review-card",
+            style: "margin: 15px 0; padding: 15px; background: rgba(0, 255, 255, 0.05); border-left: 3px solid #ffaa00;",
+            div {
+                style: "display: flex; justify-content: space-between; align-items: baseline;",
+                Link {
+                    to: Route::EssayDetail { id: essay.id.to_string() },
+                    h3 {
+                        style: "color: #00ffff; margin: 0;",
+                        {essay.title.clone()}
+                    }
+                }
+                span {
+                    style: "color: #aaaaaa;",
+                    {essay.exam_type.display_name()}
+                }
+            }
+            ul {
+                style: "color: #ffaa00; margin: 10px 0;",
+                for reason in reasons {
+                    li {
+                        {reason.description()}
+                    }
+                }
+            }
+            for (criterion, score) in criteria {
+                div {
+                    class: "rubric-item",
+                    style: "display: flex; gap: 12px; align-items: center; margin: 6px 0;",
+                    span {
+                        style: "font-weight: bold; color: #00ffff; min-width: 40px;",
+                        {criterion.clone()}
+                    }
+                    span {
+                        style: "min-width: 140px;",
+                        if let Some(interval) = confidence.get(&criterion) {
+                            {format!("{} ({}–{})", score, interval.lower, interval.upper)}
+                        } else {
+                            {score.to_string()}
+                        }
+                    }
+                    input {
+                        class: "neon-input",
+                        r#type: "number",
+                        style: "width: 90px;",
+                        value: edits().get(&criterion).cloned().unwrap_or_else(|| score.to_string()),
+                        oninput: {
+                            let criterion = criterion.clone();
+                            move |evt: FormEvent| {
+                                let value = evt.value();
+                                if value == score.to_string() {
+                                    edits.write().remove(&criterion);
+                                } else {
+                                    edits.write().insert(criterion.clone(), value);
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+            textarea {
+                class: "neon-input",
+                style: "width: 100%; margin-top: 10px;",
+                placeholder: "Comentário do professor (opcional)",
+                value: note(),
+                oninput: move |evt| note.set(evt.value()),
+            }
+            div {
+                style: "display: flex; gap: 10px; margin-top: 10px;",
+                button {
+                    class: "neon-button",
+                    onclick: on_confirm,
+                    "Confirmar notas"
+                }
+                button {
+                    class: "neon-button neon-button-secondary",
+                    disabled: edits().is_empty(),
+                    onclick: on_override,
+                    "Salvar alterações"
+                }
+            }
+            if let Some(message) = error() {
+                p {
+                    style: "color: #ff6464; margin-top: 6px;",
+                    {message}
+                }
+            }
+        }
+    }
+}
+
+/// Salva a revisão e tira a redação da fila
+fn save_review(ctx: AppContext, reviewed: Essay, mut queue: Signal<Vec<Essay>>, mut error: Signal<Option<String>>) {
+    let essay_id = reviewed.id;
+    spawn(async move {
+        match ctx.essay_repo.update(reviewed).await {
+            Ok(()) => queue.write().retain(|e| e.id != essay_id),
+            Err(e) => error.set(Some(format!("Erro ao salvar revisão: {}", e))),
+        }
+    });
+}
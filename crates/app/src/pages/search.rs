@@ -0,0 +1,180 @@
+use dioxus::prelude::*;
+use dioxus_router::Link;
+use crate::app::Route;
+use crate::components::*;
+use crate::context::AppContext;
+use data::{DocumentKind, SearchHit, Snippet};
+use domain::traits::KnowledgeTrailRepository;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const RESULT_LIMIT: usize = 30;
+
+/// Busca única em questões, leituras e trilhas
+#[component]
+pub fn Search() -> Element {
+    let ctx = use_context::<AppContext>();
+    let mut query = use_signal(String::new);
+    // Leitura -> (trilha, módulo) que a apresenta, para abrir no visualizador de lições
+    let mut lessons = use_signal(HashMap::<Uuid, (Uuid, Uuid)>::new);
+
+    let ctx_for_load = ctx.clone();
+    use_effect(move || {
+        let ctx = ctx_for_load.clone();
+        spawn(async move {
+            if let Ok(trails) = ctx.trail_repo.list_available().await {
+                let mut map = HashMap::new();
+                for trail in trails {
+                    for module in trail.modules {
+                        map.entry(module.content_id).or_insert((trail.id, module.id));
+                    }
+                }
+                lessons.set(map);
+            }
+        });
+    });
+
+    let hits = if query().trim().is_empty() {
+        Vec::new()
+    } else {
+        ctx.search_index.search(&query(), RESULT_LIMIT)
+    };
+
+    rsx! {
+        div {
+            class: "app-container",
+            StatusBar {}
+            main {
+                class: "main-content",
+                div {
+                    class: "page-container",
+                    h1 {
+                        class: "page-title",
+                        "BUSCA"
+                    }
+                    div {
+                        class: "search-section",
+                        NeonInput {
+                            placeholder: "Buscar questões, leituras e trilhas...".to_string(),
+                            value: query().to_string(),
+                            on_input: move |value: String| {
+                                query.set(value);
+                            },
+                        }
+                    }
+                    div {
+                        class: "search-results",
+                        if query().trim().is_empty() {
+                            div {
+                                class: "empty-state",
+                                "Digite um termo para buscar"
+                            }
+                        } else if hits.is_empty() {
+                            div {
+                                class: "empty-state",
+                                "Nenhum resultado encontrado"
+                            }
+                        } else {
+                            for hit in hits {
+                                SearchResult {
+                                    key: "{hit.kind:?}-{hit.id}",
+                                    route: result_route(&hit, &lessons()),
+                                    hit: hit,
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            TabBar {}
+        }
+    }
+}
+
+/// Página que abre o resultado; leituras fora de trilhas não têm uma
+fn result_route(hit: &SearchHit, lessons: &HashMap<Uuid, (Uuid, Uuid)>) -> Option<Route> {
+    match hit.kind {
+        DocumentKind::Question => Some(Route::QuestionDetail { id: hit.id.to_string() }),
+        DocumentKind::Trail => Some(Route::TrailDetail { trail_id: hit.id.to_string() }),
+        DocumentKind::Reading => lessons.get(&hit.id).map(|(trail_id, module_id)| Route::LessonViewer {
+            trail_id: trail_id.to_string(),
+            module_id: module_id.to_string(),
+        }),
+    }
+}
+
+fn kind_label(kind: DocumentKind) -> &'static str {
+    match kind {
+        DocumentKind::Question => "Questão",
+        DocumentKind::Reading => "Leitura",
+        DocumentKind::Trail => "Trilha",
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct SearchResultProps {
+    hit: SearchHit,
+    route: Option<Route>,
+}
+
+#[component]
+fn SearchResult(props: SearchResultProps) -> Element {
+    let hit = props.hit;
+    let title: String = if hit.title.chars().count() > 120 {
+        format!("{}…", hit.title.chars().take(120).collect::<String>())
+    } else {
+        hit.title.clone()
+    };
+
+    rsx! {
+        div {
+            class: "search-result",
+            span {
+                class: "search-result-kind",
+                "{kind_label(hit.kind)}"
+            }
+            if let Some(route) = props.route {
+                Link {
+                    class: "search-result-title",
+                    to: route,
+                    "{title}"
+                }
+            } else {
+                span {
+                    class: "search-result-title",
+                    "{title}"
+                }
+            }
+            HighlightedSnippet { snippet: hit.snippet }
+        }
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+struct HighlightedSnippetProps {
+    snippet: Snippet,
+}
+
+/// Trecho do documento com os termos encontrados destacados
+#[component]
+fn HighlightedSnippet(props: HighlightedSnippetProps) -> Element {
+    let segments: Vec<(String, bool)> = props
+        .snippet
+        .segments()
+        .into_iter()
+        .map(|(text, highlighted)| (text.to_string(), highlighted))
+        .collect();
+
+    rsx! {
+        p {
+            class: "search-result-snippet",
+            for (text, highlighted) in segments {
+                if highlighted {
+                    mark { "{text}" }
+                } else {
+                    "{text}"
+                }
+            }
+        }
+    }
+}
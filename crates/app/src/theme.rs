@@ -671,6 +671,19 @@ body {
     margin-bottom: 2rem;
 }
 
+.question-filters {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 0.75rem;
+    margin-bottom: 1rem;
+}
+
+.question-count {
+    margin-bottom: 1rem;
+    color: var(--light-gray);
+    font-size: 0.9rem;
+}
+
 /* Page Header */
 .page-header {
     display: flex;
@@ -1201,6 +1214,62 @@ body {
     color: var(--neon-purple-light);
 }
 
+.similar-questions {
+    margin-top: 2rem;
+}
+
+.similar-question-item {
+    padding: 0.75rem 1rem;
+    margin-bottom: 0.5rem;
+    background: rgba(10, 10, 10, 0.8);
+    border: 1px solid var(--neon-purple);
+    border-radius: 12px;
+    color: var(--light-gray);
+}
+
+.similar-question-item:hover {
+    border-color: var(--neon-cyan);
+}
+
+/* Unified Search */
+.search-result {
+    padding: 1rem;
+    margin-bottom: 0.75rem;
+    background: rgba(10, 10, 10, 0.8);
+    border: 1px solid var(--neon-purple);
+    border-radius: 12px;
+}
+
+.search-result:hover {
+    border-color: var(--neon-cyan);
+}
+
+.search-result-kind {
+    display: inline-block;
+    margin-right: 0.5rem;
+    padding: 0.1rem 0.5rem;
+    border: 1px solid var(--neon-cyan);
+    border-radius: 8px;
+    color: var(--neon-cyan);
+    font-size: 0.75rem;
+    text-transform: uppercase;
+}
+
+.search-result-title {
+    color: var(--white);
+    font-weight: bold;
+}
+
+.search-result-snippet {
+    margin-top: 0.5rem;
+    color: var(--light-gray);
+}
+
+.search-result-snippet mark {
+    background: var(--muted-purple);
+    color: var(--neon-purple-light);
+}
+
 /* Essay Detail */
 .essay-detail {
     max-width: 900px;
@@ -1336,6 +1405,74 @@ body {
     justify-content: flex-end;
 }
 
+/* Exam Conditions Mode */
+.exam-mode {
+    position: fixed;
+    inset: 0;
+    z-index: 9000;
+    background: #0a0a0f;
+    overflow-y: auto;
+    padding: 2rem;
+}
+
+.exam-toolbar {
+    display: flex;
+    gap: 1rem;
+    align-items: center;
+    justify-content: space-between;
+    flex-wrap: wrap;
+    margin-bottom: 1rem;
+}
+
+.exam-timer {
+    font-family: monospace;
+    font-size: 1.5rem;
+    color: var(--neon-cyan);
+}
+
+.exam-timer.expired {
+    color: #ff6464;
+}
+
+.line-counter {
+    font-family: monospace;
+    color: var(--neon-purple-light);
+}
+
+.line-counter.too-short,
+.line-counter.too-long {
+    color: #ff6464;
+}
+
+/* Spell checking */
+.spell-preview {
+    margin-top: 10px;
+    padding: 12px;
+    border: 1px solid rgba(0, 255, 255, 0.2);
+    border-radius: 8px;
+    white-space: pre-wrap;
+    line-height: 1.6;
+    color: #cccccc;
+}
+
+.misspelled {
+    text-decoration: underline wavy #ff4d6d;
+    text-underline-offset: 3px;
+    cursor: pointer;
+}
+
+.misspelled.selected {
+    background: rgba(255, 77, 109, 0.2);
+}
+
+.spell-suggestions {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 8px;
+    align-items: center;
+    margin-top: 8px;
+}
+
 /* Empty State */
 .empty-state {
     text-align: center;
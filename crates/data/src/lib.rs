@@ -1,5 +1,7 @@
 pub mod repositories;
+pub mod search;
 pub mod seeders;
 
 pub use repositories::*;
+pub use search::{DocumentKind, SearchDocument, SearchHit, SearchIndex, Snippet};
 pub use seeders::*;
@@ -1,31 +1,53 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use domain::{
     essay::{Essay, EssayStatus, ExamType, ExamRubric},
     question::{Question, Subject, Difficulty},
+    question_query::{QuestionCursor, QuestionPage, QuestionQuery, QuestionSort, SemanticScores},
     user::{UserProfile, UserSettings, StudyProgress},
     knowledge_trail::KnowledgeTrail,
     reading_content::ReadingContent,
+    revision::EssayRevision,
+    provenance::EvaluationRecord,
+    writing_process::WritingRecording,
     traits::*,
 };
-use shared::Result;
+use shared::{Error, Result};
+use crate::search::{text, DocumentKind, SearchDocument, SearchIndex};
 
 // In-memory implementations for development
 
 pub struct InMemoryEssayRepository {
     essays: Arc<RwLock<HashMap<Uuid, Essay>>>,
+    /// Histórico que recebe uma revisão a cada mudança de texto ou de nota
+    revisions: Option<Arc<InMemoryEssayRevisionRepository>>,
 }
 
 impl InMemoryEssayRepository {
     pub fn new() -> Self {
         Self {
             essays: Arc::new(RwLock::new(HashMap::new())),
+            revisions: None,
+        }
+    }
+
+    /// Repositório que registra em `revisions` cada redação atualizada ou corrigida de novo
+    pub fn with_revisions(revisions: Arc<InMemoryEssayRevisionRepository>) -> Self {
+        Self {
+            revisions: Some(revisions),
+            ..Self::new()
         }
     }
 }
 
+impl Default for InMemoryEssayRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl EssayRepository for InMemoryEssayRepository {
     async fn save(&self, essay: Essay) -> Result<()> {
@@ -66,8 +88,163 @@ impl EssayRepository for InMemoryEssayRepository {
             .collect())
     }
 
+    async fn list_pending_review(&self) -> Result<Vec<Essay>> {
+        let essays = self.essays.read().await;
+        Ok(essays
+            .values()
+            .filter(|e| e.review.as_ref().is_some_and(|r| r.is_pending()))
+            .cloned()
+            .collect())
+    }
+
     async fn update(&self, essay: Essay) -> Result<()> {
-        self.save(essay).await
+        self.save_revision(essay).await
+    }
+
+    /// Grava a redação e, depois de gravada, registra a revisão
+    ///
+    /// Redações novas e atualizadas passam por aqui. `save` sozinho não
+    /// registra, para que seeders e importações tragam o próprio histórico.
+    /// Atualizações que não mudam texto, nota nem rubrica (só o status, por
+    /// exemplo) não geram revisão.
+    async fn save_revision(&self, essay: Essay) -> Result<()> {
+        self.save(essay.clone()).await?;
+        if let Some(revisions) = &self.revisions {
+            let unchanged = revisions.latest(essay.id).await?.is_some_and(|latest| latest.matches(&essay));
+            if !unchanged {
+                revisions.record(&essay).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct InMemoryEssayRevisionRepository {
+    revisions: Arc<RwLock<HashMap<Uuid, Vec<EssayRevision>>>>,
+}
+
+impl InMemoryEssayRevisionRepository {
+    pub fn new() -> Self {
+        Self {
+            revisions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryEssayRevisionRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl EssayRevisionRepository for InMemoryEssayRevisionRepository {
+    async fn record(&self, essay: &Essay) -> Result<EssayRevision> {
+        let mut revisions = self.revisions.write().await;
+        let history = revisions.entry(essay.id).or_default();
+        let revision = EssayRevision::snapshot(essay, history.len() as u32 + 1);
+        history.push(revision.clone());
+        Ok(revision)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<EssayRevision>> {
+        let revisions = self.revisions.read().await;
+        Ok(revisions
+            .values()
+            .flatten()
+            .find(|r| r.id == id)
+            .cloned())
+    }
+
+    async fn list_by_essay(&self, essay_id: Uuid) -> Result<Vec<EssayRevision>> {
+        let revisions = self.revisions.read().await;
+        Ok(revisions.get(&essay_id).cloned().unwrap_or_default())
+    }
+
+    async fn latest(&self, essay_id: Uuid) -> Result<Option<EssayRevision>> {
+        let revisions = self.revisions.read().await;
+        Ok(revisions.get(&essay_id).and_then(|h| h.last()).cloned())
+    }
+}
+
+pub struct InMemoryEvaluationRecordRepository {
+    records: Arc<RwLock<HashMap<Uuid, Vec<EvaluationRecord>>>>,
+}
+
+impl InMemoryEvaluationRecordRepository {
+    pub fn new() -> Self {
+        Self {
+            records: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryEvaluationRecordRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl EvaluationRecordRepository for InMemoryEvaluationRecordRepository {
+    async fn save(&self, record: EvaluationRecord) -> Result<()> {
+        let mut records = self.records.write().await;
+        records.entry(record.essay_id).or_default().push(record);
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<EvaluationRecord>> {
+        let records = self.records.read().await;
+        Ok(records
+            .values()
+            .flatten()
+            .find(|r| r.id == id)
+            .cloned())
+    }
+
+    async fn list_by_essay(&self, essay_id: Uuid) -> Result<Vec<EvaluationRecord>> {
+        let records = self.records.read().await;
+        Ok(records.get(&essay_id).cloned().unwrap_or_default())
+    }
+
+    async fn latest(&self, essay_id: Uuid) -> Result<Option<EvaluationRecord>> {
+        let records = self.records.read().await;
+        Ok(records.get(&essay_id).and_then(|h| h.last()).cloned())
+    }
+}
+
+pub struct InMemoryWritingRecordingRepository {
+    recordings: Arc<RwLock<HashMap<Uuid, WritingRecording>>>,
+}
+
+impl InMemoryWritingRecordingRepository {
+    pub fn new() -> Self {
+        Self {
+            recordings: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryWritingRecordingRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl WritingRecordingRepository for InMemoryWritingRecordingRepository {
+    async fn save(&self, recording: WritingRecording) -> Result<()> {
+        let mut recordings = self.recordings.write().await;
+        recordings.insert(recording.id, recording);
+        Ok(())
+    }
+
+    async fn find_by_essay(&self, essay_id: Uuid) -> Result<Option<WritingRecording>> {
+        let recordings = self.recordings.read().await;
+        Ok(recordings
+            .values()
+            .find(|r| r.essay_id == Some(essay_id))
+            .cloned())
     }
 }
 
@@ -90,6 +267,12 @@ impl InMemoryExamRubricRepository {
     }
 }
 
+impl Default for InMemoryExamRubricRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl ExamRubricRepository for InMemoryExamRubricRepository {
     async fn get_rubric(&self, exam_type: ExamType) -> Result<Option<ExamRubric>> {
@@ -103,83 +286,294 @@ impl ExamRubricRepository for InMemoryExamRubricRepository {
     }
 }
 
+/// Questões com índices secundários para as consultas
+#[derive(Default)]
+struct QuestionStore {
+    questions: HashMap<Uuid, Question>,
+    by_subject: HashMap<Subject, HashSet<Uuid>>,
+    by_difficulty: HashMap<Difficulty, HashSet<Uuid>>,
+    /// Tags e exames sem acentos e em minúsculas
+    by_tag: HashMap<String, HashSet<Uuid>>,
+    by_exam: HashMap<String, HashSet<Uuid>>,
+    by_year: HashMap<u16, HashSet<Uuid>>,
+}
+
+fn index_key(value: &str) -> String {
+    text::fold(value.trim())
+}
+
+fn unindex<K: std::hash::Hash + Eq>(index: &mut HashMap<K, HashSet<Uuid>>, key: &K, id: Uuid) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+impl QuestionStore {
+    fn insert(&mut self, question: Question) {
+        self.remove(question.id);
+        let id = question.id;
+        self.by_subject.entry(question.subject.clone()).or_default().insert(id);
+        self.by_difficulty.entry(question.difficulty.clone()).or_default().insert(id);
+        for tag in &question.tags {
+            self.by_tag.entry(index_key(tag)).or_default().insert(id);
+        }
+        if let Some(source) = &question.source {
+            self.by_exam.entry(index_key(&source.exam)).or_default().insert(id);
+            self.by_year.entry(source.year).or_default().insert(id);
+        }
+        self.questions.insert(id, question);
+    }
+
+    fn remove(&mut self, id: Uuid) {
+        if let Some(question) = self.questions.remove(&id) {
+            unindex(&mut self.by_subject, &question.subject, id);
+            unindex(&mut self.by_difficulty, &question.difficulty, id);
+            for tag in &question.tags {
+                unindex(&mut self.by_tag, &index_key(tag), id);
+            }
+            if let Some(source) = &question.source {
+                unindex(&mut self.by_exam, &index_key(&source.exam), id);
+                unindex(&mut self.by_year, &source.year, id);
+            }
+        }
+    }
+
+    /// Ids que atendem aos filtros indexados de `query`; `None` se ela não tem nenhum
+    fn candidates(&self, query: &QuestionQuery) -> Option<HashSet<Uuid>> {
+        let empty = HashSet::new();
+        // Cada grupo aceita ids de qualquer um de seus conjuntos; os grupos se combinam por interseção
+        let mut groups: Vec<Vec<&HashSet<Uuid>>> = Vec::new();
+        if !query.subjects.is_empty() {
+            groups.push(query.subjects.iter().map(|s| self.by_subject.get(s).unwrap_or(&empty)).collect());
+        }
+        if !query.difficulties.is_empty() {
+            groups.push(query.difficulties.iter().map(|d| self.by_difficulty.get(d).unwrap_or(&empty)).collect());
+        }
+        for tag in query.tags.iter().filter(|tag| !tag.trim().is_empty()) {
+            groups.push(vec![self.by_tag.get(&index_key(tag)).unwrap_or(&empty)]);
+        }
+        if let Some(exam) = query.exam.as_deref().filter(|exam| !exam.trim().is_empty()) {
+            groups.push(vec![self.by_exam.get(&index_key(exam)).unwrap_or(&empty)]);
+        }
+        if let Some(year) = query.year {
+            groups.push(vec![self.by_year.get(&year).unwrap_or(&empty)]);
+        }
+
+        // Parte do menor grupo para visitar o mínimo de ids
+        groups.sort_by_key(|group| group.iter().map(|ids| ids.len()).sum::<usize>());
+        let mut groups = groups.into_iter();
+        let mut ids: HashSet<Uuid> = groups.next()?.into_iter().flatten().copied().collect();
+        for group in groups {
+            ids.retain(|id| group.iter().any(|set| set.contains(id)));
+        }
+        Some(ids)
+    }
+}
+
+/// Relevância de cada questão: o BM25, ou, com similaridades, a soma
+/// ponderada delas com o BM25 dividido pelo maior da consulta
+fn relevance(bm25: Vec<(Uuid, f32)>, semantic: Option<&SemanticScores>) -> HashMap<Uuid, f32> {
+    let semantic = match semantic {
+        Some(semantic) => semantic,
+        None => return bm25.into_iter().collect(),
+    };
+    let mut scores: HashMap<Uuid, f32> = semantic
+        .similarities
+        .iter()
+        .map(|(id, similarity)| (*id, semantic.weight * similarity.max(0.0)))
+        .collect();
+    let max = bm25.iter().map(|(_, score)| *score).fold(0.0, f32::max);
+    if max > 0.0 {
+        for (id, score) in bm25 {
+            *scores.entry(id).or_default() += (1.0 - semantic.weight) * score / max;
+        }
+    }
+    scores
+}
+
 pub struct InMemoryQuestionRepository {
-    questions: Arc<RwLock<HashMap<Uuid, Question>>>,
+    store: Arc<RwLock<QuestionStore>>,
+    /// Questões respondidas por usuário
+    answers: Arc<RwLock<HashMap<Uuid, HashSet<Uuid>>>>,
+    index: SearchIndex,
 }
 
 impl InMemoryQuestionRepository {
     pub fn new() -> Self {
+        Self::with_search_index(SearchIndex::new())
+    }
+
+    /// Repositório que indexa suas questões em `index`, compartilhado com a busca unificada
+    pub fn with_search_index(index: SearchIndex) -> Self {
         Self {
-            questions: Arc::new(RwLock::new(HashMap::new())),
+            store: Arc::new(RwLock::new(QuestionStore::default())),
+            answers: Arc::new(RwLock::new(HashMap::new())),
+            index,
         }
     }
     
     /// Método auxiliar para inserir questões diretamente (usado por seeders)
     pub async fn insert(&self, question: Question) -> Result<()> {
-        let mut questions = self.questions.write().await;
-        questions.insert(question.id, question);
+        let mut store = self.store.write().await;
+        self.index.upsert(SearchDocument::from_question(&question));
+        store.insert(question);
         Ok(())
     }
 }
 
+impl Default for InMemoryQuestionRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl QuestionRepository for InMemoryQuestionRepository {
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Question>> {
-        let questions = self.questions.read().await;
-        Ok(questions.get(&id).cloned())
+        let store = self.store.read().await;
+        Ok(store.questions.get(&id).cloned())
     }
 
     async fn list_by_subject(&self, subject: Subject) -> Result<Vec<Question>> {
-        let questions = self.questions.read().await;
-        Ok(questions
-            .values()
-            .filter(|q| q.subject == subject)
-            .cloned()
+        let store = self.store.read().await;
+        Ok(store
+            .by_subject
+            .get(&subject)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| store.questions.get(id).cloned())
             .collect())
     }
 
     async fn list_by_difficulty(&self, difficulty: Difficulty) -> Result<Vec<Question>> {
-        let questions = self.questions.read().await;
-        Ok(questions
-            .values()
-            .filter(|q| q.difficulty == difficulty)
-            .cloned()
+        let store = self.store.read().await;
+        Ok(store
+            .by_difficulty
+            .get(&difficulty)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| store.questions.get(id).cloned())
             .collect())
     }
 
     async fn search(&self, query: &str) -> Result<Vec<Question>> {
-        let questions = self.questions.read().await;
-        let query_lower = query.to_lowercase();
-        Ok(questions
-            .values()
-            .filter(|q| {
-                q.statement.to_lowercase().contains(&query_lower)
-                    || q.tags.iter().any(|tag| tag.to_lowercase().contains(&query_lower))
-            })
-            .cloned()
+        let store = self.store.read().await;
+        // Sem termos indexáveis (consulta vazia ou só stop words), lista tudo
+        if text::analyze(query).is_empty() {
+            return Ok(store.questions.values().cloned().collect());
+        }
+        Ok(self
+            .index
+            .search_kind(DocumentKind::Question, query)
+            .into_iter()
+            .filter_map(|(id, _)| store.questions.get(&id).cloned())
             .collect())
     }
+
+    async fn query(&self, query: &QuestionQuery) -> Result<QuestionPage> {
+        let store = self.store.read().await;
+        // Lida antes das notas: uma mudança no meio só invalida o cursor à toa
+        let version = self.index.version();
+        let scores: Option<HashMap<Uuid, f32>> = query
+            .text
+            .as_deref()
+            .filter(|query_text| !text::analyze(query_text).is_empty() || query.semantic.is_some())
+            .map(|query_text| {
+                relevance(self.index.search_kind(DocumentKind::Question, query_text), query.semantic.as_ref())
+            });
+        // A relevância de um texto depende do índice inteiro; as outras ordens têm chave fixa
+        let version = (query.sort == QuestionSort::Relevance && scores.is_some()).then_some(version);
+        if let Some(after) = query.after {
+            if after.version.is_some() && after.version != version {
+                return Err(Error::StaleCursor);
+            }
+        }
+
+        let mut ids: Vec<Uuid> = match (store.candidates(query), &scores) {
+            (Some(ids), Some(scores)) => ids.into_iter().filter(|id| scores.contains_key(id)).collect(),
+            (Some(ids), None) => ids.into_iter().collect(),
+            (None, Some(scores)) => scores.keys().filter(|id| store.questions.contains_key(id)).copied().collect(),
+            (None, None) => store.questions.keys().copied().collect(),
+        };
+        if let Some(filter) = query.answered {
+            let answers = self.answers.read().await;
+            let answered = answers.get(&filter.user_id);
+            ids.retain(|id| answered.is_some_and(|answered| answered.contains(id)) == filter.answered);
+        }
+        let total = ids.len();
+
+        let mut ranked: Vec<(f64, Uuid)> = ids
+            .into_iter()
+            .map(|id| {
+                let score = scores.as_ref().and_then(|scores| scores.get(&id)).copied().unwrap_or(0.0);
+                (query.sort.rank(&store.questions[&id], score), id)
+            })
+            .filter(|(rank, id)| query.after.is_none_or(|after| after.precedes(*rank, *id)))
+            .collect();
+        let order = |a: &(f64, Uuid), b: &(f64, Uuid)| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1));
+        // Só a página é ordenada; o resto apenas fica depois dela
+        let limit = query.limit.max(1);
+        let has_more = ranked.len() > limit;
+        if has_more {
+            ranked.select_nth_unstable_by(limit, order);
+            ranked.truncate(limit);
+        }
+        ranked.sort_unstable_by(order);
+
+        let next = match ranked.last() {
+            Some((rank, id)) if has_more => Some(QuestionCursor { rank: *rank, id: *id, version }),
+            _ => None,
+        };
+        Ok(QuestionPage {
+            questions: ranked.iter().map(|(_, id)| store.questions[id].clone()).collect(),
+            total,
+            next,
+        })
+    }
+
+    async fn record_answer(&self, user_id: Uuid, question_id: Uuid, _correct: bool) -> Result<()> {
+        let mut answers = self.answers.write().await;
+        answers.entry(user_id).or_default().insert(question_id);
+        Ok(())
+    }
 }
 
 pub struct InMemoryKnowledgeTrailRepository {
     trails: Arc<RwLock<HashMap<Uuid, KnowledgeTrail>>>,
+    index: SearchIndex,
 }
 
 impl InMemoryKnowledgeTrailRepository {
     pub fn new() -> Self {
+        Self::with_search_index(SearchIndex::new())
+    }
+
+    /// Repositório que indexa suas trilhas em `index`, compartilhado com a busca unificada
+    pub fn with_search_index(index: SearchIndex) -> Self {
         Self {
             trails: Arc::new(RwLock::new(HashMap::new())),
+            index,
         }
     }
     
     /// Método auxiliar para inserir trilhas diretamente (usado por seeders)
     pub async fn insert(&self, trail: KnowledgeTrail) -> Result<()> {
         let mut trails = self.trails.write().await;
+        self.index.upsert(SearchDocument::from_trail(&trail));
         trails.insert(trail.id, trail);
         Ok(())
     }
 }
 
+impl Default for InMemoryKnowledgeTrailRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl KnowledgeTrailRepository for InMemoryKnowledgeTrailRepository {
     async fn find_by_id(&self, id: Uuid) -> Result<Option<KnowledgeTrail>> {
@@ -225,6 +619,7 @@ impl KnowledgeTrailRepository for InMemoryKnowledgeTrailRepository {
 
     async fn update(&self, trail: KnowledgeTrail) -> Result<()> {
         let mut trails = self.trails.write().await;
+        self.index.upsert(SearchDocument::from_trail(&trail));
         trails.insert(trail.id, trail);
         Ok(())
     }
@@ -242,6 +637,12 @@ impl InMemoryUserRepository {
     }
 }
 
+impl Default for InMemoryUserRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl UserRepository for InMemoryUserRepository {
     async fn find_by_id(&self, id: Uuid) -> Result<Option<UserProfile>> {
@@ -276,6 +677,12 @@ impl InMemoryProgressRepository {
     }
 }
 
+impl Default for InMemoryProgressRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl ProgressRepository for InMemoryProgressRepository {
     async fn get_progress(&self, user_id: Uuid) -> Result<StudyProgress> {
@@ -339,23 +746,37 @@ impl ProgressRepository for InMemoryProgressRepository {
 
 pub struct InMemoryReadingContentRepository {
     readings: Arc<RwLock<HashMap<Uuid, ReadingContent>>>,
+    index: SearchIndex,
 }
 
 impl InMemoryReadingContentRepository {
     pub fn new() -> Self {
+        Self::with_search_index(SearchIndex::new())
+    }
+
+    /// Repository indexing its contents in `index`, shared with the unified search
+    pub fn with_search_index(index: SearchIndex) -> Self {
         Self {
             readings: Arc::new(RwLock::new(HashMap::new())),
+            index,
         }
     }
 
     /// Helper method to insert reading content directly (used by seeders)
     pub async fn insert(&self, reading: ReadingContent) -> Result<()> {
         let mut readings = self.readings.write().await;
+        self.index.upsert(SearchDocument::from_reading(&reading));
         readings.insert(reading.id, reading);
         Ok(())
     }
 }
 
+impl Default for InMemoryReadingContentRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl ReadingContentRepository for InMemoryReadingContentRepository {
     async fn find_by_id(&self, id: Uuid) -> Result<Option<ReadingContent>> {
@@ -378,3 +799,332 @@ impl ReadingContentRepository for InMemoryReadingContentRepository {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::question::{Alternative, QuestionSource};
+    use domain::question_query::AnsweredFilter;
+
+    fn essay(content: &str) -> Essay {
+        Essay::new(Uuid::new_v4(), "Educação", content, ExamType::Enem)
+    }
+
+    #[tokio::test]
+    async fn test_essay_update_records_a_revision() {
+        let revisions = Arc::new(InMemoryEssayRevisionRepository::new());
+        let repo = InMemoryEssayRepository::with_revisions(revisions.clone());
+        let mut essay = essay("Primeira versão.");
+        repo.save(essay.clone()).await.unwrap();
+        assert!(revisions.list_by_essay(essay.id).await.unwrap().is_empty());
+
+        essay.content = "Segunda versão.".to_string();
+        repo.update(essay.clone()).await.unwrap();
+        essay.score = Some(720);
+        repo.update(essay.clone()).await.unwrap();
+
+        let history = revisions.list_by_essay(essay.id).await.unwrap();
+        assert_eq!(history.iter().map(|r| r.revision_number).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(history[0].content, "Segunda versão.");
+        assert_eq!(history[1].score, Some(720));
+        assert_eq!(repo.find_by_id(essay.id).await.unwrap().unwrap().score, Some(720));
+    }
+
+    #[tokio::test]
+    async fn test_new_essay_revision_matches_the_saved_essay() {
+        let revisions = Arc::new(InMemoryEssayRevisionRepository::new());
+        let repo = InMemoryEssayRepository::with_revisions(revisions.clone());
+        let mut essay = essay("Primeira versão.");
+        repo.save_revision(essay.clone()).await.unwrap();
+        essay.content = "Segunda versão.".to_string();
+        repo.update(essay.clone()).await.unwrap();
+
+        let history = revisions.list_by_essay(essay.id).await.unwrap();
+        assert_eq!(history.iter().map(|r| r.revision_number).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(history[0].content, "Primeira versão.");
+        let stored = repo.find_by_id(essay.id).await.unwrap().unwrap();
+        assert_eq!(history[1].content, stored.content);
+    }
+
+    #[tokio::test]
+    async fn test_status_only_update_records_no_revision() {
+        let revisions = Arc::new(InMemoryEssayRevisionRepository::new());
+        let repo = InMemoryEssayRepository::with_revisions(revisions.clone());
+        let mut essay = essay("Primeira versão.");
+        essay.score = Some(600);
+        repo.save_revision(essay.clone()).await.unwrap();
+
+        essay.status = EssayStatus::Corrigida;
+        essay.updated_at = chrono::Utc::now();
+        repo.update(essay.clone()).await.unwrap();
+        assert_eq!(revisions.list_by_essay(essay.id).await.unwrap().len(), 1);
+        assert_eq!(repo.find_by_id(essay.id).await.unwrap().unwrap().status, EssayStatus::Corrigida);
+
+        essay.score = Some(640);
+        repo.update(essay.clone()).await.unwrap();
+        assert_eq!(revisions.list_by_essay(essay.id).await.unwrap().len(), 2);
+    }
+
+    fn question(
+        subject: Subject,
+        difficulty: Difficulty,
+        statement: &str,
+        tags: &[&str],
+        source: Option<(&str, u16)>,
+    ) -> Question {
+        Question {
+            id: Uuid::new_v4(),
+            subject,
+            difficulty,
+            statement: statement.to_string(),
+            alternatives: vec![Alternative { id: 0, text: "Nenhuma das anteriores".to_string() }],
+            correct_answer: 0,
+            explanation: String::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            source: source.map(|(exam, year)| QuestionSource { exam: exam.to_string(), year }),
+        }
+    }
+
+    /// Repositório com cinco questões, na ordem em que são devolvidas
+    async fn bank() -> (InMemoryQuestionRepository, Vec<Question>) {
+        let questions = vec![
+            question(Subject::Matematica, Difficulty::Facil, "Quanto é dois mais dois?", &["Aritmética"], Some(("ENEM", 2020))),
+            question(
+                Subject::Matematica,
+                Difficulty::Dificil,
+                "Resolva a equação do segundo grau",
+                &["Álgebra", "Funções"],
+                Some(("FUVEST", 2022)),
+            ),
+            question(Subject::Biologia, Difficulty::Medio, "O que é fotossíntese?", &["Botânica"], Some(("ENEM", 2022))),
+            question(Subject::Historia, Difficulty::Medio, "Causas da Revolução Francesa", &["Revolução"], None),
+            question(Subject::Fisica, Difficulty::Facil, "Velocidade média de um carro", &[], Some(("Enem", 2019))),
+        ];
+        let repo = InMemoryQuestionRepository::new();
+        for question in &questions {
+            repo.insert(question.clone()).await.unwrap();
+        }
+        (repo, questions)
+    }
+
+    async fn ids(repo: &InMemoryQuestionRepository, query: QuestionQuery) -> Vec<Uuid> {
+        let page = repo.query(&QuestionQuery { limit: 100, ..query }).await.unwrap();
+        assert_eq!(page.total, page.questions.len());
+        page.questions.iter().map(|question| question.id).collect()
+    }
+
+    async fn id_set(repo: &InMemoryQuestionRepository, query: QuestionQuery) -> HashSet<Uuid> {
+        ids(repo, query).await.into_iter().collect()
+    }
+
+    fn set(questions: &[Question], picks: &[usize]) -> HashSet<Uuid> {
+        picks.iter().map(|i| questions[*i].id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_indexed_filters() {
+        let (repo, q) = bank().await;
+
+        let query = QuestionQuery { subjects: vec![Subject::Matematica], ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[0, 1]));
+        let query = QuestionQuery { subjects: vec![Subject::Matematica, Subject::Biologia], ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[0, 1, 2]));
+        let query = QuestionQuery { difficulties: vec![Difficulty::Medio], ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[2, 3]));
+        let query = QuestionQuery {
+            subjects: vec![Subject::Matematica],
+            difficulties: vec![Difficulty::Facil],
+            ..Default::default()
+        };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[0]));
+    }
+
+    #[tokio::test]
+    async fn test_tag_exam_and_year_filters_ignore_case_and_accents() {
+        let (repo, q) = bank().await;
+
+        let query = QuestionQuery { tags: vec!["algebra".to_string()], ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[1]));
+        // Tags exigem todas
+        let query = QuestionQuery { tags: vec!["ÁLGEBRA".to_string(), "funcoes".to_string()], ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[1]));
+        let query = QuestionQuery { tags: vec!["álgebra".to_string(), "botânica".to_string()], ..Default::default() };
+        assert!(id_set(&repo, query).await.is_empty());
+
+        let query = QuestionQuery { exam: Some("enem".to_string()), ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[0, 2, 4]));
+        let query = QuestionQuery { year: Some(2022), ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[1, 2]));
+        let query = QuestionQuery { exam: Some(" ENEM ".to_string()), year: Some(2022), ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[2]));
+        let query = QuestionQuery { year: Some(1999), ..Default::default() };
+        assert!(id_set(&repo, query).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_answered_filter() {
+        let (repo, q) = bank().await;
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+        repo.record_answer(user, q[0].id, true).await.unwrap();
+        repo.record_answer(user, q[2].id, false).await.unwrap();
+
+        let answered = |user_id, answered| Some(AnsweredFilter { user_id, answered });
+
+        let query = QuestionQuery { answered: answered(user, true), ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[0, 2]));
+        let query = QuestionQuery { answered: answered(user, false), ..Default::default() };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[1, 3, 4]));
+        let query = QuestionQuery { answered: answered(other, true), ..Default::default() };
+        assert!(id_set(&repo, query).await.is_empty());
+        let query = QuestionQuery {
+            subjects: vec![Subject::Matematica],
+            answered: answered(user, false),
+            ..Default::default()
+        };
+        assert_eq!(id_set(&repo, query).await, set(&q, &[1]));
+    }
+
+    #[tokio::test]
+    async fn test_sorts() {
+        let (repo, q) = bank().await;
+        let by_id = |picks: &[usize]| {
+            let mut ids: Vec<Uuid> = picks.iter().map(|i| q[*i].id).collect();
+            ids.sort();
+            ids
+        };
+        let ordered = |sort| ids(&repo, QuestionQuery { sort, ..Default::default() });
+
+        // Empates seguem o id
+        assert_eq!(ordered(QuestionSort::Relevance).await, by_id(&[0, 1, 2, 3, 4]));
+        assert_eq!(ordered(QuestionSort::Easiest).await, [by_id(&[0, 4]), by_id(&[2, 3]), by_id(&[1])].concat());
+        assert_eq!(ordered(QuestionSort::Hardest).await, [by_id(&[1]), by_id(&[2, 3]), by_id(&[0, 4])].concat());
+        let newest = [by_id(&[1, 2]), by_id(&[0]), by_id(&[4]), by_id(&[3])].concat();
+        assert_eq!(ordered(QuestionSort::Newest).await, newest);
+        let oldest = [by_id(&[4]), by_id(&[0]), by_id(&[1, 2]), by_id(&[3])].concat();
+        assert_eq!(ordered(QuestionSort::Oldest).await, oldest);
+
+        let query = QuestionQuery { text: Some("fotossíntese".to_string()), ..Default::default() };
+        assert_eq!(ids(&repo, query).await, vec![q[2].id]);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_continues_the_listing() {
+        let (repo, _) = bank().await;
+        let all = ids(&repo, QuestionQuery { sort: QuestionSort::Easiest, ..Default::default() }).await;
+
+        let mut seen = Vec::new();
+        let mut after = None;
+        let mut pages = 0;
+        loop {
+            let query = QuestionQuery { sort: QuestionSort::Easiest, limit: 2, after, ..Default::default() };
+            let page = repo.query(&query).await.unwrap();
+            assert_eq!(page.total, 5);
+            seen.extend(page.questions.iter().map(|question| question.id));
+            pages += 1;
+            match page.next {
+                Some(cursor) => {
+                    assert_eq!(cursor.id, *seen.last().unwrap());
+                    after = Some(cursor);
+                }
+                None => break,
+            }
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(seen, all);
+    }
+
+    #[tokio::test]
+    async fn test_relevance_cursor_without_text_survives_inserts() {
+        let (repo, q) = bank().await;
+        let query = QuestionQuery { limit: 2, ..Default::default() };
+        let first = repo.query(&query).await.unwrap();
+        assert_eq!(first.next.unwrap().version, None);
+
+        let added = question(Subject::Quimica, Difficulty::Facil, "O que é um átomo?", &[], None);
+        repo.insert(added.clone()).await.unwrap();
+
+        let mut seen: Vec<Uuid> = first.questions.iter().map(|question| question.id).collect();
+        let mut after = first.next;
+        while let Some(cursor) = after {
+            let page = repo.query(&QuestionQuery { after: Some(cursor), ..query.clone() }).await.unwrap();
+            seen.extend(page.questions.iter().map(|question| question.id));
+            after = page.next;
+        }
+        // Nada se repete nem se perde; a nova só aparece se vier depois do cursor
+        let unique: HashSet<Uuid> = seen.iter().copied().collect();
+        assert_eq!(unique.len(), seen.len());
+        assert!(q.iter().all(|question| unique.contains(&question.id)));
+        assert_eq!(unique.contains(&added.id), added.id > first.next.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn test_relevance_cursor_for_text_is_rejected_after_inserts() {
+        let (repo, _) = bank().await;
+        let query = QuestionQuery { text: Some("revolução fotossíntese".to_string()), limit: 1, ..Default::default() };
+        let first = repo.query(&query).await.unwrap();
+        assert_eq!(first.total, 2);
+        let cursor = first.next.unwrap();
+        assert!(cursor.version.is_some());
+        // Sem mudanças o cursor continua valendo
+        let second = repo.query(&QuestionQuery { after: Some(cursor), ..query.clone() }).await.unwrap();
+        assert_ne!(second.questions[0].id, first.questions[0].id);
+
+        // Uma questão nova muda o BM25 de todas; a página seguinte pode repetir ou pular questões
+        repo.insert(question(Subject::Biologia, Difficulty::Facil, "Fotossíntese nas algas", &[], None)).await.unwrap();
+        let stale = repo.query(&QuestionQuery { after: Some(cursor), ..query.clone() }).await;
+        assert!(matches!(stale, Err(Error::StaleCursor)));
+
+        // Ordens de chave fixa seguem valendo com o mesmo texto
+        let easiest = QuestionQuery { sort: QuestionSort::Easiest, ..query };
+        let first = repo.query(&easiest).await.unwrap();
+        repo.insert(question(Subject::Historia, Difficulty::Dificil, "Revolução Industrial", &[], None)).await.unwrap();
+        let next = repo.query(&QuestionQuery { after: first.next, ..easiest }).await.unwrap();
+        assert!(next.questions.iter().all(|question| question.id != first.questions[0].id));
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit_returns_one_question() {
+        let (repo, _) = bank().await;
+
+        let page = repo.query(&QuestionQuery { limit: 0, ..Default::default() }).await.unwrap();
+        assert_eq!(page.questions.len(), 1);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.next.map(|cursor| cursor.id), Some(page.questions[0].id));
+    }
+
+    #[tokio::test]
+    async fn test_semantic_scores_rank_before_pagination() {
+        let (repo, q) = bank().await;
+        // A revolução não tem a palavra, mas é a mais parecida; a equação fica de fora
+        let semantic = SemanticScores {
+            similarities: [(q[3].id, 0.9), (q[2].id, 0.2)].into_iter().collect(),
+            weight: 0.7,
+        };
+        let query = QuestionQuery {
+            text: Some("fotossíntese".to_string()),
+            semantic: Some(semantic),
+            limit: 1,
+            ..Default::default()
+        };
+
+        let first = repo.query(&query).await.unwrap();
+        assert_eq!(first.total, 2);
+        assert_eq!(first.questions[0].id, q[3].id);
+        let second = repo.query(&QuestionQuery { after: first.next, ..query }).await.unwrap();
+        assert_eq!(second.questions[0].id, q[2].id);
+        assert!(second.next.is_none());
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        for rank in [0.0, -3.5, 2022.0, f64::MAX, -f64::MAX] {
+            for version in [None, Some(0), Some(u64::MAX)] {
+                let cursor = QuestionCursor { rank, id: Uuid::new_v4(), version };
+                assert_eq!(cursor.to_string().parse::<QuestionCursor>().unwrap(), cursor);
+            }
+        }
+        assert!("semponto".parse::<QuestionCursor>().is_err());
+        assert!("zz.00000000000000000000000000000000".parse::<QuestionCursor>().is_err());
+        assert!("0000000000000000.naoeumid".parse::<QuestionCursor>().is_err());
+    }
+}
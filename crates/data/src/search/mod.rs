@@ -0,0 +1,425 @@
+//! Full-text search over questions, reading contents and trails
+//!
+//! An inverted index from analyzed terms (see [`text`]) to documents, ranked
+//! with BM25. Accents and case never matter and Portuguese inflections share
+//! a stem, so "funções" finds "função" and "Funcao". Title terms count twice.
+
+pub mod stemmer;
+pub mod text;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use uuid::Uuid;
+use domain::{knowledge_trail::KnowledgeTrail, question::Question, reading_content::ReadingContent};
+
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+const TITLE_WEIGHT: u32 = 2;
+/// Shortest last query word expanded to the terms it prefixes
+const MIN_PREFIX: usize = 3;
+/// Words shown around the first match of a snippet
+const SNIPPET_WORDS: usize = 24;
+const SNIPPET_LEAD: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentKind {
+    Question,
+    Reading,
+    Trail,
+}
+
+/// Text of something searchable
+#[derive(Debug, Clone)]
+pub struct SearchDocument {
+    pub kind: DocumentKind,
+    pub id: Uuid,
+    pub title: String,
+    pub body: String,
+}
+
+impl SearchDocument {
+    pub fn from_question(question: &Question) -> Self {
+        let mut body: Vec<&str> = question.alternatives.iter().map(|a| a.text.as_str()).collect();
+        body.push(&question.explanation);
+        body.extend(question.tags.iter().map(String::as_str));
+        Self {
+            kind: DocumentKind::Question,
+            id: question.id,
+            title: question.statement.clone(),
+            body: body.join("\n"),
+        }
+    }
+
+    pub fn from_reading(reading: &ReadingContent) -> Self {
+        let mut body = reading.content.clone();
+        if let Some(author) = &reading.author {
+            body.push('\n');
+            body.push_str(author);
+        }
+        Self { kind: DocumentKind::Reading, id: reading.id, title: reading.title.clone(), body }
+    }
+
+    pub fn from_trail(trail: &KnowledgeTrail) -> Self {
+        let mut body = vec![trail.description.as_str()];
+        for module in &trail.modules {
+            body.push(&module.title);
+            body.push(&module.description);
+        }
+        Self { kind: DocumentKind::Trail, id: trail.id, title: trail.title.clone(), body: body.join("\n") }
+    }
+}
+
+/// Excerpt of a document around the query terms
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Snippet {
+    pub text: String,
+    /// Byte ranges of `text` that matched the query
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl Snippet {
+    /// `text` split into `(part, highlighted)` pieces, in order
+    pub fn segments(&self) -> Vec<(&str, bool)> {
+        let mut segments = Vec::new();
+        let mut position = 0;
+        for range in &self.highlights {
+            if range.start > position {
+                segments.push((&self.text[position..range.start], false));
+            }
+            segments.push((&self.text[range.clone()], true));
+            position = range.end;
+        }
+        if position < self.text.len() {
+            segments.push((&self.text[position..], false));
+        }
+        segments
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub kind: DocumentKind,
+    pub id: Uuid,
+    pub title: String,
+    pub score: f32,
+    pub snippet: Snippet,
+}
+
+type Key = (DocumentKind, Uuid);
+
+struct Entry {
+    document: SearchDocument,
+    frequencies: HashMap<String, u32>,
+    length: u32,
+}
+
+#[derive(Default)]
+struct Index {
+    entries: HashMap<Key, Entry>,
+    postings: BTreeMap<String, HashSet<Key>>,
+    total_length: u64,
+    /// Bumped by every change, since any change moves the BM25 scores
+    version: u64,
+}
+
+impl Index {
+    fn remove(&mut self, key: &Key) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.total_length -= entry.length as u64;
+            for term in entry.frequencies.keys() {
+                if let Some(keys) = self.postings.get_mut(term) {
+                    keys.remove(key);
+                    if keys.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Terms of `query`; the last word, which may still be being typed, also
+    /// stands for every indexed term it prefixes ("fotossín")
+    fn query_terms(&self, query: &str) -> Vec<String> {
+        let mut terms = text::analyze(query);
+        if let Some(last) = text::word_ranges(query).pop() {
+            let prefix = text::fold(&query[last]);
+            if prefix.chars().count() >= MIN_PREFIX && !text::is_stop_word(&prefix) {
+                let expanded = self.postings.range(prefix.clone()..).take_while(|(term, _)| term.starts_with(&prefix));
+                terms.extend(expanded.map(|(term, _)| term.clone()));
+            }
+        }
+        terms.sort();
+        terms.dedup();
+        terms
+    }
+
+    /// BM25 score of every document containing a query term
+    fn score(&self, terms: &[String], kind: Option<DocumentKind>) -> Vec<(Key, f32)> {
+        let count = self.entries.len() as f32;
+        if count == 0.0 {
+            return Vec::new();
+        }
+        let average_length = (self.total_length as f32 / count).max(1.0);
+
+        let mut scores: HashMap<Key, f32> = HashMap::new();
+        for term in terms {
+            let keys = match self.postings.get(term) {
+                Some(keys) => keys,
+                None => continue,
+            };
+            let df = keys.len() as f32;
+            let idf = (1.0 + (count - df + 0.5) / (df + 0.5)).ln();
+            for key in keys {
+                if kind.is_some_and(|kind| kind != key.0) {
+                    continue;
+                }
+                let entry = &self.entries[key];
+                let tf = entry.frequencies[term] as f32;
+                let norm = K1 * (1.0 - B + B * entry.length as f32 / average_length);
+                *scores.entry(*key).or_default() += idf * tf * (K1 + 1.0) / (tf + norm);
+            }
+        }
+
+        let mut ranked: Vec<(Key, f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0 .1.cmp(&b.0 .1)));
+        ranked
+    }
+}
+
+/// Inverted index shared by the repositories that feed it
+#[derive(Clone, Default)]
+pub struct SearchIndex {
+    inner: Arc<RwLock<Index>>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Index> {
+        self.inner.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Index> {
+        self.inner.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Add `document`, replacing an earlier version of it
+    pub fn upsert(&self, document: SearchDocument) {
+        let key = (document.kind, document.id);
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for term in text::analyze(&document.title) {
+            *frequencies.entry(term).or_default() += TITLE_WEIGHT;
+        }
+        for term in text::analyze(&document.body) {
+            *frequencies.entry(term).or_default() += 1;
+        }
+        let length = frequencies.values().sum();
+
+        let mut index = self.write();
+        index.remove(&key);
+        for term in frequencies.keys() {
+            index.postings.entry(term.clone()).or_default().insert(key);
+        }
+        index.total_length += length as u64;
+        index.entries.insert(key, Entry { document, frequencies, length });
+        index.version += 1;
+    }
+
+    pub fn remove(&self, kind: DocumentKind, id: Uuid) {
+        let mut index = self.write();
+        index.remove(&(kind, id));
+        index.version += 1;
+    }
+
+    /// Changes whenever a document is added, replaced or removed
+    pub fn version(&self) -> u64 {
+        self.read().version
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Best `limit` documents of any kind for `query`, with snippets
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let index = self.read();
+        let terms = index.query_terms(query);
+        index
+            .score(&terms, None)
+            .into_iter()
+            .take(limit)
+            .map(|(key, score)| {
+                let document = &index.entries[&key].document;
+                let snippet = match snippet(&document.body, &terms) {
+                    Some(snippet) => snippet,
+                    None => snippet(&document.title, &terms).unwrap_or_default(),
+                };
+                SearchHit { kind: key.0, id: key.1, title: document.title.clone(), score, snippet }
+            })
+            .collect()
+    }
+
+    /// Ids of the documents of `kind` matching `query`, best first
+    pub fn search_kind(&self, kind: DocumentKind, query: &str) -> Vec<(Uuid, f32)> {
+        let index = self.read();
+        let terms = index.query_terms(query);
+        index.score(&terms, Some(kind)).into_iter().map(|(key, score)| (key.1, score)).collect()
+    }
+}
+
+/// Window of `text` around the first word matching `terms`
+fn snippet(text: &str, terms: &[String]) -> Option<Snippet> {
+    let words = text::word_ranges(text);
+    let matches: Vec<bool> = words
+        .iter()
+        .map(|range| text::term(&text[range.clone()]).is_some_and(|term| terms.binary_search(&term).is_ok()))
+        .collect();
+    let first = matches.iter().position(|matched| *matched)?;
+
+    let start = first.saturating_sub(SNIPPET_LEAD);
+    let end = (start + SNIPPET_WORDS).min(words.len());
+    let mut snippet = Snippet::default();
+    if start > 0 {
+        snippet.text.push_str("… ");
+    }
+    let offset = words[start].start;
+    let base = snippet.text.len();
+    snippet.text.push_str(&text[offset..words[end - 1].end]);
+    for (range, _) in words[start..end].iter().zip(&matches[start..end]).filter(|(_, matched)| **matched) {
+        snippet.highlights.push(base + range.start - offset..base + range.end - offset);
+    }
+    if end < words.len() {
+        snippet.text.push_str(" …");
+    }
+    Some(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(title: &str, body: &str) -> SearchDocument {
+        SearchDocument { kind: DocumentKind::Reading, id: Uuid::new_v4(), title: title.to_string(), body: body.to_string() }
+    }
+
+    fn ids(index: &SearchIndex, query: &str) -> Vec<Uuid> {
+        index.search(query, 10).into_iter().map(|hit| hit.id).collect()
+    }
+
+    #[test]
+    fn test_accent_and_case_insensitive_matching() {
+        let index = SearchIndex::new();
+        let doc = document("Funções do primeiro grau", "O gráfico é uma reta.");
+        let id = doc.id;
+        index.upsert(doc);
+
+        for query in ["funções", "Funcao", "função", "FUNÇÃO"] {
+            assert_eq!(ids(&index, query), vec![id], "query {query}");
+        }
+    }
+
+    #[test]
+    fn test_stop_words_do_not_match() {
+        let index = SearchIndex::new();
+        index.upsert(document("A origem das espécies", "Texto de Darwin sobre a seleção natural"));
+
+        assert!(index.search("de a das", 10).is_empty());
+    }
+
+    #[test]
+    fn test_prefix_of_last_word() {
+        let index = SearchIndex::new();
+        let doc = document("Fotossíntese", "Processo das plantas verdes");
+        let id = doc.id;
+        index.upsert(doc);
+
+        assert_eq!(ids(&index, "fotossín"), vec![id]);
+        assert_eq!(ids(&index, "plantas fotoss"), vec![id]);
+        assert!(index.search("fo", 10).is_empty());
+    }
+
+    #[test]
+    fn test_upsert_replaces_earlier_version() {
+        let index = SearchIndex::new();
+        let mut doc = document("Fotossíntese", "clorofila");
+        index.upsert(doc.clone());
+        doc.title = "Mitose".to_string();
+        doc.body = "divisão celular".to_string();
+        index.upsert(doc.clone());
+
+        assert_eq!(index.len(), 1);
+        assert!(index.search("fotossíntese", 10).is_empty());
+        assert!(index.search("clorofila", 10).is_empty());
+        assert_eq!(ids(&index, "mitose"), vec![doc.id]);
+
+        let inner = index.read();
+        let entry = &inner.entries[&(doc.kind, doc.id)];
+        assert_eq!(inner.total_length, entry.length as u64);
+        assert!(inner.postings.keys().all(|term| entry.frequencies.contains_key(term)));
+    }
+
+    #[test]
+    fn test_remove_keeps_totals_and_postings_consistent() {
+        let index = SearchIndex::new();
+        let kept = document("Mitose", "divisão celular");
+        let removed = document("Meiose", "divisão celular reducional");
+        index.upsert(kept.clone());
+        index.upsert(removed.clone());
+
+        index.remove(removed.kind, removed.id);
+
+        assert_eq!(ids(&index, "divisão"), vec![kept.id]);
+        assert!(index.search("meiose", 10).is_empty());
+        let inner = index.read();
+        assert_eq!(inner.total_length, inner.entries[&(kept.kind, kept.id)].length as u64);
+        assert!(inner.postings.values().all(|keys| keys.len() == 1 && keys.contains(&(kept.kind, kept.id))));
+
+        drop(inner);
+        index.remove(kept.kind, kept.id);
+        let inner = index.read();
+        assert_eq!(inner.total_length, 0);
+        assert!(inner.postings.is_empty());
+    }
+
+    #[test]
+    fn test_title_weight_ranks_title_matches_first() {
+        let index = SearchIndex::new();
+        let in_body = document("Clorofila", "fotossíntese");
+        let in_title = document("Fotossíntese", "clorofila");
+        index.upsert(in_body.clone());
+        index.upsert(in_title.clone());
+
+        assert_eq!(ids(&index, "fotossíntese"), vec![in_title.id, in_body.id]);
+    }
+
+    #[test]
+    fn test_snippet_highlights_multibyte_text() {
+        let terms = vec![text::term("função").unwrap()];
+        let text = "Na álgebra é comum estudar o domínio e a imagem de uma função quadrática.";
+        let snippet = snippet(text, &terms).unwrap();
+
+        assert!(snippet.text.starts_with("… "));
+        assert_eq!(snippet.highlights.len(), 1);
+        assert_eq!(&snippet.text[snippet.highlights[0].clone()], "função");
+        assert_eq!(
+            snippet.segments(),
+            vec![("… domínio e a imagem de uma ", false), ("função", true), (" quadrática", false)]
+        );
+    }
+
+    #[test]
+    fn test_snippet_without_lead_has_no_ellipsis() {
+        let terms = vec![text::term("Equações").unwrap()];
+        let snippet = snippet("Equações do segundo grau", &terms).unwrap();
+
+        assert_eq!(snippet.text, "Equações do segundo grau");
+        assert_eq!(snippet.highlights, vec![0.."Equações".len()]);
+    }
+}
@@ -0,0 +1,467 @@
+//! RSLP stemmer for Portuguese (Orengo & Huyck, 2001)
+//!
+//! Suffixes are stripped in steps: plural, feminine, adverb, augmentative
+//! and diminutive, then noun suffixes or, failing those, verb suffixes, and
+//! finally a trailing vowel. Each rule names the shortest stem it may leave
+//! and words it must not touch.
+//!
+//! Words arrive lowercased but with their accents, because folding first
+//! merges words the rules keep apart ("país" would lose its "ais" like
+//! "jornais"). Text typed without accents still has to stem like the
+//! accented word, so a rule also matches its folded suffix and exceptions
+//! are compared folded, unless folding makes them another word ("país" and
+//! "pais", the plural of "pai").
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use super::text::fold;
+
+struct Rule {
+    suffix: &'static str,
+    /// Shortest stem, in characters, the rule may leave
+    min_stem: usize,
+    replacement: &'static str,
+    exceptions: &'static [&'static str],
+}
+
+const fn rule(suffix: &'static str, min_stem: usize, replacement: &'static str) -> Rule {
+    Rule { suffix, min_stem, replacement, exceptions: &[] }
+}
+
+const fn except(
+    suffix: &'static str,
+    min_stem: usize,
+    replacement: &'static str,
+    exceptions: &'static [&'static str],
+) -> Rule {
+    Rule { suffix, min_stem, replacement, exceptions }
+}
+
+/// Exceptions whose unaccented spelling is another word; they only match
+/// with their accents
+const ACCENTED_EXCEPTIONS: &[&str] = &["país"];
+
+/// Folded suffix, replacement and exceptions of a rule
+struct FoldedRule {
+    suffix: String,
+    replacement: String,
+    exceptions: HashSet<String>,
+}
+
+/// Rules of one step, folded on first use
+struct Step {
+    rules: &'static [Rule],
+    folded: OnceLock<Vec<FoldedRule>>,
+}
+
+impl Step {
+    const fn new(rules: &'static [Rule]) -> Self {
+        Self { rules, folded: OnceLock::new() }
+    }
+
+    fn folded(&self) -> &[FoldedRule] {
+        self.folded.get_or_init(|| {
+            self.rules
+                .iter()
+                .map(|rule| FoldedRule {
+                    // A lone accented letter is all that tells "irmã" from "casa"
+                    suffix: if rule.suffix.chars().count() > 1 { fold(rule.suffix) } else { rule.suffix.to_string() },
+                    replacement: fold(rule.replacement),
+                    exceptions: rule
+                        .exceptions
+                        .iter()
+                        .map(|word| if ACCENTED_EXCEPTIONS.contains(word) { word.to_string() } else { fold(word) })
+                        .collect(),
+                })
+                .collect()
+        })
+    }
+}
+
+static PLURAL: Step = Step::new(&[
+    rule("ns", 1, "m"),
+    rule("ões", 3, "ão"),
+    except("ães", 1, "ão", &["mães"]),
+    // Not RSLP's: "pais" and "país" must not lose their "ais"
+    except("ais", 1, "al", &["cais", "mais", "pais"]),
+    rule("eis", 2, "el"),
+    rule("ois", 2, "ol"),
+    except("is", 2, "il", &["lápis", "cais", "mais", "crúcis", "biquínis", "pois", "depois", "dois", "leis", "pais"]),
+    rule("les", 3, "l"),
+    except("res", 3, "r", &["árvores"]),
+    except(
+        "s",
+        2,
+        "",
+        &[
+            "aliás", "pires", "lápis", "cais", "mais", "mas", "menos", "férias", "fezes", "pêsames", "crúcis", "gás",
+            "atrás", "moisés", "através", "convés", "país", "após", "ambas", "ambos", "messias", "depois",
+        ],
+    ),
+]);
+
+static FEMININE: Step = Step::new(&[
+    except(
+        "ona",
+        3,
+        "ão",
+        &["abandona", "lona", "iona", "cortisona", "monótona", "maratona", "acetona", "detona", "carona"],
+    ),
+    rule("ora", 3, "or"),
+    except(
+        "na",
+        4,
+        "no",
+        &[
+            "carona", "abandona", "lona", "iona", "cortisona", "monótona", "maratona", "acetona", "detona", "guiana",
+            "campana", "grana", "caravana", "banana", "paisana",
+        ],
+    ),
+    except("inha", 3, "inho", &["rainha", "linha", "minha"]),
+    except("esa", 3, "ês", &["mesa", "obesa", "princesa", "turquesa", "ilesa", "pesa", "presa"]),
+    except("osa", 3, "oso", &["mucosa", "prosa"]),
+    rule("íaca", 3, "íaco"),
+    except("ica", 3, "ico", &["dica"]),
+    except("ada", 2, "ado", &["pitada"]),
+    except("ida", 3, "ido", &["vida", "dúvida"]),
+    except("ída", 3, "ido", &["recaída", "saída"]),
+    except("ima", 3, "imo", &["vítima"]),
+    except("iva", 3, "ivo", &["saliva", "oliva"]),
+    except(
+        "eira",
+        3,
+        "eiro",
+        &["beira", "cadeira", "frigideira", "bandeira", "feira", "capoeira", "barreira", "fronteira", "besteira", "poeira"],
+    ),
+    except("ã", 2, "ão", &["amanhã", "arapuã", "fã", "divã"]),
+]);
+
+static ADVERB: Step = Step::new(&[except("mente", 4, "", &["experimente"])]);
+
+static AUGMENTATIVE: Step = Step::new(&[
+    rule("díssimo", 5, ""),
+    rule("abilíssimo", 5, ""),
+    rule("íssimo", 3, ""),
+    rule("ésimo", 3, ""),
+    rule("érrimo", 4, ""),
+    rule("zinho", 2, ""),
+    rule("quinho", 4, "c"),
+    rule("uinho", 4, ""),
+    rule("adinho", 3, ""),
+    except("inho", 3, "", &["caminho", "cominho"]),
+    rule("alhão", 4, ""),
+    rule("uça", 4, ""),
+    except("aço", 4, "", &["antebraço"]),
+    rule("aça", 4, ""),
+    rule("adão", 4, ""),
+    rule("idão", 4, ""),
+    except("ázio", 3, "", &["topázio"]),
+    rule("arraz", 4, ""),
+    rule("zarrão", 3, ""),
+    rule("arrão", 4, ""),
+    rule("arra", 3, ""),
+    except("zão", 2, "", &["coalizão"]),
+    except(
+        "ão",
+        3,
+        "",
+        &[
+            "camarão", "chimarrão", "canção", "coração", "embrião", "grotão", "glutão", "ficção", "fogão", "feição",
+            "furacão", "gamão", "lampião", "leão", "macacão", "nação", "órfão", "órgão", "patrão", "portão", "quinhão",
+            "rincão", "tração", "falcão", "espião", "mamão", "folião", "cordão", "aptidão", "campeão", "colchão",
+            "limão", "leilão", "melão", "barão", "milhão", "bilhão", "fusão", "cristão", "ilusão", "capitão",
+            "estação", "senão",
+        ],
+    ),
+]);
+
+static NOUN: Step = Step::new(&[
+    rule("encialista", 4, ""),
+    rule("alista", 5, ""),
+    except("agem", 3, "", &["coragem", "chantagem", "vantagem", "carruagem"]),
+    rule("iamento", 4, ""),
+    except("amento", 3, "", &["firmamento", "fundamento", "departamento"]),
+    rule("imento", 3, ""),
+    except("mento", 6, "", &["firmamento", "elemento", "complemento", "instrumento", "departamento"]),
+    rule("alizado", 4, ""),
+    rule("atizado", 4, ""),
+    except("tizado", 4, "", &["alfabetizado"]),
+    except("izado", 5, "", &["organizado", "pulverizado"]),
+    except("ativo", 4, "", &["pejorativo", "relativo"]),
+    except("tivo", 4, "", &["relativo"]),
+    except("ivo", 4, "", &["passivo", "possessivo", "pejorativo", "positivo"]),
+    except("ado", 2, "", &["grado"]),
+    except("ido", 3, "", &["cândido", "consolido", "rápido", "decido", "tímido", "duvido", "marido"]),
+    rule("ador", 3, ""),
+    rule("edor", 3, ""),
+    except("idor", 4, "", &["ouvidor"]),
+    except("dor", 4, "", &["ouvidor"]),
+    except("sor", 4, "", &["assessor"]),
+    rule("atoria", 5, ""),
+    except("tor", 3, "", &["benfeitor", "leitor", "editor", "pastor", "produtor", "promotor", "consultor"]),
+    except(
+        "or",
+        2,
+        "",
+        &[
+            "motor", "melhor", "redor", "rigor", "sensor", "tambor", "tumor", "assessor", "benfeitor", "pastor",
+            "terior", "favor", "autor",
+        ],
+    ),
+    rule("abilidade", 5, ""),
+    rule("icionista", 4, ""),
+    rule("cionista", 5, ""),
+    rule("ionista", 5, ""),
+    rule("ionar", 5, ""),
+    rule("ional", 4, ""),
+    rule("ência", 3, ""),
+    except("ância", 4, "", &["ambulância"]),
+    rule("edouro", 3, ""),
+    rule("queiro", 3, "c"),
+    except("adeiro", 4, "", &["desfiladeiro"]),
+    except("eiro", 3, "", &["desfiladeiro", "pioneiro", "mosteiro"]),
+    rule("uoso", 3, ""),
+    except("oso", 3, "", &["precioso"]),
+    rule("alizaç", 5, ""),
+    rule("atizaç", 5, ""),
+    rule("tizaç", 5, ""),
+    except("izaç", 5, "", &["organizaç"]),
+    except("aç", 3, "", &["equaç", "relaç"]),
+    except("iç", 3, "", &["eleiç"]),
+    except("ário", 3, "", &["voluntário", "salário", "aniversário", "diário", "lionário", "armário"]),
+    rule("atório", 3, ""),
+    except(
+        "rio",
+        5,
+        "",
+        &["voluntário", "salário", "aniversário", "diário", "compulsório", "lionário", "próprio", "stério", "armário"],
+    ),
+    rule("ério", 6, ""),
+    rule("ês", 4, ""),
+    rule("eza", 3, ""),
+    rule("ez", 4, ""),
+    rule("esco", 4, ""),
+    except("ante", 2, "", &["gigante", "elefante", "adiante", "possante", "instante", "restaurante"]),
+    except("ástico", 4, "", &["eclesiástico"]),
+    rule("alístico", 3, ""),
+    rule("áutico", 4, ""),
+    rule("êutico", 4, ""),
+    except(
+        "tico",
+        3,
+        "",
+        &[
+            "político", "eclesiástico", "diagnóstico", "prático", "doméstico", "idêntico", "alopático", "artístico",
+            "autêntico", "eclético", "crítico",
+        ],
+    ),
+    except("ico", 4, "", &["tico", "público", "explico"]),
+    rule("ividade", 5, ""),
+    except("idade", 4, "", &["autoridade", "comunidade"]),
+    except("oria", 4, "", &["categoria"]),
+    rule("encial", 5, ""),
+    rule("ista", 4, ""),
+    rule("auta", 5, ""),
+    rule("quice", 4, "c"),
+    except("ice", 4, "", &["cúmplice"]),
+    rule("íaco", 3, ""),
+    except("ente", 4, "", &["frequente", "alimente", "acrescente", "permanente", "oriente", "aparente"]),
+    rule("ense", 5, ""),
+    rule("inal", 3, ""),
+    rule("ano", 4, ""),
+    except("ável", 2, "", &["afável", "razoável", "potável", "vulnerável"]),
+    except("ível", 3, "", &["possível"]),
+    except("vel", 5, "", &["possível", "vulnerável", "solúvel"]),
+    rule("bil", 3, "vel"),
+    except("ura", 4, "", &["imatura", "acupuntura", "costura"]),
+    rule("ural", 4, ""),
+    except("ual", 3, "", &["bissexual", "virtual", "visual", "pontual"]),
+    rule("ial", 3, ""),
+    except(
+        "al",
+        4,
+        "",
+        &[
+            "afinal", "animal", "estatal", "bissexual", "desleal", "fiscal", "formal", "pessoal", "liberal", "postal",
+            "virtual", "visual", "pontual", "sideral", "sucursal",
+        ],
+    ),
+    rule("alismo", 4, ""),
+    rule("ivismo", 4, ""),
+    except("ismo", 3, "", &["cinismo"]),
+]);
+
+static VERB: Step = Step::new(&[
+    rule("aríamo", 2, ""),
+    rule("ássemo", 2, ""),
+    rule("eríamo", 2, ""),
+    rule("êssemo", 2, ""),
+    rule("iríamo", 3, ""),
+    rule("íssemo", 3, ""),
+    rule("áramo", 2, ""),
+    rule("arei", 2, ""),
+    rule("aremo", 2, ""),
+    rule("ariam", 2, ""),
+    rule("aríei", 2, ""),
+    rule("ássei", 2, ""),
+    rule("assem", 2, ""),
+    rule("ávamo", 2, ""),
+    rule("êramo", 3, ""),
+    rule("eremo", 3, ""),
+    rule("eriam", 3, ""),
+    rule("eríei", 3, ""),
+    rule("êssei", 3, ""),
+    rule("essem", 3, ""),
+    rule("íramo", 3, ""),
+    rule("iremo", 3, ""),
+    rule("iriam", 3, ""),
+    rule("iríei", 3, ""),
+    rule("íssei", 3, ""),
+    rule("issem", 3, ""),
+    rule("ando", 2, ""),
+    rule("endo", 3, ""),
+    rule("indo", 3, ""),
+    rule("ondo", 3, ""),
+    rule("aram", 2, ""),
+    rule("arão", 2, ""),
+    rule("arde", 2, ""),
+    rule("arem", 2, ""),
+    rule("aria", 2, ""),
+    rule("armo", 2, ""),
+    rule("asse", 2, ""),
+    rule("aste", 2, ""),
+    except("avam", 2, "", &["agravam"]),
+    rule("ávei", 2, ""),
+    rule("eram", 3, ""),
+    rule("erão", 3, ""),
+    rule("erde", 3, ""),
+    rule("erei", 3, ""),
+    rule("erem", 3, ""),
+    rule("eria", 3, ""),
+    rule("ermo", 3, ""),
+    rule("esse", 3, ""),
+    except("este", 3, "", &["faroeste", "agreste"]),
+    rule("íamo", 3, ""),
+    rule("iram", 3, ""),
+    rule("irão", 2, ""),
+    rule("irde", 2, ""),
+    except("irei", 3, "", &["admirei"]),
+    except("irem", 3, "", &["adquirem"]),
+    rule("iria", 3, ""),
+    rule("irmo", 3, ""),
+    rule("isse", 3, ""),
+    rule("iste", 4, ""),
+    except("iava", 4, "", &["ampliava"]),
+    rule("amo", 2, ""),
+    rule("iona", 3, ""),
+    except("ara", 2, "", &["arara", "prepara", "alvará"]),
+    except("are", 2, "", &["prepare"]),
+    except("ava", 2, "", &["agrava"]),
+    rule("emo", 2, ""),
+    except("era", 3, "", &["acelera", "espera"]),
+    except("ere", 3, "", &["espere"]),
+    except("iam", 3, "", &["enfiam", "ampliam", "elogiam", "ensaiam"]),
+    rule("íei", 3, ""),
+    except("imo", 3, "", &["reprimo", "intimo", "nimo", "queimo", "ximo"]),
+    except("ira", 3, "", &["fronteira", "sátira"]),
+    rule("ído", 3, ""),
+    except("tizar", 4, "", &["alfabetizar"]),
+    except("izar", 5, "", &["organizar"]),
+    except("itar", 5, "", &["acreditar", "explicitar", "estreitar"]),
+    except("ire", 3, "", &["adquire"]),
+    rule("omo", 3, ""),
+    rule("ai", 2, ""),
+    rule("am", 2, ""),
+    except("ear", 4, "", &["alardear", "nuclear"]),
+    except("ar", 2, "", &["azar", "bazaar", "patamar"]),
+    rule("uei", 3, ""),
+    rule("uía", 5, "u"),
+    rule("ei", 3, ""),
+    rule("guem", 3, "g"),
+    except("em", 2, "", &["alem", "virgem"]),
+    except("er", 2, "", &["éter", "pier"]),
+    except("eu", 3, "", &["chapéu"]),
+    except(
+        "ia",
+        3,
+        "",
+        &["estória", "fatia", "acia", "praia", "elogia", "mania", "lábia", "aprecia", "polícia", "arredia", "cheia", "ásia"],
+    ),
+    except("ir", 3, "", &["freir"]),
+    rule("iu", 3, ""),
+    rule("eou", 5, ""),
+    rule("ou", 3, ""),
+    rule("i", 3, ""),
+]);
+
+static VOWEL: Step = Step::new(&[except("a", 3, "", &["ia"]), rule("e", 3, ""), except("o", 3, "", &["oo"])]);
+
+/// Apply the first rule of `step` that fits `word`
+fn apply(word: &mut String, step: &Step) -> bool {
+    for (rule, folded) in step.rules.iter().zip(step.folded()) {
+        let (suffix, replacement) = if word.ends_with(rule.suffix) {
+            (rule.suffix, rule.replacement)
+        } else if word.ends_with(folded.suffix.as_str()) {
+            (folded.suffix.as_str(), folded.replacement.as_str())
+        } else {
+            continue;
+        };
+        let stem_len = word[..word.len() - suffix.len()].chars().count();
+        let exception = folded.exceptions.contains(&fold(word)) || folded.exceptions.contains(word.as_str());
+        if stem_len >= rule.min_stem && !exception {
+            word.truncate(word.len() - suffix.len());
+            word.push_str(replacement);
+            return true;
+        }
+    }
+    false
+}
+
+/// Stem of a lowercase word, accents kept (NFC)
+pub fn stem(word: &str) -> String {
+    let mut word = word.to_string();
+    if word.chars().count() < 3 || word.chars().any(|c| c.is_ascii_digit()) {
+        return word;
+    }
+
+    if word.ends_with('s') {
+        apply(&mut word, &PLURAL);
+    }
+    if word.ends_with('a') || word.ends_with('ã') {
+        apply(&mut word, &FEMININE);
+    }
+    apply(&mut word, &ADVERB);
+    apply(&mut word, &AUGMENTATIVE);
+    if !apply(&mut word, &NOUN) && !apply(&mut word, &VERB) {
+        apply(&mut word, &VOWEL);
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folded_stem(word: &str) -> String {
+        fold(&stem(word))
+    }
+
+    #[test]
+    fn test_accented_exceptions() {
+        assert_eq!(folded_stem("país"), folded_stem("países"));
+        // Without the accent it is the plural of "pai"
+        assert_eq!(folded_stem("pais"), folded_stem("pai"));
+        assert_ne!(folded_stem("pais"), folded_stem("país"));
+        assert_eq!(folded_stem("jornais"), folded_stem("jornal"));
+    }
+
+    #[test]
+    fn test_unaccented_words_share_the_stem() {
+        let stem = folded_stem("função");
+        assert_eq!(folded_stem("funções"), stem);
+        assert_eq!(folded_stem("funcao"), stem);
+        assert_eq!(folded_stem("funcoes"), stem);
+        assert_eq!(folded_stem("equações"), folded_stem("equacao"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Text analysis for full-text search
+//!
+//! Words are lowercased and reduced to their RSLP stem, then decomposed
+//! (NFD) and stripped of diacritics, so "Equação", "equacao" and "EQUAÇÃO"
+//! are the same word. The stemmer sees the accents; stop words are dropped.
+
+use std::ops::Range;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use super::stemmer::stem;
+
+/// Portuguese stop words, already folded and sorted for binary search
+const STOP_WORDS: &[&str] = &[
+    "a", "ao", "aos", "aquela", "aquelas", "aquele", "aqueles", "aquilo", "as", "ate", "com", "como", "da", "das",
+    "de", "dela", "delas", "dele", "deles", "depois", "do", "dos", "e", "ela", "elas", "ele", "eles", "em", "entre",
+    "era", "essa", "essas", "esse", "esses", "esta", "estas", "este", "estes", "eu", "foi", "ha", "isso", "isto",
+    "ja", "lhe", "lhes", "mais", "mas", "me", "mesmo", "meu", "minha", "muito", "na", "nao", "nas", "nem", "no",
+    "nos", "num", "numa", "o", "os", "ou", "para", "pela", "pelas", "pelo", "pelos", "por", "quais", "qual",
+    "quando", "que", "quem", "se", "sem", "ser", "seu", "seus", "so", "sua", "suas", "tambem", "te", "tem", "um",
+    "uma", "umas", "uns", "voce", "voces",
+];
+
+/// Lowercase `text` without diacritics
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub fn is_stop_word(folded: &str) -> bool {
+    STOP_WORDS.binary_search(&folded).is_ok()
+}
+
+/// Byte ranges of the words (runs of letters and digits) in `text`
+pub fn word_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push(s..text.len());
+    }
+    ranges
+}
+
+/// Index term of one word; `None` for stop words
+pub fn term(word: &str) -> Option<String> {
+    let lower: String = word.nfc().flat_map(char::to_lowercase).collect();
+    if is_stop_word(&fold(&lower)) {
+        None
+    } else {
+        Some(fold(&stem(&lower)))
+    }
+}
+
+/// Index terms of `text`, in order
+pub fn analyze(text: &str) -> Vec<String> {
+    word_ranges(text).into_iter().filter_map(|range| term(&text[range])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accents_and_case_do_not_matter() {
+        let expected = term("função");
+        assert!(expected.is_some());
+        assert_eq!(term("funções"), expected);
+        assert_eq!(term("Funcao"), expected);
+        assert_eq!(term("FUNÇÃO"), expected);
+    }
+
+    #[test]
+    fn test_stop_words_are_dropped() {
+        assert_eq!(term("de"), None);
+        assert_eq!(term("Não"), None);
+        assert_eq!(analyze("O domínio de uma função"), vec![term("domínio").unwrap(), term("função").unwrap()]);
+    }
+
+    #[test]
+    fn test_word_ranges_are_byte_ranges() {
+        let text = "ação, reação";
+        let words: Vec<&str> = word_ranges(text).into_iter().map(|range| &text[range]).collect();
+        assert_eq!(words, vec!["ação", "reação"]);
+    }
+}
@@ -79,6 +79,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 0,
             explanation: "Sabendo que f(0) = c = 6 e que as raízes são 2 e -3, temos: f(x) = a(x-2)(x+3) = a(x² + x - 6). Como f(0) = -6a = 6, então a = -1.".to_string(),
             tags: vec!["função quadrática".to_string(), "raízes".to_string(), "álgebra".to_string()],
+            source: None,
         },
         Question {
             id: Uuid::parse_str("10000000-0000-0000-0000-000000000002").unwrap(),
@@ -95,6 +96,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "Para resolver: 2x + 5 = 15, subtraímos 5 de ambos os lados: 2x = 10. Dividindo por 2: x = 5.".to_string(),
             tags: vec!["equação do primeiro grau".to_string(), "álgebra".to_string()],
+            source: None,
         },
         Question {
             id: Uuid::parse_str("10000000-0000-0000-0000-000000000003").unwrap(),
@@ -111,6 +113,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 0,
             explanation: "Usando a propriedade dos logaritmos: log₂(x) + log₂(x+2) = log₂(x(x+2)) = 3. Logo, x(x+2) = 2³ = 8. Resolvendo: x² + 2x - 8 = 0. As raízes são x = 2 e x = -4. Como x > 0 no domínio do logaritmo, x = 2.".to_string(),
             tags: vec!["logaritmos".to_string(), "equação logarítmica".to_string()],
+            source: None,
         },
         
         // História
@@ -129,6 +132,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "O Brasil foi descoberto em 22 de abril de 1500 pela expedição comandada por Pedro Álvares Cabral, que estava a caminho das Índias.".to_string(),
             tags: vec!["história do brasil".to_string(), "descobrimento".to_string(), "colonização".to_string()],
+            source: None,
         },
         Question {
             id: Uuid::parse_str("20000000-0000-0000-0000-000000000002").unwrap(),
@@ -145,6 +149,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "A Inconfidência Mineira (1789) foi motivada principalmente pela insatisfação com os altos impostos portugueses, especialmente a derrama, e pelo desejo de independência da região de Minas Gerais.".to_string(),
             tags: vec!["história do brasil".to_string(), "inconfidência mineira".to_string(), "período colonial".to_string()],
+            source: None,
         },
         
         // Física
@@ -163,6 +168,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "Usando a equação de Torricelli: v² = v₀² + 2aΔs. No ponto de altura máxima, v = 0. Logo: 0 = 20² - 2(10)h. Portanto, h = 400/20 = 20 m.".to_string(),
             tags: vec!["mecânica".to_string(), "lançamento vertical".to_string(), "cinemática".to_string()],
+            source: None,
         },
         Question {
             id: Uuid::parse_str("30000000-0000-0000-0000-000000000002").unwrap(),
@@ -179,6 +185,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "A carga inicial é Q = CV = 10×10⁻⁶ × 100 = 10⁻³ C. A capacitância equivalente em paralelo é C_eq = 10 + 5 = 15 μF. Como a carga se conserva, V_final = Q/C_eq = 10⁻³/(15×10⁻⁶) = 66,7 V.".to_string(),
             tags: vec!["eletricidade".to_string(), "capacitores".to_string(), "circuitos".to_string()],
+            source: None,
         },
         
         // Química
@@ -197,6 +204,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "HCl é um ácido forte que se dissocia completamente. Portanto, [H⁺] = 0,01 mol/L = 10⁻² mol/L. Logo, pH = -log[H⁺] = -log(10⁻²) = 2.".to_string(),
             tags: vec!["química analítica".to_string(), "pH".to_string(), "ácidos e bases".to_string()],
+            source: None,
         },
         
         // Biologia
@@ -215,6 +223,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "A fotossíntese ocorre nos cloroplastos, organelas que contêm clorofila e outros pigmentos necessários para capturar a energia luminosa e convertê-la em energia química armazenada na glicose.".to_string(),
             tags: vec!["biologia celular".to_string(), "fotossíntese".to_string(), "organelas".to_string()],
+            source: None,
         },
         
         // Literatura
@@ -233,6 +242,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "Dom Casmurro, publicado em 1899, é uma das obras mais famosas de Machado de Assis e da literatura brasileira, narrando a história de Bentinho e Capitu com sua prosa irônica e psicológica característica.".to_string(),
             tags: vec!["literatura brasileira".to_string(), "machado de assis".to_string(), "romance".to_string()],
+            source: None,
         },
         
         // Geografia
@@ -251,6 +261,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 1,
             explanation: "A Amazônia é o maior bioma brasileiro, ocupando cerca de 49% do território nacional, além de se estender por outros países da América do Sul.".to_string(),
             tags: vec!["geografia do brasil".to_string(), "biomas".to_string(), "amazônia".to_string()],
+            source: None,
         },
         
         // Português
@@ -269,6 +280,7 @@ pub async fn seed_questions(question_repo: &InMemoryQuestionRepository) -> Resul
             correct_answer: 0,
             explanation: "Segundo o Acordo Ortográfico de 2009, 'ideia' não tem mais acento (era 'idéia'), 'jiboia' não tem acento (era 'jibóia'), e 'assembleia' não tem mais acento (era 'assembléia').".to_string(),
             tags: vec!["português".to_string(), "ortografia".to_string(), "acentuação".to_string()],
+            source: None,
         },
     ];
     
@@ -304,6 +316,11 @@ Portanto, é essencial que políticas públicas sejam desenvolvidas para democra
             feedback: None,
             corrections: None,
             rubric_scores: None,
+            estimated_lines: None,
+            text_metrics: None,
+            attributions: None,
+            confidence: None,
+            review: None,
             created_at: now - Duration::days(5),
             updated_at: now - Duration::hours(2),
             submitted_at: None,
@@ -346,6 +363,11 @@ Dessa forma, é possível construir caminhos para reduzir a evasão escolar e ga
                     map
                 },
             }),
+            estimated_lines: None,
+            text_metrics: None,
+            attributions: None,
+            confidence: None,
+            review: None,
             created_at: now - Duration::days(15),
             updated_at: now - Duration::days(10),
             submitted_at: Some(now - Duration::days(10)),
@@ -363,6 +385,11 @@ Dessa forma, é possível construir caminhos para reduzir a evasão escolar e ga
             feedback: None,
             corrections: None,
             rubric_scores: None,
+            estimated_lines: None,
+            text_metrics: None,
+            attributions: None,
+            confidence: None,
+            review: None,
             created_at: now - Duration::days(2),
             updated_at: now - Duration::hours(12),
             submitted_at: None,
@@ -396,6 +423,11 @@ Dessa forma, é possível construir caminhos para reduzir a evasão escolar e ga
                 },
                 detailed_feedback: HashMap::new(),
             }),
+            estimated_lines: None,
+            text_metrics: None,
+            attributions: None,
+            confidence: None,
+            review: None,
             created_at: now - Duration::days(20),
             updated_at: now - Duration::days(18),
             submitted_at: Some(now - Duration::days(18)),
@@ -421,6 +453,11 @@ Portanto, é necessário repensar o modelo de desenvolvimento urbano, priorizand
             feedback: None,
             corrections: None,
             rubric_scores: None,
+            estimated_lines: None,
+            text_metrics: None,
+            attributions: None,
+            confidence: None,
+            review: None,
             created_at: now - Duration::days(3),
             updated_at: now - Duration::hours(6),
             submitted_at: None,
@@ -447,6 +484,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia se o texto aborda adequadamente o tema proposto e se há fuga parcial ou total do assunto.".to_string(),
                 weight: 0.2,
                 max_score: 8,
+                levels: 8,
                 evaluation_points: vec![
                     "Abordagem completa e adequada ao tema (7-8 pontos)".to_string(),
                     "Abordagem parcial ou com leve desvio (4-6 pontos)".to_string(),
@@ -459,6 +497,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia se o texto está adequado ao gênero dissertativo-argumentativo, com introdução, desenvolvimento e conclusão.".to_string(),
                 weight: 0.2,
                 max_score: 8,
+                levels: 8,
                 evaluation_points: vec![
                     "Gênero adequado com estrutura completa e bem organizada (7-8 pontos)".to_string(),
                     "Gênero adequado com estrutura parcial ou organização razoável (4-6 pontos)".to_string(),
@@ -471,6 +510,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a consistência lógica do texto, a progressão das ideias e a argumentação.".to_string(),
                 weight: 0.2,
                 max_score: 8,
+                levels: 8,
                 evaluation_points: vec![
                     "Texto coerente com argumentação sólida e lógica (7-8 pontos)".to_string(),
                     "Texto geralmente coerente com pequenas inconsistências (4-6 pontos)".to_string(),
@@ -483,6 +523,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia os mecanismos de coesão textual: conectivos, referências, paralelismos e articulação entre parágrafos.".to_string(),
                 weight: 0.2,
                 max_score: 8,
+                levels: 8,
                 evaluation_points: vec![
                     "Coesão adequada com uso correto de conectivos e articulações (7-8 pontos)".to_string(),
                     "Coesão razoável com alguns problemas de articulação (4-6 pontos)".to_string(),
@@ -495,6 +536,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia aspectos gramaticais: ortografia, acentuação, pontuação, concordância, regência e colocação pronominal.".to_string(),
                 weight: 0.2,
                 max_score: 8,
+                levels: 8,
                 evaluation_points: vec![
                     "Domínio excelente da norma padrão com poucos ou nenhum erro (7-8 pontos)".to_string(),
                     "Bom domínio com alguns desvios da norma padrão (4-6 pontos)".to_string(),
@@ -518,6 +560,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o domínio da modalidade escrita formal da Língua Portuguesa, incluindo ortografia, acentuação, pontuação, concordância e regência.".to_string(),
                 weight: 0.2,
                 max_score: 200,
+                levels: 5,
                 evaluation_points: vec![
                     "Domínio excelente da norma padrão (160-200 pontos)".to_string(),
                     "Bom domínio com poucos desvios (120-159 pontos)".to_string(),
@@ -531,6 +574,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia se o candidato compreendeu a proposta de redação e se desenvolveu o tema dentro dos limites estruturais do texto dissertativo-argumentativo.".to_string(),
                 weight: 0.2,
                 max_score: 200,
+                levels: 5,
                 evaluation_points: vec![
                     "Desenvolvimento completo do tema com fuga parcial inexistente (160-200 pontos)".to_string(),
                     "Desenvolvimento adequado com leve fuga parcial (120-159 pontos)".to_string(),
@@ -544,6 +588,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a capacidade de selecionar, relacionar, organizar e interpretar informações, fatos, opiniões e argumentos em defesa de um ponto de vista.".to_string(),
                 weight: 0.2,
                 max_score: 200,
+                levels: 5,
                 evaluation_points: vec![
                     "Informações selecionadas e organizadas de forma excelente (160-200 pontos)".to_string(),
                     "Informações bem selecionadas e organizadas (120-159 pontos)".to_string(),
@@ -557,6 +602,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o conhecimento dos mecanismos linguísticos necessários para a construção da argumentação, incluindo coesão e coerência.".to_string(),
                 weight: 0.2,
                 max_score: 200,
+                levels: 5,
                 evaluation_points: vec![
                     "Demonstração excelente dos mecanismos linguísticos (160-200 pontos)".to_string(),
                     "Demonstração adequada dos mecanismos linguísticos (120-159 pontos)".to_string(),
@@ -570,6 +616,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a proposta de intervenção para o problema abordado, respeitando os direitos humanos e apresentando ações específicas.".to_string(),
                 weight: 0.2,
                 max_score: 200,
+                levels: 5,
                 evaluation_points: vec![
                     "Proposta de intervenção excelente e detalhada, respeitando direitos humanos (160-200 pontos)".to_string(),
                     "Proposta de intervenção adequada e respeitando direitos humanos (120-159 pontos)".to_string(),
@@ -594,6 +641,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia se o texto está adequado ao tema proposto e ao gênero textual solicitado.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Adequação completa ao tema e gênero (5-6 pontos)".to_string(),
                     "Adequação adequada com pequenos desvios (3-4 pontos)".to_string(),
@@ -606,6 +654,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a consistência lógica do texto e os mecanismos de coesão textual.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Excelente coerência e coesão (5-6 pontos)".to_string(),
                     "Boa coerência e coesão (3-4 pontos)".to_string(),
@@ -618,6 +667,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o domínio da escrita formal da Língua Portuguesa.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Domínio excelente da norma padrão (5-6 pontos)".to_string(),
                     "Bom domínio com poucos desvios (3-4 pontos)".to_string(),
@@ -630,6 +680,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o uso adequado de recursos expressivos e figuras de linguagem.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Uso excelente de recursos expressivos (5-6 pontos)".to_string(),
                     "Uso adequado de recursos expressivos (3-4 pontos)".to_string(),
@@ -642,6 +693,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a riqueza e precisão do vocabulário utilizado.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Vocabulário rico e preciso (5-6 pontos)".to_string(),
                     "Vocabulário adequado (3-4 pontos)".to_string(),
@@ -654,6 +706,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a organização e estruturação do texto.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Estrutura textual excelente (5-6 pontos)".to_string(),
                     "Estrutura textual adequada (3-4 pontos)".to_string(),
@@ -666,6 +719,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a qualidade e força dos argumentos apresentados.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Argumentação sólida e convincente (5-6 pontos)".to_string(),
                     "Argumentação adequada (3-4 pontos)".to_string(),
@@ -678,6 +732,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a criatividade e originalidade na abordagem do tema.".to_string(),
                 weight: 0.125,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Abordagem muito original e criativa (5-6 pontos)".to_string(),
                     "Abordagem original (3-4 pontos)".to_string(),
@@ -701,6 +756,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a capacidade de analisar e interpretar informações e contextos.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Análise e interpretação excelentes (5-6 pontos)".to_string(),
                     "Análise e interpretação adequadas (3-4 pontos)".to_string(),
@@ -713,6 +769,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a qualidade e consistência dos argumentos apresentados.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Argumentação sólida e bem fundamentada (5-6 pontos)".to_string(),
                     "Argumentação adequada (3-4 pontos)".to_string(),
@@ -725,6 +782,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a consistência lógica e a progressão das ideias no texto.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Coerência textual excelente (5-6 pontos)".to_string(),
                     "Coerência textual adequada (3-4 pontos)".to_string(),
@@ -737,6 +795,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia os mecanismos de coesão textual utilizados.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Coesão textual excelente (5-6 pontos)".to_string(),
                     "Coesão textual adequada (3-4 pontos)".to_string(),
@@ -749,6 +808,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia se o texto está adequado ao gênero textual solicitado.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Adequação completa ao gênero (5-6 pontos)".to_string(),
                     "Adequação adequada ao gênero (3-4 pontos)".to_string(),
@@ -761,6 +821,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o uso adequado da linguagem conforme o contexto e gênero.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Uso muito adequado da linguagem (5-6 pontos)".to_string(),
                     "Uso adequado da linguagem (3-4 pontos)".to_string(),
@@ -773,6 +834,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a riqueza, precisão e adequação do vocabulário.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Vocabulário rico e preciso (5-6 pontos)".to_string(),
                     "Vocabulário adequado (3-4 pontos)".to_string(),
@@ -785,6 +847,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a organização e estruturação do texto.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Estruturação excelente (5-6 pontos)".to_string(),
                     "Estruturação adequada (3-4 pontos)".to_string(),
@@ -797,6 +860,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a criatividade e originalidade na abordagem do tema.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Muito criativo e original (5-6 pontos)".to_string(),
                     "Criativo e adequado (3-4 pontos)".to_string(),
@@ -809,6 +873,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a correção ortográfica e acentual do texto.".to_string(),
                 weight: 0.1,
                 max_score: 6,
+                levels: 6,
                 evaluation_points: vec![
                     "Ortografia e acentuação corretas (5-6 pontos)".to_string(),
                     "Poucos erros de ortografia e acentuação (3-4 pontos)".to_string(),
@@ -832,6 +897,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a abordagem do tema, a qualidade das informações e argumentos apresentados.".to_string(),
                 weight: 0.25,
                 max_score: 5,
+                levels: 5,
                 evaluation_points: vec![
                     "Conteúdo temático completo e bem desenvolvido (4-5 pontos)".to_string(),
                     "Conteúdo temático adequado (3 pontos)".to_string(),
@@ -844,6 +910,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a organização, estruturação e progressão do texto dissertativo-argumentativo.".to_string(),
                 weight: 0.25,
                 max_score: 5,
+                levels: 5,
                 evaluation_points: vec![
                     "Estrutura textual excelente (4-5 pontos)".to_string(),
                     "Estrutura textual adequada (3 pontos)".to_string(),
@@ -856,6 +923,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o domínio da linguagem formal, vocabulário e expressão adequados ao gênero.".to_string(),
                 weight: 0.25,
                 max_score: 5,
+                levels: 5,
                 evaluation_points: vec![
                     "Linguagem excelente e adequada (4-5 pontos)".to_string(),
                     "Linguagem adequada (3 pontos)".to_string(),
@@ -868,6 +936,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o uso adequado de mecanismos de coesão textual: conectivos, referências e articulações.".to_string(),
                 weight: 0.25,
                 max_score: 5,
+                levels: 5,
                 evaluation_points: vec![
                     "Mecanismos de coesão excelentes (4-5 pontos)".to_string(),
                     "Mecanismos de coesão adequados (3 pontos)".to_string(),
@@ -892,6 +961,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia se o texto está adequado ao tema proposto e ao gênero dissertativo-argumentativo.".to_string(),
                 weight: 0.2,
                 max_score: 2,
+                levels: 2,
                 evaluation_points: vec![
                     "Adequação completa (2 pontos)".to_string(),
                     "Adequação parcial (1 ponto)".to_string(),
@@ -903,6 +973,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a organização e estruturação do texto.".to_string(),
                 weight: 0.2,
                 max_score: 2,
+                levels: 2,
                 evaluation_points: vec![
                     "Estrutura excelente (2 pontos)".to_string(),
                     "Estrutura adequada (1 ponto)".to_string(),
@@ -914,6 +985,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a consistência lógica e os mecanismos de coesão.".to_string(),
                 weight: 0.2,
                 max_score: 2,
+                levels: 2,
                 evaluation_points: vec![
                     "Excelente coerência e coesão (2 pontos)".to_string(),
                     "Coerência e coesão adequadas (1 ponto)".to_string(),
@@ -925,6 +997,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o domínio da norma padrão e o vocabulário.".to_string(),
                 weight: 0.2,
                 max_score: 2,
+                levels: 2,
                 evaluation_points: vec![
                     "Domínio excelente (2 pontos)".to_string(),
                     "Domínio adequado (1 ponto)".to_string(),
@@ -936,6 +1009,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a qualidade dos argumentos apresentados.".to_string(),
                 weight: 0.2,
                 max_score: 2,
+                levels: 2,
                 evaluation_points: vec![
                     "Argumentação sólida (2 pontos)".to_string(),
                     "Argumentação adequada (1 ponto)".to_string(),
@@ -959,6 +1033,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia se o texto aborda adequadamente o tema proposto.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Abordagem completa e adequada (8-10 pontos)".to_string(),
                         "Abordagem adequada (5-7 pontos)".to_string(),
@@ -971,6 +1046,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia se o texto está adequado ao gênero solicitado.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Gênero adequado com estrutura completa (8-10 pontos)".to_string(),
                         "Gênero adequado com estrutura parcial (5-7 pontos)".to_string(),
@@ -983,6 +1059,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia a consistência lógica e progressão das ideias.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Texto muito coerente (8-10 pontos)".to_string(),
                         "Texto coerente (5-7 pontos)".to_string(),
@@ -995,6 +1072,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia os mecanismos de coesão textual.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Coesão excelente (8-10 pontos)".to_string(),
                         "Coesão adequada (5-7 pontos)".to_string(),
@@ -1007,6 +1085,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia a qualidade e força dos argumentos.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Argumentação sólida e convincente (8-10 pontos)".to_string(),
                         "Argumentação adequada (5-7 pontos)".to_string(),
@@ -1019,6 +1098,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia o domínio da escrita formal da Língua Portuguesa.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Domínio excelente (8-10 pontos)".to_string(),
                         "Domínio adequado (5-7 pontos)".to_string(),
@@ -1031,6 +1111,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia a riqueza e precisão do vocabulário.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Vocabulário rico e preciso (8-10 pontos)".to_string(),
                         "Vocabulário adequado (5-7 pontos)".to_string(),
@@ -1043,6 +1124,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia a organização e estruturação do texto.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Estruturação excelente (8-10 pontos)".to_string(),
                         "Estruturação adequada (5-7 pontos)".to_string(),
@@ -1055,6 +1137,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia a criatividade e originalidade na abordagem.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Muito original e criativo (8-10 pontos)".to_string(),
                         "Original e adequado (5-7 pontos)".to_string(),
@@ -1067,6 +1150,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                     description: "Avalia a correção ortográfica e acentual.".to_string(),
                     weight: 0.1,
                     max_score: 10,
+                    levels: 10,
                     evaluation_points: vec![
                         "Ortografia e acentuação corretas (8-10 pontos)".to_string(),
                         "Poucos erros (5-7 pontos)".to_string(),
@@ -1123,6 +1207,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a qualidade da argumentação técnica e científica.".to_string(),
                 weight: 0.3,
                 max_score: 15,
+                levels: 15,
                 evaluation_points: vec![
                     "Argumentação técnica excelente (12-15 pontos)".to_string(),
                     "Argumentação técnica adequada (8-11 pontos)".to_string(),
@@ -1135,6 +1220,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o rigor científico e precisão técnica.".to_string(),
                 weight: 0.25,
                 max_score: 12,
+                levels: 12,
                 evaluation_points: vec![
                     "Rigor científico excelente (10-12 pontos)".to_string(),
                     "Rigor científico adequado (6-9 pontos)".to_string(),
@@ -1147,6 +1233,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a coerência lógica e estruturação do texto.".to_string(),
                 weight: 0.2,
                 max_score: 10,
+                levels: 10,
                 evaluation_points: vec![
                     "Coerência e estrutura excelentes (8-10 pontos)".to_string(),
                     "Coerência e estrutura adequadas (5-7 pontos)".to_string(),
@@ -1159,6 +1246,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o domínio da linguagem formal e técnica.".to_string(),
                 weight: 0.15,
                 max_score: 8,
+                levels: 8,
                 evaluation_points: vec![
                     "Domínio linguístico excelente (6-8 pontos)".to_string(),
                     "Domínio linguístico adequado (4-5 pontos)".to_string(),
@@ -1171,6 +1259,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a originalidade e criatividade na abordagem técnica.".to_string(),
                 weight: 0.1,
                 max_score: 5,
+                levels: 5,
                 evaluation_points: vec![
                     "Originalidade técnica excelente (4-5 pontos)".to_string(),
                     "Originalidade técnica adequada (2-3 pontos)".to_string(),
@@ -1193,6 +1282,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a qualidade da argumentação técnica e científica.".to_string(),
                 weight: 0.25,
                 max_score: 25,
+                levels: 25,
                 evaluation_points: vec![
                     "Argumentação técnica excelente (20-25 pontos)".to_string(),
                     "Argumentação técnica adequada (13-19 pontos)".to_string(),
@@ -1205,6 +1295,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o rigor científico e precisão técnica.".to_string(),
                 weight: 0.2,
                 max_score: 20,
+                levels: 20,
                 evaluation_points: vec![
                     "Rigor científico excelente (16-20 pontos)".to_string(),
                     "Rigor científico adequado (10-15 pontos)".to_string(),
@@ -1217,6 +1308,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a capacidade de aplicar conhecimentos técnicos.".to_string(),
                 weight: 0.2,
                 max_score: 20,
+                levels: 20,
                 evaluation_points: vec![
                     "Aplicação prática excelente (16-20 pontos)".to_string(),
                     "Aplicação prática adequada (10-15 pontos)".to_string(),
@@ -1229,6 +1321,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a coerência lógica e estruturação do texto.".to_string(),
                 weight: 0.15,
                 max_score: 15,
+                levels: 15,
                 evaluation_points: vec![
                     "Coerência e estrutura excelentes (12-15 pontos)".to_string(),
                     "Coerência e estrutura adequadas (7-11 pontos)".to_string(),
@@ -1241,6 +1334,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia o domínio da linguagem técnica e formal.".to_string(),
                 weight: 0.1,
                 max_score: 10,
+                levels: 10,
                 evaluation_points: vec![
                     "Domínio linguístico técnico excelente (8-10 pontos)".to_string(),
                     "Domínio linguístico técnico adequado (5-7 pontos)".to_string(),
@@ -1253,6 +1347,7 @@ pub async fn seed_rubrics(rubric_repo: &InMemoryExamRubricRepository) -> Result<
                 description: "Avalia a originalidade e criatividade na abordagem técnica.".to_string(),
                 weight: 0.1,
                 max_score: 10,
+                levels: 10,
                 evaluation_points: vec![
                     "Originalidade técnica excelente (8-10 pontos)".to_string(),
                     "Originalidade técnica adequada (5-7 pontos)".to_string(),
@@ -1405,3 +1500,44 @@ pub async fn seed_knowledge_trails(
     
     Ok(())
 }
+
+/// Cria o histórico de revisões das redações já existentes
+/// A redação corrigida do ENEM recebe uma versão anterior com nota mais baixa
+pub async fn seed_essay_revisions(
+    revision_repo: &InMemoryEssayRevisionRepository,
+    essay_repo: &InMemoryEssayRepository,
+    user_id: Uuid,
+) -> Result<()> {
+    let essays = essay_repo.list_by_user(user_id).await?;
+    let evasion_id = Uuid::parse_str("a0000000-0000-0000-0000-000000000002").unwrap();
+
+    for essay in essays {
+        if essay.id == evasion_id {
+            let mut first_draft = essay.clone();
+            first_draft.content = "A evasão escolar é um problema que afeta muitos estudantes brasileiros. Diversos fatores contribuem para isso, como questões socioeconômicas e falta de interesse.
+
+Para combater a evasão escolar, o governo precisa fazer alguma coisa. É importante investir na escola.
+
+A família também é importante para os estudantes.".to_string();
+            first_draft.score = Some(560);
+            first_draft.feedback = Some("Texto curto e com desenvolvimento insuficiente. A proposta de intervenção não apresenta agente, ação nem detalhamento.".to_string());
+            first_draft.rubric_scores = Some(RubricScores {
+                scores: {
+                    let mut map = HashMap::new();
+                    map.insert("Competência 1".to_string(), 120);
+                    map.insert("Competência 2".to_string(), 120);
+                    map.insert("Competência 3".to_string(), 120);
+                    map.insert("Competência 4".to_string(), 120);
+                    map.insert("Competência 5".to_string(), 80);
+                    map
+                },
+                detailed_feedback: HashMap::new(),
+            });
+            first_draft.updated_at = essay.created_at + Duration::days(2);
+            revision_repo.record(&first_draft).await?;
+        }
+        revision_repo.record(&essay).await?;
+    }
+
+    Ok(())
+}
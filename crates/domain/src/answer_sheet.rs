@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use super::essay::ExamType;
+
+/// Physical constraints of the handwritten answer sheet of an exam
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnswerSheet {
+    /// Lines below which the essay receives zero, for exams with such a rule
+    pub min_lines: Option<u16>,
+    pub max_lines: u16,
+    /// Average handwritten characters that fit in one line of the sheet
+    pub chars_per_line: u16,
+    /// Suggested time for the essay under exam conditions
+    pub time_budget_minutes: u16,
+}
+
+/// Verdict of the line count against the answer sheet limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineCheck {
+    TooShort,
+    Within,
+    TooLong,
+}
+
+impl AnswerSheet {
+    pub fn for_exam(exam_type: &ExamType) -> Self {
+        match exam_type {
+            // Folha de redação do ENEM: 30 linhas, textos com menos de 7 recebem nota zero
+            ExamType::Enem => Self {
+                min_lines: Some(7),
+                max_lines: 30,
+                chars_per_line: 65,
+                time_budget_minutes: 90,
+            },
+            // Os vestibulares limitam a folha, mas não zeram textos curtos pelo número de linhas
+            ExamType::Fuvest | ExamType::Unesp => Self {
+                min_lines: None,
+                max_lines: 30,
+                chars_per_line: 65,
+                time_budget_minutes: 120,
+            },
+            ExamType::Unicamp => Self {
+                min_lines: None,
+                max_lines: 30,
+                chars_per_line: 65,
+                time_budget_minutes: 150,
+            },
+            _ => Self {
+                min_lines: None,
+                max_lines: 30,
+                chars_per_line: 65,
+                time_budget_minutes: 90,
+            },
+        }
+    }
+
+    /// Estimate how many handwritten lines `content` takes on this sheet
+    /// Each paragraph starts on a new line and words wrap greedily at `chars_per_line`
+    pub fn estimate_lines(&self, content: &str) -> u16 {
+        let lines = self.layout(content).last().map_or(0, |&(_, line)| line);
+        lines.min(u16::MAX as usize) as u16
+    }
+
+    /// Prefix of `content` that fits on the sheet
+    /// What is written past the last line of the sheet is disregarded
+    pub fn text_within<'a>(&self, content: &'a str) -> &'a str {
+        let max_lines = self.max_lines as usize;
+        let end = self
+            .layout(content)
+            .into_iter()
+            .take_while(|&(_, line)| line <= max_lines)
+            .last()
+            .map_or(0, |(end, _)| end);
+        &content[..end]
+    }
+
+    /// `content` cut to the sheet when it runs past the last line, `None` when it fits
+    pub fn truncate<'a>(&self, content: &'a str) -> Option<&'a str> {
+        if self.estimate_lines(content) <= self.max_lines {
+            return None;
+        }
+        Some(self.text_within(content))
+    }
+
+    /// Byte offset where each word ends, with the line it ends on (from 1)
+    fn layout(&self, content: &str) -> Vec<(usize, usize)> {
+        let width = self.chars_per_line.max(1) as usize;
+        let mut words = Vec::new();
+        let mut lines = 0usize;
+        let mut offset = 0usize;
+
+        for paragraph in content.split_inclusive('\n') {
+            let paragraph_start = offset;
+            offset += paragraph.len();
+            if paragraph.trim().is_empty() {
+                continue;
+            }
+            let mut current = 0usize;
+            let mut cursor = 0usize;
+            lines += 1;
+            for word in paragraph.split_whitespace() {
+                cursor += paragraph[cursor..].find(word).unwrap_or(0) + word.len();
+                let len = word.chars().count();
+                let needed = if current == 0 { len } else { current + 1 + len };
+                if needed <= width {
+                    current = needed;
+                } else if current == 0 {
+                    // Word longer than a line: it is broken across lines
+                    lines += (len - 1) / width;
+                    current = (len - 1) % width + 1;
+                } else {
+                    lines += 1 + (len.max(1) - 1) / width;
+                    current = (len.max(1) - 1) % width + 1;
+                }
+                words.push((paragraph_start + cursor, lines));
+            }
+        }
+
+        words
+    }
+
+    pub fn check(&self, lines: u16) -> LineCheck {
+        if self.min_lines.is_some_and(|min_lines| lines < min_lines) {
+            LineCheck::TooShort
+        } else if lines > self.max_lines {
+            LineCheck::TooLong
+        } else {
+            LineCheck::Within
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_cuts_only_text_past_the_sheet() {
+        let sheet = AnswerSheet::for_exam(&ExamType::Enem);
+        let line = "palavra ".repeat(8);
+        let full = format!("{}\n", line.trim_end()).repeat(30);
+        assert_eq!(sheet.estimate_lines(&full), 30);
+        assert_eq!(sheet.truncate(&full), None);
+
+        let pasted = format!("{}extra", full);
+        assert_eq!(sheet.truncate(&pasted), Some(full.trim_end()));
+
+        // Breaking a line adds one without changing the length of the text
+        let broken = full.replacen(' ', "\n", 1);
+        assert_eq!(broken.len(), full.len());
+        let within = sheet.truncate(&broken).unwrap();
+        assert_eq!(sheet.estimate_lines(within), 30);
+        assert!(broken.starts_with(within));
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Influência de uma palavra na nota de cada competência
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenAttribution {
+    /// Posição (em caracteres) do início da palavra no texto
+    pub start: usize,
+    pub end: usize,
+    pub token: String,
+    /// Pontos que a palavra soma (positivo) ou tira (negativo) de cada competência
+    pub competencies: HashMap<String, f32>,
+}
+
+/// Influência de uma frase: quanto a nota muda quando ela é retirada do texto
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentenceAttribution {
+    /// Posição (em caracteres) do início da frase no texto
+    pub start: usize,
+    pub end: usize,
+    /// Pontos que a frase soma (positivo) ou tira (negativo) de cada competência
+    pub competencies: HashMap<String, f32>,
+}
+
+impl SentenceAttribution {
+    pub fn weight(&self, criterion: &str) -> f32 {
+        self.competencies.get(criterion).copied().unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::answer_sheet::AnswerSheet;
+use super::essay::{Essay, ExamType};
+use super::writing_process::WritingRecording;
+
+/// Work-in-progress text from the essay editor
+/// Kept apart from `Essay` so unfinished writing never shows up in statistics
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EssayDraft {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub content: String,
+    pub exam_type: ExamType,
+    /// Sequence number of the last journaled edit included in this state
+    pub sequence: u64,
+    /// Gravação do processo de escrita, quando ativada no editor
+    #[serde(default)]
+    pub recording: Option<WritingRecording>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EssayDraft {
+    pub fn new(user_id: Uuid, exam_type: ExamType) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            title: String::new(),
+            content: String::new(),
+            exam_type,
+            sequence: 0,
+            recording: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.trim().is_empty() && self.content.trim().is_empty()
+    }
+
+    /// Turn the draft into an essay in progress
+    pub fn to_essay(&self) -> Essay {
+        let mut essay = Essay::new(self.user_id, &self.title, &self.content, self.exam_type.clone());
+        essay.estimated_lines = Some(AnswerSheet::for_exam(&self.exam_type).estimate_lines(&self.content));
+        essay.created_at = self.created_at;
+        essay
+    }
+}
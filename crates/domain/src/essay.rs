@@ -2,6 +2,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
+use super::attribution::SentenceAttribution;
+use super::review::{HumanReview, ScoreInterval};
+use super::text_metrics::TextMetrics;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -164,11 +167,54 @@ pub struct Essay {
     pub feedback: Option<String>,
     pub corrections: Option<Vec<Correction>>,
     pub rubric_scores: Option<RubricScores>,
+    /// Handwritten lines the text would take on the exam answer sheet
+    #[serde(default)]
+    pub estimated_lines: Option<u16>,
+    /// Readability and lexical metrics computed at evaluation time
+    #[serde(default)]
+    pub text_metrics: Option<TextMetrics>,
+    /// How much each sentence pulled each competency up or down
+    #[serde(default)]
+    pub attributions: Option<Vec<SentenceAttribution>>,
+    /// Confidence interval of each competency score
+    #[serde(default)]
+    pub confidence: Option<HashMap<String, ScoreInterval>>,
+    /// Teacher review requested for uncertain evaluations
+    #[serde(default)]
+    pub review: Option<HumanReview>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub submitted_at: Option<DateTime<Utc>>,
 }
 
+impl Essay {
+    /// Redação em andamento, ainda sem avaliação
+    pub fn new(user_id: Uuid, title: &str, content: &str, exam_type: ExamType) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            title: title.to_string(),
+            content: content.to_string(),
+            max_score: exam_type.max_score(),
+            exam_type,
+            status: EssayStatus::EmProgresso,
+            score: None,
+            feedback: None,
+            corrections: None,
+            rubric_scores: None,
+            estimated_lines: None,
+            text_metrics: None,
+            attributions: None,
+            confidence: None,
+            review: None,
+            created_at: now,
+            updated_at: now,
+            submitted_at: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Correction {
     pub position: usize,
@@ -178,7 +224,7 @@ pub struct Correction {
     pub rubric_criterion: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RubricScores {
     pub scores: HashMap<String, u16>,
     pub detailed_feedback: HashMap<String, String>,
@@ -198,6 +244,21 @@ pub struct RubricCriterion {
     pub description: String,
     pub weight: f32,
     pub max_score: u16,
+    /// Níveis de desempenho acima de zero, igualmente espaçados até `max_score`
+    /// (5 no ENEM: 40, 80, …, 200; bancas que dão notas inteiras usam `max_score`)
+    pub levels: u16,
     pub evaluation_points: Vec<String>,
 }
 
+impl RubricCriterion {
+    /// Pontos entre dois níveis consecutivos
+    pub fn level_step(&self) -> u16 {
+        (self.max_score / self.levels.max(1)).max(1)
+    }
+
+    /// Se a nota corresponde a um dos níveis do critério
+    pub fn is_level(&self, score: u16) -> bool {
+        score <= self.max_score && score.is_multiple_of(self.level_step())
+    }
+}
+
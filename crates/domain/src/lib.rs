@@ -1,13 +1,31 @@
 pub mod essay;
+pub mod attribution;
+pub mod answer_sheet;
+pub mod draft;
 pub mod question;
+pub mod question_query;
 pub mod user;
 pub mod knowledge_trail;
 pub mod reading_content;
+pub mod revision;
+pub mod provenance;
+pub mod review;
+pub mod text_metrics;
+pub mod writing_process;
 pub mod traits;
 
 pub use essay::*;
+pub use attribution::*;
+pub use answer_sheet::*;
+pub use draft::*;
 pub use question::*;
+pub use question_query::*;
 pub use user::*;
 pub use knowledge_trail::*;
 pub use reading_content::*;
+pub use revision::*;
+pub use provenance::*;
+pub use review::*;
+pub use text_metrics::*;
+pub use writing_process::*;
 
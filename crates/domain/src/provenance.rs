@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+use super::essay::ExamType;
+
+/// Encoder that produced the embeddings behind a score
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelProvenance {
+    /// Hugging Face repository, e.g. `neuralmind/bert-base-portuguese-cased`
+    pub model_id: String,
+    /// Commit of the repository the weights were loaded from, when known
+    pub revision: Option<String>,
+    /// SHA-256 of `tokenizer.json`
+    pub tokenizer_hash: String,
+    /// Backend and weight precision, e.g. `candle/f32` or `candle/q8`; ONNX
+    /// exports add a prefix of their SHA-256, e.g. `onnx/3f2a9c0d41be7e55`
+    #[serde(default)]
+    pub weights: Option<String>,
+    /// File format the weights were loaded from: `safetensors`, `pytorch`,
+    /// `gguf` or `onnx`
+    #[serde(default)]
+    pub weight_format: Option<String>,
+}
+
+/// Thresholds of the heuristic competency scorer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeuristicConfig {
+    /// C1: words needed for the higher base score
+    pub c1_min_words: u32,
+    /// C1: MTLD needed for the lexical bonus
+    pub c1_min_mtld: f32,
+    /// C1: points lost per informal register marker
+    pub c1_register_penalty: u16,
+    /// C1: register markers counted at most
+    pub c1_max_register_markers: u32,
+    /// C3: sentences and words needed for the higher score
+    pub c3_min_sentences: u32,
+    pub c3_min_words: u32,
+    /// C4: average sentence length range, in words
+    pub c4_sentence_length: (f32, f32),
+    /// C4: average subordination depth range
+    pub c4_subordination_depth: (f32, f32),
+    pub c4_max_subordination_depth: u32,
+    /// C5: words that signal an intervention proposal
+    pub c5_proposal_markers: Vec<String>,
+}
+
+impl Default for HeuristicConfig {
+    fn default() -> Self {
+        Self {
+            c1_min_words: 200,
+            c1_min_mtld: 70.0,
+            c1_register_penalty: 40,
+            c1_max_register_markers: 3,
+            c3_min_sentences: 15,
+            c3_min_words: 250,
+            c4_sentence_length: (15.0, 25.0),
+            c4_subordination_depth: (0.5, 2.5),
+            c4_max_subordination_depth: 4,
+            c5_proposal_markers: vec![
+                "proposta".to_string(),
+                "solução".to_string(),
+                "necessário".to_string(),
+            ],
+        }
+    }
+}
+
+/// Everything needed to re-run and compare one automatic evaluation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationRecord {
+    pub id: Uuid,
+    pub essay_id: Uuid,
+    pub exam_type: ExamType,
+    /// `None` when the essay got nota zero before reaching the model
+    pub model: Option<ModelProvenance>,
+    pub scoring_head_version: String,
+    /// Hash of the rubric the scores were mapped to
+    pub rubric_version: String,
+    pub heuristic_config: HeuristicConfig,
+    /// SHA-256 of the title and content that were scored
+    pub input_hash: String,
+    pub scores: HashMap<String, u16>,
+    pub total_score: u16,
+    pub duration_ms: u64,
+    pub created_at: DateTime<Utc>,
+}
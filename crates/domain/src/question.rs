@@ -23,6 +23,25 @@ pub enum Subject {
 }
 
 impl Subject {
+    pub const ALL: [Subject; 16] = [
+        Subject::LinguaPortuguesa,
+        Subject::Literatura,
+        Subject::Ingles,
+        Subject::Espanhol,
+        Subject::Artes,
+        Subject::EducacaoFisica,
+        Subject::Tic,
+        Subject::Historia,
+        Subject::Geografia,
+        Subject::Filosofia,
+        Subject::Sociologia,
+        Subject::Fisica,
+        Subject::Quimica,
+        Subject::Biologia,
+        Subject::Matematica,
+        Subject::Redacao,
+    ];
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Subject::LinguaPortuguesa => "Língua Portuguesa",
@@ -45,7 +64,7 @@ impl Subject {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Difficulty {
     Facil,
     Medio,
@@ -53,6 +72,8 @@ pub enum Difficulty {
 }
 
 impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Facil, Difficulty::Medio, Difficulty::Dificil];
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Difficulty::Facil => "Fácil",
@@ -60,6 +81,15 @@ impl Difficulty {
             Difficulty::Dificil => "Difícil",
         }
     }
+
+    /// 0 para fácil, 2 para difícil
+    pub fn level(&self) -> u8 {
+        match self {
+            Difficulty::Facil => 0,
+            Difficulty::Medio => 1,
+            Difficulty::Dificil => 2,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +102,17 @@ pub struct Question {
     pub correct_answer: usize,
     pub explanation: String,
     pub tags: Vec<String>,
+    /// Prova de onde a questão foi tirada, quando conhecida
+    #[serde(default)]
+    pub source: Option<QuestionSource>,
+}
+
+/// Exame e ano de aplicação de uma questão
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuestionSource {
+    /// Nome do exame, como "ENEM" ou "FUVEST"
+    pub exam: String,
+    pub year: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+use super::question::{Difficulty, Question, Subject};
+
+/// Ordem dos resultados de uma consulta de questões
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum QuestionSort {
+    /// Mais relevantes para o texto buscado; sem texto, ordem estável por id
+    #[default]
+    Relevance,
+    Easiest,
+    Hardest,
+    /// Provas mais recentes primeiro; questões sem origem por último
+    Newest,
+    Oldest,
+}
+
+impl QuestionSort {
+    pub const ALL: [QuestionSort; 5] = [
+        QuestionSort::Relevance,
+        QuestionSort::Easiest,
+        QuestionSort::Hardest,
+        QuestionSort::Newest,
+        QuestionSort::Oldest,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            QuestionSort::Relevance => "Relevância",
+            QuestionSort::Easiest => "Mais fáceis",
+            QuestionSort::Hardest => "Mais difíceis",
+            QuestionSort::Newest => "Mais recentes",
+            QuestionSort::Oldest => "Mais antigas",
+        }
+    }
+
+    /// Chave de ordenação crescente de `question`; `score` é a relevância do texto buscado
+    pub fn rank(&self, question: &Question, score: f32) -> f64 {
+        let year = question.source.as_ref().map(|source| source.year as f64);
+        match self {
+            QuestionSort::Relevance => -(score as f64),
+            QuestionSort::Easiest => question.difficulty.level() as f64,
+            QuestionSort::Hardest => -(question.difficulty.level() as f64),
+            QuestionSort::Newest => year.map_or(f64::MAX, |year| -year),
+            QuestionSort::Oldest => year.unwrap_or(f64::MAX),
+        }
+    }
+}
+
+/// Respondidas ou não por um usuário
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnsweredFilter {
+    pub user_id: Uuid,
+    pub answered: bool,
+}
+
+/// Posição após a última questão de uma página
+///
+/// Guarda a chave de ordenação e o id da questão. Nas ordens por chave fixa
+/// (dificuldade, ano, ou relevância sem texto buscado) continua a listagem
+/// mesmo que questões sejam inseridas entre uma página e outra. Na
+/// relevância de um texto a nota de cada questão depende do índice inteiro,
+/// então o cursor fixa a versão do índice e é recusado depois de qualquer
+/// mudança nele; as similaridades semânticas também devem ser as mesmas
+/// entre as páginas. Só vale para a mesma consulta e ordem que a produziram.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuestionCursor {
+    pub rank: f64,
+    pub id: Uuid,
+    /// Versão do índice de texto, quando a ordem depende dele
+    pub version: Option<u64>,
+}
+
+impl QuestionCursor {
+    /// Se a questão com `rank` e `id` vem depois do cursor
+    pub fn precedes(&self, rank: f64, id: Uuid) -> bool {
+        rank.total_cmp(&self.rank).then(id.cmp(&self.id)).is_gt()
+    }
+}
+
+impl fmt::Display for QuestionCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}.{}", self.rank.to_bits(), self.id.simple())?;
+        match self.version {
+            Some(version) => write!(f, ".{}", version),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for QuestionCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rank, rest) = s.split_once('.').ok_or_else(|| anyhow::anyhow!("Cursor inválido: {}", s))?;
+        let (id, version) = match rest.split_once('.') {
+            Some((id, version)) => (id, Some(version.parse()?)),
+            None => (rest, None),
+        };
+        Ok(Self {
+            rank: f64::from_bits(u64::from_str_radix(rank, 16)?),
+            id: Uuid::parse_str(id)?,
+            version,
+        })
+    }
+}
+
+/// Similaridade semântica do texto buscado com as questões
+///
+/// Calculada por quem tem o modelo carregado e somada ao BM25 na relevância,
+/// antes da paginação, para que todas as páginas sigam a mesma ordem.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SemanticScores {
+    /// Similaridade de cosseno por questão; questões ausentes só entram pelo texto
+    pub similarities: HashMap<Uuid, f32>,
+    /// Parte da relevância dada à similaridade; o resto vai para o BM25 normalizado
+    pub weight: f32,
+}
+
+/// Consulta ao banco de questões
+///
+/// Filtros vazios não restringem nada. Matérias e dificuldades aceitam
+/// qualquer um dos valores; tags exigem todas. Exame e tags ignoram
+/// maiúsculas e acentos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestionQuery {
+    pub subjects: Vec<Subject>,
+    pub difficulties: Vec<Difficulty>,
+    pub tags: Vec<String>,
+    pub exam: Option<String>,
+    pub year: Option<u16>,
+    pub answered: Option<AnsweredFilter>,
+    pub text: Option<String>,
+    /// Similaridade com `text`; sem ela a relevância é só o BM25
+    pub semantic: Option<SemanticScores>,
+    pub sort: QuestionSort,
+    /// Questões por página; zero vale como um
+    pub limit: usize,
+    /// Página seguinte a este cursor; `None` para a primeira
+    pub after: Option<QuestionCursor>,
+}
+
+impl QuestionQuery {
+    pub const DEFAULT_LIMIT: usize = 20;
+}
+
+impl Default for QuestionQuery {
+    fn default() -> Self {
+        Self {
+            subjects: Vec::new(),
+            difficulties: Vec::new(),
+            tags: Vec::new(),
+            exam: None,
+            year: None,
+            answered: None,
+            text: None,
+            semantic: None,
+            sort: QuestionSort::default(),
+            limit: Self::DEFAULT_LIMIT,
+            after: None,
+        }
+    }
+}
+
+/// Uma página de resultados
+#[derive(Debug, Clone)]
+pub struct QuestionPage {
+    pub questions: Vec<Question>,
+    /// Total de questões que atendem aos filtros, em todas as páginas
+    pub total: usize,
+    /// Cursor da página seguinte; `None` na última
+    pub next: Option<QuestionCursor>,
+}
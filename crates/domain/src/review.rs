@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Intervalo de confiança da nota de uma competência
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreInterval {
+    pub lower: u16,
+    pub upper: u16,
+    /// Fração das amostras que repetiram a nota atribuída (0 a 1)
+    pub agreement: f32,
+}
+
+impl ScoreInterval {
+    pub fn width(&self) -> u16 {
+        self.upper.saturating_sub(self.lower)
+    }
+}
+
+/// Motivo para uma correção automática passar pela revisão de um professor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReviewReason {
+    /// O intervalo de confiança cobre mais de um nível da competência
+    IncertezaAlta { criterion: String, lower: u16, upper: u16 },
+    /// A nota oscila entre dois níveis vizinhos
+    LimiteDeNivel { criterion: String, score: u16, agreement: f32 },
+}
+
+impl ReviewReason {
+    pub fn criterion(&self) -> &str {
+        match self {
+            ReviewReason::IncertezaAlta { criterion, .. } | ReviewReason::LimiteDeNivel { criterion, .. } => criterion,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            ReviewReason::IncertezaAlta { criterion, lower, upper } => format!(
+                "{}: nota incerta, entre {} e {} pontos",
+                criterion, lower, upper
+            ),
+            ReviewReason::LimiteDeNivel { criterion, score, agreement } => format!(
+                "{}: {} pontos no limite entre níveis ({:.0}% de concordância)",
+                criterion,
+                score,
+                agreement * 100.0
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewStatus {
+    Pendente,
+    /// O professor manteve as notas da correção automática
+    Confirmada,
+    /// O professor alterou uma ou mais notas
+    Alterada,
+}
+
+/// Revisão humana de uma correção automática
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HumanReview {
+    pub status: ReviewStatus,
+    pub reasons: Vec<ReviewReason>,
+    pub note: Option<String>,
+    /// Notas da correção automática, guardadas quando o professor as altera
+    pub original_scores: Option<HashMap<String, u16>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+impl HumanReview {
+    pub fn pending(reasons: Vec<ReviewReason>) -> Self {
+        Self {
+            status: ReviewStatus::Pendente,
+            reasons,
+            note: None,
+            original_scores: None,
+            reviewed_at: None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == ReviewStatus::Pendente
+    }
+}
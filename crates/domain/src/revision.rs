@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::essay::{Correction, Essay, RubricScores};
+
+/// Immutable snapshot of an essay at a given point of its rewrite history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EssayRevision {
+    pub id: Uuid,
+    pub essay_id: Uuid,
+    pub revision_number: u32,
+    pub title: String,
+    pub content: String,
+    pub score: Option<u16>,
+    pub max_score: u16,
+    pub feedback: Option<String>,
+    pub corrections: Option<Vec<Correction>>,
+    pub rubric_scores: Option<RubricScores>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EssayRevision {
+    /// Capture the current state of an essay as a new revision
+    pub fn snapshot(essay: &Essay, revision_number: u32) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            essay_id: essay.id,
+            revision_number,
+            title: essay.title.clone(),
+            content: essay.content.clone(),
+            score: essay.score,
+            max_score: essay.max_score,
+            feedback: essay.feedback.clone(),
+            corrections: essay.corrections.clone(),
+            rubric_scores: essay.rubric_scores.clone(),
+            created_at: essay.updated_at,
+        }
+    }
+
+    /// Whether `essay` has the text, score and rubric scores captured here
+    ///
+    /// Status changes such as a review override leave these untouched and do
+    /// not deserve a revision of their own.
+    pub fn matches(&self, essay: &Essay) -> bool {
+        self.title == essay.title
+            && self.content == essay.content
+            && self.score == essay.score
+            && self.max_score == essay.max_score
+            && self.rubric_scores == essay.rubric_scores
+    }
+
+    pub fn is_evaluated(&self) -> bool {
+        self.score.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Run of consecutive words sharing the same diff operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Score change of a single rubric criterion between two revisions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CriterionDelta {
+    pub criterion: String,
+    pub before: Option<u16>,
+    pub after: Option<u16>,
+    pub delta: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionComparison {
+    pub from_revision: u32,
+    pub to_revision: u32,
+    pub diff: Vec<DiffSegment>,
+    pub criterion_deltas: Vec<CriterionDelta>,
+    pub total_delta: Option<i32>,
+    pub words_added: usize,
+    pub words_removed: usize,
+}
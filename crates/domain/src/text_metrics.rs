@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Marcadores de registro informal encontrados no texto
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegisterMarkers {
+    /// Marcas de oralidade ("né", "tipo assim", "pra")
+    pub orality: u32,
+    /// Gírias ("galera", "mano", "treta")
+    pub slang: u32,
+    /// Pronomes e possessivos de primeira pessoa do singular
+    pub first_person: u32,
+    /// Marcadores distintos encontrados, na ordem em que aparecem
+    pub found: Vec<String>,
+}
+
+impl RegisterMarkers {
+    pub fn total(&self) -> u32 {
+        self.orality + self.slang + self.first_person
+    }
+
+    pub fn is_formal(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Métricas de legibilidade e léxico de uma redação
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextMetrics {
+    pub word_count: u32,
+    pub sentence_count: u32,
+    pub paragraph_count: u32,
+    /// Palavras por frase
+    pub avg_sentence_length: f32,
+    pub avg_syllables_per_word: f32,
+    /// Índice Flesch adaptado ao português (Martins et al., 1996), de 0 a 100
+    pub flesch_martins: f32,
+    /// Razão tipo/ocorrência (type-token ratio)
+    pub type_token_ratio: f32,
+    /// Measure of Textual Lexical Diversity
+    pub mtld: f32,
+    /// Conectores subordinativos por frase
+    pub avg_subordination_depth: f32,
+    pub max_subordination_depth: u32,
+    /// Fração das frases com voz passiva analítica ou sintética
+    pub passive_voice_ratio: f32,
+    pub register: RegisterMarkers,
+}
+
+impl TextMetrics {
+    /// Faixa de leitura do índice Flesch–Martins
+    pub fn readability_level(&self) -> &'static str {
+        match self.flesch_martins {
+            f if f >= 75.0 => "Muito fácil",
+            f if f >= 50.0 => "Fácil",
+            f if f >= 25.0 => "Difícil",
+            _ => "Muito difícil",
+        }
+    }
+}
@@ -2,9 +2,13 @@ use async_trait::async_trait;
 use uuid::Uuid;
 use super::essay::{Essay, EssayStatus, ExamType, ExamRubric};
 use super::question::{Question, Subject, Difficulty};
+use super::question_query::{QuestionPage, QuestionQuery};
 use super::user::{UserProfile, UserSettings, StudyProgress};
 use super::knowledge_trail::KnowledgeTrail;
 use super::reading_content::ReadingContent;
+use super::revision::EssayRevision;
+use super::provenance::EvaluationRecord;
+use super::writing_process::WritingRecording;
 use shared::Result;
 
 #[async_trait]
@@ -14,7 +18,39 @@ pub trait EssayRepository: Send + Sync {
     async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<Essay>>;
     async fn list_by_status(&self, user_id: Uuid, status: EssayStatus) -> Result<Vec<Essay>>;
     async fn list_by_exam_type(&self, user_id: Uuid, exam_type: ExamType) -> Result<Vec<Essay>>;
+    /// Evaluations of every user still waiting for a teacher's review
+    async fn list_pending_review(&self) -> Result<Vec<Essay>>;
     async fn update(&self, essay: Essay) -> Result<()>;
+    /// Saves the essay and records a revision of it, for repositories that
+    /// keep history; `save` alone never records one
+    async fn save_revision(&self, essay: Essay) -> Result<()> {
+        self.save(essay).await
+    }
+}
+
+#[async_trait]
+pub trait EssayRevisionRepository: Send + Sync {
+    /// Stores a new snapshot and returns it with its assigned revision number
+    async fn record(&self, essay: &Essay) -> Result<EssayRevision>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<EssayRevision>>;
+    /// Revisions of an essay ordered from oldest to newest
+    async fn list_by_essay(&self, essay_id: Uuid) -> Result<Vec<EssayRevision>>;
+    async fn latest(&self, essay_id: Uuid) -> Result<Option<EssayRevision>>;
+}
+
+#[async_trait]
+pub trait EvaluationRecordRepository: Send + Sync {
+    async fn save(&self, record: EvaluationRecord) -> Result<()>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<EvaluationRecord>>;
+    /// Records of an essay, oldest first
+    async fn list_by_essay(&self, essay_id: Uuid) -> Result<Vec<EvaluationRecord>>;
+    async fn latest(&self, essay_id: Uuid) -> Result<Option<EvaluationRecord>>;
+}
+
+#[async_trait]
+pub trait WritingRecordingRepository: Send + Sync {
+    async fn save(&self, recording: WritingRecording) -> Result<()>;
+    async fn find_by_essay(&self, essay_id: Uuid) -> Result<Option<WritingRecording>>;
 }
 
 #[async_trait]
@@ -29,6 +65,10 @@ pub trait QuestionRepository: Send + Sync {
     async fn list_by_subject(&self, subject: Subject) -> Result<Vec<Question>>;
     async fn list_by_difficulty(&self, difficulty: Difficulty) -> Result<Vec<Question>>;
     async fn search(&self, query: &str) -> Result<Vec<Question>>;
+    /// Uma página das questões que atendem a `query`, na ordem pedida
+    async fn query(&self, query: &QuestionQuery) -> Result<QuestionPage>;
+    /// Registra que `user_id` respondeu a questão, para o filtro de respondidas
+    async fn record_answer(&self, user_id: Uuid, question_id: Uuid, correct: bool) -> Result<()>;
 }
 
 #[async_trait]
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Consecutive insertions closer than this are merged into one event
+const COALESCE_WINDOW_MS: u64 = 1_500;
+
+/// Replacement of a character range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    /// Character offset where the edit starts
+    pub at: usize,
+    /// Number of characters removed at `at`
+    pub delete: usize,
+    pub insert: String,
+}
+
+impl TextEdit {
+    /// Smallest single edit turning `old` into `new`, or `None` if they are equal
+    pub fn between(old: &str, new: &str) -> Option<Self> {
+        if old == new {
+            return None;
+        }
+
+        let old_chars: Vec<char> = old.chars().collect();
+        let new_chars: Vec<char> = new.chars().collect();
+
+        let prefix = old_chars
+            .iter()
+            .zip(&new_chars)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+        let suffix = old_chars
+            .iter()
+            .rev()
+            .zip(new_chars.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Some(Self {
+            at: prefix,
+            delete: old_chars.len() - prefix - suffix,
+            insert: new_chars[prefix..new_chars.len() - suffix].iter().collect(),
+        })
+    }
+
+    /// Apply the edit to `text`, clamping out-of-range offsets to the end of the text
+    pub fn apply(&self, text: &mut String) {
+        let byte_offset = |chars: usize| {
+            text.char_indices()
+                .nth(chars)
+                .map(|(i, _)| i)
+                .unwrap_or(text.len())
+        };
+        let start = byte_offset(self.at);
+        let end = byte_offset(self.at + self.delete);
+        text.replace_range(start..end, &self.insert);
+    }
+}
+
+/// Timestamped edit, with times relative to the start of the recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditEvent {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    #[serde(flatten)]
+    pub edit: TextEdit,
+}
+
+/// Stream of edits that produced an essay, used to replay how it was written
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WritingRecording {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub essay_id: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    pub events: Vec<EditEvent>,
+}
+
+impl WritingRecording {
+    pub fn new(user_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            essay_id: None,
+            started_at: Utc::now(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Milliseconds elapsed since the recording started
+    pub fn elapsed_ms(&self, now: DateTime<Utc>) -> u64 {
+        (now - self.started_at).num_milliseconds().max(0) as u64
+    }
+
+    /// Append an edit, merging it into the previous event when it continues
+    /// the same run of typing
+    pub fn push(&mut self, elapsed_ms: u64, edit: TextEdit) {
+        if let Some(last) = self.events.last_mut() {
+            let continues_typing = last.edit.delete == 0
+                && edit.delete == 0
+                && edit.at == last.edit.at + last.edit.insert.chars().count()
+                && elapsed_ms.saturating_sub(last.end_ms) < COALESCE_WINDOW_MS;
+            if continues_typing {
+                last.edit.insert.push_str(&edit.insert);
+                last.end_ms = elapsed_ms;
+                return;
+            }
+        }
+
+        self.events.push(EditEvent {
+            start_ms: elapsed_ms,
+            end_ms: elapsed_ms,
+            edit,
+        });
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map(|e| e.end_ms).unwrap_or(0)
+    }
+
+    /// Reconstruct the text as it was at `elapsed_ms`
+    pub fn text_at(&self, elapsed_ms: u64) -> String {
+        let mut text = String::new();
+        for event in self.events.iter().take_while(|e| e.start_ms <= elapsed_ms) {
+            event.edit.apply(&mut text);
+        }
+        text
+    }
+
+    pub fn final_text(&self) -> String {
+        self.text_at(u64::MAX)
+    }
+}
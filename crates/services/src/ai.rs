@@ -1,33 +1,219 @@
 use anyhow::{Context, Result};
-use candle_core::{Device, Tensor};
-use candle_nn::VarBuilder;
-use candle_transformers::models::bert::{BertModel, Config as BertConfig};
-use hf_hub::{api::sync::Api, Repo, RepoType};
+use candle_core::Device;
+use domain::provenance::{HeuristicConfig, ModelProvenance};
+use domain::review::ScoreInterval;
+use hf_hub::{Cache, CacheRepo, Repo, RepoType};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokenizers::Tokenizer;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::ai_config::{AIConfigManager, CacheInfo, DownloadPreferences, InferenceBackend, ModelStatus, QuantizationLevel};
+use crate::download::{DownloadError, DownloadManager};
+use crate::embedding_cache::{EmbeddingCache, EmbeddingKey};
+use crate::explain::{explain_scores, ScoreExplanation};
+use crate::inference::{
+    find_weights, prepare_weights, quantize_checkpoint, CancelToken, CandleBackend, ConversionError, EncoderBackend,
+    InferenceWorker, JobOptions, OnnxBackend, QuantizedBackend, DEFAULT_BATCH_SIZE,
+};
+use crate::model_install::WEIGHT_FILES;
+use crate::model_registry::{snapshot_commit, ActiveModel, ModelRegistry};
+use crate::provenance::{sha256_file, sha256_hex};
+use crate::readability::compute_text_metrics;
+use crate::uncertainty::{bootstrap_intervals, BOOTSTRAP_SAMPLES};
+
+/// Hugging Face repository of the encoder
+pub const MODEL_ID: &str = "neuralmind/bert-base-portuguese-cased";
 
 /// Progress callback for model loading
 pub type ProgressCallback = Arc<dyn Fn(f32, String) + Send + Sync>;
 
+/// Lifecycle events kept for subscribers that fall behind
+const EVENT_CAPACITY: usize = 64;
+
+/// Event published to `AIService::subscribe` receivers
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelEvent {
+    /// The model moved to a new status
+    Status(ModelStatus),
+    /// Fraction done of the load in progress, with a message for the user
+    Progress { fraction: f32, message: String },
+}
+
+/// Encoder files in the local Hub cache
+///
+/// Models installed offline (see `install_model`) never touch the network;
+/// anything missing is fetched by the download manager.
+struct ModelFiles {
+    local: CacheRepo,
+    repo_dir: PathBuf,
+    revision: String,
+    downloads: DownloadManager,
+}
+
+impl ModelFiles {
+    fn new(model: &ActiveModel, preferences: DownloadPreferences) -> Result<Self> {
+        let cache = Cache::new(AIConfigManager::get_default_cache_dir()?);
+        let model_id = &model.descriptor.id;
+        Ok(Self {
+            local: cache.repo(Repo::with_revision(model_id.clone(), RepoType::Model, model.revision.clone())),
+            repo_dir: AIConfigManager::get_model_cache_dir(model_id)?,
+            revision: model.revision.clone(),
+            downloads: DownloadManager::new(model_id, preferences).with_revision(&model.revision),
+        })
+    }
+
+    fn with_token(mut self, token: Option<String>) -> Self {
+        self.downloads = self.downloads.with_token(token);
+        self
+    }
+
+    fn has_all(&self, names: &[&str]) -> bool {
+        names.iter().all(|name| self.local.get(name).is_some())
+    }
+
+    /// Download the snapshot of `names` at the model revision unless the
+    /// cache has all of them
+    fn ensure(&self, names: &[&str], cancel: &CancelToken, progress: Option<&ProgressCallback>) -> Result<()> {
+        if self.has_all(names) {
+            return Ok(());
+        }
+        self.downloads.download_snapshot(&self.repo_dir, names, cancel, progress)?;
+        Ok(())
+    }
+
+    /// Commit the revision points at in the cache, if any of it was fetched
+    fn commit(&self) -> Option<String> {
+        let commit = std::fs::read_to_string(self.repo_dir.join("refs").join(&self.revision)).ok()?;
+        Some(commit.trim().to_string())
+    }
+
+    fn get(&self, filename: &str) -> Result<PathBuf> {
+        self.local
+            .get(filename)
+            .with_context(|| format!("{} is not in the model cache", filename))
+    }
+}
+
+/// Competency scores with the attributions that explain them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoredEssay {
+    pub scores: Vec<u16>,
+    /// Confidence interval of each score, in the same order
+    pub confidence: Vec<ScoreInterval>,
+    pub explanation: ScoreExplanation,
+}
+
 /// AI Service for essay evaluation using BERTimbau model
 #[derive(Clone)]
 pub struct AIService {
-    model: Arc<RwLock<Option<BertModel>>>,
-    tokenizer: Arc<RwLock<Option<Tokenizer>>>,
+    worker: Arc<RwLock<Option<Arc<InferenceWorker>>>>,
     device: Device,
+    config_manager: Arc<AIConfigManager>,
+    provenance: Arc<RwLock<Option<ModelProvenance>>>,
+    /// Stops the download of the initialization in progress
+    download_cancel: Arc<std::sync::Mutex<CancelToken>>,
+    status: Arc<std::sync::RwLock<ModelStatus>>,
+    events: broadcast::Sender<ModelEvent>,
+    /// Held while loading or unloading, so concurrent calls load once
+    lifecycle: Arc<Mutex<()>>,
+    embeddings: Option<Arc<EmbeddingCache>>,
 }
 
 impl AIService {
     /// Create a new AI service instance
     pub fn new() -> Result<Self> {
-        let device = Device::Cpu;
-        
-        Ok(Self {
-            model: Arc::new(RwLock::new(None)),
-            tokenizer: Arc::new(RwLock::new(None)),
-            device,
-        })
+        let config_manager = Arc::new(AIConfigManager::new()?);
+        let embeddings = Self::open_embedding_cache(&config_manager);
+        Ok(Self::from_parts(config_manager, None, None).with_embedding_cache(embeddings))
+    }
+
+    /// Create a service around an already loaded encoder
+    ///
+    /// Used to compare backends and precisions on the same corpus.
+    pub fn with_encoder(encoder: Arc<dyn EncoderBackend>, provenance: ModelProvenance) -> Result<Self> {
+        let worker = Arc::new(Self::spawn_worker(encoder)?);
+        let config_manager = Arc::new(AIConfigManager::new()?);
+        let embeddings = Self::open_embedding_cache(&config_manager);
+        Ok(Self::from_parts(config_manager, Some(worker), Some(provenance)).with_embedding_cache(embeddings))
+    }
+
+    /// Use `cache` for embeddings instead of the configured one; `None` disables caching
+    pub fn with_embedding_cache(mut self, cache: Option<Arc<EmbeddingCache>>) -> Self {
+        self.embeddings = cache;
+        self
+    }
+
+    /// The embedding cache in the model cache directory, unless disabled
+    fn open_embedding_cache(config_manager: &AIConfigManager) -> Option<Arc<EmbeddingCache>> {
+        let preferences = config_manager.load().map(|config| config.embedding_cache).unwrap_or_default();
+        if !preferences.enabled {
+            return None;
+        }
+        let max_bytes = preferences.max_size_mb * 1024 * 1024;
+        match AIConfigManager::get_embedding_cache_dir().and_then(|dir| EmbeddingCache::open(dir, max_bytes)) {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(e) => {
+                tracing::warn!("Embedding cache unavailable, embeddings will not be reused: {:#}", e);
+                None
+            }
+        }
+    }
+
+    fn from_parts(
+        config_manager: Arc<AIConfigManager>,
+        worker: Option<Arc<InferenceWorker>>,
+        provenance: Option<ModelProvenance>,
+    ) -> Self {
+        let status = match worker {
+            Some(_) => ModelStatus::Ready,
+            None => Self::idle_status(&config_manager),
+        };
+        Self {
+            worker: Arc::new(RwLock::new(worker)),
+            device: Device::Cpu,
+            config_manager,
+            provenance: Arc::new(RwLock::new(provenance)),
+            download_cancel: Arc::new(std::sync::Mutex::new(CancelToken::new())),
+            status: Arc::new(std::sync::RwLock::new(status)),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            lifecycle: Arc::new(Mutex::new(())),
+            embeddings: None,
+        }
+    }
+
+    /// Status without a loaded model: `Unloaded` when the active model is
+    /// cached, otherwise whether a token is saved for downloading it
+    fn idle_status(config_manager: &Arc<AIConfigManager>) -> ModelStatus {
+        let cached = AIConfigManager::get_default_cache_dir()
+            .map(|cache_dir| ModelRegistry::new(config_manager.clone(), cache_dir))
+            .and_then(|registry| registry.active())
+            .and_then(|model| AIConfigManager::is_model_cached(&model.descriptor.id))
+            .unwrap_or(false);
+        if cached {
+            ModelStatus::Unloaded
+        } else if config_manager.is_token_configured() {
+            ModelStatus::TokenSaved
+        } else {
+            ModelStatus::NotConfigured
+        }
+    }
+
+    /// Current lifecycle status
+    pub fn status(&self) -> ModelStatus {
+        self.status.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Receive every status transition and progress event from now on
+    ///
+    /// Call `status` for the state at the time of subscribing.
+    pub fn subscribe(&self) -> broadcast::Receiver<ModelEvent> {
+        self.events.subscribe()
+    }
+
+    fn set_status(&self, status: ModelStatus) {
+        *self.status.write().unwrap_or_else(|e| e.into_inner()) = status.clone();
+        // Sending only fails when nobody is subscribed
+        let _ = self.events.send(ModelEvent::Status(status));
     }
 
     /// Initialize the model by loading from local cache or downloading from HuggingFace
@@ -36,128 +222,345 @@ impl AIService {
     }
 
     /// Initialize the model with progress callback
+    ///
+    /// Progress goes to the callback and, with every status transition, to
+    /// `subscribe` receivers.
     pub async fn initialize_with_progress(
         &self,
         progress_callback: Option<ProgressCallback>,
     ) -> Result<()> {
+        let _lifecycle = self.lifecycle.lock().await;
         // Check if already initialized
-        if self.model.read().await.is_some() {
+        if self.worker.read().await.is_some() {
             if let Some(cb) = progress_callback {
                 cb(1.0, "Model already loaded".to_string());
             }
             return Ok(());
         }
 
-<<<<<<< Local
-        let report_progress = |progress: f32, message: String| {
+        let events = self.events.clone();
+        let report: ProgressCallback = Arc::new(move |fraction: f32, message: String| {
+            let _ = events.send(ModelEvent::Progress { fraction, message: message.clone() });
             if let Some(ref cb) = progress_callback {
-                cb(progress, message);
+                cb(fraction, message);
             }
-        };
-
-        report_progress(0.0, "Starting model initialization...".to_string());
-=======
-        let report_progress = |progress: f32, message: String| {
-            if let Some(ref cb) = progress_callback {
-                cb(progress, message);
+        });
+
+        self.set_status(ModelStatus::Connecting);
+        let result = self.load_model(report).await;
+        match &result {
+            Ok(()) => self.set_status(ModelStatus::Ready),
+            Err(e)
+                if matches!(e.downcast_ref::<DownloadError>(), Some(DownloadError::Cancelled(_)))
+                    || matches!(e.downcast_ref::<ConversionError>(), Some(ConversionError::Cancelled(_))) =>
+            {
+                self.set_status(Self::idle_status(&self.config_manager));
             }
-        };
+            Err(e) => {
+                tracing::error!("Failed to load the encoder: {:#}", e);
+                self.set_status(ModelStatus::Error(e.to_string()));
+            }
+        }
+        result
+    }
 
-        report_progress(0.0, LoadingStage::Validating.as_str().to_string());
->>>>>>> Remote
-        tracing::info!("Initializing BERTimbau model...");
+    async fn load_model(&self, report: ProgressCallback) -> Result<()> {
+        let report_progress = |fraction: f32, message: String| report(fraction, message);
 
-        // Get token if configured (for future downloads)
-        // Note: hf-hub 0.3 uses HF_TOKEN environment variable for authentication
-        // Token is stored in config but environment variable takes precedence
-        let _token = self.config_manager.get_token()?;
+        report_progress(0.0, "Starting model initialization...".to_string());
+        let ai_config = self.config_manager.load()?;
+        if ai_config.inference_backend == InferenceBackend::Onnx && ai_config.onnx_model_path.is_none() {
+            anyhow::bail!("ONNX backend selected but no ONNX model configured");
+        }
+        let registry = ModelRegistry::new(self.config_manager.clone(), AIConfigManager::get_default_cache_dir()?);
+        let model = registry.active()?;
+        let model_id = model.descriptor.id.clone();
+        tracing::info!("Initializing {} ({}) at {}...", model.descriptor.name, model_id, model.revision);
 
-        // Download or load from cache
-<<<<<<< Local
+        // Load from cache, downloading only what is missing
         report_progress(0.1, "Connecting to model repository...".to_string());
-=======
-        report_progress(0.1, LoadingStage::Connecting.as_str().to_string());
->>>>>>> Remote
-        let api = Api::new()?;
-        
-        let repo = api.repo(Repo::new(
-            "neuralmind/bert-base-portuguese-cased".to_string(),
-            RepoType::Model,
-        ));
+        let mut repo = ModelFiles::new(&model, ai_config.download_preferences.clone())?;
+        let mut needed = model.descriptor.files.clone();
+        let quantized_cached = match repo.commit() {
+            Some(commit) => AIConfigManager::get_quantized_model_path(&model_id, &commit, ai_config.quantization)?.exists(),
+            None => false,
+        };
+        let weights_cached = WEIGHT_FILES.iter().any(|file| repo.get(file).is_ok());
+        if ai_config.inference_backend == InferenceBackend::Candle
+            && (ai_config.quantization == QuantizationLevel::F32 || !quantized_cached)
+            && !weights_cached
+        {
+            needed.push(model.descriptor.weight_format.file_name().to_string());
+        }
+
+        let needed: Vec<&str> = needed.iter().map(String::as_str).collect();
+        if !repo.has_all(&needed) {
+            self.set_status(ModelStatus::Downloading);
+            // Only a download needs the token; a public model downloads without one
+            let token = self.config_manager.resolve_token().unwrap_or_else(|e| {
+                tracing::warn!("Downloading without a token: {:#}", e);
+                None
+            });
+            repo = repo.with_token(token);
+        }
+
+        let cancel = CancelToken::new();
+        *self.download_cancel.lock().unwrap_or_else(|e| e.into_inner()) = cancel.clone();
+        let download_report = report.clone();
+        let download_progress: ProgressCallback =
+            Arc::new(move |fraction: f32, message: String| download_report(0.1 + 0.7 * fraction, message));
+        let needed: Vec<String> = needed.into_iter().map(str::to_string).collect();
+        let download_cancel = cancel.clone();
+        let repo = tokio::task::spawn_blocking(move || {
+            let needed: Vec<&str> = needed.iter().map(String::as_str).collect();
+            repo.ensure(&needed, &download_cancel, Some(&download_progress)).map(|()| repo)
+        })
+        .await
+        .context("Download task failed")??;
 
         // Get model files
-<<<<<<< Local
-        report_progress(0.2, "Downloading configuration...".to_string());
-=======
-        report_progress(0.2, LoadingStage::DownloadingConfig.as_str().to_string());
->>>>>>> Remote
+        self.set_status(ModelStatus::Loading);
         let config_path = repo.get("config.json")?;
-<<<<<<< Local
-        
-        report_progress(0.3, "Downloading tokenizer...".to_string());
-=======
-        
-        report_progress(0.4, LoadingStage::DownloadingTokenizer.as_str().to_string());
->>>>>>> Remote
         let tokenizer_path = repo.get("tokenizer.json")?;
-<<<<<<< Local
-        
-        report_progress(0.4, "Downloading model weights...".to_string());
-=======
-        
-        report_progress(0.5, LoadingStage::DownloadingWeights.as_str().to_string());
->>>>>>> Remote
-        let weights_path = repo.get("pytorch_model.bin")?;
-
-        // Load tokenizer
-<<<<<<< Local
-        report_progress(0.6, "Loading tokenizer...".to_string());
-=======
-        report_progress(0.8, "Loading tokenizer...".to_string());
->>>>>>> Remote
-        let tokenizer = Tokenizer::from_file(tokenizer_path)
-            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
-
-        // Load model configuration
-<<<<<<< Local
-        report_progress(0.7, "Loading model configuration...".to_string());
-=======
-        report_progress(0.85, "Loading model configuration...".to_string());
->>>>>>> Remote
-        let config: BertConfig = serde_json::from_str(
-            &std::fs::read_to_string(config_path)?
-        )?;
-
-        // Load model weights
-<<<<<<< Local
-        report_progress(0.8, "Loading model weights...".to_string());
-=======
-        report_progress(0.9, LoadingStage::LoadingModel.as_str().to_string());
->>>>>>> Remote
-        let vb = VarBuilder::from_pth(&weights_path, candle_core::DType::F32, &self.device)?;
-        
-        report_progress(0.9, "Initializing model...".to_string());
-        let model = BertModel::load(vb, &config)?;
+        let tokenizer_hash = sha256_hex(&std::fs::read(&tokenizer_path)?);
+
+        // The hub cache keeps files under snapshots/<commit>/
+        let snapshot_dir = config_path.parent().context("Model files are not in a snapshot")?;
+        let revision = snapshot_commit(&config_path);
+        let converted_path =
+            AIConfigManager::get_converted_weights_path(&model_id, revision.as_deref().unwrap_or("local"))?;
+
+        let (encoder, weights, weight_format): (Arc<dyn EncoderBackend>, _, _) = match ai_config.inference_backend {
+            InferenceBackend::Candle if ai_config.quantization != QuantizationLevel::F32 => {
+                let level = ai_config.quantization;
+                let commit = revision.as_deref().context("Model files are not in a cache snapshot")?;
+                let quantized_path = AIConfigManager::get_quantized_model_path(&model_id, commit, level)?;
+                let source = if quantized_path.exists() {
+                    None
+                } else {
+                    let source = find_weights(snapshot_dir, &converted_path)
+                        .with_context(|| format!("No weights for {} in the model cache", model_id))?;
+                    Some(source)
+                };
+
+                // Quantizing and reading the GGUF file block for seconds
+                let report = report.clone();
+                let device = self.device.clone();
+                let backend = tokio::task::spawn_blocking(move || {
+                    if let Some(source) = source {
+                        report(0.6, format!("Quantizing model weights to {}...", level.as_str()));
+                        quantize_checkpoint(&config_path, &source.path, &quantized_path, level)?;
+                    }
+                    report(0.8, "Loading quantized model weights...".to_string());
+                    QuantizedBackend::load(&quantized_path, &tokenizer_path, &device)
+                })
+                .await
+                .context("Quantization task failed")??;
+                (Arc::new(backend), format!("candle/{}", level.as_str()), "gguf")
+            }
+            InferenceBackend::Candle => {
+                // Converting a PyTorch checkpoint and reading the weights block for seconds
+                let snapshot_dir = snapshot_dir.to_path_buf();
+                let report = report.clone();
+                let device = self.device.clone();
+                let cancel = cancel.clone();
+                let (backend, format) = tokio::task::spawn_blocking(move || {
+                    let conversion_report = report.clone();
+                    let conversion_progress: ProgressCallback = Arc::new(move |fraction: f32, message: String| {
+                        conversion_report(0.8 + 0.1 * fraction, message)
+                    });
+                    let source = prepare_weights(&snapshot_dir, &converted_path, &cancel, Some(&conversion_progress))?;
+                    tracing::info!("Loading {} weights from {}", source.format.as_str(), source.path.display());
+
+                    report(0.9, format!("Loading model weights ({})...", source.format.as_str()));
+                    let backend = CandleBackend::load(&config_path, &tokenizer_path, &source.path, &device)?;
+                    anyhow::Ok((backend, source.format))
+                })
+                .await
+                .context("Weight loading task failed")??;
+                (Arc::new(backend), "candle/f32".to_string(), format.as_str())
+            }
+            InferenceBackend::Onnx => {
+                let onnx_path = ai_config.onnx_model_path.context("ONNX backend selected but no ONNX model configured")?;
+                if ai_config.quantization != QuantizationLevel::F32 {
+                    tracing::warn!("Quantization applies to the candle backend only; loading the ONNX model as exported");
+                }
+                report_progress(0.8, "Loading ONNX model...".to_string());
+                let onnx_path = Path::new(&onnx_path);
+                let backend = OnnxBackend::load(onnx_path, &tokenizer_path)?;
+                (Arc::new(backend), onnx_weights_name(onnx_path)?, "onnx")
+            }
+        };
 
-        // Store model and tokenizer
-        *self.model.write().await = Some(model);
-        *self.tokenizer.write().await = Some(tokenizer);
+        // Hand the encoder to the inference worker
+        *self.worker.write().await = Some(Arc::new(Self::spawn_worker(encoder)?));
+        *self.provenance.write().await = Some(ModelProvenance {
+            model_id,
+            revision,
+            tokenizer_hash,
+            weights: Some(weights),
+            weight_format: Some(weight_format.to_string()),
+        });
 
-<<<<<<< Local
-        report_progress(1.0, "Model loaded successfully!".to_string());
-=======
         // Update last successful load timestamp
         let _ = self.config_manager.update_last_load();
 
-        report_progress(1.0, LoadingStage::Complete.as_str().to_string());
->>>>>>> Remote
-        tracing::info!("BERTimbau model initialized successfully");
+        report_progress(1.0, "Model loaded successfully!".to_string());
+        tracing::info!("{} initialized successfully", model.descriptor.name);
+        Ok(())
+    }
+
+    /// Swap in an already loaded encoder, as a reload would
+    pub async fn replace_encoder(&self, encoder: Arc<dyn EncoderBackend>, provenance: ModelProvenance) -> Result<()> {
+        let _lifecycle = self.lifecycle.lock().await;
+        *self.worker.write().await = Some(Arc::new(Self::spawn_worker(encoder)?));
+        *self.provenance.write().await = Some(provenance);
+        self.set_status(ModelStatus::Ready);
         Ok(())
     }
 
+    /// Stop downloading or converting model files; partial downloads are kept for resuming
+    pub fn cancel_download(&self) {
+        self.download_cancel.lock().unwrap_or_else(|e| e.into_inner()).cancel();
+    }
+
     /// Check if the model is already initialized
     pub async fn is_initialized(&self) -> bool {
-        self.model.read().await.is_some()
+        self.worker.read().await.is_some()
+    }
+
+    /// Drop the loaded model to free its memory
+    ///
+    /// Jobs already submitted finish on the old worker; the next scoring
+    /// call loads the model again.
+    pub async fn unload(&self) {
+        let _lifecycle = self.lifecycle.lock().await;
+        if self.worker.write().await.take().is_none() {
+            return;
+        }
+        *self.provenance.write().await = None;
+        tracing::info!("Encoder unloaded");
+        self.set_status(Self::idle_status(&self.config_manager));
+    }
+
+    /// Unload the model and load it again, e.g. after changing the backend
+    pub async fn reload(&self, progress_callback: Option<ProgressCallback>) -> Result<()> {
+        self.unload().await;
+        self.initialize_with_progress(progress_callback).await
+    }
+
+    /// Whether a HuggingFace token is saved or set in `HF_TOKEN`
+    pub fn is_token_configured(&self) -> bool {
+        self.config_manager.is_token_configured()
+    }
+
+    /// Check the shape of a token before saving it
+    pub fn validate_token_format(token: &str) -> bool {
+        AIConfigManager::validate_token_format(token)
+    }
+
+    /// Save the HuggingFace token used for downloads
+    pub fn set_token(&self, token: &str) -> Result<()> {
+        self.config_manager.set_token(token)?;
+        if matches!(self.status(), ModelStatus::NotConfigured | ModelStatus::TokenSaved) {
+            self.set_status(Self::idle_status(&self.config_manager));
+        }
+        Ok(())
+    }
+
+    fn registry(&self) -> Result<ModelRegistry> {
+        Ok(ModelRegistry::new(self.config_manager.clone(), AIConfigManager::get_default_cache_dir()?))
+    }
+
+    /// Whether the active model is in the local cache
+    pub fn check_model_cache(&self) -> Result<bool> {
+        AIConfigManager::is_model_cached(&self.registry()?.active()?.descriptor.id)
+    }
+
+    /// Cache location and size of the active model and of the embedding cache
+    pub fn get_cache_info(&self) -> Result<CacheInfo> {
+        let mut info = AIConfigManager::get_cache_info(&self.registry()?.active()?.descriptor.id)?;
+        info.embeddings = self.embeddings.as_ref().map(|cache| cache.stats());
+        Ok(info)
+    }
+
+    /// Unload the model and delete the active model's cached files
+    pub async fn clear_cache(&self) -> Result<()> {
+        self.unload().await;
+        let _lifecycle = self.lifecycle.lock().await;
+        AIConfigManager::clear_cache(&self.registry()?.active()?.descriptor.id)?;
+        self.set_status(Self::idle_status(&self.config_manager));
+        Ok(())
+    }
+
+    /// One inference thread: candle already spreads a forward pass across cores
+    fn spawn_worker(encoder: Arc<dyn EncoderBackend>) -> Result<InferenceWorker> {
+        InferenceWorker::new(encoder, 1, DEFAULT_BATCH_SIZE)
+    }
+
+    /// Model id, revision and tokenizer hash of the loaded model
+    pub async fn provenance(&self) -> Option<ModelProvenance> {
+        self.provenance.read().await.clone()
+    }
+
+    /// Name of the loaded weights, as embeddings are keyed by; `None` until
+    /// a model is loaded
+    ///
+    /// Embeddings from two names are not comparable.
+    pub async fn embedding_model(&self) -> Option<String> {
+        self.provenance().await.map(|provenance| embedding_model_name(&provenance))
+    }
+
+    /// Embeddings of `texts` with the pooling in `options`, in order
+    ///
+    /// Texts encoded before by the same weights come from the embedding
+    /// cache; only the rest are submitted to the inference worker.
+    pub async fn embed_texts(&self, texts: Vec<String>, options: JobOptions) -> Result<Vec<Vec<f32>>> {
+        // Ensure model is initialized
+        if self.worker.read().await.is_none() {
+            self.initialize().await?;
+        }
+
+        let worker = self.worker.read().await.clone()
+            .context("Model not initialized")?;
+        let (cache, model) = match (&self.embeddings, self.provenance().await) {
+            (Some(cache), Some(provenance)) => (cache.clone(), embedding_model_name(&provenance)),
+            _ => return worker.submit(texts, options).wait().await,
+        };
+
+        let pooling = options.pooling;
+        let lookup = tokio::task::spawn_blocking(move || {
+            let keys: Vec<EmbeddingKey> = texts.iter().map(|text| EmbeddingKey::new(&model, text, pooling)).collect();
+            let cached: Vec<Option<Vec<f32>>> = keys.iter().map(|key| cache.get(key)).collect();
+            (texts, keys, cached, cache)
+        });
+        let (texts, keys, mut embeddings, cache) = lookup.await.context("Embedding cache task failed")?;
+
+        let missing: Vec<usize> = (0..texts.len()).filter(|&i| embeddings[i].is_none()).collect();
+        if missing.is_empty() {
+            if let Some(progress) = &options.progress {
+                progress(1.0, format!("{} embeddings from cache", texts.len()));
+            }
+        } else {
+            let pending = missing.iter().map(|&i| texts[i].clone()).collect();
+            let computed = worker.submit(pending, options).wait().await?;
+            let stored: Vec<(EmbeddingKey, Vec<f32>)> =
+                missing.iter().zip(&computed).map(|(&i, embedding)| (keys[i].clone(), embedding.clone())).collect();
+            tokio::task::spawn_blocking(move || {
+                for (key, embedding) in stored {
+                    if let Err(e) = cache.put(&key, &embedding) {
+                        tracing::warn!("Could not cache an embedding: {:#}", e);
+                    }
+                }
+            })
+            .await
+            .context("Embedding cache task failed")?;
+            for (i, embedding) in missing.into_iter().zip(computed) {
+                embeddings[i] = Some(embedding);
+            }
+        }
+
+        embeddings.into_iter().collect::<Option<Vec<_>>>().context("Missing embedding")
     }
 
     /// Score an essay using the AI model
@@ -167,106 +570,201 @@ impl AIService {
         theme: &str,
         content: &str,
     ) -> Result<Vec<u16>> {
-        // Ensure model is initialized
-        if self.model.read().await.is_none() {
-            self.initialize().await?;
-        }
-
-        let model_guard = self.model.read().await;
-        let tokenizer_guard = self.tokenizer.read().await;
+        self.score_essay_with(theme, content, &HeuristicConfig::default(), JobOptions::default()).await
+    }
 
-        let model = model_guard.as_ref()
-            .context("Model not initialized")?;
-        let tokenizer = tokenizer_guard.as_ref()
-            .context("Tokenizer not initialized")?;
+    /// Score an essay with the given heuristic thresholds
+    ///
+    /// `options` carries the worker priority, progress callback and cancel
+    /// token of the inference job.
+    pub async fn score_essay_with(
+        &self,
+        theme: &str,
+        content: &str,
+        config: &HeuristicConfig,
+        options: JobOptions,
+    ) -> Result<Vec<u16>> {
+        let essays = [(theme.to_string(), content.to_string())];
+        let mut scores = self.score_essays(&essays, config, options).await?;
+        scores.pop().context("No scores returned")
+    }
 
+    /// Score several `(theme, content)` essays in encoder batches
+    ///
+    /// The job runs on the inference worker with the given priority;
+    /// progress reports the queue position, encoded batches and an ETA.
+    pub async fn score_essays(
+        &self,
+        essays: &[(String, String)],
+        config: &HeuristicConfig,
+        options: JobOptions,
+    ) -> Result<Vec<Vec<u16>>> {
         // Prepare input text with theme separator
-        let input_text = format!("{} <SEP> {}", theme, content);
-
-        // Tokenize input
-        let encoding = tokenizer
-            .encode(input_text, true)
-            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+        let texts = essays
+            .iter()
+            .map(|(theme, content)| format!("{} <SEP> {}", theme, content))
+            .collect();
 
-        let tokens = encoding.get_ids();
-        let token_ids = Tensor::new(tokens, &self.device)?
-            .unsqueeze(0)?; // Add batch dimension
-
-        // Run inference
-        let token_type_ids = Tensor::zeros(token_ids.shape(), candle_core::DType::U32, &self.device)?;
-        let outputs = model.forward(&token_ids, &token_type_ids, None)?;
-        
-        // Extract CLS token embedding (first token)
-        let cls_embedding = outputs.get(0)?.get(0)?;
+        // Run inference (or reuse cached embeddings) and keep the CLS token embeddings
+        let embeddings = self.embed_texts(texts, options).await?;
 
         // For now, use a simple heuristic-based scoring
         // In production, this would use a fine-tuned regression head
-        let scores = self.heuristic_scoring(&cls_embedding, content).await?;
+        let mut scores = Vec::with_capacity(essays.len());
+        for ((_, content), cls_embedding) in essays.iter().zip(&embeddings) {
+            scores.push(self.heuristic_scoring(cls_embedding, content, config).await?);
+        }
 
         Ok(scores)
     }
 
+    /// Score an essay with a confidence interval and an explanation per competency
+    ///
+    /// `criteria` names the competencies in score order. The intervals and
+    /// the attributions both come from the scorer that produced the scores.
+    pub async fn score_essay_explained(
+        &self,
+        theme: &str,
+        content: &str,
+        criteria: &[String],
+        config: &HeuristicConfig,
+        options: JobOptions,
+    ) -> Result<ScoredEssay> {
+        let scores = self.score_essay_with(theme, content, config, options).await?;
+        let explanation = explanation(content, criteria, config).await?;
+        let confidence = confidence_intervals(content, &scores, config).await?;
+
+        Ok(ScoredEssay { scores, confidence, explanation })
+    }
+
+    /// Score an essay from the text heuristics alone, without loading the encoder
+    ///
+    /// The scores and intervals match `score_essay_explained`, which reads its
+    /// scores from the same heuristics; only the attributions are missing.
+    pub async fn score_essay_heuristic(&self, content: &str, config: &HeuristicConfig) -> Result<ScoredEssay> {
+        let scores = heuristic_scores(content, config);
+        let confidence = confidence_intervals(content, &scores, config).await?;
+
+        Ok(ScoredEssay { scores, confidence, explanation: ScoreExplanation::default() })
+    }
+
     /// Heuristic-based scoring (placeholder for fine-tuned model)
     /// This is a simplified version that will be replaced with actual model output
     async fn heuristic_scoring(
         &self,
-        _embedding: &Tensor,
+        _embedding: &[f32],
         content: &str,
+        config: &HeuristicConfig,
     ) -> Result<Vec<u16>> {
-        // Simple heuristics based on essay characteristics
-        let word_count = content.split_whitespace().count();
-        let sentence_count = content.split(&['.', '!', '?'][..]).count();
-        let paragraph_count = content.split("\n\n").count();
-
-        // C1: Grammar and formal writing (based on length and structure)
-        let c1 = if word_count > 250 && word_count < 350 {
-            160
-        } else if word_count >= 200 {
-            120
-        } else {
-            80
-        };
+        Ok(heuristic_scores(content, config))
+    }
+}
 
-        // C2: Theme comprehension (based on structure)
-        let c2 = if paragraph_count >= 4 {
-            160
-        } else if paragraph_count >= 3 {
-            120
-        } else {
-            80
-        };
+/// Bootstrap intervals around `scores`, off the async runtime
+async fn confidence_intervals(content: &str, scores: &[u16], config: &HeuristicConfig) -> Result<Vec<ScoreInterval>> {
+    let content = content.to_string();
+    let scores = scores.to_vec();
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || {
+        let scorer = |text: &str| heuristic_scores(text, &config);
+        bootstrap_intervals(&content, &scores, scorer, BOOTSTRAP_SAMPLES)
+    })
+    .await
+    .context("Confidence task failed")
+}
 
-        // C3: Argument organization
-        let c3 = if sentence_count >= 15 && word_count > 250 {
-            160
-        } else {
-            120
-        };
+/// Sentence attributions of the heuristic scores, off the async runtime
+async fn explanation(content: &str, criteria: &[String], config: &HeuristicConfig) -> Result<ScoreExplanation> {
+    let content = content.to_string();
+    let criteria = criteria.to_vec();
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || {
+        let scorer = |text: &str| heuristic_scores(text, &config);
+        explain_scores(&content, &criteria, scorer)
+    })
+    .await
+    .context("Attribution task failed")
+}
 
-        // C4: Linguistic mechanisms (based on average sentence length)
-        let avg_sentence_len = if sentence_count > 0 {
-            word_count / sentence_count
-        } else {
-            0
-        };
-        let c4 = if avg_sentence_len >= 15 && avg_sentence_len <= 25 {
-            160
-        } else {
-            120
-        };
+/// Names an ONNX export by its content
+///
+/// The export is picked by path, outside the Hub snapshot, so the model
+/// revision does not tell two exports apart.
+fn onnx_weights_name(path: &Path) -> Result<String> {
+    let hash = sha256_file(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("onnx/{}", &hash[..16]))
+}
 
-        // C5: Intervention proposal (check for proposal indicators)
-        let has_proposal = content.to_lowercase().contains("proposta") ||
-                           content.to_lowercase().contains("solução") ||
-                           content.to_lowercase().contains("necessário");
-        let c5 = if has_proposal {
-            160
-        } else {
-            80
-        };
+/// Names the weights behind an embedding: model, revision and precision
+fn embedding_model_name(provenance: &ModelProvenance) -> String {
+    format!(
+        "{}@{}/{}",
+        provenance.model_id,
+        provenance.revision.as_deref().unwrap_or("local"),
+        provenance.weights.as_deref().unwrap_or("unknown")
+    )
+}
 
-        Ok(vec![c1, c2, c3, c4, c5])
-    }
+/// Scores for the 5 ENEM competencies from characteristics of the text
+fn heuristic_scores(content: &str, config: &HeuristicConfig) -> Vec<u16> {
+    // Simple heuristics based on essay characteristics
+    let metrics = compute_text_metrics(content);
+    let word_count = metrics.word_count;
+    let sentence_count = metrics.sentence_count;
+    let paragraph_count = content.split("\n\n").count();
+
+    // C1: Formal writing (length, register markers and lexical diversity)
+    let base = if word_count >= config.c1_min_words { 160 } else { 80 };
+    let lexical_bonus = if metrics.mtld >= config.c1_min_mtld && metrics.register.is_formal() {
+        40
+    } else {
+        0
+    };
+    let register_penalty = config.c1_register_penalty as i32
+        * metrics.register.total().min(config.c1_max_register_markers) as i32;
+    let c1 = (base + lexical_bonus - register_penalty).clamp(40, 200) as u16;
+
+    // C2: Theme comprehension (based on structure)
+    let c2 = if paragraph_count >= 4 {
+        160
+    } else if paragraph_count >= 3 {
+        120
+    } else {
+        80
+    };
+
+    // C3: Argument organization
+    let c3 = if sentence_count >= config.c3_min_sentences && word_count > config.c3_min_words {
+        160
+    } else {
+        120
+    };
+
+    // C4: Linguistic mechanisms (sentence length and subordination)
+    let (min_length, max_length) = config.c4_sentence_length;
+    let (min_depth, max_depth) = config.c4_subordination_depth;
+    let sentence_length_ok = (min_length..=max_length).contains(&metrics.avg_sentence_length);
+    let subordination_ok = (min_depth..=max_depth).contains(&metrics.avg_subordination_depth)
+        && metrics.max_subordination_depth <= config.c4_max_subordination_depth;
+    let c4 = match (sentence_length_ok, subordination_ok) {
+        (true, true) => 200,
+        (true, false) | (false, true) => 160,
+        (false, false) => 120,
+    };
+
+    // C5: Intervention proposal (check for proposal indicators)
+    let lower = content.to_lowercase();
+    let has_proposal = config
+        .c5_proposal_markers
+        .iter()
+        .any(|marker| lower.contains(marker.as_str()));
+    let c5 = if has_proposal {
+        160
+    } else {
+        80
+    };
+
+    vec![c1, c2, c3, c4, c5]
 }
 
 impl Default for AIService {
@@ -278,6 +776,136 @@ impl Default for AIService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ai_config::AIConfiguration;
+    use crate::explain::occlusion_attributions;
+    use crate::inference::EncodedInput;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::Tokenizer;
+
+    /// Embeds every text as its length, counting the texts it encodes
+    struct LengthEncoder {
+        tokenizer: Tokenizer,
+        encoded: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl EncoderBackend for LengthEncoder {
+        fn kind(&self) -> InferenceBackend {
+            InferenceBackend::Candle
+        }
+
+        fn tokenizer(&self) -> &Tokenizer {
+            &self.tokenizer
+        }
+
+        fn forward(&self, _input: &EncodedInput) -> Result<Vec<Vec<f32>>> {
+            unreachable!("the length encoder only embeds batches")
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.encoded.lock().unwrap().extend_from_slice(texts);
+            Ok(texts.iter().map(|text| vec![text.len() as f32; 4]).collect())
+        }
+    }
+
+    fn encoder() -> Arc<LengthEncoder> {
+        Arc::new(LengthEncoder { tokenizer: Tokenizer::new(WordLevel::default()), encoded: Default::default() })
+    }
+
+    fn provenance() -> ModelProvenance {
+        ModelProvenance {
+            model_id: MODEL_ID.to_string(),
+            revision: None,
+            tokenizer_hash: String::new(),
+            weights: Some("candle/f32".to_string()),
+            weight_format: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unload_frees_the_model_and_publishes_status() {
+        let service = AIService::with_encoder(encoder(), provenance()).unwrap().with_embedding_cache(None);
+        assert_eq!(service.status(), ModelStatus::Ready);
+        let mut events = service.subscribe();
+
+        service.unload().await;
+        assert!(!service.is_initialized().await);
+        assert!(service.provenance().await.is_none());
+        match events.recv().await.unwrap() {
+            ModelEvent::Status(status) => assert_eq!(status, service.status()),
+            other => panic!("expected a status event, got {:?}", other),
+        }
+        assert_ne!(service.status(), ModelStatus::Ready);
+
+        // Unloading twice publishes nothing more
+        service.unload().await;
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_are_reused_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Arc::new(EmbeddingCache::open(dir.path().join("embeddings"), 1 << 20).unwrap());
+        let encoder = encoder();
+        let service = AIService::with_encoder(encoder.clone(), provenance())
+            .unwrap()
+            .with_embedding_cache(Some(cache.clone()));
+        let texts = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        let first = service.embed_texts(texts(&["a", "bb"]), JobOptions::default()).await.unwrap();
+        let second = service.embed_texts(texts(&["bb", "ccc", "a"]), JobOptions::default()).await.unwrap();
+        assert_eq!(first, vec![vec![1.0; 4], vec![2.0; 4]]);
+        assert_eq!(second, vec![vec![2.0; 4], vec![3.0; 4], vec![1.0; 4]]);
+        assert_eq!(*encoder.encoded.lock().unwrap(), texts(&["a", "bb", "ccc"]));
+
+        assert_eq!(cache.stats().entries, 3);
+
+        // Other weights do not share entries
+        let quantized = ModelProvenance { weights: Some("candle/q8".to_string()), ..provenance() };
+        let service = AIService::with_encoder(encoder.clone(), quantized).unwrap().with_embedding_cache(Some(cache));
+        service.embed_texts(texts(&["a"]), JobOptions::default()).await.unwrap();
+        assert_eq!(encoder.encoded.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_onnx_exports_are_named_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (dir.path().join("a.onnx"), dir.path().join("b.onnx"));
+        std::fs::write(&first, b"export 1").unwrap();
+        std::fs::write(&second, b"export 2").unwrap();
+
+        let name = onnx_weights_name(&first).unwrap();
+        assert!(name.starts_with("onnx/"));
+        assert_ne!(name, onnx_weights_name(&second).unwrap());
+
+        // Same revision, different exports: different cache keys
+        let export = |weights: String| ModelProvenance { weights: Some(weights), ..provenance() };
+        assert_ne!(
+            embedding_model_name(&export(name)),
+            embedding_model_name(&export(onnx_weights_name(&second).unwrap()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_publishes_error_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_manager = Arc::new(AIConfigManager::with_config_path(dir.path().join("ai_config.json")).unwrap());
+        let config = AIConfiguration { inference_backend: InferenceBackend::Onnx, ..Default::default() };
+        config_manager.save(&config).unwrap();
+
+        let service = AIService::from_parts(config_manager, None, None);
+        let mut events = service.subscribe();
+        assert!(service.initialize().await.is_err());
+
+        let mut statuses = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let ModelEvent::Status(status) = event {
+                statuses.push(status);
+            }
+        }
+        assert_eq!(statuses.first(), Some(&ModelStatus::Connecting));
+        assert!(matches!(statuses.last(), Some(ModelStatus::Error(_))));
+        assert_eq!(Some(&service.status()), statuses.last());
+    }
 
     #[tokio::test]
     async fn test_ai_service_creation() {
@@ -300,14 +928,41 @@ mod tests {
 \
                       Conclusão do texto.";
         
-        let device = Device::Cpu;
-        let dummy_tensor = Tensor::zeros((768,), candle_core::DType::F32, &device).unwrap();
-        let scores = service.heuristic_scoring(&dummy_tensor, content).await;
+        let dummy_tensor = vec![0.0; 768];
+        let scores = service.heuristic_scoring(&dummy_tensor, content, &HeuristicConfig::default()).await;
         
         assert!(scores.is_ok());
         let scores = scores.unwrap();
         assert_eq!(scores.len(), 5);
         assert!(scores.iter().all(|&s| s <= 200));
     }
-}
 
+    #[test]
+    fn test_attributions_point_at_proposal_sentence() {
+        let criteria: Vec<String> = (1..=5).map(|i| format!("C{}", i)).collect();
+        let content = "A evasão escolar cresce no país. Uma solução é ampliar o ensino integral.";
+        let config = HeuristicConfig::default();
+        let scorer = |text: &str| heuristic_scores(text, &config);
+        let explanation = explain_scores(content, &criteria, scorer);
+
+        assert_eq!(explanation.sentences.len(), 2);
+        assert_eq!(explanation.sentences[0].weight("C5"), 0.0);
+        assert!(explanation.sentences[1].weight("C5") > 0.0);
+        let tokens = occlusion_attributions(content, &criteria, scorer);
+        let solucao = tokens.iter().find(|t| t.token == "solução").unwrap();
+        assert_eq!(solucao.competencies["C5"], 80.0);
+    }
+
+    #[tokio::test]
+    async fn test_informal_register_lowers_c1() {
+        let service = AIService::new().unwrap();
+        let dummy_tensor = vec![0.0; 768];
+        let formal = "A educação pública precisa de investimentos contínuos. ".repeat(40);
+        let informal = "Eu acho que a galera tá certa, né, pra mim a escola precisa mudar. ".repeat(40);
+
+        let config = HeuristicConfig::default();
+        let formal_scores = service.heuristic_scoring(&dummy_tensor, &formal, &config).await.unwrap();
+        let informal_scores = service.heuristic_scoring(&dummy_tensor, &informal, &config).await.unwrap();
+        assert!(informal_scores[0] < formal_scores[0]);
+    }
+}
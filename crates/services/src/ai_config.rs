@@ -1,8 +1,17 @@
 use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::embedding_cache::EmbeddingCacheStats;
+use crate::model_install::WEIGHT_FILES;
+use crate::model_registry::repo_dir_name;
+use crate::secrets::{generate_salt, is_sealed, SecretKey};
+
+/// Associated data bound to the sealed token
+const TOKEN_CONTEXT: &str = "huggingface_token";
 
 /// Model loading status
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,15 +22,32 @@ pub enum ModelStatus {
     Downloading,
     Loading,
     Ready,
+    /// Cached on disk but not in memory
+    Unloaded,
     Error(String),
 }
 
+impl ModelStatus {
+    /// Not loaded and not loading, e.g. after a cancelled download or an unload
+    pub fn is_idle(&self) -> bool {
+        matches!(self, Self::NotConfigured | Self::TokenSaved | Self::Unloaded)
+    }
+}
+
 /// AI Configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIConfiguration {
-    /// Encrypted HuggingFace token (base64 encoded)
+    /// HuggingFace token sealed with XChaCha20-Poly1305
+    ///
+    /// Configurations written by older versions hold plain base64 here;
+    /// it is re-sealed the first time the token is read.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub huggingface_token_encrypted: Option<String>,
+
+    /// Argon2 salt (base64) when the token key comes from a passphrase
+    /// instead of the local key file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_passphrase_salt: Option<String>,
     
     /// Auto-load model on startup
     #[serde(default)]
@@ -35,13 +61,89 @@ pub struct AIConfiguration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_successful_load: Option<DateTime<Utc>>,
     
-    /// Model version identifier
+    /// Registry id of the encoder to load; BERTimbau Base when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_model: Option<String>,
+
+    /// Revision (branch, tag or commit) the active model is pinned to
+    ///
+    /// Older versions stored the model id here; see `pinned_revision`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_version: Option<String>,
     
     /// Download preferences
     #[serde(default)]
     pub download_preferences: DownloadPreferences,
+    
+    /// Local GGUF instruct model for generative feedback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback_model_path: Option<String>,
+
+    /// Runtime that runs the encoder
+    #[serde(default)]
+    pub inference_backend: InferenceBackend,
+
+    /// Exported ONNX encoder, required by the ONNX backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub onnx_model_path: Option<String>,
+
+    /// Precision of the encoder weights on the candle backend
+    #[serde(default)]
+    pub quantization: QuantizationLevel,
+
+    /// On-disk cache of text embeddings
+    #[serde(default)]
+    pub embedding_cache: EmbeddingCachePreferences,
+}
+
+/// Runtime for the encoder forward pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InferenceBackend {
+    /// candle with the PyTorch weights from the Hub
+    #[default]
+    Candle,
+    /// tract with an exported ONNX model
+    Onnx,
+}
+
+/// Precision of the encoder weights
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuantizationLevel {
+    /// Original weights, no conversion
+    #[default]
+    F32,
+    F16,
+    /// 8-bit blocks (GGML Q8_0)
+    Q8,
+    /// 4-bit blocks (GGML Q4_0), smallest and least accurate
+    Q4,
+}
+
+impl QuantizationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuantizationLevel::F32 => "f32",
+            QuantizationLevel::F16 => "f16",
+            QuantizationLevel::Q8 => "q8",
+            QuantizationLevel::Q4 => "q4",
+        }
+    }
+}
+
+impl std::str::FromStr for QuantizationLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "f32" => Ok(QuantizationLevel::F32),
+            "f16" => Ok(QuantizationLevel::F16),
+            "q8" | "int8" => Ok(QuantizationLevel::Q8),
+            "q4" | "int4" => Ok(QuantizationLevel::Q4),
+            other => anyhow::bail!("Unknown quantization level {}", other),
+        }
+    }
 }
 
 /// Download preferences
@@ -69,35 +171,93 @@ impl Default for DownloadPreferences {
     }
 }
 
+/// Embedding cache preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCachePreferences {
+    /// Reuse embeddings of texts encoded before
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Size limit; least recently used embeddings are evicted past it
+    #[serde(default = "default_embedding_cache_mb")]
+    pub max_size_mb: u64,
+}
+
+fn default_embedding_cache_mb() -> u64 {
+    256
+}
+
+impl Default for EmbeddingCachePreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_mb: default_embedding_cache_mb(),
+        }
+    }
+}
+
 impl Default for AIConfiguration {
     fn default() -> Self {
         Self {
             huggingface_token_encrypted: None,
+            token_passphrase_salt: None,
             auto_load_on_startup: false,
             model_cache_path: None,
             last_successful_load: None,
-            model_version: Some("neuralmind/bert-base-portuguese-cased".to_string()),
+            active_model: None,
+            model_version: None,
             download_preferences: DownloadPreferences::default(),
+            feedback_model_path: None,
+            inference_backend: InferenceBackend::Candle,
+            onnx_model_path: None,
+            quantization: QuantizationLevel::F32,
+            embedding_cache: EmbeddingCachePreferences::default(),
         }
     }
 }
 
+impl AIConfiguration {
+    /// The pinned revision, ignoring a model id left by older versions
+    pub fn pinned_revision(&self) -> Option<&str> {
+        self.model_version.as_deref().filter(|version| !version.contains('/'))
+    }
+}
+
 /// AI Configuration Manager
 pub struct AIConfigManager {
     config_path: PathBuf,
+    passphrase: Option<String>,
 }
 
 impl AIConfigManager {
     /// Create a new configuration manager
     pub fn new() -> Result<Self> {
-        let config_path = Self::get_config_path()?;
-        
+        Self::with_config_path(Self::get_config_path()?)
+    }
+
+    /// Manager for a configuration file at a custom location
+    pub fn with_config_path(config_path: PathBuf) -> Result<Self> {
         // Ensure config directory exists
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
         }
         
-        Ok(Self { config_path })
+        Ok(Self { config_path, passphrase: None })
+    }
+
+    /// Seal the token with a key derived from `passphrase` instead of the key file
+    pub fn with_passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(passphrase.to_string());
+        self
+    }
+
+    pub(crate) fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// Locally generated token key, next to the configuration file
+    fn get_key_path(&self) -> PathBuf {
+        self.config_path.with_file_name("token.key")
     }
     
     /// Get the platform-specific configuration file path
@@ -152,34 +312,73 @@ impl AIConfigManager {
     
     /// Get the HuggingFace token (decrypted)
     pub fn get_token(&self) -> Result<Option<String>> {
-        let config = self.load()?;
+        let mut config = self.load()?;
         
-        if let Some(encrypted) = config.huggingface_token_encrypted {
-            // Simple base64 decoding for now
-            // In production, use proper encryption
-            let decoded = general_purpose::STANDARD.decode(&encrypted)
-                .context("Failed to decode token")?;
-            let token = String::from_utf8(decoded)
-                .context("Failed to parse token")?;
-            Ok(Some(token))
-        } else {
-            Ok(None)
+        let stored = match config.huggingface_token_encrypted.clone() {
+            Some(stored) => stored,
+            None => return Ok(None),
+        };
+        if is_sealed(&stored) {
+            return self.opening_key(&config)?.open(&stored, TOKEN_CONTEXT).map(Some);
         }
+
+        // Older versions stored the token as plain base64; seal it in place
+        let decoded = general_purpose::STANDARD.decode(&stored)
+            .context("Failed to decode token")?;
+        let token = String::from_utf8(decoded)
+            .context("Failed to parse token")?;
+        self.seal_token(&mut config, &token)?;
+        self.save(&config)?;
+        tracing::info!("Migrated the stored HuggingFace token to authenticated encryption");
+        Ok(Some(token))
     }
     
     /// Set the HuggingFace token (encrypted)
     pub fn set_token(&self, token: &str) -> Result<()> {
         let mut config = self.load()?;
-        
-        // Simple base64 encoding for now
-        // In production, use proper encryption
-        let encrypted = general_purpose::STANDARD.encode(token);
-        config.huggingface_token_encrypted = Some(encrypted);
-        
+        self.seal_token(&mut config, token)?;
         self.save(&config)?;
         Ok(())
     }
+
+    fn seal_token(&self, config: &mut AIConfiguration, token: &str) -> Result<()> {
+        let key = match &self.passphrase {
+            Some(passphrase) => {
+                let salt = generate_salt();
+                config.token_passphrase_salt = Some(general_purpose::STANDARD.encode(salt));
+                SecretKey::from_passphrase(passphrase, &salt)?
+            }
+            None => {
+                config.token_passphrase_salt = None;
+                SecretKey::load_or_create(&self.get_key_path())?
+            }
+        };
+        config.huggingface_token_encrypted = Some(key.seal(token, TOKEN_CONTEXT)?);
+        Ok(())
+    }
+
+    fn opening_key(&self, config: &AIConfiguration) -> Result<SecretKey> {
+        match (&config.token_passphrase_salt, &self.passphrase) {
+            (Some(salt), Some(passphrase)) => {
+                let salt = general_purpose::STANDARD.decode(salt)
+                    .context("Failed to decode token salt")?;
+                SecretKey::from_passphrase(passphrase, &salt)
+            }
+            (Some(_), None) => anyhow::bail!("The token is protected by a passphrase"),
+            (None, _) => SecretKey::load(&self.get_key_path())
+                .context("Cannot open the stored token; set it again"),
+        }
+    }
     
+    /// Token for Hub requests; the HF_TOKEN environment variable takes
+    /// precedence over the stored token
+    pub fn resolve_token(&self) -> Result<Option<String>> {
+        match std::env::var("HF_TOKEN") {
+            Ok(token) if !token.is_empty() => Ok(Some(token)),
+            _ => self.get_token(),
+        }
+    }
+
     /// Validate token format
     pub fn validate_token_format(token: &str) -> bool {
         token.starts_with("hf_") && token.len() > 10
@@ -189,6 +388,7 @@ impl AIConfigManager {
     pub fn clear_token(&self) -> Result<()> {
         let mut config = self.load()?;
         config.huggingface_token_encrypted = None;
+        config.token_passphrase_salt = None;
         self.save(&config)?;
         Ok(())
     }
@@ -212,15 +412,43 @@ impl AIConfigManager {
         Ok(cache_dir)
     }
     
-    /// Get the model-specific cache directory
-    pub fn get_model_cache_dir() -> Result<PathBuf> {
-        Ok(Self::get_default_cache_dir()?
-            .join("models--neuralmind--bert-base-portuguese-cased"))
+    /// Get the cache directory of a model
+    pub fn get_model_cache_dir(model_id: &str) -> Result<PathBuf> {
+        Ok(Self::get_default_cache_dir()?.join(repo_dir_name(model_id)))
+    }
+    
+    /// Converted weights of a quantization level, kept next to the Hub cache
+    ///
+    /// One conversion per snapshot commit: weights converted from another
+    /// revision of the model must not be picked up after a switch or update.
+    pub fn get_quantized_model_path(model_id: &str, commit: &str, level: QuantizationLevel) -> Result<PathBuf> {
+        Ok(Self::get_model_cache_dir(model_id)?
+            .join("quantized")
+            .join(commit)
+            .join(format!("model-{}.gguf", level.as_str())))
+    }
+    
+    /// Safetensors converted from a revision that ships PyTorch weights only
+    pub fn get_converted_weights_path(model_id: &str, revision: &str) -> Result<PathBuf> {
+        Ok(Self::get_model_cache_dir(model_id)?
+            .join("converted")
+            .join(revision)
+            .join("model.safetensors"))
+    }
+    
+    /// Get the directory of the embedding cache
+    pub fn get_embedding_cache_dir() -> Result<PathBuf> {
+        Ok(Self::get_default_cache_dir()?.join("embeddings"))
+    }
+    
+    /// Get the directory holding spell-checking dictionaries
+    pub fn get_dictionary_dir() -> Result<PathBuf> {
+        Ok(Self::get_default_cache_dir()?.join("dictionaries"))
     }
     
     /// Check if model is cached locally
-    pub fn is_model_cached() -> Result<bool> {
-        let cache_dir = Self::get_model_cache_dir()?;
+    pub fn is_model_cached(model_id: &str) -> Result<bool> {
+        let cache_dir = Self::get_model_cache_dir(model_id)?;
         
         if !cache_dir.exists() {
             return Ok(false);
@@ -239,7 +467,7 @@ impl AIConfigManager {
                 if snapshot_dir.is_dir() {
                     let has_config = snapshot_dir.join("config.json").exists();
                     let has_tokenizer = snapshot_dir.join("tokenizer.json").exists();
-                    let has_model = snapshot_dir.join("pytorch_model.bin").exists();
+                    let has_model = WEIGHT_FILES.iter().any(|file| snapshot_dir.join(file).exists());
                     
                     if has_config && has_tokenizer && has_model {
                         return Ok(true);
@@ -252,8 +480,8 @@ impl AIConfigManager {
     }
     
     /// Get cache information (size, location)
-    pub fn get_cache_info() -> Result<CacheInfo> {
-        let cache_dir = Self::get_model_cache_dir()?;
+    pub fn get_cache_info(model_id: &str) -> Result<CacheInfo> {
+        let cache_dir = Self::get_model_cache_dir(model_id)?;
         let exists = cache_dir.exists();
         
         let size_bytes = if exists {
@@ -267,11 +495,12 @@ impl AIConfigManager {
             exists,
             size_bytes,
             size_human: Self::format_bytes(size_bytes),
+            embeddings: None,
         })
     }
     
     /// Calculate directory size recursively
-    fn calculate_dir_size(path: &PathBuf) -> Result<u64> {
+    pub(crate) fn calculate_dir_size(path: &PathBuf) -> Result<u64> {
         let mut total = 0;
         
         if path.is_dir() {
@@ -309,8 +538,8 @@ impl AIConfigManager {
     }
     
     /// Clear model cache
-    pub fn clear_cache() -> Result<()> {
-        let cache_dir = Self::get_model_cache_dir()?;
+    pub fn clear_cache(model_id: &str) -> Result<()> {
+        let cache_dir = Self::get_model_cache_dir(model_id)?;
         
         if cache_dir.exists() {
             fs::remove_dir_all(&cache_dir)
@@ -336,11 +565,14 @@ pub struct CacheInfo {
     pub exists: bool,
     pub size_bytes: u64,
     pub size_human: String,
+    /// Embedding cache, when the service has one open
+    pub embeddings: Option<EmbeddingCacheStats>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
     
     #[test]
     fn test_token_validation() {
@@ -362,9 +594,122 @@ mod tests {
     fn test_default_config() {
         let config = AIConfiguration::default();
         assert_eq!(config.huggingface_token_encrypted, None);
-        assert_eq!(config.auto_load_on_startup, false);
-        assert_eq!(config.model_version, Some("neuralmind/bert-base-portuguese-cased".to_string()));
+        assert!(!config.auto_load_on_startup);
+        assert_eq!(config.active_model, None);
+        assert_eq!(config.pinned_revision(), None);
         assert!(config.download_preferences.resume_on_interrupt);
         assert!(config.download_preferences.verify_integrity);
+        assert_eq!(config.inference_backend, InferenceBackend::Candle);
+        assert!(config.embedding_cache.enabled);
+        assert_eq!(config.embedding_cache.max_size_mb, 256);
+    }
+
+    #[test]
+    fn test_backend_defaults_to_candle_in_old_configs() {
+        let config: AIConfiguration = serde_json::from_str(r#"{"auto_load_on_startup": true}"#).unwrap();
+        assert_eq!(config.inference_backend, InferenceBackend::Candle);
+
+        let config: AIConfiguration =
+            serde_json::from_str(r#"{"inference_backend": "onnx", "onnx_model_path": "/modelos/bert.onnx"}"#).unwrap();
+        assert_eq!(config.inference_backend, InferenceBackend::Onnx);
+        assert_eq!(config.quantization, QuantizationLevel::F32);
+    }
+
+    fn temp_manager() -> (AIConfigManager, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        (AIConfigManager::with_config_path(dir.path().join("ai_config.json")).unwrap(), dir)
+    }
+
+    #[test]
+    fn test_token_is_sealed_on_disk() {
+        let (manager, dir) = temp_manager();
+        manager.set_token("hf_abcdefghijklmnop").unwrap();
+
+        let stored = manager.load().unwrap().huggingface_token_encrypted.unwrap();
+        assert!(is_sealed(&stored));
+        assert_ne!(stored, general_purpose::STANDARD.encode("hf_abcdefghijklmnop"));
+        assert_eq!(manager.get_token().unwrap().as_deref(), Some("hf_abcdefghijklmnop"));
+        assert!(dir.path().join("token.key").exists());
+
+        manager.clear_token().unwrap();
+        assert_eq!(manager.get_token().unwrap(), None);
+    }
+
+    #[test]
+    fn test_legacy_base64_token_is_migrated() {
+        let (manager, _dir) = temp_manager();
+        let legacy = AIConfiguration {
+            huggingface_token_encrypted: Some(general_purpose::STANDARD.encode("hf_abcdefghijklmnop")),
+            ..Default::default()
+        };
+        manager.save(&legacy).unwrap();
+
+        assert_eq!(manager.get_token().unwrap().as_deref(), Some("hf_abcdefghijklmnop"));
+        let stored = manager.load().unwrap().huggingface_token_encrypted.unwrap();
+        assert!(is_sealed(&stored));
+        assert_eq!(manager.get_token().unwrap().as_deref(), Some("hf_abcdefghijklmnop"));
+    }
+
+    #[test]
+    fn test_tampered_token_is_rejected() {
+        let (manager, _dir) = temp_manager();
+        manager.set_token("hf_abcdefghijklmnop").unwrap();
+
+        let mut config = manager.load().unwrap();
+        let stored = config.huggingface_token_encrypted.take().unwrap();
+        let mut tampered: Vec<char> = stored.chars().collect();
+        let last = tampered.len() - 3;
+        tampered[last] = if tampered[last] == 'A' { 'B' } else { 'A' };
+        config.huggingface_token_encrypted = Some(tampered.into_iter().collect());
+        manager.save(&config).unwrap();
+
+        assert!(manager.get_token().is_err());
+    }
+
+    #[test]
+    fn test_missing_key_file_is_reported_and_not_recreated() {
+        let (manager, dir) = temp_manager();
+        manager.set_token("hf_abcdefghijklmnop").unwrap();
+        let key_path = dir.path().join("token.key");
+        std::fs::remove_file(&key_path).unwrap();
+
+        let error = manager.get_token().unwrap_err();
+        assert!(format!("{:#}", error).contains("missing"));
+        assert!(!key_path.exists());
+    }
+
+    #[test]
+    fn test_passphrase_protected_token() {
+        let (manager, dir) = temp_manager();
+        let manager = manager.with_passphrase("senha da escola");
+        manager.set_token("hf_abcdefghijklmnop").unwrap();
+        assert!(manager.load().unwrap().token_passphrase_salt.is_some());
+        assert!(!dir.path().join("token.key").exists());
+        assert_eq!(manager.get_token().unwrap().as_deref(), Some("hf_abcdefghijklmnop"));
+
+        let config_path = dir.path().join("ai_config.json");
+        let without = AIConfigManager::with_config_path(config_path.clone()).unwrap();
+        assert!(without.get_token().is_err());
+        let wrong = AIConfigManager::with_config_path(config_path).unwrap().with_passphrase("outra senha");
+        assert!(wrong.get_token().is_err());
+    }
+
+    #[test]
+    fn test_legacy_model_version_is_not_a_revision() {
+        let config: AIConfiguration =
+            serde_json::from_str(r#"{"model_version": "neuralmind/bert-base-portuguese-cased"}"#).unwrap();
+        assert_eq!(config.pinned_revision(), None);
+
+        let config: AIConfiguration = serde_json::from_str(r#"{"model_version": "94d69c95f98f7d5b"}"#).unwrap();
+        assert_eq!(config.pinned_revision(), Some("94d69c95f98f7d5b"));
+    }
+
+    #[test]
+    fn test_quantization_level_names() {
+        let config: AIConfiguration = serde_json::from_str(r#"{"quantization": "q8"}"#).unwrap();
+        assert_eq!(config.quantization, QuantizationLevel::Q8);
+        assert_eq!("int8".parse::<QuantizationLevel>().unwrap(), QuantizationLevel::Q8);
+        assert_eq!("Q4".parse::<QuantizationLevel>().unwrap().as_str(), "q4");
+        assert!("q3".parse::<QuantizationLevel>().is_err());
     }
 }
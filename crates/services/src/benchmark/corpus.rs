@@ -0,0 +1,161 @@
+//! Labelled essay corpora in JSON or CSV
+
+use anyhow::{bail, Context, Result};
+use domain::essay::{Essay, EssayStatus, ExamType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+/// An essay with the scores human graders gave it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabeledEssay {
+    pub id: String,
+    #[serde(default = "default_exam_type")]
+    pub exam_type: ExamType,
+    #[serde(default)]
+    pub title: String,
+    pub content: String,
+    /// Criterion name to human score
+    pub scores: HashMap<String, u16>,
+}
+
+fn default_exam_type() -> ExamType {
+    ExamType::Enem
+}
+
+impl LabeledEssay {
+    /// Submitted essay ready for `EvaluationService`
+    pub fn to_essay(&self) -> Essay {
+        let mut essay = Essay::new(Uuid::nil(), &self.title, &self.content, self.exam_type.clone());
+        essay.status = EssayStatus::Enviada;
+        essay.submitted_at = Some(essay.created_at);
+        essay
+    }
+}
+
+/// Load a corpus, picking the format from the file extension
+pub fn load_corpus(path: &Path) -> Result<Vec<LabeledEssay>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read corpus {}", path.display()))?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(&content).context("Failed to parse JSON corpus"),
+        Some("csv") => parse_csv_corpus(&content),
+        _ => bail!("Unsupported corpus format: {}", path.display()),
+    }
+}
+
+/// Columns `id`, `content` and optionally `title` and `exam_type`; every
+/// other column is a criterion score
+pub fn parse_csv_corpus(content: &str) -> Result<Vec<LabeledEssay>> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows.next().context("Empty CSV corpus")?;
+    let column = |name: &str| header.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let id_col = column("id").context("Missing id column")?;
+    let content_col = column("content").context("Missing content column")?;
+    let title_col = column("title");
+    let exam_col = column("exam_type");
+    let score_cols: Vec<(usize, &String)> = header
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| ![Some(id_col), Some(content_col), title_col, exam_col].contains(&Some(*idx)))
+        .collect();
+
+    rows.enumerate()
+        .filter(|(_, row)| row.iter().any(|f| !f.trim().is_empty()))
+        .map(|(line, row)| {
+            let field = |idx: usize| row.get(idx).map(|f| f.trim()).unwrap_or_default();
+            let exam_type = match exam_col.map(field).filter(|e| !e.is_empty()) {
+                Some(exam) => serde_json::from_value(serde_json::Value::String(exam.to_uppercase()))
+                    .with_context(|| format!("Unknown exam type {} on row {}", exam, line + 2))?,
+                None => default_exam_type(),
+            };
+            let mut scores = HashMap::new();
+            for (idx, name) in &score_cols {
+                let value = field(*idx);
+                if value.is_empty() {
+                    continue;
+                }
+                let score = value
+                    .parse()
+                    .with_context(|| format!("Invalid {} score {:?} on row {}", name, value, line + 2))?;
+                scores.insert(name.trim().to_string(), score);
+            }
+            Ok(LabeledEssay {
+                id: field(id_col).to_string(),
+                exam_type,
+                title: title_col.map(field).unwrap_or_default().to_string(),
+                content: row.get(content_col).cloned().unwrap_or_default(),
+                scores,
+            })
+        })
+        .collect()
+}
+
+/// RFC 4180 fields: quoted fields may hold commas, line breaks and `""`
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_corpus() {
+        let csv = "id,title,content,C1,C2\r\n\
+                   e1,Tema,\"Primeiro parágrafo, com vírgula.\n\nSegundo \"\"citado\"\".\",160,120\r\n\
+                   e2,,Texto curto.,80,\n";
+        let corpus = parse_csv_corpus(csv).unwrap();
+        assert_eq!(corpus.len(), 2);
+        assert_eq!(corpus[0].content, "Primeiro parágrafo, com vírgula.\n\nSegundo \"citado\".");
+        assert_eq!(corpus[0].scores["C2"], 120);
+        assert_eq!(corpus[0].exam_type, ExamType::Enem);
+        assert_eq!(corpus[1].scores.len(), 1);
+    }
+
+    #[test]
+    fn test_csv_rejects_bad_scores() {
+        let error = parse_csv_corpus("id,content,C1\ne1,Texto.,alto\n").unwrap_err();
+        assert!(error.to_string().contains("row 2"));
+    }
+
+    #[test]
+    fn test_parse_json_corpus() {
+        let json = r#"[{"id": "e1", "exam_type": "FUVEST", "content": "Texto.", "scores": {"Estrutura": 12}}]"#;
+        let corpus: Vec<LabeledEssay> = serde_json::from_str(json).unwrap();
+        assert_eq!(corpus[0].exam_type, ExamType::Fuvest);
+        assert_eq!(corpus[0].title, "");
+
+        let rubric = crate::rubrics::get_rubric(&ExamType::Fuvest).unwrap();
+        let estrutura = rubric.criteria.iter().find(|c| c.name == "Estrutura").unwrap();
+        assert!(estrutura.is_level(corpus[0].scores["Estrutura"]));
+    }
+}
@@ -0,0 +1,150 @@
+//! Agreement metrics between human and automatic grades
+
+use serde::{Deserialize, Serialize};
+
+/// Counts of (human level, predicted level) pairs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfusionMatrix {
+    /// Score of each level, lowest first
+    pub levels: Vec<u16>,
+    /// `counts[human][predicted]`
+    pub counts: Vec<Vec<u32>>,
+}
+
+impl ConfusionMatrix {
+    /// Levels are `0, step, 2 * step, ...` up to `max_score`
+    pub fn new(max_score: u16, step: u16) -> Self {
+        let step = step.max(1);
+        let levels: Vec<u16> = (0..=max_score / step).map(|i| i * step).collect();
+        let counts = vec![vec![0; levels.len()]; levels.len()];
+        Self { levels, counts }
+    }
+
+    /// Level index closest to a score
+    pub fn level_of(&self, score: u16) -> usize {
+        self.levels
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &level)| level.abs_diff(score))
+            .map(|(idx, _)| idx)
+            .unwrap_or(0)
+    }
+
+    pub fn add(&mut self, human: u16, predicted: u16) {
+        let (h, p) = (self.level_of(human), self.level_of(predicted));
+        self.counts[h][p] += 1;
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// Quadratic weighted kappa over the matrix levels
+    ///
+    /// 1 is perfect agreement and 0 is what chance would give. When both
+    /// graders use a single level the kappa is undefined and reported as 1
+    /// if they agree everywhere.
+    pub fn quadratic_weighted_kappa(&self) -> f64 {
+        let k = self.levels.len();
+        let n = self.total() as f64;
+        if n == 0.0 {
+            return 0.0;
+        }
+        let rows: Vec<f64> = self.counts.iter().map(|r| r.iter().sum::<u32>() as f64).collect();
+        let cols: Vec<f64> = (0..k)
+            .map(|j| self.counts.iter().map(|r| r[j]).sum::<u32>() as f64)
+            .collect();
+
+        let mut observed = 0.0;
+        let mut expected = 0.0;
+        for (i, (row, row_total)) in self.counts.iter().zip(&rows).enumerate() {
+            for (j, (&count, col_total)) in row.iter().zip(&cols).enumerate() {
+                let weight = ((i as f64) - (j as f64)).powi(2);
+                observed += weight * count as f64;
+                expected += weight * row_total * col_total / n;
+            }
+        }
+        if expected == 0.0 {
+            return if observed == 0.0 { 1.0 } else { 0.0 };
+        }
+        1.0 - observed / expected
+    }
+
+    /// Share of essays graded at the same level
+    pub fn exact_agreement(&self) -> f64 {
+        self.agreement_within(0)
+    }
+
+    /// Share of essays graded at most one level apart
+    pub fn adjacent_agreement(&self) -> f64 {
+        self.agreement_within(1)
+    }
+
+    fn agreement_within(&self, levels: usize) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        let agreeing: u32 = self
+            .counts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().enumerate().map(move |(j, &c)| (i, j, c)))
+            .filter(|(i, j, _)| i.abs_diff(*j) <= levels)
+            .map(|(_, _, c)| c)
+            .sum();
+        agreeing as f64 / total as f64
+    }
+}
+
+/// Mean absolute difference in points
+pub fn mean_absolute_error(pairs: &[(u16, u16)]) -> f64 {
+    if pairs.is_empty() {
+        return 0.0;
+    }
+    let total: u32 = pairs.iter().map(|(h, p)| h.abs_diff(*p) as u32).sum();
+    total as f64 / pairs.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(pairs: &[(u16, u16)]) -> ConfusionMatrix {
+        let mut matrix = ConfusionMatrix::new(200, 40);
+        for &(h, p) in pairs {
+            matrix.add(h, p);
+        }
+        matrix
+    }
+
+    #[test]
+    fn test_quadratic_weighted_kappa() {
+        let perfect = matrix(&[(0, 0), (80, 80), (200, 200)]);
+        assert_eq!(perfect.quadratic_weighted_kappa(), 1.0);
+
+        let one_off = matrix(&[(0, 0), (40, 40), (80, 80), (120, 120), (160, 160), (200, 160)]);
+        assert!((one_off.quadratic_weighted_kappa() - 0.967_741_9).abs() < 1e-6);
+
+        let mixed = matrix(&[(80, 120), (120, 120), (160, 160), (160, 200), (120, 80)]);
+        assert!((mixed.quadratic_weighted_kappa() - 0.634_146_3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_agreement_and_mae() {
+        let pairs = [(120, 120), (160, 120), (80, 200), (200, 200)];
+        let matrix = matrix(&pairs);
+        assert_eq!(matrix.exact_agreement(), 0.5);
+        assert_eq!(matrix.adjacent_agreement(), 0.75);
+        assert_eq!(matrix.counts[2][5], 1);
+        assert_eq!(mean_absolute_error(&pairs), 40.0);
+    }
+
+    #[test]
+    fn test_scores_snap_to_nearest_level() {
+        let matrix = ConfusionMatrix::new(200, 40);
+        assert_eq!(matrix.level_of(130), 3);
+        assert_eq!(matrix.level_of(150), 4);
+        assert_eq!(matrix.level_of(200), 5);
+    }
+}
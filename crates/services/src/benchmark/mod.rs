@@ -0,0 +1,309 @@
+//! Scoring benchmark against human grades
+//!
+//! Runs `EvaluationService` over a labelled corpus and compares its scores
+//! with the human ones for each criterion. Results are checked against a
+//! stored baseline so that a scorer change that grades worse fails loudly.
+
+mod corpus;
+mod metrics;
+
+pub use corpus::{load_corpus, parse_csv_corpus, LabeledEssay};
+pub use metrics::{mean_absolute_error, ConfusionMatrix};
+
+use anyhow::{Context, Result};
+use domain::essay::ExamType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::evaluation::EvaluationService;
+use crate::rubrics::get_rubric;
+
+/// Allowed drop in kappa and agreement, and rise in MAE as a share of the criterion maximum
+pub const DEFAULT_TOLERANCE: f64 = 0.02;
+
+/// Agreement on one criterion of one exam
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompetencyReport {
+    pub exam_type: ExamType,
+    pub criterion: String,
+    pub max_score: u16,
+    pub essays: usize,
+    pub qwk: f64,
+    pub mae: f64,
+    pub exact_agreement: f64,
+    pub adjacent_agreement: f64,
+    pub confusion: ConfusionMatrix,
+}
+
+impl CompetencyReport {
+    /// Baseline key, e.g. `ENEM/C1`
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.exam_type.display_name(), self.criterion)
+    }
+}
+
+/// (human, predicted) scores collected for one criterion
+struct CriterionPairs {
+    exam_type: ExamType,
+    criterion: String,
+    max_score: u16,
+    step: u16,
+    pairs: Vec<(u16, u16)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub essays: usize,
+    pub competencies: Vec<CompetencyReport>,
+}
+
+impl BenchmarkReport {
+    /// Compare human scores with predicted ones, criterion by criterion
+    ///
+    /// Criteria without a human score on an essay are left out for that essay.
+    pub fn from_scores(results: &[(LabeledEssay, HashMap<String, u16>)]) -> Self {
+        let mut grouped: BTreeMap<(String, usize), CriterionPairs> = BTreeMap::new();
+        for (labeled, predicted) in results {
+            let Some(rubric) = get_rubric(&labeled.exam_type) else {
+                tracing::warn!("No rubric for {:?}, skipping essay {}", labeled.exam_type, labeled.id);
+                continue;
+            };
+            for (order, criterion) in rubric.criteria.iter().enumerate() {
+                let Some(&human) = labeled.scores.get(&criterion.name) else {
+                    continue;
+                };
+                let prediction = predicted.get(&criterion.name).copied().unwrap_or(0);
+                grouped
+                    .entry((labeled.exam_type.display_name().to_string(), order))
+                    .or_insert_with(|| CriterionPairs {
+                        exam_type: labeled.exam_type.clone(),
+                        criterion: criterion.name.clone(),
+                        max_score: criterion.max_score,
+                        step: criterion.level_step(),
+                        pairs: Vec::new(),
+                    })
+                    .pairs
+                    .push((human, prediction));
+            }
+        }
+
+        let competencies = grouped
+            .into_values()
+            .map(|group| {
+                let mut confusion = ConfusionMatrix::new(group.max_score, group.step);
+                for &(human, predicted) in &group.pairs {
+                    confusion.add(human, predicted);
+                }
+                CompetencyReport {
+                    exam_type: group.exam_type,
+                    criterion: group.criterion,
+                    max_score: group.max_score,
+                    essays: group.pairs.len(),
+                    qwk: confusion.quadratic_weighted_kappa(),
+                    mae: mean_absolute_error(&group.pairs),
+                    exact_agreement: confusion.exact_agreement(),
+                    adjacent_agreement: confusion.adjacent_agreement(),
+                    confusion,
+                }
+            })
+            .collect();
+
+        Self {
+            essays: results.len(),
+            competencies,
+        }
+    }
+
+    /// Plain-text summary with one confusion matrix per criterion
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{} essays\n\n", self.essays);
+        let _ = writeln!(
+            out,
+            "{:<16} {:>6} {:>7} {:>8} {:>7} {:>9}",
+            "criterion", "essays", "QWK", "MAE", "exact", "adjacent"
+        );
+        for c in &self.competencies {
+            let _ = writeln!(
+                out,
+                "{:<16} {:>6} {:>7.3} {:>8.1} {:>6.1}% {:>8.1}%",
+                c.key(),
+                c.essays,
+                c.qwk,
+                c.mae,
+                c.exact_agreement * 100.0,
+                c.adjacent_agreement * 100.0
+            );
+        }
+        for c in &self.competencies {
+            let _ = writeln!(out, "\n{} (rows: human, columns: predicted)", c.key());
+            let header: String = c.confusion.levels.iter().map(|l| format!("{:>6}", l)).collect();
+            let _ = writeln!(out, "{:>6}{}", "", header);
+            for (level, row) in c.confusion.levels.iter().zip(&c.confusion.counts) {
+                let cells: String = row.iter().map(|n| format!("{:>6}", n)).collect();
+                let _ = writeln!(out, "{:>6}{}", level, cells);
+            }
+        }
+        out
+    }
+}
+
+/// Evaluate every essay of the corpus and compare with the human grades
+pub async fn run_benchmark(service: &EvaluationService, corpus: &[LabeledEssay]) -> Result<BenchmarkReport> {
+    let mut results = Vec::with_capacity(corpus.len());
+    for labeled in corpus {
+        let evaluated = service
+            .evaluate_essay(labeled.to_essay())
+            .await
+            .with_context(|| format!("Failed to evaluate essay {}", labeled.id))?;
+        let predicted = evaluated.rubric_scores.map(|r| r.scores).unwrap_or_default();
+        results.push((labeled.clone(), predicted));
+    }
+    Ok(BenchmarkReport::from_scores(&results))
+}
+
+/// Stored metrics of a criterion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineMetrics {
+    pub qwk: f64,
+    pub mae: f64,
+    pub exact_agreement: f64,
+    pub adjacent_agreement: f64,
+}
+
+/// Metrics a benchmark run must not fall below
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub tolerance: f64,
+    pub competencies: BTreeMap<String, BaselineMetrics>,
+}
+
+impl Baseline {
+    pub fn from_report(report: &BenchmarkReport, tolerance: f64) -> Self {
+        let competencies = report
+            .competencies
+            .iter()
+            .map(|c| {
+                (
+                    c.key(),
+                    BaselineMetrics {
+                        qwk: c.qwk,
+                        mae: c.mae,
+                        exact_agreement: c.exact_agreement,
+                        adjacent_agreement: c.adjacent_agreement,
+                    },
+                )
+            })
+            .collect();
+        Self { tolerance, competencies }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse baseline")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?).context("Failed to write baseline")
+    }
+
+    /// Every metric that got worse than the baseline by more than the tolerance
+    pub fn regressions(&self, report: &BenchmarkReport) -> Vec<String> {
+        let current: HashMap<String, &CompetencyReport> =
+            report.competencies.iter().map(|c| (c.key(), c)).collect();
+        let mut regressions = Vec::new();
+
+        for (key, base) in &self.competencies {
+            let Some(c) = current.get(key) else {
+                regressions.push(format!("{}: missing from this run", key));
+                continue;
+            };
+            let mut check = |name: &str, now: f64, before: f64, higher_is_better: bool, tolerance: f64| {
+                let worse = if higher_is_better {
+                    now < before - tolerance
+                } else {
+                    now > before + tolerance
+                };
+                if worse {
+                    regressions.push(format!("{} {}: {:.3} (baseline {:.3})", key, name, now, before));
+                }
+            };
+            check("QWK", c.qwk, base.qwk, true, self.tolerance);
+            check("MAE", c.mae, base.mae, false, self.tolerance * c.max_score as f64);
+            check("exact agreement", c.exact_agreement, base.exact_agreement, true, self.tolerance);
+            check("adjacent agreement", c.adjacent_agreement, base.adjacent_agreement, true, self.tolerance);
+        }
+        regressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labeled(id: &str, c1: u16, c2: u16) -> LabeledEssay {
+        LabeledEssay {
+            id: id.to_string(),
+            exam_type: ExamType::Enem,
+            title: String::new(),
+            content: "Texto.".to_string(),
+            scores: HashMap::from([("C1".to_string(), c1), ("C2".to_string(), c2)]),
+        }
+    }
+
+    fn report(predicted_c1: [u16; 3]) -> BenchmarkReport {
+        let humans = [labeled("a", 120, 160), labeled("b", 160, 80), labeled("c", 200, 120)];
+        let results: Vec<_> = humans
+            .into_iter()
+            .zip(predicted_c1)
+            .map(|(h, c1)| {
+                let c2 = h.scores["C2"];
+                (h, HashMap::from([("C1".to_string(), c1), ("C2".to_string(), c2)]))
+            })
+            .collect();
+        BenchmarkReport::from_scores(&results)
+    }
+
+    #[test]
+    fn test_report_per_criterion() {
+        let report = report([120, 160, 160]);
+        assert_eq!(report.essays, 3);
+        let keys: Vec<_> = report.competencies.iter().map(CompetencyReport::key).collect();
+        assert_eq!(keys, vec!["ENEM/C1", "ENEM/C2"]);
+
+        let c1 = &report.competencies[0];
+        assert!((c1.mae - 40.0 / 3.0).abs() < 1e-9);
+        assert!((c1.exact_agreement - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(c1.adjacent_agreement, 1.0);
+        assert_eq!(report.competencies[1].qwk, 1.0);
+        assert!(report.to_text().contains("ENEM/C1"));
+    }
+
+    #[tokio::test]
+    async fn test_heuristic_benchmark_runs_without_the_model() {
+        let mut essay = labeled("a", 120, 160);
+        essay.content = "A educação transforma a sociedade brasileira de maneira profunda. ".repeat(30);
+        let service = EvaluationService::heuristic_only().unwrap();
+
+        let report = run_benchmark(&service, &[essay]).await.unwrap();
+        assert_eq!(report.essays, 1);
+        assert_eq!(report.competencies.len(), 2);
+    }
+
+    #[test]
+    fn test_regressions_against_baseline() {
+        let baseline = Baseline::from_report(&report([120, 160, 200]), DEFAULT_TOLERANCE);
+        assert!(baseline.regressions(&report([120, 160, 200])).is_empty());
+
+        let regressions = baseline.regressions(&report([80, 160, 120]));
+        assert!(regressions.iter().any(|r| r.starts_with("ENEM/C1 QWK")));
+        assert!(regressions.iter().any(|r| r.starts_with("ENEM/C1 MAE")));
+        assert!(regressions.iter().all(|r| r.starts_with("ENEM/C1")));
+    }
+}
@@ -0,0 +1,68 @@
+//! Install an encoder model from a local package, without a network
+//!
+//! Usage: install_model <dir|package.zip|package.tar.gz>
+//!        install_model --create-manifest <dir> [--model <id>] [--revision <commit>]
+//!
+//! `--create-manifest` writes `manifest.json` for a directory holding the
+//! model files, to prepare a package on a machine that has them. The model
+//! defaults to BERTimbau Base.
+
+use anyhow::{bail, Context, Result};
+use services::{install_model, AIConfigManager, ModelManifest, MODEL_ID};
+use std::path::PathBuf;
+
+enum Command {
+    Install(PathBuf),
+    CreateManifest { dir: PathBuf, model: String, revision: Option<String> },
+}
+
+fn parse_args() -> Result<Command> {
+    let mut args = std::env::args().skip(1);
+    let mut source = None;
+    let mut manifest_dir = None;
+    let mut model = None;
+    let mut revision = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--create-manifest" => manifest_dir = Some(args.next().context("--create-manifest needs a directory")?.into()),
+            "--model" => model = Some(args.next().context("--model needs a model id")?),
+            "--revision" => revision = Some(args.next().context("--revision needs a commit")?),
+            other if other.starts_with("--") => bail!("Unknown option {}", other),
+            other => source = Some(PathBuf::from(other)),
+        }
+    }
+
+    match (manifest_dir, source) {
+        (Some(dir), None) => Ok(Command::CreateManifest {
+            dir,
+            model: model.unwrap_or_else(|| MODEL_ID.to_string()),
+            revision,
+        }),
+        (None, Some(source)) if revision.is_none() && model.is_none() => Ok(Command::Install(source)),
+        _ => bail!(
+            "Usage: install_model <dir|package.zip|package.tar.gz> | --create-manifest <dir> [--model <id>] [--revision <commit>]"
+        ),
+    }
+}
+
+fn main() -> Result<()> {
+    match parse_args()? {
+        Command::CreateManifest { dir, model, revision } => {
+            let manifest = ModelManifest::for_directory(&dir, &model, revision)?;
+            manifest.save(&dir)?;
+            println!("Wrote manifest for {} files in {}", manifest.files.len(), dir.display());
+        }
+        Command::Install(source) => {
+            let installed = install_model(&source, &AIConfigManager::get_default_cache_dir()?)?;
+            println!(
+                "Installed {} of {} ({}) into {}",
+                installed.files.join(", "),
+                installed.model_id,
+                installed.revision,
+                installed.snapshot_dir.display()
+            );
+        }
+    }
+    Ok(())
+}
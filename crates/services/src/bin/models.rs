@@ -0,0 +1,88 @@
+//! Manage the encoder models in the registry
+//!
+//! Usage: models list
+//!        models install <id> [--revision <rev>]
+//!        models switch <id> [--revision <rev>]
+//!        models remove <id>
+//!
+//! `switch` without `--revision` follows the model's default revision.
+
+use anyhow::{bail, Context, Result};
+use services::{CancelToken, ModelRegistry, ProgressCallback};
+use std::sync::Arc;
+
+enum Command {
+    List,
+    Install { model: String, revision: Option<String> },
+    Switch { model: String, revision: Option<String> },
+    Remove { model: String },
+}
+
+const USAGE: &str = "Usage: models list | install <id> [--revision <rev>] | switch <id> [--revision <rev>] | remove <id>";
+
+fn parse_args() -> Result<Command> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().context(USAGE)?;
+    let mut model = None;
+    let mut revision = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--revision" => revision = Some(args.next().context("--revision needs a value")?),
+            other if other.starts_with("--") => bail!("Unknown option {}", other),
+            other if model.is_none() => model = Some(other.to_string()),
+            other => bail!("Unexpected argument {}", other),
+        }
+    }
+
+    match (command.as_str(), model) {
+        ("list", None) if revision.is_none() => Ok(Command::List),
+        ("install", Some(model)) => Ok(Command::Install { model, revision }),
+        ("switch", Some(model)) => Ok(Command::Switch { model, revision }),
+        ("remove", Some(model)) if revision.is_none() => Ok(Command::Remove { model }),
+        _ => bail!(USAGE),
+    }
+}
+
+fn main() -> Result<()> {
+    let registry = ModelRegistry::open()?;
+    match parse_args()? {
+        Command::List => {
+            for model in registry.list()? {
+                let descriptor = &model.descriptor;
+                println!(
+                    "{} {} ({}; {} dims; {}; {})",
+                    if model.active { "*" } else { " " },
+                    descriptor.id,
+                    descriptor.name,
+                    descriptor.embedding_size,
+                    descriptor.languages.join(", "),
+                    descriptor.license
+                );
+                if !model.installed_revisions.is_empty() {
+                    println!(
+                        "    installed: {} ({:.1} MB)",
+                        model.installed_revisions.join(", "),
+                        model.size_bytes as f64 / 1_048_576.0
+                    );
+                }
+            }
+        }
+        Command::Install { model, revision } => {
+            let progress: ProgressCallback = Arc::new(|fraction, message| {
+                println!("[{:>3.0}%] {}", fraction * 100.0, message);
+            });
+            let snapshot = registry.install(&model, revision.as_deref(), &CancelToken::new(), Some(&progress))?;
+            println!("Installed {} into {}", model, snapshot.display());
+        }
+        Command::Switch { model, revision } => {
+            let active = registry.switch(&model, revision.as_deref())?;
+            println!("Active model: {} at {}", active.descriptor.id, active.revision);
+        }
+        Command::Remove { model } => {
+            registry.remove(&model)?;
+            println!("Removed {}", model);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! Compare quantized encoders with the F32 checkpoint
+//!
+//! Usage: quantization_benchmark <corpus.json|corpus.csv> [--levels f16,q8,q4]
+//!
+//! For each level reports file size, memory added by loading the weights,
+//! per-essay latency and the cosine drift of the `[CLS]` embeddings from the
+//! F32 ones. Competency scores are not compared: they come from the text
+//! heuristics and do not move with the weights yet.
+//!
+//! The active model of the registry is benchmarked at its pinned revision,
+//! downloaded when it is not in the Hub cache. Its weights are read as
+//! safetensors, converting a PyTorch checkpoint once, and missing quantized
+//! files are converted into the model cache first.
+
+use anyhow::{bail, Context, Result};
+use candle_core::Device;
+use hf_hub::{Cache, Repo, RepoType};
+use services::bench::{embedding_drift, resident_memory_bytes, time_encoder, EncoderTiming};
+use services::{
+    load_corpus, prepare_weights, quantize_checkpoint, snapshot_commit, AIConfigManager, ActiveModel, CancelToken,
+    CandleBackend, EncoderBackend, ModelRegistry, QuantizationLevel, QuantizedBackend,
+};
+use std::path::{Path, PathBuf};
+
+struct Args {
+    corpus: PathBuf,
+    levels: Vec<QuantizationLevel>,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = std::env::args().skip(1);
+    let mut corpus = None;
+    let mut levels = vec![QuantizationLevel::F16, QuantizationLevel::Q8, QuantizationLevel::Q4];
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--levels" => {
+                levels = args
+                    .next()
+                    .context("--levels needs a list")?
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<_>>()?
+            }
+            other if other.starts_with("--") => bail!("Unknown option {}", other),
+            other => corpus = Some(PathBuf::from(other)),
+        }
+    }
+
+    Ok(Args {
+        corpus: corpus.context("Usage: quantization_benchmark <corpus.json|corpus.csv> [--levels f16,q8,q4]")?,
+        levels,
+    })
+}
+
+struct LevelResult {
+    level: QuantizationLevel,
+    file_bytes: u64,
+    memory_bytes: Option<u64>,
+    timing: EncoderTiming,
+}
+
+/// Load an encoder and measure how much resident memory it added
+fn load_measured<T>(load: impl FnOnce() -> Result<T>) -> Result<(T, Option<u64>)> {
+    let before = resident_memory_bytes();
+    let encoder = load()?;
+    let memory = before.zip(resident_memory_bytes()).map(|(b, a)| a.saturating_sub(b));
+    Ok((encoder, memory))
+}
+
+fn run_level(
+    level: QuantizationLevel,
+    encoder: &dyn EncoderBackend,
+    file_bytes: u64,
+    memory_bytes: Option<u64>,
+    texts: &[String],
+) -> Result<LevelResult> {
+    let timing = time_encoder(encoder, texts)?;
+    Ok(LevelResult { level, file_bytes, memory_bytes, timing })
+}
+
+fn megabytes(bytes: Option<u64>) -> String {
+    bytes.map_or("n/a".to_string(), |b| format!("{:.1}", b as f64 / 1_048_576.0))
+}
+
+fn print_row(reference: &LevelResult, result: &LevelResult) {
+    let drift = embedding_drift(&reference.timing.embeddings, &result.timing.embeddings);
+    println!(
+        "{:<5} {:>9} {:>9} {:>9.1} {:>9.1} {:>8.4} {:>8.4} {:>10.2e}",
+        result.level.as_str(),
+        megabytes(Some(result.file_bytes)),
+        megabytes(result.memory_bytes),
+        result.timing.mean_ms,
+        result.timing.p95_ms,
+        drift.mean_cosine,
+        drift.min_cosine,
+        drift.max_abs_diff,
+    );
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    let corpus = load_corpus(&args.corpus)?;
+    if corpus.is_empty() {
+        bail!("Corpus {} has no essays", args.corpus.display());
+    }
+
+    let device = Device::Cpu;
+    let registry = ModelRegistry::open()?;
+    let model = registry.active()?;
+    let snapshot_dir = model_snapshot(&registry, &model)?;
+    let config_path = snapshot_dir.join("config.json");
+    let tokenizer_path = snapshot_dir.join("tokenizer.json");
+    let commit = snapshot_commit(&config_path).context("Model files are not in a cache snapshot")?;
+    let converted_path = AIConfigManager::get_converted_weights_path(&model.descriptor.id, &commit)?;
+    let weights_path = prepare_weights(&snapshot_dir, &converted_path, &CancelToken::new(), None)?.path;
+    println!("{} at {} ({})", model.descriptor.id, commit, weights_path.display());
+    let texts: Vec<String> = corpus.iter().map(|e| format!("{} <SEP> {}", e.title, e.content)).collect();
+
+    let (reference, memory) = load_measured(|| CandleBackend::load(&config_path, &tokenizer_path, &weights_path, &device))?;
+    let file_bytes = std::fs::metadata(&weights_path)?.len();
+    let reference = run_level(QuantizationLevel::F32, &reference, file_bytes, memory, &texts)?;
+
+    let mut results = Vec::with_capacity(args.levels.len());
+    for level in args.levels.into_iter().filter(|&l| l != QuantizationLevel::F32) {
+        let path = quantized_path(&model.descriptor.id, &commit, level, &config_path, &weights_path)?;
+        let (encoder, memory) = load_measured(|| QuantizedBackend::load(&path, &tokenizer_path, &device))?;
+        let file_bytes = std::fs::metadata(&path)?.len();
+        results.push(run_level(level, &encoder, file_bytes, memory, &texts)?);
+    }
+
+    println!("{} essays", corpus.len());
+    println!(
+        "{:<5} {:>9} {:>9} {:>9} {:>9} {:>8} {:>8} {:>10}",
+        "level", "file MB", "mem MB", "mean ms", "p95 ms", "cos avg", "cos min", "max |Δ|"
+    );
+    print_row(&reference, &reference);
+    for result in &results {
+        print_row(&reference, result);
+    }
+    Ok(())
+}
+
+/// Snapshot directory of the active model, downloading it when the cache lacks any file
+fn model_snapshot(registry: &ModelRegistry, model: &ActiveModel) -> Result<PathBuf> {
+    let cache = Cache::new(AIConfigManager::get_default_cache_dir()?);
+    let repo = cache.repo(Repo::with_revision(model.descriptor.id.clone(), RepoType::Model, model.revision.clone()));
+    let files = model.descriptor.required_files();
+    if let Some(config_path) = repo.get("config.json") {
+        if files.iter().all(|file| repo.get(file).is_some()) {
+            return config_path.parent().map(Path::to_path_buf).context("Invalid cache layout");
+        }
+    }
+
+    println!("Downloading {} at {}...", model.descriptor.id, model.revision);
+    registry.install(&model.descriptor.id, Some(&model.revision), &CancelToken::new(), None)
+}
+
+/// Cached quantized weights of `level`, converting the checkpoint when missing
+fn quantized_path(
+    model_id: &str,
+    commit: &str,
+    level: QuantizationLevel,
+    config_path: &Path,
+    weights_path: &Path,
+) -> Result<PathBuf> {
+    let path = AIConfigManager::get_quantized_model_path(model_id, commit, level)?;
+    if !path.exists() {
+        println!("Quantizing to {}...", level.as_str());
+        quantize_checkpoint(config_path, weights_path, &path, level)?;
+    }
+    Ok(path)
+}
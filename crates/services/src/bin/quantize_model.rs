@@ -0,0 +1,91 @@
+//! Convert the active encoder checkpoint to quantized weights in the model cache
+//!
+//! Usage: quantize_model [--level f16|q8|q4] [--config <config.json>]
+//!        [--weights <pytorch_model.bin>] [--output <model.gguf>] [--select]
+//!
+//! Without `--config` and `--weights` the files of the active model are
+//! fetched from the Hub cache. The output defaults to the cache entry of the
+//! weights' snapshot commit, so `--output` is needed for weights from
+//! elsewhere. `--select` makes the level the one the app loads.
+
+use anyhow::{bail, Context, Result};
+use hf_hub::{api::sync::Api, Repo, RepoType};
+use services::{quantize_checkpoint, snapshot_commit, AIConfigManager, ModelRegistry, QuantizationLevel};
+use std::path::PathBuf;
+
+struct Args {
+    level: QuantizationLevel,
+    config: Option<PathBuf>,
+    weights: Option<PathBuf>,
+    output: Option<PathBuf>,
+    select: bool,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = Args {
+        level: QuantizationLevel::Q8,
+        config: None,
+        weights: None,
+        output: None,
+        select: false,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--level" => parsed.level = args.next().context("--level needs a value")?.parse()?,
+            "--config" => parsed.config = Some(args.next().context("--config needs a path")?.into()),
+            "--weights" => parsed.weights = Some(args.next().context("--weights needs a path")?.into()),
+            "--output" => parsed.output = Some(args.next().context("--output needs a path")?.into()),
+            "--select" => parsed.select = true,
+            other => bail!("Unknown option {}", other),
+        }
+    }
+    Ok(parsed)
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    if args.level == QuantizationLevel::F32 {
+        bail!("F32 is the original checkpoint; pick f16, q8 or q4");
+    }
+
+    let model = ModelRegistry::open()?.active()?;
+    let (config, weights) = match (args.config, args.weights) {
+        (Some(config), Some(weights)) => (config, weights),
+        (None, None) => {
+            let repo = Repo::with_revision(model.descriptor.id.clone(), RepoType::Model, model.revision.clone());
+            let repo = Api::new()?.repo(repo);
+            (repo.get("config.json")?, repo.get(model.descriptor.weight_format.file_name())?)
+        }
+        _ => bail!("--config and --weights must be given together"),
+    };
+    if args.select && args.output.is_some() {
+        bail!("--select loads from the model cache and cannot be combined with --output");
+    }
+    let output = match args.output {
+        Some(output) => output,
+        None => {
+            let commit = snapshot_commit(&weights).context("--output is needed for weights outside the model cache")?;
+            AIConfigManager::get_quantized_model_path(&model.descriptor.id, &commit, args.level)?
+        }
+    };
+
+    let summary = quantize_checkpoint(&config, &weights, &output, args.level)?;
+    println!(
+        "Wrote {} ({} tensors, {} quantized, {:.1} MB)",
+        output.display(),
+        summary.tensors,
+        summary.quantized,
+        summary.bytes as f64 / 1_048_576.0
+    );
+
+    if args.select {
+        let manager = AIConfigManager::new()?;
+        let mut ai_config = manager.load()?;
+        ai_config.quantization = args.level;
+        manager.save(&ai_config)?;
+        println!("Quantization level set to {}", args.level.as_str());
+    }
+    Ok(())
+}
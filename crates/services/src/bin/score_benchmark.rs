@@ -0,0 +1,103 @@
+//! Grade a labelled corpus and compare with the human scores
+//!
+//! Usage: score_benchmark <corpus.json|corpus.csv> [--baseline <path>]
+//!        [--update-baseline] [--tolerance <value>] [--json <path>] [--heuristic]
+//!
+//! `--heuristic` scores from the text heuristics alone, without downloading
+//! or loading the model.
+//!
+//! Exits with status 1 when a metric regresses past the baseline.
+
+use anyhow::{bail, Context, Result};
+use services::{load_corpus, run_benchmark, Baseline, EvaluationService, DEFAULT_TOLERANCE};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const DEFAULT_BASELINE: &str = "benchmarks/baseline.json";
+
+struct Args {
+    corpus: PathBuf,
+    baseline: PathBuf,
+    update_baseline: bool,
+    tolerance: f64,
+    json: Option<PathBuf>,
+    heuristic: bool,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = std::env::args().skip(1);
+    let mut corpus = None;
+    let mut baseline = PathBuf::from(DEFAULT_BASELINE);
+    let mut update_baseline = false;
+    let mut tolerance = DEFAULT_TOLERANCE;
+    let mut json = None;
+    let mut heuristic = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => baseline = args.next().context("--baseline needs a path")?.into(),
+            "--update-baseline" => update_baseline = true,
+            "--tolerance" => {
+                tolerance = args
+                    .next()
+                    .context("--tolerance needs a value")?
+                    .parse()
+                    .context("Invalid tolerance")?
+            }
+            "--json" => json = Some(args.next().context("--json needs a path")?.into()),
+            "--heuristic" => heuristic = true,
+            other if other.starts_with("--") => bail!("Unknown option {}", other),
+            other => corpus = Some(PathBuf::from(other)),
+        }
+    }
+
+    Ok(Args {
+        corpus: corpus.context("Usage: score_benchmark <corpus.json|corpus.csv> [--baseline <path>] [--update-baseline] [--tolerance <value>] [--json <path>] [--heuristic]")?,
+        baseline,
+        update_baseline,
+        tolerance,
+        json,
+        heuristic,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    let args = parse_args()?;
+    let corpus = load_corpus(&args.corpus)?;
+    let service = if args.heuristic {
+        EvaluationService::heuristic_only()?
+    } else {
+        EvaluationService::new()?
+    };
+    let report = run_benchmark(&service, &corpus).await?;
+
+    println!("{}", report.to_text());
+    if let Some(path) = &args.json {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    if args.update_baseline {
+        Baseline::from_report(&report, args.tolerance).save(&args.baseline)?;
+        println!("Baseline written to {}", args.baseline.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if !args.baseline.exists() {
+        println!("No baseline at {}; run with --update-baseline to create one", args.baseline.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let regressions = Baseline::load(&args.baseline)?.regressions(&report);
+    if regressions.is_empty() {
+        println!("No regressions against {}", args.baseline.display());
+        Ok(ExitCode::SUCCESS)
+    } else {
+        eprintln!("Regressions against {}:", args.baseline.display());
+        for regression in &regressions {
+            eprintln!("  {}", regression);
+        }
+        Ok(ExitCode::FAILURE)
+    }
+}
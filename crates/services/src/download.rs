@@ -0,0 +1,359 @@
+//! Model download manager
+//!
+//! Files are fetched from the Hub (or any server with the same URL layout)
+//! into `<name>.part`, resumed with an HTTP range request after an
+//! interruption, and checked against the checksum the repository publishes
+//! for each file before they are moved into the cache snapshot: the SHA-256
+//! of files stored with Git LFS, the git blob id of the others. Every file
+//! is fetched from the commit the revision pointed at when the download
+//! started. Progress is reported in bytes across all files of a download.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::ai::ProgressCallback;
+use crate::ai_config::DownloadPreferences;
+use crate::inference::CancelToken;
+use crate::provenance::{git_blob_id_file, sha256_file};
+
+pub const DEFAULT_ENDPOINT: &str = "https://huggingface.co";
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Bytes between two progress reports
+const REPORT_EVERY: u64 = 512 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadError {
+    #[error("Download of {0} was cancelled")]
+    Cancelled(String),
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch { file: String, expected: String, actual: String },
+    #[error("{file} is {actual} bytes, expected {expected}")]
+    SizeMismatch { file: String, expected: u64, actual: u64 },
+}
+
+/// A file of the repository as the Hub describes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFile {
+    pub name: String,
+    pub size: Option<u64>,
+    /// Published for files stored with Git LFS, which includes the weights
+    pub sha256: Option<String>,
+    /// Git blob id of files stored in the repository itself; for LFS files
+    /// the Hub reports the id of the pointer instead, so it is left out
+    pub blob_id: Option<String>,
+}
+
+/// Files of one repository commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteSnapshot {
+    pub commit: String,
+    pub files: Vec<RemoteFile>,
+}
+
+impl RemoteSnapshot {
+    pub fn file(&self, name: &str) -> Option<&RemoteFile> {
+        self.files.iter().find(|file| file.name == name)
+    }
+}
+
+#[derive(Deserialize)]
+struct RevisionInfo {
+    sha: String,
+    #[serde(default)]
+    siblings: Vec<Sibling>,
+}
+
+#[derive(Deserialize)]
+struct Sibling {
+    rfilename: String,
+    #[serde(default, rename = "blobId")]
+    blob_id: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    lfs: Option<LfsInfo>,
+}
+
+#[derive(Deserialize)]
+struct LfsInfo {
+    sha256: String,
+    size: u64,
+}
+
+pub struct DownloadManager {
+    endpoint: String,
+    model_id: String,
+    revision: String,
+    token: Option<String>,
+    preferences: DownloadPreferences,
+    agent: ureq::Agent,
+}
+
+impl DownloadManager {
+    /// Manager for the `main` branch of `model_id`, honouring `HF_ENDPOINT`
+    pub fn new(model_id: &str, preferences: DownloadPreferences) -> Self {
+        let endpoint = std::env::var("HF_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string());
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            model_id: model_id.to_string(),
+            revision: "main".to_string(),
+            token: None,
+            preferences,
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(10))
+                .timeout_read(Duration::from_secs(30))
+                .build(),
+        }
+    }
+
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_revision(mut self, revision: &str) -> Self {
+        self.revision = revision.to_string();
+        self
+    }
+
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    fn request(&self, url: &str) -> ureq::Request {
+        let request = self.agent.get(url).set("Accept-Encoding", "identity");
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+
+    /// Commit, sizes and checksums of the repository files at the revision
+    pub fn snapshot(&self) -> Result<RemoteSnapshot> {
+        let url = format!("{}/api/models/{}/revision/{}?blobs=true", self.endpoint, self.model_id, self.revision);
+        let response = self
+            .request(&url)
+            .call()
+            .with_context(|| format!("Failed to fetch file list of {}", self.model_id))?;
+        let info: RevisionInfo = serde_json::from_reader(response.into_reader())
+            .context("Invalid repository metadata")?;
+
+        Ok(RemoteSnapshot {
+            commit: info.sha,
+            files: info
+                .siblings
+                .into_iter()
+                .map(|sibling| RemoteFile {
+                    name: sibling.rfilename,
+                    size: sibling.lfs.as_ref().map(|lfs| lfs.size).or(sibling.size),
+                    blob_id: sibling.blob_id.filter(|_| sibling.lfs.is_none()),
+                    sha256: sibling.lfs.map(|lfs| lfs.sha256),
+                })
+                .collect(),
+        })
+    }
+
+    /// Download `names` into `snapshots/<commit>/` of the repository cache
+    /// directory and point `refs/<revision>` at the commit
+    ///
+    /// Files already in the snapshot are kept. `progress` receives the
+    /// fraction of bytes done across all files.
+    pub fn download_snapshot(
+        &self,
+        repo_dir: &Path,
+        names: &[&str],
+        cancel: &CancelToken,
+        progress: Option<&ProgressCallback>,
+    ) -> Result<PathBuf> {
+        let snapshot = self.snapshot()?;
+        let snapshot_dir = repo_dir.join("snapshots").join(&snapshot.commit);
+        fs::create_dir_all(&snapshot_dir)?;
+
+        let files = names
+            .iter()
+            .map(|name| snapshot.file(name).with_context(|| format!("{} has no file {}", self.model_id, name)))
+            .collect::<Result<Vec<_>>>()?;
+        let missing: Vec<&RemoteFile> = files.into_iter().filter(|file| !snapshot_dir.join(&file.name).exists()).collect();
+        let total: u64 = missing.iter().filter_map(|file| file.size).sum();
+
+        let mut done_before = 0;
+        for file in missing {
+            let placement = ByteProgress { callback: progress, done_before, total };
+            self.download_into(&snapshot.commit, file, &snapshot_dir.join(&file.name), cancel, placement)?;
+            done_before += file.size.unwrap_or(0);
+        }
+
+        let refs = repo_dir.join("refs");
+        fs::create_dir_all(&refs)?;
+        fs::write(refs.join(&self.revision), &snapshot.commit)?;
+        Ok(snapshot_dir)
+    }
+
+    /// Download one file of `commit` to `dest`, resuming from `dest.part` when allowed
+    pub fn download(
+        &self,
+        commit: &str,
+        file: &RemoteFile,
+        dest: &Path,
+        cancel: &CancelToken,
+        progress: Option<&ProgressCallback>,
+    ) -> Result<()> {
+        let progress = ByteProgress { callback: progress, done_before: 0, total: file.size.unwrap_or(0) };
+        self.download_into(commit, file, dest, cancel, progress)
+    }
+
+    fn download_into(
+        &self,
+        commit: &str,
+        file: &RemoteFile,
+        dest: &Path,
+        cancel: &CancelToken,
+        progress: ByteProgress,
+    ) -> Result<()> {
+        let partial = partial_path(dest);
+        let mut offset = match fs::metadata(&partial) {
+            Ok(metadata) if self.preferences.resume_on_interrupt => metadata.len(),
+            _ => 0,
+        };
+        if file.size.is_some_and(|size| offset > size) {
+            offset = 0;
+        }
+
+        if file.size != Some(offset) || offset == 0 {
+            self.fetch(commit, file, &partial, offset, cancel, progress)?;
+        }
+        self.verify(file, &partial)?;
+        fs::rename(&partial, dest)
+            .with_context(|| format!("Failed to move {} into the cache", file.name))?;
+        Ok(())
+    }
+
+    fn fetch(
+        &self,
+        commit: &str,
+        file: &RemoteFile,
+        partial: &Path,
+        mut offset: u64,
+        cancel: &CancelToken,
+        progress: ByteProgress,
+    ) -> Result<()> {
+        // By commit, not branch: a push while downloading must not mix two revisions
+        let url = format!("{}/{}/resolve/{}/{}", self.endpoint, self.model_id, commit, file.name);
+        let mut request = self.request(&url);
+        if offset > 0 {
+            request = request.set("Range", &format!("bytes={}-", offset));
+        }
+
+        let response = match request.call() {
+            Ok(response) => response,
+            // The part file already holds everything the server has
+            Err(ureq::Error::Status(416, _)) if offset > 0 => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to download {}", file.name)),
+        };
+
+        let mut output = if offset > 0 && response.status() == 206 {
+            OpenOptions::new().append(true).open(partial)?
+        } else {
+            // The server ignored the range: start over
+            offset = 0;
+            File::create(partial)?
+        };
+
+        let mut reader = response.into_reader();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut last_report = offset;
+        progress.report(file, offset);
+        loop {
+            if cancel.is_cancelled() {
+                output.flush()?;
+                return Err(DownloadError::Cancelled(file.name.clone()).into());
+            }
+            let read = reader
+                .read(&mut buffer)
+                .with_context(|| format!("Download of {} was interrupted", file.name))?;
+            if read == 0 {
+                break;
+            }
+            output.write_all(&buffer[..read])?;
+            offset += read as u64;
+            if offset - last_report >= REPORT_EVERY {
+                progress.report(file, offset);
+                last_report = offset;
+            }
+        }
+        output.flush()?;
+        progress.report(file, offset);
+        Ok(())
+    }
+
+    fn verify(&self, file: &RemoteFile, partial: &Path) -> Result<()> {
+        let actual_size = fs::metadata(partial)?.len();
+        if let Some(expected) = file.size {
+            if actual_size != expected {
+                // Resuming would append to bytes that are already wrong
+                let _ = fs::remove_file(partial);
+                bail!(DownloadError::SizeMismatch { file: file.name.clone(), expected, actual: actual_size });
+            }
+        }
+
+        if !self.preferences.verify_integrity {
+            return Ok(());
+        }
+        let (expected, actual) = match (&file.sha256, &file.blob_id) {
+            (Some(expected), _) => (expected, sha256_file(partial)?),
+            (None, Some(expected)) => (expected, git_blob_id_file(partial)?),
+            (None, None) => {
+                tracing::debug!("{} has no published checksum; checked its size only", file.name);
+                return Ok(());
+            }
+        };
+        if !actual.eq_ignore_ascii_case(expected) {
+            // A corrupt part file must not be resumed
+            let _ = fs::remove_file(partial);
+            bail!(DownloadError::ChecksumMismatch { file: file.name.clone(), expected: expected.clone(), actual });
+        }
+        Ok(())
+    }
+}
+
+/// Where one file's bytes fall within the whole download
+#[derive(Clone, Copy)]
+struct ByteProgress<'a> {
+    callback: Option<&'a ProgressCallback>,
+    done_before: u64,
+    total: u64,
+}
+
+impl ByteProgress<'_> {
+    fn report(&self, file: &RemoteFile, done: u64) {
+        let callback = match self.callback {
+            Some(callback) => callback,
+            None => return,
+        };
+        let fraction = if self.total > 0 {
+            (self.done_before + done) as f32 / self.total as f32
+        } else {
+            0.0
+        };
+        let message = match file.size {
+            Some(size) => format!("Downloading {}: {} / {}", file.name, megabytes(done), megabytes(size)),
+            None => format!("Downloading {}: {}", file.name, megabytes(done)),
+        };
+        callback(fraction.min(1.0), message);
+    }
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1_048_576.0)
+}
@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use domain::draft::EssayDraft;
+use domain::essay::ExamType;
+use domain::writing_process::{TextEdit, WritingRecording};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum JournalOp {
+    Title { edit: TextEdit },
+    Content { edit: TextEdit },
+    ExamType { exam_type: ExamType },
+    /// Starts (`Some`) or stops (`None`) recording the writing process
+    Recording { recording_id: Option<Uuid> },
+}
+
+/// One line of the append-only draft journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub op: JournalOp,
+}
+
+impl JournalEntry {
+    fn apply(&self, draft: &mut EssayDraft) {
+        match &self.op {
+            JournalOp::Title { edit } => edit.apply(&mut draft.title),
+            JournalOp::Content { edit } => {
+                edit.apply(&mut draft.content);
+                if let Some(recording) = draft.recording.as_mut() {
+                    let elapsed = recording.elapsed_ms(self.recorded_at);
+                    recording.push(elapsed, edit.clone());
+                }
+            }
+            JournalOp::ExamType { exam_type } => draft.exam_type = exam_type.clone(),
+            JournalOp::Recording { recording_id } => {
+                draft.recording = recording_id.map(|id| {
+                    let mut recording = WritingRecording {
+                        id,
+                        started_at: self.recorded_at,
+                        ..WritingRecording::new(draft.user_id)
+                    };
+                    // The text already written is the first event
+                    if let Some(edit) = TextEdit::between("", &draft.content) {
+                        recording.push(0, edit);
+                    }
+                    recording
+                });
+            }
+        }
+        draft.sequence = self.seq;
+        draft.updated_at = self.recorded_at;
+    }
+}
+
+/// Crash-safe storage for essay drafts
+///
+/// Every draft has a checkpoint file (`<id>.json`) and a journal (`<id>.journal`)
+/// with one JSON edit per line. Edits are appended as the student types and the
+/// checkpoint is rewritten periodically, which truncates the journal. Recovery
+/// replays the journal entries newer than the checkpoint, ignoring a torn last line.
+///
+/// A writing recording lives in the draft and is fed from the journaled content
+/// edits, so it survives a crash too; its events have the journal's granularity.
+pub struct DraftStore {
+    dir: PathBuf,
+}
+
+impl DraftStore {
+    /// Create a store in the platform data directory
+    pub fn new() -> Result<Self> {
+        let dir = dirs::data_local_dir()
+            .context("Failed to get data directory")?
+            .join("neuronexus")
+            .join("drafts");
+        Self::with_dir(dir)
+    }
+
+    /// Create a store in a specific directory
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).context("Failed to create drafts directory")?;
+        Ok(Self { dir })
+    }
+
+    fn checkpoint_path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn journal_path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.journal", id))
+    }
+
+    /// Journal the changes between `draft` and the new editor state, updating `draft` in place
+    pub fn record_edit(
+        &self,
+        draft: &mut EssayDraft,
+        title: &str,
+        content: &str,
+        exam_type: &ExamType,
+    ) -> Result<()> {
+        let mut ops = Vec::new();
+        if let Some(edit) = TextEdit::between(&draft.title, title) {
+            ops.push(JournalOp::Title { edit });
+        }
+        if let Some(edit) = TextEdit::between(&draft.content, content) {
+            ops.push(JournalOp::Content { edit });
+        }
+        if &draft.exam_type != exam_type {
+            ops.push(JournalOp::ExamType { exam_type: exam_type.clone() });
+        }
+        self.append(draft, ops)
+    }
+
+    /// Start or stop recording the writing process of `draft`
+    pub fn set_recording(&self, draft: &mut EssayDraft, enabled: bool) -> Result<()> {
+        if draft.recording.is_some() == enabled {
+            return Ok(());
+        }
+        let recording_id = enabled.then(Uuid::new_v4);
+        self.append(draft, vec![JournalOp::Recording { recording_id }])
+    }
+
+    /// Append `ops` to the journal and apply them to `draft`
+    fn append(&self, draft: &mut EssayDraft, ops: Vec<JournalOp>) -> Result<()> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        if !self.checkpoint_path(draft.id).exists() {
+            self.checkpoint(draft)?;
+        }
+
+        let mut journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.journal_path(draft.id))
+            .context("Failed to open draft journal")?;
+
+        let now = Utc::now();
+        let mut lines = String::new();
+        let mut entries = Vec::with_capacity(ops.len());
+        for op in ops {
+            let entry = JournalEntry {
+                seq: draft.sequence + entries.len() as u64 + 1,
+                recorded_at: now,
+                op,
+            };
+            lines.push_str(&serde_json::to_string(&entry)?);
+            lines.push('\n');
+            entries.push(entry);
+        }
+        journal.write_all(lines.as_bytes()).context("Failed to write draft journal")?;
+        journal.sync_data().context("Failed to sync draft journal")?;
+
+        for entry in &entries {
+            entry.apply(draft);
+        }
+        Ok(())
+    }
+
+    /// Persist the full draft and truncate the journal
+    pub fn checkpoint(&self, draft: &EssayDraft) -> Result<()> {
+        let path = self.checkpoint_path(draft.id);
+        let tmp_path = path.with_extension("json.tmp");
+        {
+            let mut file = File::create(&tmp_path).context("Failed to create draft checkpoint")?;
+            file.write_all(serde_json::to_string_pretty(draft)?.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path).context("Failed to replace draft checkpoint")?;
+
+        // Entries up to `draft.sequence` are now in the checkpoint
+        File::create(self.journal_path(draft.id)).context("Failed to truncate draft journal")?;
+        Ok(())
+    }
+
+    /// Rebuild a draft from its checkpoint and journal
+    pub fn recover(&self, id: Uuid) -> Result<Option<EssayDraft>> {
+        let path = self.checkpoint_path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path).context("Failed to read draft checkpoint")?;
+        let mut draft: EssayDraft =
+            serde_json::from_str(&content).context("Failed to parse draft checkpoint")?;
+
+        if let Ok(journal) = File::open(self.journal_path(id)) {
+            for line in BufReader::new(journal).lines() {
+                let Ok(line) = line else { break };
+                // A crash mid-write leaves a partial last line
+                let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else { break };
+                if entry.seq > draft.sequence {
+                    entry.apply(&mut draft);
+                }
+            }
+        }
+
+        Ok(Some(draft))
+    }
+
+    /// Recoverable drafts of a user, most recently edited first
+    pub fn list_by_user(&self, user_id: Uuid) -> Result<Vec<EssayDraft>> {
+        let mut drafts = Vec::new();
+        for entry in fs::read_dir(&self.dir)?.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(id) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| Uuid::parse_str(s).ok())
+            else {
+                continue;
+            };
+            if let Ok(Some(draft)) = self.recover(id) {
+                if draft.user_id == user_id && !draft.is_empty() {
+                    drafts.push(draft);
+                }
+            }
+        }
+        drafts.sort_by_key(|d| std::cmp::Reverse(d.updated_at));
+        Ok(drafts)
+    }
+
+    /// Remove a draft, e.g. once it has been saved as an essay
+    pub fn discard(&self, id: Uuid) -> Result<()> {
+        for path in [self.checkpoint_path(id), self.journal_path(id)] {
+            if path.exists() {
+                fs::remove_file(&path).context("Failed to remove draft file")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Store in a directory removed when the returned guard drops
+    fn temp_store() -> (tempfile::TempDir, DraftStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DraftStore::with_dir(dir.path().join("drafts")).unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn test_text_edit_roundtrip() {
+        let old = "A educação é fundamental.";
+        let new = "A educação pública é fundamental!";
+        let edit = TextEdit::between(old, new).unwrap();
+        let mut text = old.to_string();
+        edit.apply(&mut text);
+        assert_eq!(text, new);
+        assert!(TextEdit::between(new, new).is_none());
+    }
+
+    #[test]
+    fn test_recover_replays_journal() {
+        let (_dir, store) = temp_store();
+        let mut draft = EssayDraft::new(Uuid::new_v4(), ExamType::Enem);
+
+        store.record_edit(&mut draft, "Título", "Primeiro parágrafo.", &ExamType::Enem).unwrap();
+        store.checkpoint(&draft).unwrap();
+        store.record_edit(&mut draft, "Título", "Primeiro parágrafo. Segundo", &ExamType::Fuvest).unwrap();
+
+        // Simulate a crash in the middle of a journal write
+        let mut journal = OpenOptions::new().append(true).open(store.journal_path(draft.id)).unwrap();
+        journal.write_all(b"{\"seq\":99,\"field\":\"con").unwrap();
+
+        let recovered = store.recover(draft.id).unwrap().unwrap();
+        assert_eq!(recovered.content, "Primeiro parágrafo. Segundo");
+        assert_eq!(recovered.exam_type, ExamType::Fuvest);
+        assert_eq!(recovered.sequence, draft.sequence);
+    }
+
+    #[test]
+    fn test_recover_keeps_writing_recording() {
+        let (_dir, store) = temp_store();
+        let mut draft = EssayDraft::new(Uuid::new_v4(), ExamType::Enem);
+
+        store.record_edit(&mut draft, "Título", "Antes da gravação.", &ExamType::Enem).unwrap();
+        store.set_recording(&mut draft, true).unwrap();
+        store.checkpoint(&draft).unwrap();
+        store.record_edit(&mut draft, "Título", "Antes da gravação. Depois dela.", &ExamType::Enem).unwrap();
+
+        let recovered = store.recover(draft.id).unwrap().unwrap();
+        let recording = recovered.recording.expect("recording should survive recovery");
+        assert_eq!(Some(&recording), draft.recording.as_ref());
+        assert_eq!(recording.final_text(), "Antes da gravação. Depois dela.");
+
+        store.set_recording(&mut draft, false).unwrap();
+        assert!(store.recover(draft.id).unwrap().unwrap().recording.is_none());
+    }
+
+    #[test]
+    fn test_discard_removes_draft() {
+        let (_dir, store) = temp_store();
+        let user_id = Uuid::new_v4();
+        let mut draft = EssayDraft::new(user_id, ExamType::Enem);
+        store.record_edit(&mut draft, "Rascunho", "Texto", &ExamType::Enem).unwrap();
+
+        assert_eq!(store.list_by_user(user_id).unwrap().len(), 1);
+        store.discard(draft.id).unwrap();
+        assert!(store.list_by_user(user_id).unwrap().is_empty());
+    }
+}
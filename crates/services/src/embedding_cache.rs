@@ -0,0 +1,262 @@
+//! On-disk cache of text embeddings
+//!
+//! Embeddings are keyed by the model that produced them, a hash of the text
+//! and the pooling strategy, so re-opening an evaluated essay or searching
+//! questions again skips the forward pass. Each entry is one file of
+//! little-endian `f32` values; the file's modification time records when it
+//! was last used, and the least recently used entries are evicted once the
+//! cache grows past its size limit.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::inference::Pooling;
+
+const ENTRY_EXTENSION: &str = "emb";
+
+/// Identity of a cached embedding
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmbeddingKey(String);
+
+impl EmbeddingKey {
+    /// `model` names the weights, e.g. `<model id>@<revision>/<precision>`;
+    /// embeddings of other weights are never reused
+    pub fn new(model: &str, text: &str, pooling: Pooling) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0]);
+        hasher.update(pooling.as_str().as_bytes());
+        hasher.update([0]);
+        hasher.update(text.as_bytes());
+        let hash: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        Self(hash)
+    }
+
+    fn file_name(&self) -> String {
+        format!("{}.{}", self.0, ENTRY_EXTENSION)
+    }
+}
+
+/// Size and usage of the embedding cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingCacheStats {
+    pub location: String,
+    pub entries: usize,
+    pub size_bytes: u64,
+    pub max_bytes: u64,
+}
+
+struct Entry {
+    size: u64,
+    last_used: SystemTime,
+}
+
+#[derive(Default)]
+struct Index {
+    entries: HashMap<String, Entry>,
+    total_bytes: u64,
+}
+
+/// Embedding cache in a directory, bounded to `max_bytes`
+pub struct EmbeddingCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: Mutex<Index>,
+}
+
+impl EmbeddingCache {
+    /// Open the cache in `dir`, creating it if needed, and trim it to `max_bytes`
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let mut index = Index::default();
+        for entry in fs::read_dir(&dir)?.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != ENTRY_EXTENSION) {
+                continue;
+            }
+            let (name, metadata) = match (entry.file_name().into_string(), entry.metadata()) {
+                (Ok(name), Ok(metadata)) => (name, metadata),
+                _ => continue,
+            };
+            let last_used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            index.total_bytes += metadata.len();
+            index.entries.insert(name, Entry { size: metadata.len(), last_used });
+        }
+
+        let cache = Self { dir, max_bytes, index: Mutex::new(index) };
+        cache.evict(&mut cache.lock());
+        Ok(cache)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Index> {
+        self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Cached embedding of `key`, marking it as recently used
+    pub fn get(&self, key: &EmbeddingKey) -> Option<Vec<f32>> {
+        let name = key.file_name();
+        let mut index = self.lock();
+        index.entries.get(&name)?;
+
+        let path = self.path(&name);
+        match read_embedding(&path) {
+            Ok(embedding) => {
+                let now = SystemTime::now();
+                // Recency survives restarts through the modification time
+                if let Err(e) = fs::File::options().write(true).open(&path).and_then(|file| file.set_modified(now)) {
+                    tracing::debug!("Could not touch {}: {}", path.display(), e);
+                }
+                if let Some(entry) = index.entries.get_mut(&name) {
+                    entry.last_used = now;
+                }
+                Some(embedding)
+            }
+            Err(e) => {
+                tracing::warn!("Dropping unreadable cached embedding {}: {:#}", path.display(), e);
+                self.remove(&mut index, &name);
+                None
+            }
+        }
+    }
+
+    /// Store the embedding of `key`, evicting the least recently used
+    /// entries if the cache outgrows its limit
+    pub fn put(&self, key: &EmbeddingKey, embedding: &[f32]) -> Result<()> {
+        let name = key.file_name();
+        let bytes: Vec<u8> = embedding.iter().flat_map(|value| value.to_le_bytes()).collect();
+
+        let mut index = self.lock();
+        // Written aside and renamed, so a reader never sees half an entry
+        let path = self.path(&name);
+        let partial = path.with_extension("partial");
+        fs::write(&partial, &bytes).with_context(|| format!("Failed to write {}", partial.display()))?;
+        fs::rename(&partial, &path)?;
+
+        let size = bytes.len() as u64;
+        let previous = index.entries.insert(name, Entry { size, last_used: SystemTime::now() });
+        index.total_bytes = index.total_bytes - previous.map_or(0, |entry| entry.size) + size;
+        self.evict(&mut index);
+        Ok(())
+    }
+
+    fn remove(&self, index: &mut Index, name: &str) {
+        if let Some(entry) = index.entries.remove(name) {
+            index.total_bytes -= entry.size;
+            let _ = fs::remove_file(self.path(name));
+        }
+    }
+
+    fn evict(&self, index: &mut Index) {
+        if index.total_bytes <= self.max_bytes {
+            return;
+        }
+        let mut by_age: Vec<(SystemTime, String)> =
+            index.entries.iter().map(|(name, entry)| (entry.last_used, name.clone())).collect();
+        by_age.sort();
+        for (_, name) in by_age {
+            if index.total_bytes <= self.max_bytes {
+                break;
+            }
+            self.remove(index, &name);
+        }
+    }
+
+    /// Delete every cached embedding
+    pub fn clear(&self) {
+        let mut index = self.lock();
+        let names: Vec<String> = index.entries.keys().cloned().collect();
+        for name in names {
+            self.remove(&mut index, &name);
+        }
+    }
+
+    pub fn stats(&self) -> EmbeddingCacheStats {
+        let index = self.lock();
+        EmbeddingCacheStats {
+            location: self.dir.to_string_lossy().to_string(),
+            entries: index.entries.len(),
+            size_bytes: index.total_bytes,
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+fn read_embedding(path: &Path) -> Result<Vec<f32>> {
+    let bytes = fs::read(path)?;
+    anyhow::ensure!(bytes.len() % 4 == 0, "{} bytes is not a whole number of f32 values", bytes.len());
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_keys_separate_models_and_pooling() {
+        let key = EmbeddingKey::new("bertimbau@main/candle/f32", "texto", Pooling::Cls);
+        assert_eq!(key, EmbeddingKey::new("bertimbau@main/candle/f32", "texto", Pooling::Cls));
+        assert_ne!(key, EmbeddingKey::new("bertimbau@main/candle/q8", "texto", Pooling::Cls));
+        assert_ne!(key, EmbeddingKey::new("bertimbau@main/candle/f32", "texto", Pooling::Mean));
+        assert_ne!(key, EmbeddingKey::new("bertimbau@main/candle/f32", "texto.", Pooling::Cls));
+    }
+
+    #[test]
+    fn test_entries_persist_across_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = EmbeddingKey::new("model", "redação", Pooling::Cls);
+        let cache = EmbeddingCache::open(dir.path(), 1024).unwrap();
+        assert_eq!(cache.get(&key), None);
+        cache.put(&key, &[0.5, -1.25, 3.0]).unwrap();
+        drop(cache);
+
+        let cache = EmbeddingCache::open(dir.path(), 1024).unwrap();
+        assert_eq!(cache.get(&key), Some(vec![0.5, -1.25, 3.0]));
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.stats().size_bytes, 12);
+
+        cache.clear();
+        assert_eq!(cache.get(&key), None);
+        assert_eq!(cache.stats().size_bytes, 0);
+    }
+
+    #[test]
+    fn test_least_recently_used_entries_are_evicted() {
+        let dir = tempfile::tempdir().unwrap();
+        // Room for two entries of four values
+        let cache = EmbeddingCache::open(dir.path(), 32).unwrap();
+        let keys: Vec<EmbeddingKey> =
+            ["a", "b", "c"].iter().map(|text| EmbeddingKey::new("model", text, Pooling::Cls)).collect();
+
+        cache.put(&keys[0], &[0.0; 4]).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        cache.put(&keys[1], &[1.0; 4]).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        // Using "a" makes "b" the least recently used
+        assert!(cache.get(&keys[0]).is_some());
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        cache.put(&keys[2], &[2.0; 4]).unwrap();
+
+        assert!(cache.get(&keys[0]).is_some());
+        assert_eq!(cache.get(&keys[1]), None);
+        assert!(cache.get(&keys[2]).is_some());
+        assert!(cache.stats().size_bytes <= 32);
+
+        // A smaller limit trims the cache when it is opened
+        drop(cache);
+        let cache = EmbeddingCache::open(dir.path(), 16).unwrap();
+        assert_eq!(cache.stats().entries, 1);
+    }
+}
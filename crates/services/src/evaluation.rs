@@ -1,39 +1,245 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use chrono::Utc;
+use domain::answer_sheet::AnswerSheet;
+use domain::provenance::{EvaluationRecord, HeuristicConfig, ModelProvenance};
+use domain::review::HumanReview;
 use domain::essay::{
-    Correction, Essay, EssayStatus, ExamType, RubricScores,
+    Correction, Essay, EssayStatus, ExamRubric, ExamType, RubricScores,
 };
+use domain::traits::EvaluationRecordRepository;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use uuid::Uuid;
 
 use crate::ai::AIService;
+use crate::inference::JobOptions;
+use crate::provenance::{compare_records, input_hash, rubric_version, RecordComparison, SCORING_HEAD_VERSION};
+use crate::readability::compute_text_metrics;
 use crate::rubrics::{get_rubric, get_enem_score_level};
+use crate::spelling::SpellChecker;
+use crate::uncertainty::review_reasons;
+
+/// Reasons an essay receives zero without going through competency scoring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotaZeroReason {
+    /// Blank answer sheet
+    EmBranco,
+    /// Fewer handwritten lines than the answer sheet minimum
+    TextoInsuficiente { lines: u16, min_lines: u16 },
+}
+
+impl NotaZeroReason {
+    pub fn description(&self) -> String {
+        match self {
+            NotaZeroReason::EmBranco => "Redação em branco.".to_string(),
+            NotaZeroReason::TextoInsuficiente { lines, min_lines } => format!(
+                "Texto insuficiente: aproximadamente {} linhas manuscritas, abaixo do mínimo de {} linhas.",
+                lines, min_lines
+            ),
+        }
+    }
+}
+
+/// Check the essay against the eliminatory criteria of the answer sheet
+pub fn screen_nota_zero(content: &str, lines: u16, sheet: &AnswerSheet) -> Option<NotaZeroReason> {
+    if content.trim().is_empty() {
+        return Some(NotaZeroReason::EmBranco);
+    }
+    // Only exams with a minimum, such as ENEM, zero short essays
+    sheet
+        .min_lines
+        .filter(|&min_lines| lines < min_lines)
+        .map(|min_lines| NotaZeroReason::TextoInsuficiente { lines, min_lines })
+}
+
+/// Model id and revision, or that scoring did not go through a model
+fn describe_model(model: Option<&ModelProvenance>) -> String {
+    match model {
+        Some(ModelProvenance { model_id, revision: Some(revision), .. }) => format!("{}@{}", model_id, revision),
+        Some(model) => format!("{} (unknown revision)", model.model_id),
+        None => "no model".to_string(),
+    }
+}
 
 /// Evaluation Service for orchestrating essay evaluation
 pub struct EvaluationService {
     ai_service: AIService,
+    /// Score from the text heuristics without loading the encoder
+    heuristic_only: bool,
+    /// Where the record of every evaluation is kept
+    records: Option<Arc<dyn EvaluationRecordRepository>>,
 }
 
 impl EvaluationService {
     /// Create a new evaluation service
     pub fn new() -> Result<Self> {
-        let ai_service = AIService::new()?;
-        Ok(Self { ai_service })
+        Ok(Self::with_ai_service(AIService::new()?))
+    }
+
+    /// Evaluation service that scores with an existing (possibly loaded) model
+    pub fn with_ai_service(ai_service: AIService) -> Self {
+        Self { ai_service, heuristic_only: false, records: None }
+    }
+
+    /// Evaluation service that never downloads or loads the model
+    ///
+    /// Scores and intervals are the same as with the model; essays get no
+    /// attributions.
+    pub fn heuristic_only() -> Result<Self> {
+        Ok(Self { heuristic_only: true, ..Self::new()? })
+    }
+
+    /// Save the record of every evaluation and accepted rerun to `records`
+    pub fn with_records(mut self, records: Arc<dyn EvaluationRecordRepository>) -> Self {
+        self.records = Some(records);
+        self
     }
 
     /// Evaluate an essay and return updated essay with scores and feedback
-    pub async fn evaluate_essay(&self, mut essay: Essay) -> Result<Essay> {
+    pub async fn evaluate_essay(&self, essay: Essay) -> Result<Essay> {
+        self.evaluate_essay_with(essay, JobOptions::default()).await
+    }
+
+    /// Evaluate an essay, reporting the progress of the model's inference job
+    ///
+    /// `options` sets the job's priority on the inference worker, its progress
+    /// callback and the token that cancels it; heuristic-only and nota-zero
+    /// evaluations never reach the worker.
+    pub async fn evaluate_essay_with(&self, essay: Essay, options: JobOptions) -> Result<Essay> {
+        let (essay, _) = self.evaluate_with_record(essay, options).await?;
+        Ok(essay)
+    }
+
+    /// Evaluate an essay and describe how the scores were produced
+    pub async fn evaluate_with_record(
+        &self,
+        essay: Essay,
+        options: JobOptions,
+    ) -> Result<(Essay, EvaluationRecord)> {
+        let (essay, record) = self
+            .evaluate_recorded(essay, &HeuristicConfig::default(), options)
+            .await?;
+        self.save_record(&record).await?;
+        Ok((essay, record))
+    }
+
+    /// Score an essay again with the heuristic thresholds of a past evaluation
+    ///
+    /// Fails if the essay is not the one that was evaluated, its title or
+    /// content changed since, or the model loaded now is not the model id and
+    /// revision the evaluation used: scores from another model say nothing
+    /// about whether the original ones can be reproduced.
+    pub async fn rerun(
+        &self,
+        record: &EvaluationRecord,
+        essay: &Essay,
+    ) -> Result<(EvaluationRecord, RecordComparison)> {
+        if essay.id != record.essay_id {
+            bail!("Record {} belongs to another essay", record.id);
+        }
+        if input_hash(&essay.title, &essay.content) != record.input_hash {
+            bail!("Essay changed since evaluation {}", record.id);
+        }
+        let (_, rerun) = self
+            .evaluate_recorded(essay.clone(), &record.heuristic_config, JobOptions::default())
+            .await?;
+        if rerun.model != record.model {
+            bail!(
+                "Evaluation {} used {}, but {} is loaded now",
+                record.id,
+                describe_model(record.model.as_ref()),
+                describe_model(rerun.model.as_ref())
+            );
+        }
+        self.save_record(&rerun).await?;
+        let comparison = compare_records(record, &rerun);
+        Ok((rerun, comparison))
+    }
+
+    async fn save_record(&self, record: &EvaluationRecord) -> Result<()> {
+        if let Some(records) = &self.records {
+            records
+                .save(record.clone())
+                .await
+                .context("Failed to save evaluation record")?;
+        }
+        Ok(())
+    }
+
+    async fn evaluate_recorded(
+        &self,
+        essay: Essay,
+        config: &HeuristicConfig,
+        options: JobOptions,
+    ) -> Result<(Essay, EvaluationRecord)> {
+        let started = Instant::now();
+        let hash = input_hash(&essay.title, &essay.content);
+        let (essay, used_model) = self.score(essay, config, options).await?;
+        let model = if used_model {
+            self.ai_service.provenance().await
+        } else {
+            None
+        };
+
+        let rubric = get_rubric(&essay.exam_type).context("Rubric not found for exam type")?;
+        let scores = essay.rubric_scores.as_ref().map(|r| r.scores.clone()).unwrap_or_default();
+        let record = EvaluationRecord {
+            id: Uuid::new_v4(),
+            essay_id: essay.id,
+            exam_type: essay.exam_type.clone(),
+            model,
+            scoring_head_version: SCORING_HEAD_VERSION.to_string(),
+            rubric_version: rubric_version(rubric),
+            heuristic_config: config.clone(),
+            input_hash: hash,
+            scores,
+            total_score: essay.score.unwrap_or(0),
+            duration_ms: started.elapsed().as_millis() as u64,
+            created_at: Utc::now(),
+        };
+        Ok((essay, record))
+    }
+
+    /// Scored essay and whether it went through the model
+    async fn score(
+        &self,
+        mut essay: Essay,
+        config: &HeuristicConfig,
+        options: JobOptions,
+    ) -> Result<(Essay, bool)> {
         // Get rubric for exam type
         let rubric = get_rubric(&essay.exam_type)
             .context("Rubric not found for exam type")?;
 
+        // Nota-zero screening based on the handwritten line estimate, always
+        // taken from the content: a stored estimate may predate an edit
+        let sheet = AnswerSheet::for_exam(&essay.exam_type);
+        let lines = sheet.estimate_lines(&essay.content);
+        essay.estimated_lines = Some(lines);
+        essay.text_metrics = Some(compute_text_metrics(&essay.content));
+
+        if let Some(reason) = screen_nota_zero(&essay.content, lines, &sheet) {
+            return Ok((self.apply_nota_zero(essay, rubric, &reason), false));
+        }
+
         // Extract theme from title (in production, this would be more sophisticated)
         let theme = &essay.title;
 
-        // Score the essay using AI
-        let competency_scores = self.ai_service
-            .score_essay(theme, &essay.content)
-            .await
-            .context("Failed to score essay")?;
+        // Lines past the end of the sheet are disregarded, not penalized
+        let scored_text = sheet.text_within(&essay.content);
+
+        // Score the essay using AI, keeping the attributions behind each score
+        let criteria: Vec<String> = rubric.criteria.iter().map(|c| c.name.clone()).collect();
+        let scored = if self.heuristic_only {
+            self.ai_service.score_essay_heuristic(scored_text, config).await
+        } else {
+            self.ai_service
+                .score_essay_explained(theme, scored_text, &criteria, config, options)
+                .await
+        }
+        .context("Failed to score essay")?;
+        let competency_scores = scored.scores;
 
         // Build rubric scores
         let mut scores = HashMap::new();
@@ -56,13 +262,29 @@ impl EvaluationService {
         }
 
         // Generate corrections (basic implementation)
-        let corrections = self.generate_corrections(&essay.content, &competency_scores);
+        let mut corrections = self.generate_corrections(scored_text, &competency_scores);
+
+        // Spelling mistakes count against C1 when the dictionary is installed;
+        // its files are read off the async runtime
+        let user_id = essay.user_id;
+        let checker = tokio::task::spawn_blocking(move || SpellChecker::for_user(user_id))
+            .await
+            .context("Spell checker task failed")?;
+        if let Some(checker) = checker {
+            corrections.extend(checker.corrections(scored_text));
+        }
 
         // Generate overall feedback
-        let overall_feedback = self.generate_overall_feedback(
+        let mut overall_feedback = self.generate_overall_feedback(
             &essay.exam_type,
             &competency_scores,
         );
+        if lines > sheet.max_lines {
+            overall_feedback.push_str(&format!(
+                "\n\nTexto excedente: aproximadamente {} linhas manuscritas; o que passa da linha {} da folha foi desconsiderado.",
+                lines, sheet.max_lines
+            ));
+        }
 
         // Update essay
         essay.score = Some(total_score);
@@ -72,11 +294,57 @@ impl EvaluationService {
             detailed_feedback,
         });
         essay.corrections = Some(corrections);
+        let sentences = scored.explanation.sentences;
+        essay.attributions = (!sentences.is_empty()).then_some(sentences);
+
+        // Uncertain or borderline scores go to a teacher before being final
+        let reasons = review_reasons(&rubric.criteria, &competency_scores, &scored.confidence);
+        essay.confidence = Some(
+            rubric
+                .criteria
+                .iter()
+                .map(|c| c.name.clone())
+                .zip(scored.confidence)
+                .collect(),
+        );
+        essay.review = (!reasons.is_empty()).then(|| HumanReview::pending(reasons));
         essay.feedback = Some(overall_feedback);
         essay.status = EssayStatus::Corrigida;
         essay.updated_at = Utc::now();
 
-        Ok(essay)
+        Ok((essay, !self.heuristic_only))
+    }
+
+    /// Zero every criterion and explain the eliminatory reason
+    fn apply_nota_zero(&self, mut essay: Essay, rubric: &ExamRubric, reason: &NotaZeroReason) -> Essay {
+        let description = reason.description();
+        let scores = rubric
+            .criteria
+            .iter()
+            .map(|c| (c.name.clone(), 0))
+            .collect();
+        let detailed_feedback = rubric
+            .criteria
+            .iter()
+            .map(|c| (c.name.clone(), description.clone()))
+            .collect();
+
+        essay.score = Some(0);
+        essay.max_score = rubric.max_score;
+        essay.rubric_scores = Some(RubricScores {
+            scores,
+            detailed_feedback,
+        });
+        essay.corrections = Some(Vec::new());
+        // Nothing was scored, so nothing is uncertain or attributed, and an
+        // earlier evaluation's must not linger
+        essay.attributions = None;
+        essay.confidence = None;
+        essay.review = None;
+        essay.feedback = Some(format!("Redação com nota zero.\n\n{}", description));
+        essay.status = EssayStatus::Corrigida;
+        essay.updated_at = Utc::now();
+        essay
     }
 
     /// Generate feedback for a specific competency
@@ -206,10 +474,45 @@ impl EvaluationService {
             });
         }
 
+        // Informal register markers compromise the formal written standard (C1)
+        let register = compute_text_metrics(content).register;
+        for marker in register.found.iter().take(5) {
+            if let Some((position, original_text)) = find_word(content, marker) {
+                corrections.push(Correction {
+                    position,
+                    original_text,
+                    suggested_text: "Reescreva em registro formal e impessoal".to_string(),
+                    reason: "Marcas de oralidade, gíria ou primeira pessoa não são adequadas ao texto dissertativo".to_string(),
+                    rubric_criterion: "C1".to_string(),
+                });
+            }
+        }
+
         corrections
     }
 }
 
+/// Char offset and original spelling of the first whole-word occurrence of `target`
+///
+/// Compares char by char, ignoring case, over the original text: lowercasing
+/// the whole text first can change its length ("İ" becomes two chars) and
+/// shift every offset after it.
+fn find_word(content: &str, target: &str) -> Option<(usize, String)> {
+    let chars: Vec<char> = content.chars().collect();
+    let target: Vec<char> = target.chars().collect();
+    if target.is_empty() {
+        return None;
+    }
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+    (0..=chars.len().checked_sub(target.len())?).find_map(|begin| {
+        let end = begin + target.len();
+        let matches = chars[begin..end].iter().zip(&target).all(|(a, b)| same(*a, *b));
+        let before_ok = begin == 0 || !chars[begin - 1].is_alphanumeric();
+        let after_ok = chars.get(end).is_none_or(|c| !c.is_alphanumeric());
+        (matches && before_ok && after_ok).then(|| (begin, chars[begin..end].iter().collect()))
+    })
+}
+
 impl Default for EvaluationService {
     fn default() -> Self {
         Self::new().expect("Failed to create evaluation service")
@@ -219,6 +522,8 @@ impl Default for EvaluationService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use data::InMemoryEvaluationRecordRepository;
+    use domain::answer_sheet::LineCheck;
 
     #[test]
     fn test_generate_enem_feedback() {
@@ -228,6 +533,147 @@ mod tests {
         assert!(feedback.contains("160"));
     }
 
+    #[test]
+    fn test_screen_nota_zero() {
+        let sheet = AnswerSheet::for_exam(&ExamType::Enem);
+        let short = "A educação é importante.\n\nPortanto, devemos investir nela.";
+        let lines = sheet.estimate_lines(short);
+        assert_eq!(lines, 2);
+        assert_eq!(
+            screen_nota_zero(short, lines, &sheet),
+            Some(NotaZeroReason::TextoInsuficiente { lines: 2, min_lines: 7 })
+        );
+        assert_eq!(screen_nota_zero("   ", 0, &sheet), Some(NotaZeroReason::EmBranco));
+
+        let long = "palavra ".repeat(600);
+        assert_eq!(screen_nota_zero(&long, sheet.estimate_lines(&long), &sheet), None);
+    }
+
+    #[test]
+    fn test_short_essays_are_only_zeroed_for_enem() {
+        let short = "A educação é importante.\n\nPortanto, devemos investir nela.";
+        for exam in [ExamType::Fuvest, ExamType::Unicamp, ExamType::Unesp] {
+            let sheet = AnswerSheet::for_exam(&exam);
+            let lines = sheet.estimate_lines(short);
+            assert_eq!(screen_nota_zero(short, lines, &sheet), None);
+            assert_eq!(sheet.check(lines), LineCheck::Within);
+            assert_eq!(screen_nota_zero("   ", 0, &sheet), Some(NotaZeroReason::EmBranco));
+        }
+    }
+
+    #[test]
+    fn test_text_past_the_sheet_is_disregarded() {
+        let sheet = AnswerSheet::for_exam(&ExamType::Enem);
+        let within = "palavra ".repeat(150);
+        assert_eq!(sheet.text_within(&within), within.trim_end());
+
+        let long = format!("{}\n\nFinal além da folha.", "palavra ".repeat(600));
+        let kept = sheet.text_within(&long);
+        assert_eq!(sheet.estimate_lines(kept), sheet.max_lines);
+        assert!(long.starts_with(kept));
+        assert!(!kept.contains("Final"));
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_recomputes_stale_line_estimate() {
+        let service = EvaluationService::new().unwrap();
+        let mut essay = Essay::new(uuid::Uuid::nil(), "Educação", "Texto curto demais.", ExamType::Enem);
+        essay.status = EssayStatus::Enviada;
+        // Estimate left over from a longer version of the text
+        essay.estimated_lines = Some(20);
+        essay.submitted_at = Some(Utc::now());
+
+        let evaluated = service.evaluate_essay(essay).await.unwrap();
+        assert_eq!(evaluated.estimated_lines, Some(1));
+        assert_eq!(evaluated.score, Some(0));
+    }
+
+    #[test]
+    fn test_corrections_flag_informal_register() {
+        let service = EvaluationService::new().unwrap();
+        let content = "Portanto, Eu acho que a escola mudou, né.";
+        let corrections = service.generate_corrections(content, &[]);
+        let informal: Vec<_> = corrections.iter().filter(|c| c.rubric_criterion == "C1").collect();
+        assert_eq!(informal.len(), 2);
+        assert_eq!(informal[0].original_text, "Eu");
+        assert_eq!(informal[0].position, 10);
+        assert_eq!(informal[1].original_text, "né");
+    }
+
+    #[tokio::test]
+    async fn test_rerun_nota_zero_record() {
+        let service = EvaluationService::new().unwrap();
+        let mut essay = Essay::new(Uuid::nil(), "Educação", "Texto curto demais.", ExamType::Enem);
+        essay.status = EssayStatus::Enviada;
+
+        let (evaluated, record) = service.evaluate_with_record(essay.clone(), JobOptions::default()).await.unwrap();
+        assert_eq!(record.essay_id, essay.id);
+        assert_eq!(record.model, None);
+        assert_eq!(record.total_score, 0);
+        assert_eq!(record.scores.len(), 5);
+        assert_eq!(record.input_hash, input_hash(&essay.title, &essay.content));
+
+        let (rerun, comparison) = service.rerun(&record, &evaluated).await.unwrap();
+        assert_ne!(rerun.id, record.id);
+        assert!(comparison.is_reproduced());
+
+        essay.content.push_str(" Mais uma frase.");
+        assert!(service.rerun(&record, &essay).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nota_zero_clears_an_earlier_evaluation() {
+        let service = EvaluationService::heuristic_only().unwrap();
+        let mut essay = Essay::new(Uuid::nil(), "Educação", "Texto curto demais.", ExamType::Enem);
+        essay.attributions = Some(Vec::new());
+        essay.confidence = Some(HashMap::new());
+        essay.review = Some(HumanReview::pending(Vec::new()));
+
+        let (zeroed, _) = service.evaluate_with_record(essay, JobOptions::default()).await.unwrap();
+        assert_eq!(zeroed.score, Some(0));
+        assert_eq!(zeroed.attributions, None);
+        assert_eq!(zeroed.confidence, None);
+        assert_eq!(zeroed.review, None);
+    }
+
+    #[tokio::test]
+    async fn test_records_are_saved_and_reruns_need_the_same_model() {
+        let records = Arc::new(InMemoryEvaluationRecordRepository::new());
+        let service = EvaluationService::heuristic_only().unwrap().with_records(records.clone());
+        let content = "A educação pública precisa de investimentos contínuos e planejamento. ".repeat(30);
+        let essay = Essay::new(Uuid::nil(), "Educação", &content, ExamType::Enem);
+
+        let (evaluated, record) = service.evaluate_with_record(essay, JobOptions::default()).await.unwrap();
+        assert_eq!(evaluated.status, EssayStatus::Corrigida);
+        assert_eq!(records.find_by_id(record.id).await.unwrap(), Some(record.clone()));
+
+        let (rerun, _) = service.rerun(&record, &evaluated).await.unwrap();
+        assert_eq!(records.latest(evaluated.id).await.unwrap(), Some(rerun));
+
+        // Scores recorded with a model cannot be reproduced without it
+        let mut with_model = record.clone();
+        with_model.model = Some(ModelProvenance {
+            model_id: crate::ai::MODEL_ID.to_string(),
+            revision: Some("abc123".to_string()),
+            tokenizer_hash: String::new(),
+            weights: None,
+            weight_format: None,
+        });
+        let error = service.rerun(&with_model, &evaluated).await.unwrap_err();
+        assert!(error.to_string().contains("@abc123"));
+        assert_eq!(records.list_by_essay(evaluated.id).await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_find_word_offsets_follow_the_original_text() {
+        // "İ" lowercases to two chars; the offset must still count the original ones
+        assert_eq!(find_word("İSTANBUL: Eu acho", "eu"), Some((10, "Eu".to_string())));
+        assert_eq!(find_word("Meu eu", "eu"), Some((4, "eu".to_string())));
+        assert_eq!(find_word("NÉ? né", "né"), Some((0, "NÉ".to_string())));
+        assert_eq!(find_word("europa", "eu"), None);
+        assert_eq!(find_word("", "eu"), None);
+    }
+
     #[test]
     fn test_generate_overall_feedback() {
         let service = EvaluationService::new().unwrap();
@@ -0,0 +1,211 @@
+//! Explanations for competency scores
+//!
+//! The scores come from heuristics over the text rather than a trained
+//! regression head, so there are no gradients to integrate. Passages are
+//! occluded instead: the essay is rescored without each sentence (or each
+//! word), and the change of every competency score is that passage's
+//! attribution. Passages that raise a score get positive weights and passages
+//! that lower it negative ones.
+//!
+//! Scoring explains by sentence only; occluding every word rescores the essay
+//! hundreds of times and is left to callers that need word weights.
+
+use domain::attribution::{SentenceAttribution, TokenAttribution};
+use std::collections::HashMap;
+
+/// Sentence attributions for one scored essay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreExplanation {
+    pub sentences: Vec<SentenceAttribution>,
+}
+
+/// Span of text with char offsets for the UI and byte offsets for slicing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    end: usize,
+    byte_start: usize,
+    byte_end: usize,
+}
+
+fn word_spans(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut current: Option<Span> = None;
+    for (idx, (byte, c)) in text.char_indices().enumerate() {
+        if c.is_alphanumeric() || ((c == '-' || c == '\'') && current.is_some()) {
+            let span = current.get_or_insert(Span {
+                start: idx,
+                end: idx,
+                byte_start: byte,
+                byte_end: byte,
+            });
+            span.end = idx + 1;
+            span.byte_end = byte + c.len_utf8();
+        } else if let Some(span) = current.take() {
+            spans.push(span);
+        }
+    }
+    spans.extend(current);
+    spans
+}
+
+/// Sentences end at `.`, `!`, `?` or a line break; blank stretches are skipped
+pub(crate) fn sentence_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut count = 0;
+    for (idx, c) in text.chars().enumerate() {
+        count = idx + 1;
+        if start.is_none() && !c.is_whitespace() {
+            start = Some(idx);
+        }
+        if matches!(c, '.' | '!' | '?' | '\n') {
+            if let Some(s) = start.take() {
+                let end = if c == '\n' { idx } else { idx + 1 };
+                spans.push((s, end));
+            }
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, count));
+    }
+    spans
+}
+
+/// Score change of each competency when `(byte_start, byte_end)` is cut out
+fn occlusion_deltas<F>(
+    content: &str,
+    (byte_start, byte_end): (usize, usize),
+    criteria: &[String],
+    baseline: &[u16],
+    scorer: &F,
+) -> HashMap<String, f32>
+where
+    F: Fn(&str) -> Vec<u16>,
+{
+    let occluded = format!("{}{}", &content[..byte_start], &content[byte_end..]);
+    let scores = scorer(&occluded);
+    criteria
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let full = baseline.get(idx).copied().unwrap_or(0) as f32;
+            let without = scores.get(idx).copied().unwrap_or(0) as f32;
+            (name.clone(), full - without)
+        })
+        .collect()
+}
+
+/// Score change when each word is removed from the text
+///
+/// `scorer` returns one score per entry of `criteria`, in the same order.
+/// Positive weights are points the word adds, negative weights points it costs.
+pub fn occlusion_attributions<F>(content: &str, criteria: &[String], scorer: F) -> Vec<TokenAttribution>
+where
+    F: Fn(&str) -> Vec<u16>,
+{
+    let baseline = scorer(content);
+    word_spans(content)
+        .into_iter()
+        .map(|span| TokenAttribution {
+            start: span.start,
+            end: span.end,
+            token: content[span.byte_start..span.byte_end].to_string(),
+            competencies: occlusion_deltas(content, (span.byte_start, span.byte_end), criteria, &baseline, &scorer),
+        })
+        .collect()
+}
+
+/// Score change when each sentence is removed from the text
+///
+/// Most heuristics react to whole passages (a proposal, a paragraph, the
+/// length of the text) rather than to single words, so sentences are
+/// occluded as a whole instead of summing their word attributions.
+pub fn sentence_attributions<F>(content: &str, criteria: &[String], scorer: F) -> Vec<SentenceAttribution>
+where
+    F: Fn(&str) -> Vec<u16>,
+{
+    let baseline = scorer(content);
+    let bytes: Vec<usize> = content
+        .char_indices()
+        .map(|(b, _)| b)
+        .chain(std::iter::once(content.len()))
+        .collect();
+    sentence_spans(content)
+        .into_iter()
+        .map(|(start, end)| SentenceAttribution {
+            start,
+            end,
+            competencies: occlusion_deltas(content, (bytes[start], bytes[end]), criteria, &baseline, &scorer),
+        })
+        .collect()
+}
+
+/// Sentence attributions of the scores `scorer` gives `content`
+pub fn explain_scores<F>(content: &str, criteria: &[String], scorer: F) -> ScoreExplanation
+where
+    F: Fn(&str) -> Vec<u16>,
+{
+    ScoreExplanation {
+        sentences: sentence_attributions(content, criteria, &scorer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn criteria() -> Vec<String> {
+        vec!["C1".to_string(), "C5".to_string()]
+    }
+
+    /// Rewards a proposal keyword and penalizes slang
+    fn scorer(text: &str) -> Vec<u16> {
+        let c1 = if text.contains("galera") { 80 } else { 160 };
+        let c5 = if text.contains("solução") { 160 } else { 80 };
+        vec![c1, c5]
+    }
+
+    #[test]
+    fn test_sentence_spans() {
+        let spans = sentence_spans("Primeira frase. Segunda!\nTerceira sem ponto");
+        assert_eq!(spans, vec![(0, 15), (16, 24), (25, 43)]);
+    }
+
+    #[test]
+    fn test_occlusion_finds_influential_words() {
+        let content = "A galera reclama. É preciso uma solução.";
+        let tokens = occlusion_attributions(content, &criteria(), scorer);
+
+        let galera = tokens.iter().find(|t| t.token == "galera").unwrap();
+        assert_eq!((galera.start, galera.end), (2, 8));
+        assert_eq!(galera.competencies["C1"], -80.0);
+
+        let solucao = tokens.iter().find(|t| t.token == "solução").unwrap();
+        assert_eq!(solucao.competencies["C5"], 80.0);
+        assert_eq!(solucao.competencies["C1"], 0.0);
+    }
+
+    #[test]
+    fn test_sentences_pull_each_competency_their_own_way() {
+        let explanation = explain_scores("A galera reclama. É preciso uma solução.", &criteria(), scorer);
+        assert_eq!(explanation.sentences.len(), 2);
+        assert_eq!(explanation.sentences[0].weight("C1"), -80.0);
+        assert_eq!(explanation.sentences[0].weight("C5"), 0.0);
+        assert_eq!(explanation.sentences[1].weight("C1"), 0.0);
+        assert_eq!(explanation.sentences[1].weight("C5"), 80.0);
+    }
+
+    #[test]
+    fn test_sentence_occlusion_catches_passages_no_single_word_moves() {
+        // C5 needs two proposal words; removing either one alone changes nothing
+        let scorer = |text: &str| vec![if text.contains("governo") || text.contains("escolas") { 160 } else { 80 }];
+        let criteria = vec!["C5".to_string()];
+        let content = "O tema é grave. O governo deve ampliar as escolas.";
+        let explanation = explain_scores(content, &criteria, scorer);
+
+        assert!(occlusion_attributions(content, &criteria, scorer).iter().all(|t| t.competencies["C5"] == 0.0));
+        assert_eq!(explanation.sentences[0].weight("C5"), 0.0);
+        assert_eq!(explanation.sentences[1].weight("C5"), 80.0);
+    }
+}
@@ -0,0 +1,220 @@
+//! WordprocessingML writer; corrections become Word comments
+
+use anyhow::Result;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+use super::{AnnotatedParagraph, ExportDocument};
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/><Override PartName="/word/comments.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.comments+xml"/></Types>"#;
+
+const PACKAGE_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+const DOCUMENT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/comments" Target="comments.xml"/></Relationships>"#;
+
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn run(text: &str, bold: bool, size_half_points: u32) -> String {
+    let mut props = String::new();
+    if bold {
+        props.push_str("<w:b/>");
+    }
+    props.push_str(&format!("<w:sz w:val=\"{}\"/>", size_half_points));
+    format!(
+        "<w:r><w:rPr>{}</w:rPr><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+        props,
+        escape(text)
+    )
+}
+
+fn paragraph(text: &str, bold: bool, size_half_points: u32) -> String {
+    format!("<w:p>{}</w:p>", run(text, bold, size_half_points))
+}
+
+/// Essay paragraph with comment ranges around the corrected passages
+fn annotated_paragraph(paragraph: &AnnotatedParagraph) -> String {
+    let mut xml = String::from("<w:p>");
+    let mut rest = paragraph.text.as_str();
+    let mut trailing = Vec::new();
+
+    for annotation in &paragraph.annotations {
+        let id = annotation.number - 1;
+        let found = if annotation.original_text.is_empty() {
+            None
+        } else {
+            rest.find(&annotation.original_text)
+        };
+        match found {
+            Some(idx) => {
+                let end = idx + annotation.original_text.len();
+                if idx > 0 {
+                    xml.push_str(&run(&rest[..idx], false, 24));
+                }
+                xml.push_str(&format!("<w:commentRangeStart w:id=\"{}\"/>", id));
+                xml.push_str(&run(&rest[idx..end], false, 24));
+                xml.push_str(&format!("<w:commentRangeEnd w:id=\"{}\"/>", id));
+                xml.push_str(&format!("<w:r><w:commentReference w:id=\"{}\"/></w:r>", id));
+                rest = &rest[end..];
+            }
+            // Passage not found verbatim: comment the whole paragraph
+            None => trailing.push(id),
+        }
+    }
+
+    if !trailing.is_empty() {
+        let starts: String = trailing
+            .iter()
+            .map(|id| format!("<w:commentRangeStart w:id=\"{}\"/>", id))
+            .collect();
+        xml.insert_str("<w:p>".len(), &starts);
+    }
+    if !rest.is_empty() {
+        xml.push_str(&run(rest, false, 24));
+    }
+    for id in trailing {
+        xml.push_str(&format!("<w:commentRangeEnd w:id=\"{}\"/>", id));
+        xml.push_str(&format!("<w:r><w:commentReference w:id=\"{}\"/></w:r>", id));
+    }
+    xml.push_str("</w:p>");
+    xml
+}
+
+fn table_cell(text: &str, bold: bool) -> String {
+    format!("<w:tc>{}</w:tc>", paragraph(text, bold, 20))
+}
+
+fn document_xml(doc: &ExportDocument) -> String {
+    let mut body = String::new();
+    body.push_str(&paragraph(&doc.title, true, 36));
+    for line in &doc.metadata {
+        body.push_str(&paragraph(line, false, 20));
+    }
+    for p in &doc.paragraphs {
+        body.push_str(&annotated_paragraph(p));
+    }
+
+    if !doc.rubric.is_empty() {
+        body.push_str(&paragraph("Notas por competência", true, 28));
+        body.push_str("<w:tbl><w:tblPr><w:tblBorders>");
+        for side in ["top", "left", "bottom", "right", "insideH", "insideV"] {
+            body.push_str(&format!("<w:{} w:val=\"single\" w:sz=\"4\"/>", side));
+        }
+        body.push_str("</w:tblBorders></w:tblPr>");
+        body.push_str(&format!(
+            "<w:tr>{}{}{}</w:tr>",
+            table_cell("Critério", true),
+            table_cell("Nota", true),
+            table_cell("Comentário", true)
+        ));
+        for row in &doc.rubric {
+            body.push_str(&format!(
+                "<w:tr>{}{}{}</w:tr>",
+                table_cell(&row.criterion, false),
+                table_cell(&row.score.to_string(), false),
+                table_cell(row.feedback.as_deref().unwrap_or(""), false)
+            ));
+        }
+        body.push_str("</w:tbl>");
+    }
+
+    if let Some(feedback) = &doc.feedback {
+        body.push_str(&paragraph("Feedback geral", true, 28));
+        for line in feedback.lines() {
+            body.push_str(&paragraph(line, false, 22));
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:document xmlns:w=\"{}\"><w:body>{}</w:body></w:document>",
+        W_NS, body
+    )
+}
+
+fn comments_xml(doc: &ExportDocument) -> String {
+    let mut comments = String::new();
+    for annotation in doc.paragraphs.iter().flat_map(|p| &p.annotations) {
+        comments.push_str(&format!(
+            "<w:comment w:id=\"{}\" w:author=\"NeuroNexus\" w:initials=\"NN\">{}</w:comment>",
+            annotation.number - 1,
+            paragraph(&format!("[{}] {}", annotation.criterion, annotation.note), false, 20)
+        ));
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:comments xmlns:w=\"{}\">{}</w:comments>",
+        W_NS, comments
+    )
+}
+
+pub(crate) fn render(doc: &ExportDocument) -> Result<Vec<u8>> {
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", PACKAGE_RELS.to_string()),
+        ("word/_rels/document.xml.rels", DOCUMENT_RELS.to_string()),
+        ("word/document.xml", document_xml(doc)),
+        ("word/comments.xml", comments_xml(doc)),
+    ];
+    // Timestamps pinned to the DOS epoch so the same essay gives the same bytes
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default());
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in parts {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Annotation;
+
+    #[test]
+    fn test_comment_wraps_original_text() {
+        let paragraph = AnnotatedParagraph {
+            text: "O governo precisa agir.".to_string(),
+            annotations: vec![Annotation {
+                number: 1,
+                criterion: "C5".to_string(),
+                original_text: "O governo".to_string(),
+                note: "Especifique o agente.".to_string(),
+            }],
+        };
+        let xml = annotated_paragraph(&paragraph);
+        let start = xml.find("<w:commentRangeStart w:id=\"0\"/>").unwrap();
+        let end = xml.find("<w:commentRangeEnd w:id=\"0\"/>").unwrap();
+        assert!(xml[start..end].contains("O governo"));
+        assert!(xml[end..].contains("precisa agir."));
+    }
+
+    #[test]
+    fn test_package_holds_every_part() {
+        let doc = ExportDocument {
+            title: "Educação".to_string(),
+            metadata: Vec::new(),
+            paragraphs: Vec::new(),
+            rubric: Vec::new(),
+            feedback: None,
+        };
+        let bytes = render(&doc).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert_eq!(names.len(), 5);
+        let mut document = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("word/document.xml").unwrap(), &mut document).unwrap();
+        assert!(document.contains("Educação"));
+    }
+}
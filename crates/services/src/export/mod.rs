@@ -0,0 +1,172 @@
+//! Essay export to PDF and DOCX
+//!
+//! Both writers are deterministic: the same essay always produces the same
+//! bytes, which keeps the golden-file tests stable. The PDF is written by
+//! hand; the DOCX container comes from the `zip` crate with pinned timestamps.
+
+mod docx;
+mod pdf;
+
+use anyhow::{Context, Result};
+use domain::essay::{Essay, EssayStatus};
+use std::path::PathBuf;
+
+/// Supported export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Pdf,
+    Docx,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Docx => "docx",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "application/pdf",
+            ExportFormat::Docx => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+        }
+    }
+}
+
+/// Correction attached to a paragraph of the essay
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Annotation {
+    /// 1-based marker shown in the text
+    pub number: usize,
+    pub criterion: String,
+    pub original_text: String,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AnnotatedParagraph {
+    pub text: String,
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RubricRow {
+    pub criterion: String,
+    pub score: u16,
+    pub feedback: Option<String>,
+}
+
+/// Format-independent view of an essay ready to be rendered
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExportDocument {
+    pub title: String,
+    pub metadata: Vec<String>,
+    pub paragraphs: Vec<AnnotatedParagraph>,
+    pub rubric: Vec<RubricRow>,
+    pub feedback: Option<String>,
+}
+
+impl ExportDocument {
+    pub fn from_essay(essay: &Essay) -> Self {
+        let status = match essay.status {
+            EssayStatus::EmProgresso => "Em Progresso",
+            EssayStatus::Corrigida => "Corrigida",
+            EssayStatus::Enviada => "Enviada",
+        };
+        let mut metadata = vec![
+            format!("Exame: {}", essay.exam_type.display_name()),
+            format!("Situação: {}", status),
+            format!("Última atualização: {}", essay.updated_at.format("%d/%m/%Y %H:%M")),
+        ];
+        if let Some(score) = essay.score {
+            metadata.push(format!("Nota: {}/{}", score, essay.max_score));
+        }
+
+        // Split content into paragraphs, remembering where each one starts
+        let mut paragraphs = Vec::new();
+        let mut starts = Vec::new();
+        let mut offset = 0;
+        for line in essay.content.split('\n') {
+            if !line.trim().is_empty() {
+                starts.push(offset);
+                paragraphs.push(AnnotatedParagraph {
+                    text: line.trim().to_string(),
+                    annotations: Vec::new(),
+                });
+            }
+            offset += line.chars().count() + 1;
+        }
+
+        // Corrections are anchored to the paragraph containing their position
+        for (idx, correction) in essay.corrections.iter().flatten().enumerate() {
+            if paragraphs.is_empty() {
+                break;
+            }
+            let paragraph = starts
+                .iter()
+                .rposition(|start| *start <= correction.position)
+                .unwrap_or(0);
+            let note = if correction.suggested_text.is_empty() {
+                correction.reason.clone()
+            } else {
+                format!("{} Sugestão: {}", correction.reason, correction.suggested_text)
+            };
+            paragraphs[paragraph].annotations.push(Annotation {
+                number: idx + 1,
+                criterion: correction.rubric_criterion.clone(),
+                original_text: correction.original_text.clone(),
+                note,
+            });
+        }
+
+        let mut rubric: Vec<RubricRow> = essay
+            .rubric_scores
+            .iter()
+            .flat_map(|r| {
+                r.scores.iter().map(|(criterion, score)| RubricRow {
+                    criterion: criterion.clone(),
+                    score: *score,
+                    feedback: r.detailed_feedback.get(criterion).cloned(),
+                })
+            })
+            .collect();
+        rubric.sort_by(|a, b| a.criterion.cmp(&b.criterion));
+
+        Self {
+            title: essay.title.clone(),
+            metadata,
+            paragraphs,
+            rubric,
+            feedback: essay.feedback.clone(),
+        }
+    }
+}
+
+/// Render an essay with its rubric table, feedback and annotated corrections
+pub fn export_essay(essay: &Essay, format: ExportFormat) -> Result<Vec<u8>> {
+    let document = ExportDocument::from_essay(essay);
+    match format {
+        ExportFormat::Pdf => Ok(pdf::render(&document)),
+        ExportFormat::Docx => docx::render(&document),
+    }
+}
+
+/// Export an essay into the user's downloads folder, returning the written path
+pub fn save_essay_export(essay: &Essay, format: ExportFormat) -> Result<PathBuf> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .context("Diretório de downloads não encontrado")?;
+    std::fs::create_dir_all(&dir)?;
+
+    let stem: String = essay
+        .title
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("redacao_{}.{}", stem.trim_matches('_'), format.extension()));
+    std::fs::write(&path, export_essay(essay, format)?)?;
+    Ok(path)
+}
@@ -0,0 +1,392 @@
+//! PDF 1.4 writer using the standard Helvetica fonts
+//!
+//! Corrections are numbered in the text and explained as margin notes in a
+//! narrow right-hand column next to the paragraph they refer to.
+
+use super::ExportDocument;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const NOTES_WIDTH: f32 = 130.0;
+const COLUMN_GAP: f32 = 16.0;
+
+const BODY_SIZE: f32 = 11.0;
+const BODY_LEADING: f32 = 15.0;
+const NOTE_SIZE: f32 = 8.0;
+const NOTE_LEADING: f32 = 10.0;
+
+/// Helvetica advance widths for ASCII 32..=126, in 1/1000 em
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Strip Portuguese diacritics to find the base glyph width
+fn base_char(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'ç' => 'c',
+        'Ç' => 'C',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        _ => c,
+    }
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            let c = base_char(c) as u32;
+            if (32..=126).contains(&c) {
+                HELVETICA_WIDTHS[(c - 32) as usize] as u32
+            } else {
+                556
+            }
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Greedy word wrap to `width` points
+fn wrap(text: &str, width: f32, size: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if text_width(&candidate, size) <= width || current.is_empty() {
+            current = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut current, word.to_string()));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Encode text as a PDF string literal in WinAnsiEncoding
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                c as u8
+            }
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        };
+        out.push(byte);
+    }
+    out.push(b')');
+    out
+}
+
+struct Layout {
+    pages: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    y: f32,
+    note_y: f32,
+    pending_notes: Vec<String>,
+    body_width: f32,
+}
+
+impl Layout {
+    fn new(has_notes: bool) -> Self {
+        let full_width = PAGE_WIDTH - 2.0 * MARGIN;
+        Self {
+            pages: Vec::new(),
+            current: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            note_y: PAGE_HEIGHT - MARGIN,
+            pending_notes: Vec::new(),
+            body_width: if has_notes {
+                full_width - NOTES_WIDTH - COLUMN_GAP
+            } else {
+                full_width
+            },
+        }
+    }
+
+    fn notes_x(&self) -> f32 {
+        MARGIN + self.body_width + COLUMN_GAP
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.current));
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.note_y = PAGE_HEIGHT - MARGIN;
+        let pending = std::mem::take(&mut self.pending_notes);
+        self.place_notes(pending);
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn draw_text(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.current.extend_from_slice(
+            format!("BT /{} {} Tf {:.1} {:.1} Td ", font.resource(), size, x, y).as_bytes(),
+        );
+        self.current.extend_from_slice(&pdf_string(text));
+        self.current.extend_from_slice(b" Tj ET\n");
+    }
+
+    fn draw_rule(&mut self, y: f32) {
+        self.current.extend_from_slice(
+            format!(
+                "0.6 w {:.1} {:.1} m {:.1} {:.1} l S\n",
+                MARGIN,
+                y,
+                MARGIN + self.body_width,
+                y
+            )
+            .as_bytes(),
+        );
+    }
+
+    /// Wrapped text in the body column
+    fn body(&mut self, text: &str, font: Font, size: f32, leading: f32) {
+        for line in wrap(text, self.body_width, size) {
+            self.ensure_space(leading);
+            self.y -= leading;
+            self.draw_text(MARGIN, self.y, font, size, &line);
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Margin-note lines, carried over to the next page when the column is full
+    fn place_notes(&mut self, lines: Vec<String>) {
+        let x = self.notes_x();
+        let mut lines = lines.into_iter();
+        while let Some(line) = lines.next() {
+            if self.note_y - NOTE_LEADING < MARGIN {
+                self.pending_notes.push(line);
+                self.pending_notes.extend(lines);
+                return;
+            }
+            self.note_y -= NOTE_LEADING;
+            if !line.is_empty() {
+                self.draw_text(x, self.note_y, Font::Regular, NOTE_SIZE, &line);
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<Vec<u8>> {
+        while !self.pending_notes.is_empty() {
+            self.new_page();
+        }
+        self.pages.push(self.current);
+        self.pages
+    }
+}
+
+fn layout(doc: &ExportDocument) -> Vec<Vec<u8>> {
+    let has_notes = doc.paragraphs.iter().any(|p| !p.annotations.is_empty());
+    let mut page = Layout::new(has_notes);
+
+    page.body(&doc.title, Font::Bold, 16.0, 20.0);
+    page.gap(4.0);
+    for line in &doc.metadata {
+        page.body(line, Font::Regular, 9.0, 12.0);
+    }
+    page.gap(10.0);
+
+    for paragraph in &doc.paragraphs {
+        // Numbered markers right after the corrected passage
+        let mut text = paragraph.text.clone();
+        let mut unanchored = Vec::new();
+        for annotation in paragraph.annotations.iter().rev() {
+            let marker = format!(" [{}]", annotation.number);
+            let found = Some(&annotation.original_text)
+                .filter(|original| !original.is_empty())
+                .and_then(|original| text.find(original.as_str()));
+            match found {
+                Some(idx) => text.insert_str(idx + annotation.original_text.len(), &marker),
+                None => unanchored.push(marker),
+            }
+        }
+        for marker in unanchored.into_iter().rev() {
+            text.push_str(&marker);
+        }
+
+        page.ensure_space(BODY_LEADING);
+        page.note_y = page.note_y.min(page.y);
+        let notes: Vec<String> = paragraph
+            .annotations
+            .iter()
+            .flat_map(|a| {
+                let note = format!("[{}] {}: {}", a.number, a.criterion, a.note);
+                let mut lines = wrap(&note, NOTES_WIDTH, NOTE_SIZE);
+                lines.push(String::new());
+                lines
+            })
+            .collect();
+        page.place_notes(notes);
+
+        page.body(&text, Font::Regular, BODY_SIZE, BODY_LEADING);
+        page.gap(6.0);
+    }
+
+    if !doc.rubric.is_empty() {
+        page.gap(8.0);
+        page.body("Notas por competência", Font::Bold, 13.0, 18.0);
+        for row in &doc.rubric {
+            page.ensure_space(30.0);
+            page.draw_rule(page.y - 4.0);
+            page.gap(18.0);
+            let y = page.y;
+            page.draw_text(MARGIN, y, Font::Bold, 10.0, &row.criterion);
+            let score = row.score.to_string();
+            let score_x = MARGIN + page.body_width - text_width(&score, 10.0);
+            page.draw_text(score_x, y, Font::Bold, 10.0, &score);
+            if let Some(feedback) = &row.feedback {
+                for line in feedback.lines() {
+                    page.body(line, Font::Regular, 9.0, 12.0);
+                }
+            }
+        }
+        page.draw_rule(page.y - 4.0);
+        page.gap(8.0);
+    }
+
+    if let Some(feedback) = &doc.feedback {
+        page.gap(8.0);
+        page.body("Feedback geral", Font::Bold, 13.0, 18.0);
+        for line in feedback.lines() {
+            if line.trim().is_empty() {
+                page.gap(6.0);
+            } else {
+                page.body(line, Font::Regular, 10.0, 13.0);
+            }
+        }
+    }
+
+    page.finish()
+}
+
+pub(crate) fn render(doc: &ExportDocument) -> Vec<u8> {
+    let pages = layout(doc);
+
+    // Objects: 1 catalog, 2 page tree, 3-4 fonts, then a page and a content stream per page
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 5 + 2 * i))
+        .collect();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes(),
+    );
+    objects.push(
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+    );
+    objects.push(
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+    );
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT, 6 + 2 * i
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"endstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = out.len();
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_respects_width() {
+        let text = "A educação pública de qualidade é um direito de todos os cidadãos brasileiros.";
+        let lines = wrap(text, 150.0, BODY_SIZE);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| text_width(l, BODY_SIZE) <= 150.0));
+        assert_eq!(lines.join(" "), text);
+    }
+
+    #[test]
+    fn test_pdf_string_encoding() {
+        assert_eq!(pdf_string("ação (1)"), b"(a\xE7\xE3o \\(1\\))".to_vec());
+    }
+}
@@ -0,0 +1,136 @@
+//! GGUF-quantized instruct model running on the CPU through candle
+
+use anyhow::{bail, Context, Result};
+use candle_core::quantized::gguf_file;
+use candle_core::{Device, Tensor};
+use candle_transformers::generation::LogitsProcessor;
+use candle_transformers::models::{quantized_llama, quantized_qwen2};
+use std::fs::File;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+use super::prompt::ChatFormat;
+
+/// Nucleus sampling cutoff
+const TOP_P: f64 = 0.9;
+
+enum Weights {
+    Llama(quantized_llama::ModelWeights),
+    Qwen2(quantized_qwen2::ModelWeights),
+}
+
+impl Weights {
+    fn forward(&mut self, input: &Tensor, index_pos: usize) -> candle_core::Result<Tensor> {
+        match self {
+            Weights::Llama(model) => model.forward(input, index_pos),
+            Weights::Qwen2(model) => model.forward(input, index_pos),
+        }
+    }
+}
+
+pub(crate) struct LocalLlm {
+    weights: Weights,
+    tokenizer: Tokenizer,
+    device: Device,
+    format: ChatFormat,
+    stop_tokens: Vec<u32>,
+}
+
+impl LocalLlm {
+    /// Load quantized weights and the matching `tokenizer.json`
+    pub fn load(model_path: &Path, tokenizer_path: &Path) -> Result<Self> {
+        let device = Device::Cpu;
+        let mut file = File::open(model_path)
+            .with_context(|| format!("Failed to open {}", model_path.display()))?;
+        let content = gguf_file::Content::read(&mut file)
+            .map_err(|e| anyhow::anyhow!("Invalid GGUF file: {}", e))?;
+
+        let architecture = content
+            .metadata
+            .get("general.architecture")
+            .and_then(|v| v.to_string().ok())
+            .cloned()
+            .unwrap_or_else(|| "llama".to_string());
+        let weights = match architecture.as_str() {
+            "llama" | "mistral" => {
+                Weights::Llama(quantized_llama::ModelWeights::from_gguf(content, &mut file, &device)?)
+            }
+            "qwen2" => {
+                Weights::Qwen2(quantized_qwen2::ModelWeights::from_gguf(content, &mut file, &device)?)
+            }
+            other => bail!("Unsupported model architecture: {}", other),
+        };
+
+        let tokenizer = Tokenizer::from_file(tokenizer_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+
+        let format = if tokenizer.token_to_id("<|im_start|>").is_some() {
+            ChatFormat::ChatMl
+        } else if tokenizer.token_to_id("<|start_header_id|>").is_some() {
+            ChatFormat::Llama3
+        } else {
+            ChatFormat::Inst
+        };
+        let stop_tokens = format
+            .stop_tokens()
+            .iter()
+            .filter_map(|t| tokenizer.token_to_id(t))
+            .collect();
+
+        Ok(Self {
+            weights,
+            tokenizer,
+            device,
+            format,
+            stop_tokens,
+        })
+    }
+
+    /// Generate the assistant's answer, calling `on_token` with each new piece of text
+    pub fn generate(
+        &mut self,
+        system: &str,
+        user: &str,
+        max_tokens: usize,
+        temperature: f64,
+        seed: u64,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        let prompt = self.format.render(system, user);
+        let encoding = self
+            .tokenizer
+            .encode(prompt, false)
+            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+
+        let mut processor = LogitsProcessor::new(seed, Some(temperature), Some(TOP_P));
+        let mut input = encoding.get_ids().to_vec();
+        let mut index_pos = 0;
+        let mut generated = Vec::new();
+        let mut text = String::new();
+
+        for _ in 0..max_tokens {
+            let x = Tensor::new(input.as_slice(), &self.device)?.unsqueeze(0)?;
+            let logits = self.weights.forward(&x, index_pos)?.squeeze(0)?;
+            index_pos += input.len();
+
+            let next = processor.sample(&logits)?;
+            if self.stop_tokens.contains(&next) {
+                break;
+            }
+            generated.push(next);
+            input = vec![next];
+
+            // Decode everything so far so multi-token characters come out whole
+            let decoded = self
+                .tokenizer
+                .decode(&generated, true)
+                .map_err(|e| anyhow::anyhow!("Decoding failed: {}", e))?;
+            if decoded.len() > text.len() && decoded.starts_with(&text) && !decoded.ends_with('\u{FFFD}') {
+                on_token(&decoded[text.len()..]);
+                text = decoded;
+            }
+        }
+
+        Ok(text)
+    }
+}
@@ -0,0 +1,235 @@
+//! Personalized competency feedback from a local quantized LLM
+//!
+//! Entirely optional: without a GGUF model configured in `AIConfiguration`
+//! the evaluation keeps the template feedback of `EvaluationService`.
+
+mod llm;
+mod prompt;
+
+pub use prompt::{build_feedback_prompt, parse_generated_feedback, PromptTemplate};
+
+use anyhow::{Context, Result};
+use domain::essay::Essay;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::ai_config::{AIConfigManager, AIConfiguration};
+use llm::LocalLlm;
+
+/// Heading of the rewritten paragraph appended to the overall feedback
+const REWRITE_HEADING: &str = "\n\nSugestão de reescrita:\n";
+
+/// Feedback written by the local model
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerativeFeedback {
+    /// Criterion name to personalized comment
+    pub competencies: HashMap<String, String>,
+    pub rewritten_paragraph: Option<String>,
+    /// Unparsed model output
+    pub raw: String,
+}
+
+impl GenerativeFeedback {
+    /// Replace template comments with generated ones, keeping templates for missing criteria
+    ///
+    /// A rewritten paragraph replaces the one from an earlier generation.
+    pub fn apply_to(&self, essay: &mut Essay) {
+        if let Some(rubric) = essay.rubric_scores.as_mut() {
+            for (criterion, comment) in &self.competencies {
+                if rubric.scores.contains_key(criterion) {
+                    rubric.detailed_feedback.insert(criterion.clone(), comment.clone());
+                }
+            }
+        }
+        if let Some(paragraph) = &self.rewritten_paragraph {
+            let feedback = essay.feedback.get_or_insert_with(String::new);
+            if let Some(start) = feedback.find(REWRITE_HEADING) {
+                feedback.truncate(start);
+            }
+            feedback.push_str(REWRITE_HEADING);
+            feedback.push_str(paragraph);
+        }
+    }
+}
+
+/// Progress of a streamed generation
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedbackEvent {
+    Token(String),
+    Done(GenerativeFeedback),
+    Error(String),
+}
+
+/// Where the local model lives and how to sample from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerativeFeedbackConfig {
+    pub model_path: PathBuf,
+    /// Defaults to `tokenizer.json` next to the model file
+    pub tokenizer_path: PathBuf,
+    pub max_tokens: usize,
+    pub temperature: f64,
+    pub seed: u64,
+}
+
+impl GenerativeFeedbackConfig {
+    /// Config for the model set in `AIConfiguration`, if its files exist
+    pub fn from_ai_config(config: &AIConfiguration) -> Option<Self> {
+        let model_path = PathBuf::from(config.feedback_model_path.as_ref()?);
+        let tokenizer_path = model_path.with_file_name("tokenizer.json");
+        if !model_path.exists() || !tokenizer_path.exists() {
+            return None;
+        }
+        Some(Self {
+            model_path,
+            tokenizer_path,
+            max_tokens: 900,
+            temperature: 0.7,
+            seed: 42,
+        })
+    }
+}
+
+/// Loaded model shared by every generation
+#[derive(Clone)]
+pub struct FeedbackGenerator {
+    llm: Arc<Mutex<LocalLlm>>,
+    config: GenerativeFeedbackConfig,
+}
+
+static SHARED_GENERATOR: Lazy<Mutex<Option<FeedbackGenerator>>> = Lazy::new(|| Mutex::new(None));
+
+impl FeedbackGenerator {
+    pub fn load(config: GenerativeFeedbackConfig) -> Result<Self> {
+        let llm = LocalLlm::load(&config.model_path, &config.tokenizer_path)
+            .context("Failed to load feedback model")?;
+        Ok(Self {
+            llm: Arc::new(Mutex::new(llm)),
+            config,
+        })
+    }
+
+    /// Generator for the configured model, loaded once per process
+    ///
+    /// Blocking: the first call reads the whole model file.
+    pub fn shared() -> Option<Self> {
+        let mut cached = SHARED_GENERATOR.lock().ok()?;
+        let config = AIConfigManager::new()
+            .and_then(|m| m.load())
+            .ok()
+            .and_then(|c| GenerativeFeedbackConfig::from_ai_config(&c))?;
+
+        if cached.as_ref().is_some_and(|g| g.config == config) {
+            return cached.clone();
+        }
+        match Self::load(config) {
+            Ok(generator) => {
+                *cached = Some(generator.clone());
+                Some(generator)
+            }
+            Err(e) => {
+                tracing::warn!("Generative feedback unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether a model is configured, without loading it
+    pub fn is_configured() -> bool {
+        AIConfigManager::new()
+            .and_then(|m| m.load())
+            .ok()
+            .and_then(|c| GenerativeFeedbackConfig::from_ai_config(&c))
+            .is_some()
+    }
+
+    /// Write feedback for an evaluated essay (blocking)
+    pub fn generate(&self, essay: &Essay, on_token: &mut dyn FnMut(&str)) -> Result<GenerativeFeedback> {
+        let scores = essay
+            .rubric_scores
+            .as_ref()
+            .context("Essay has not been evaluated")?;
+        let (system, user) = build_feedback_prompt(essay, scores);
+
+        let mut llm = self
+            .llm
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Feedback model lock poisoned"))?;
+        let raw = llm.generate(
+            &system,
+            &user,
+            self.config.max_tokens,
+            self.config.temperature,
+            self.config.seed,
+            on_token,
+        )?;
+        Ok(parse_generated_feedback(&raw))
+    }
+
+    /// Generate on the blocking pool, streaming tokens as they are produced
+    pub fn stream(&self, essay: Essay) -> mpsc::UnboundedReceiver<FeedbackEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let generator = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let token_tx = tx.clone();
+            let mut on_token = |token: &str| {
+                let _ = token_tx.send(FeedbackEvent::Token(token.to_string()));
+            };
+            let event = match generator.generate(&essay, &mut on_token) {
+                Ok(feedback) => FeedbackEvent::Done(feedback),
+                Err(e) => FeedbackEvent::Error(e.to_string()),
+            };
+            let _ = tx.send(event);
+        });
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::essay::{EssayStatus, ExamType, RubricScores};
+    use uuid::Uuid;
+
+    fn graded_essay() -> Essay {
+        let mut essay = Essay::new(Uuid::new_v4(), "Tema", "Texto.", ExamType::Enem);
+        essay.status = EssayStatus::Corrigida;
+        essay.score = Some(280);
+        essay.feedback = Some("Pontuação total: 280/1000".to_string());
+        essay.rubric_scores = Some(RubricScores {
+            scores: HashMap::from([("C1".to_string(), 160), ("C2".to_string(), 120)]),
+            detailed_feedback: HashMap::from([
+                ("C1".to_string(), "modelo C1".to_string()),
+                ("C2".to_string(), "modelo C2".to_string()),
+            ]),
+        });
+        essay
+    }
+
+    #[test]
+    fn test_apply_keeps_templates_for_missing_criteria() {
+        let mut essay = graded_essay();
+        let generated = parse_generated_feedback("[C1]\nComentário gerado.\n[C9]\nIgnorado.\n[REESCRITA]\nNovo parágrafo.");
+        generated.apply_to(&mut essay);
+
+        let rubric = essay.rubric_scores.unwrap();
+        assert_eq!(rubric.detailed_feedback["C1"], "Comentário gerado.");
+        assert_eq!(rubric.detailed_feedback["C2"], "modelo C2");
+        assert!(!rubric.detailed_feedback.contains_key("C9"));
+        assert!(essay.feedback.unwrap().ends_with("Sugestão de reescrita:\nNovo parágrafo."));
+    }
+
+    #[test]
+    fn test_apply_twice_replaces_the_rewrite() {
+        let mut essay = graded_essay();
+        parse_generated_feedback("[REESCRITA]\nPrimeira versão.").apply_to(&mut essay);
+        parse_generated_feedback("[REESCRITA]\nSegunda versão.").apply_to(&mut essay);
+
+        assert_eq!(
+            essay.feedback.unwrap(),
+            "Pontuação total: 280/1000\n\nSugestão de reescrita:\nSegunda versão."
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Prompt templates per exam and parsing of the model's answer
+
+use domain::essay::{Essay, ExamType, RubricScores};
+
+use super::GenerativeFeedback;
+use crate::rubrics::get_rubric;
+
+/// Section header the model uses for the rewritten paragraph
+const REWRITE_SECTION: &str = "REESCRITA";
+
+const SYSTEM_PROMPT: &str = "Você é um corretor experiente de redações de vestibular. \
+Escreva em português do Brasil, em tom encorajador e específico. \
+Sempre cite entre aspas trechos do próprio texto do aluno para justificar cada comentário.";
+
+/// Exam-specific guidance added to the prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub exam_label: &'static str,
+    pub genre: &'static str,
+    pub guidance: &'static str,
+}
+
+impl PromptTemplate {
+    pub fn for_exam(exam_type: &ExamType) -> Self {
+        match exam_type {
+            ExamType::Enem => Self {
+                exam_label: "ENEM",
+                genre: "texto dissertativo-argumentativo",
+                guidance: "Avalie as cinco competências da matriz do ENEM. Na C5, verifique se a proposta \
+                           de intervenção tem agente, ação, meio, finalidade e detalhamento e se respeita \
+                           os direitos humanos.",
+            },
+            ExamType::Fuvest => Self {
+                exam_label: "FUVEST",
+                genre: "dissertação em prosa",
+                guidance: "A FUVEST valoriza a reflexão crítica a partir da coletânea e não exige proposta \
+                           de intervenção. Observe a profundidade da análise e a progressão das ideias.",
+            },
+            ExamType::Unicamp => Self {
+                exam_label: "UNICAMP",
+                genre: "gênero discursivo pedido na proposta",
+                guidance: "Verifique a adequação ao gênero, ao interlocutor e ao propósito solicitados, \
+                           além da leitura dos textos de apoio.",
+            },
+            ExamType::Unesp => Self {
+                exam_label: "UNESP",
+                genre: "texto dissertativo-argumentativo",
+                guidance: "Avalie o desenvolvimento do tema, a estrutura, a coesão e o uso da norma culta.",
+            },
+            _ => Self {
+                exam_label: "vestibular",
+                genre: "texto dissertativo",
+                guidance: "Avalie tema, argumentação, coesão e norma culta.",
+            },
+        }
+    }
+}
+
+/// Criteria in rubric order, falling back to the scored ones
+fn criteria(exam_type: &ExamType, scores: &RubricScores) -> Vec<(String, String)> {
+    match get_rubric(exam_type) {
+        Some(rubric) => rubric
+            .criteria
+            .iter()
+            .map(|c| (c.name.clone(), c.description.clone()))
+            .collect(),
+        None => {
+            let mut names: Vec<_> = scores.scores.keys().cloned().collect();
+            names.sort();
+            names.into_iter().map(|n| (n, String::new())).collect()
+        }
+    }
+}
+
+/// System and user messages asking for feedback on an evaluated essay
+pub fn build_feedback_prompt(essay: &Essay, scores: &RubricScores) -> (String, String) {
+    let template = PromptTemplate::for_exam(&essay.exam_type);
+    let criteria = criteria(&essay.exam_type, scores);
+
+    let mut user = format!(
+        "Redação do {} ({}), título: \"{}\".\n{}\n\nNotas atribuídas:\n",
+        template.exam_label, template.genre, essay.title, template.guidance
+    );
+    for (name, description) in &criteria {
+        let score = scores.scores.get(name).copied().unwrap_or(0);
+        if description.is_empty() {
+            user.push_str(&format!("- {}: {} pontos\n", name, score));
+        } else {
+            user.push_str(&format!("- {} ({}): {} pontos\n", name, description, score));
+        }
+    }
+    user.push_str(&format!("\nTexto do aluno:\n\"\"\"\n{}\n\"\"\"\n\n", essay.content.trim()));
+    user.push_str(
+        "Para cada critério, explique a nota citando frases do aluno e diga o que fazer para subir de nível. \
+         Depois, reescreva o parágrafo mais fraco do texto mantendo as ideias do aluno.\n\
+         Responda exatamente neste formato, sem texto fora das seções:\n",
+    );
+    for (name, _) in &criteria {
+        user.push_str(&format!("[{}]\n<comentário>\n", name));
+    }
+    user.push_str(&format!("[{}]\n<parágrafo reescrito>\n", REWRITE_SECTION));
+
+    (SYSTEM_PROMPT.to_string(), user)
+}
+
+/// Split the model's answer into its `[SECTION]` blocks
+pub fn parse_generated_feedback(raw: &str) -> GenerativeFeedback {
+    fn flush(section: &Option<String>, body: &mut String, feedback: &mut GenerativeFeedback) {
+        let text = body.trim().to_string();
+        body.clear();
+        match section {
+            Some(name) if !text.is_empty() => {
+                if name.eq_ignore_ascii_case(REWRITE_SECTION) {
+                    feedback.rewritten_paragraph = Some(text);
+                } else {
+                    feedback.competencies.insert(name.clone(), text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut feedback = GenerativeFeedback {
+        raw: raw.to_string(),
+        ..Default::default()
+    };
+    let mut section: Option<String> = None;
+    let mut body = String::new();
+
+    for line in raw.lines() {
+        let trimmed = line.trim();
+        let header = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .filter(|name| !name.is_empty() && name.len() <= 32);
+        match header {
+            Some(name) => {
+                flush(&section, &mut body, &mut feedback);
+                section = Some(name.trim().to_string());
+            }
+            None => {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+    }
+    flush(&section, &mut body, &mut feedback);
+    feedback
+}
+
+/// Chat markup expected by the instruct model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChatFormat {
+    /// Qwen and other `<|im_start|>` models
+    ChatMl,
+    Llama3,
+    /// Mistral/Llama 2 `[INST]` markup
+    Inst,
+}
+
+impl ChatFormat {
+    pub fn render(&self, system: &str, user: &str) -> String {
+        match self {
+            ChatFormat::ChatMl => format!(
+                "<|im_start|>system\n{}<|im_end|>\n<|im_start|>user\n{}<|im_end|>\n<|im_start|>assistant\n",
+                system, user
+            ),
+            ChatFormat::Llama3 => format!(
+                "<|begin_of_text|><|start_header_id|>system<|end_header_id|>\n\n{}<|eot_id|>\
+                 <|start_header_id|>user<|end_header_id|>\n\n{}<|eot_id|>\
+                 <|start_header_id|>assistant<|end_header_id|>\n\n",
+                system, user
+            ),
+            ChatFormat::Inst => format!("<s>[INST] {}\n\n{} [/INST]", system, user),
+        }
+    }
+
+    /// Tokens that end the assistant turn
+    pub fn stop_tokens(&self) -> &'static [&'static str] {
+        match self {
+            ChatFormat::ChatMl => &["<|im_end|>", "<|endoftext|>"],
+            ChatFormat::Llama3 => &["<|eot_id|>", "<|end_of_text|>"],
+            ChatFormat::Inst => &["</s>"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::essay::EssayStatus;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn essay() -> Essay {
+        let mut essay = Essay::new(Uuid::new_v4(), "Evasão escolar", "A evasão escolar é grave.", ExamType::Enem);
+        essay.status = EssayStatus::Corrigida;
+        essay.score = Some(600);
+        essay
+    }
+
+    #[test]
+    fn test_prompt_lists_every_criterion() {
+        let scores = RubricScores {
+            scores: HashMap::from([("C1".to_string(), 120), ("C5".to_string(), 40)]),
+            detailed_feedback: HashMap::new(),
+        };
+        let (system, user) = build_feedback_prompt(&essay(), &scores);
+        assert!(system.contains("cite entre aspas"));
+        assert!(user.contains("ENEM"));
+        assert!(user.contains("C5 (Proposta de intervenção respeitando direitos humanos): 40 pontos"));
+        assert!(user.contains("A evasão escolar é grave."));
+        for section in ["[C1]", "[C2]", "[C3]", "[C4]", "[C5]", "[REESCRITA]"] {
+            assert!(user.contains(section));
+        }
+    }
+
+    #[test]
+    fn test_parse_generated_feedback() {
+        let raw = "[C1]\nBom uso da norma: \"A evasão escolar é grave.\"\n\n[C5]\nFalta agente.\n[REESCRITA]\nO Ministério da Educação deve agir.\n";
+        let feedback = parse_generated_feedback(raw);
+        assert_eq!(feedback.competencies.len(), 2);
+        assert_eq!(feedback.competencies["C5"], "Falta agente.");
+        assert!(feedback.competencies["C1"].contains("\"A evasão escolar é grave.\""));
+        assert_eq!(
+            feedback.rewritten_paragraph.as_deref(),
+            Some("O Ministério da Educação deve agir.")
+        );
+    }
+
+    #[test]
+    fn test_chat_format_render() {
+        let prompt = ChatFormat::ChatMl.render("sys", "user");
+        assert!(prompt.ends_with("<|im_start|>assistant\n"));
+        assert!(ChatFormat::Llama3.render("sys", "user").contains("<|eot_id|>"));
+    }
+}
@@ -0,0 +1,110 @@
+//! Measurements for comparing encoder backends and precisions
+
+use anyhow::Result;
+use std::time::Instant;
+
+use super::EncoderBackend;
+
+/// Per-text latency of one encoder over a corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncoderTiming {
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+    /// `[CLS]` embeddings in corpus order
+    pub embeddings: Vec<Vec<f32>>,
+}
+
+/// Embed every text once and record how long each took
+pub fn time_encoder(encoder: &dyn EncoderBackend, texts: &[String]) -> Result<EncoderTiming> {
+    let mut latencies = Vec::with_capacity(texts.len());
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for text in texts {
+        let start = Instant::now();
+        embeddings.push(encoder.embed(text)?);
+        latencies.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    let mean_ms = if latencies.is_empty() {
+        0.0
+    } else {
+        latencies.iter().sum::<f64>() / latencies.len() as f64
+    };
+    latencies.sort_by(|a, b| a.total_cmp(b));
+    let p95_ms = latencies
+        .get((latencies.len() as f64 * 0.95).ceil() as usize)
+        .or(latencies.last())
+        .copied()
+        .unwrap_or(0.0);
+
+    Ok(EncoderTiming { mean_ms, p95_ms, embeddings })
+}
+
+/// How far a quantized encoder's embeddings moved from the reference
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmbeddingDrift {
+    pub mean_cosine: f32,
+    pub min_cosine: f32,
+    pub max_abs_diff: f32,
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+pub fn embedding_drift(reference: &[Vec<f32>], candidate: &[Vec<f32>]) -> EmbeddingDrift {
+    let cosines: Vec<f32> = reference
+        .iter()
+        .zip(candidate)
+        .map(|(a, b)| cosine_similarity(a, b))
+        .collect();
+    let max_abs_diff = reference
+        .iter()
+        .zip(candidate)
+        .flat_map(|(a, b)| a.iter().zip(b).map(|(x, y)| (x - y).abs()))
+        .fold(0.0, f32::max);
+
+    EmbeddingDrift {
+        mean_cosine: if cosines.is_empty() { 1.0 } else { cosines.iter().sum::<f32>() / cosines.len() as f32 },
+        min_cosine: cosines.iter().copied().fold(1.0, f32::min),
+        max_abs_diff,
+    }
+}
+
+/// Resident set size of this process, where the platform exposes it
+pub fn resident_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_embedding_drift() {
+        let reference = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let same = embedding_drift(&reference, &reference);
+        assert_eq!(same.min_cosine, 1.0);
+        assert_eq!(same.max_abs_diff, 0.0);
+
+        let moved = embedding_drift(&reference, &[vec![1.0, 0.0], vec![0.5, 1.0]]);
+        assert!(moved.min_cosine < 0.95);
+        assert!(moved.mean_cosine > moved.min_cosine);
+        assert_eq!(moved.max_abs_diff, 0.5);
+    }
+}
@@ -0,0 +1,64 @@
+//! BERT forward pass through candle
+
+use anyhow::Result;
+use candle_core::{DType, Device, Tensor};
+use candle_transformers::models::bert::{BertModel, Config as BertConfig};
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+use super::weights::var_builder;
+use super::{load_tokenizer, EncodedInput, EncoderBackend, PaddedBatch};
+use crate::ai_config::InferenceBackend;
+
+pub struct CandleBackend {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+}
+
+impl CandleBackend {
+    pub fn new(model: BertModel, tokenizer: Tokenizer, device: Device) -> Self {
+        Self { model, tokenizer, device }
+    }
+
+    /// Load `config.json`, `tokenizer.json` and the weights, memory-mapping
+    /// safetensors and reading anything else as a PyTorch checkpoint
+    pub fn load(config_path: &Path, tokenizer_path: &Path, weights_path: &Path, device: &Device) -> Result<Self> {
+        let tokenizer = load_tokenizer(tokenizer_path)?;
+        let config: BertConfig = serde_json::from_str(&std::fs::read_to_string(config_path)?)?;
+        let vb = var_builder(weights_path, DType::F32, device)?;
+        let model = BertModel::load(vb, &config)?;
+        Ok(Self::new(model, tokenizer, device.clone()))
+    }
+}
+
+impl EncoderBackend for CandleBackend {
+    fn kind(&self) -> InferenceBackend {
+        InferenceBackend::Candle
+    }
+
+    fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    fn forward(&self, input: &EncodedInput) -> Result<Vec<Vec<f32>>> {
+        let ids = Tensor::new(input.ids.as_slice(), &self.device)?.unsqueeze(0)?;
+        let type_ids = Tensor::new(input.type_ids.as_slice(), &self.device)?.unsqueeze(0)?;
+        let mask = Tensor::new(input.attention_mask.as_slice(), &self.device)?.unsqueeze(0)?;
+        let hidden = self.model.forward(&ids, &type_ids, Some(&mask))?;
+        Ok(hidden.squeeze(0)?.to_vec2::<f32>()?)
+    }
+
+    fn forward_batch(&self, inputs: &[EncodedInput]) -> Result<Vec<Vec<Vec<f32>>>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let batch = PaddedBatch::new(inputs);
+        let shape = (inputs.len(), batch.len);
+        let ids = Tensor::from_vec(batch.ids, shape, &self.device)?;
+        let type_ids = Tensor::from_vec(batch.type_ids, shape, &self.device)?;
+        let mask = Tensor::from_vec(batch.attention_mask, shape, &self.device)?;
+        let hidden = self.model.forward(&ids, &type_ids, Some(&mask))?;
+        Ok(PaddedBatch::unpad(hidden.to_vec3::<f32>()?, inputs))
+    }
+}
@@ -0,0 +1,228 @@
+//! Encoder backends
+//!
+//! A backend tokenizes text and runs the encoder forward pass. candle is the
+//! default; tract runs an exported ONNX model without native dependencies.
+//! Both use the same `tokenizer.json`, so only the forward pass differs.
+//! The candle backend can also run weights quantized to F16, Q8 or Q4.
+
+pub mod bench;
+mod candle;
+mod onnx;
+mod quantized;
+mod weights;
+mod worker;
+
+pub use self::candle::CandleBackend;
+pub use self::onnx::OnnxBackend;
+pub use self::quantized::{
+    ggml_dtype, quantize_checkpoint, quantize_tensors, write_quantized, QuantizationSummary, QuantizedBackend,
+};
+pub use self::weights::{
+    convert_pth_to_safetensors, detect_weight_formats, find_weights, prepare_weights, read_tensors, var_builder,
+    ConversionError, WeightSource,
+};
+pub use self::worker::{
+    CancelToken, InferenceWorker, JobHandle, JobOptions, JobPriority, WorkerError, DEFAULT_BATCH_SIZE,
+};
+
+use anyhow::{Context, Result};
+use tokenizers::{Encoding, Tokenizer, TruncationParams};
+
+use crate::ai_config::InferenceBackend;
+
+/// Token ids of one input sequence, with the `[CLS]` and `[SEP]` markers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedInput {
+    pub ids: Vec<u32>,
+    pub type_ids: Vec<u32>,
+    pub attention_mask: Vec<u32>,
+}
+
+impl EncodedInput {
+    pub fn from_encoding(encoding: &Encoding) -> Self {
+        Self {
+            ids: encoding.get_ids().to_vec(),
+            type_ids: encoding.get_type_ids().to_vec(),
+            attention_mask: encoding.get_attention_mask().to_vec(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// How the token states of a text are reduced to one embedding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Pooling {
+    /// State of the `[CLS]` token
+    #[default]
+    Cls,
+    /// Average of the token states
+    Mean,
+}
+
+impl Pooling {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Pooling::Cls => "cls",
+            Pooling::Mean => "mean",
+        }
+    }
+}
+
+pub trait EncoderBackend: Send + Sync {
+    fn kind(&self) -> InferenceBackend;
+
+    fn tokenizer(&self) -> &Tokenizer;
+
+    /// Last hidden state, one row of `hidden_size` values per token
+    fn forward(&self, input: &EncodedInput) -> Result<Vec<Vec<f32>>>;
+
+    /// Last hidden states of several inputs, without the padding rows
+    ///
+    /// Backends that can pad a batch into one forward pass override this.
+    fn forward_batch(&self, inputs: &[EncodedInput]) -> Result<Vec<Vec<Vec<f32>>>> {
+        inputs.iter().map(|input| self.forward(input)).collect()
+    }
+
+    fn encode(&self, text: &str) -> Result<EncodedInput> {
+        let encoding = self
+            .tokenizer()
+            .encode(text, true)
+            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+        Ok(EncodedInput::from_encoding(&encoding))
+    }
+
+    /// Embedding of the `[CLS]` token
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let input = self.encode(text)?;
+        self.forward(&input)?
+            .into_iter()
+            .next()
+            .context("Encoder returned no tokens")
+    }
+
+    /// `[CLS]` embeddings of several texts, in order
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let inputs = texts.iter().map(|text| self.encode(text)).collect::<Result<Vec<_>>>()?;
+        self.forward_batch(&inputs)?
+            .into_iter()
+            .map(|hidden| hidden.into_iter().next().context("Encoder returned no tokens"))
+            .collect()
+    }
+
+    /// Embeddings of several texts with the given pooling, in order
+    fn pool_batch(&self, texts: &[String], pooling: Pooling) -> Result<Vec<Vec<f32>>> {
+        if pooling == Pooling::Cls {
+            return self.embed_batch(texts);
+        }
+        let inputs = texts.iter().map(|text| self.encode(text)).collect::<Result<Vec<_>>>()?;
+        self.forward_batch(&inputs)?.into_iter().map(|hidden| mean_pool(&hidden)).collect()
+    }
+}
+
+/// Average of the rows of a hidden state
+fn mean_pool(hidden: &[Vec<f32>]) -> Result<Vec<f32>> {
+    let first = hidden.first().context("Encoder returned no tokens")?;
+    let mut sum = vec![0.0; first.len()];
+    for row in hidden {
+        for (total, value) in sum.iter_mut().zip(row) {
+            *total += value;
+        }
+    }
+    let count = hidden.len() as f32;
+    Ok(sum.into_iter().map(|total| total / count).collect())
+}
+
+/// Inputs padded to the longest one, flattened row by row
+struct PaddedBatch {
+    ids: Vec<u32>,
+    type_ids: Vec<u32>,
+    attention_mask: Vec<u32>,
+    len: usize,
+}
+
+impl PaddedBatch {
+    fn new(inputs: &[EncodedInput]) -> Self {
+        let len = inputs.iter().map(EncodedInput::len).max().unwrap_or(0);
+        let pad = |values: &[u32], out: &mut Vec<u32>| {
+            out.extend_from_slice(values);
+            out.resize(out.len() + len - values.len(), 0);
+        };
+        let mut batch = Self {
+            ids: Vec::with_capacity(inputs.len() * len),
+            type_ids: Vec::with_capacity(inputs.len() * len),
+            attention_mask: Vec::with_capacity(inputs.len() * len),
+            len,
+        };
+        for input in inputs {
+            pad(&input.ids, &mut batch.ids);
+            pad(&input.type_ids, &mut batch.type_ids);
+            pad(&input.attention_mask, &mut batch.attention_mask);
+        }
+        batch
+    }
+
+    /// Drop the padding rows of each input's hidden state
+    fn unpad(hidden: Vec<Vec<Vec<f32>>>, inputs: &[EncodedInput]) -> Vec<Vec<Vec<f32>>> {
+        hidden
+            .into_iter()
+            .zip(inputs)
+            .map(|(mut rows, input)| {
+                rows.truncate(input.len());
+                rows
+            })
+            .collect()
+    }
+}
+
+/// Longest input of the encoders, in tokens; longer ones overflow the position embeddings
+pub const MAX_SEQUENCE_LENGTH: usize = 512;
+
+/// Load `tokenizer.json`, truncating inputs to `MAX_SEQUENCE_LENGTH` tokens
+/// (or the tokenizer's own limit, if lower)
+fn load_tokenizer(path: &std::path::Path) -> Result<Tokenizer> {
+    let mut tokenizer = Tokenizer::from_file(path).map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+    let max_length = tokenizer
+        .get_truncation()
+        .map_or(MAX_SEQUENCE_LENGTH, |truncation| truncation.max_length.min(MAX_SEQUENCE_LENGTH));
+    tokenizer
+        .with_truncation(Some(TruncationParams { max_length, ..Default::default() }))
+        .map_err(|e| anyhow::anyhow!("Failed to configure tokenizer truncation: {}", e))?;
+    Ok(tokenizer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Word-level tokenizer that knows one word
+    const TOKENIZER_JSON: &str = r#"{
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": null,
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": null,
+        "decoder": null,
+        "model": {"type": "WordLevel", "vocab": {"[UNK]": 0, "palavra": 1}, "unk_token": "[UNK]"}
+    }"#;
+
+    #[test]
+    fn test_long_inputs_are_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokenizer.json");
+        std::fs::write(&path, TOKENIZER_JSON).unwrap();
+
+        let tokenizer = load_tokenizer(&path).unwrap();
+        let long = "palavra ".repeat(2000);
+        assert_eq!(tokenizer.encode(long.as_str(), true).unwrap().len(), MAX_SEQUENCE_LENGTH);
+        assert_eq!(tokenizer.encode("palavra palavra", true).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,94 @@
+//! Encoder exported to ONNX, run by tract
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use tokenizers::Tokenizer;
+use tract_onnx::prelude::*;
+
+use super::{load_tokenizer, EncodedInput, EncoderBackend};
+use crate::ai_config::InferenceBackend;
+
+/// Graph inputs, in the order the model declares them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnnxInput {
+    InputIds,
+    AttentionMask,
+    TokenTypeIds,
+}
+
+pub struct OnnxBackend {
+    plan: TypedRunnableModel<TypedModel>,
+    inputs: Vec<OnnxInput>,
+    tokenizer: Tokenizer,
+}
+
+impl OnnxBackend {
+    /// Load an ONNX model with `input_ids` and optional `attention_mask` and
+    /// `token_type_ids` inputs whose first output is the last hidden state
+    pub fn load(model_path: &Path, tokenizer_path: &Path) -> Result<Self> {
+        let tokenizer = load_tokenizer(tokenizer_path)?;
+        let model = tract_onnx::onnx()
+            .model_for_path(model_path)
+            .with_context(|| format!("Failed to read ONNX model {}", model_path.display()))?;
+
+        let inputs = model
+            .input_outlets()?
+            .iter()
+            .map(|outlet| match model.node(outlet.node).name.as_str() {
+                "input_ids" => Ok(OnnxInput::InputIds),
+                "attention_mask" => Ok(OnnxInput::AttentionMask),
+                "token_type_ids" => Ok(OnnxInput::TokenTypeIds),
+                other => bail!("Unsupported ONNX model input {}", other),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !inputs.contains(&OnnxInput::InputIds) {
+            bail!("ONNX model has no input_ids input");
+        }
+
+        let plan = model.into_optimized()?.into_runnable()?;
+        Ok(Self { plan, inputs, tokenizer })
+    }
+}
+
+impl EncoderBackend for OnnxBackend {
+    fn kind(&self) -> InferenceBackend {
+        InferenceBackend::Onnx
+    }
+
+    fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    fn forward(&self, input: &EncodedInput) -> Result<Vec<Vec<f32>>> {
+        let tensor = |values: &[u32]| -> Result<TValue> {
+            let values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
+            Ok(Tensor::from_shape(&[1, values.len()], &values)?.into())
+        };
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|kind| match kind {
+                OnnxInput::InputIds => tensor(&input.ids),
+                OnnxInput::AttentionMask => tensor(&input.attention_mask),
+                OnnxInput::TokenTypeIds => tensor(&input.type_ids),
+            })
+            .collect::<Result<TVec<_>>>()?;
+
+        let outputs = self.plan.run(inputs)?;
+        let hidden = outputs
+            .first()
+            .context("ONNX model returned no outputs")?
+            .to_array_view::<f32>()?;
+        let shape = hidden.shape();
+        if shape.len() != 3 || shape[1] != input.len() {
+            bail!("Unexpected ONNX output shape {:?}", shape);
+        }
+        Ok(hidden
+            .outer_iter()
+            .next()
+            .context("ONNX output has an empty batch")?
+            .outer_iter()
+            .map(|row| row.iter().copied().collect())
+            .collect())
+    }
+}
@@ -0,0 +1,302 @@
+//! BERT encoder with quantized weights
+//!
+//! The PyTorch checkpoint is converted once into a GGUF file: weight
+//! matrices become F16 or 8/4-bit blocks, vectors stay F32, and the
+//! original `config.json` travels in the file metadata.
+
+use anyhow::{bail, Context, Result};
+use candle_core::quantized::{gguf_file, GgmlDType, QTensor};
+use candle_core::{DType, Device, Module, Tensor};
+use candle_nn::LayerNorm;
+use candle_transformers::models::bert::{Config as BertConfig, HiddenAct};
+use candle_transformers::quantized_nn::{layer_norm, linear, Embedding, Linear};
+use candle_transformers::quantized_var_builder::VarBuilder;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tokenizers::Tokenizer;
+
+use super::weights::read_tensors;
+use super::{load_tokenizer, EncodedInput, EncoderBackend, PaddedBatch};
+use crate::ai_config::{InferenceBackend, QuantizationLevel};
+
+const CONFIG_KEY: &str = "bert.config_json";
+const LEVEL_KEY: &str = "neuronexus.quantization";
+
+pub fn ggml_dtype(level: QuantizationLevel) -> GgmlDType {
+    match level {
+        QuantizationLevel::F32 => GgmlDType::F32,
+        QuantizationLevel::F16 => GgmlDType::F16,
+        QuantizationLevel::Q8 => GgmlDType::Q8_0,
+        QuantizationLevel::Q4 => GgmlDType::Q4_0,
+    }
+}
+
+struct Embeddings {
+    word: Embedding,
+    position: Embedding,
+    token_type: Embedding,
+    norm: LayerNorm,
+}
+
+impl Embeddings {
+    fn load(vb: VarBuilder, config: &BertConfig) -> Result<Self> {
+        Ok(Self {
+            word: Embedding::new(config.vocab_size, config.hidden_size, vb.pp("word_embeddings"))?,
+            position: Embedding::new(config.max_position_embeddings, config.hidden_size, vb.pp("position_embeddings"))?,
+            token_type: Embedding::new(config.type_vocab_size, config.hidden_size, vb.pp("token_type_embeddings"))?,
+            norm: layer_norm(config.hidden_size, config.layer_norm_eps, vb.pp("LayerNorm"))?,
+        })
+    }
+
+    fn forward(&self, ids: &Tensor, type_ids: &Tensor) -> Result<Tensor> {
+        let positions = Tensor::arange(0u32, ids.dim(1)? as u32, ids.device())?;
+        let embeddings = (self.word.forward(ids)? + self.token_type.forward(type_ids)?)?
+            .broadcast_add(&self.position.forward(&positions)?)?;
+        Ok(self.norm.forward(&embeddings)?)
+    }
+}
+
+struct Layer {
+    query: Linear,
+    key: Linear,
+    value: Linear,
+    attention_output: Linear,
+    attention_norm: LayerNorm,
+    intermediate: Linear,
+    output: Linear,
+    output_norm: LayerNorm,
+    heads: usize,
+    head_size: usize,
+}
+
+impl Layer {
+    fn load(vb: VarBuilder, config: &BertConfig) -> Result<Self> {
+        let hidden = config.hidden_size;
+        let attention = vb.pp("attention");
+        Ok(Self {
+            query: linear(hidden, hidden, attention.pp("self").pp("query"))?,
+            key: linear(hidden, hidden, attention.pp("self").pp("key"))?,
+            value: linear(hidden, hidden, attention.pp("self").pp("value"))?,
+            attention_output: linear(hidden, hidden, attention.pp("output").pp("dense"))?,
+            attention_norm: layer_norm(hidden, config.layer_norm_eps, attention.pp("output").pp("LayerNorm"))?,
+            intermediate: linear(hidden, config.intermediate_size, vb.pp("intermediate").pp("dense"))?,
+            output: linear(config.intermediate_size, hidden, vb.pp("output").pp("dense"))?,
+            output_norm: layer_norm(hidden, config.layer_norm_eps, vb.pp("output").pp("LayerNorm"))?,
+            heads: config.num_attention_heads,
+            head_size: hidden / config.num_attention_heads,
+        })
+    }
+
+    fn forward(&self, hidden: &Tensor, mask: &Tensor) -> Result<Tensor> {
+        let (batch, seq_len, _) = hidden.dims3()?;
+        let split = |projection: &Linear| -> Result<Tensor> {
+            Ok(projection
+                .forward(hidden)?
+                .reshape((batch, seq_len, self.heads, self.head_size))?
+                .transpose(1, 2)?
+                .contiguous()?)
+        };
+        let (query, key, value) = (split(&self.query)?, split(&self.key)?, split(&self.value)?);
+
+        let scores = (query.matmul(&key.t()?)? / (self.head_size as f64).sqrt())?.broadcast_add(mask)?;
+        let probs = candle_nn::ops::softmax_last_dim(&scores)?;
+        let context = probs.matmul(&value)?.transpose(1, 2)?.contiguous()?.flatten_from(2)?;
+
+        let attention = self
+            .attention_norm
+            .forward(&(self.attention_output.forward(&context)? + hidden)?)?;
+        let intermediate = self.intermediate.forward(&attention)?.gelu_erf()?;
+        Ok(self
+            .output_norm
+            .forward(&(self.output.forward(&intermediate)? + attention)?)?)
+    }
+}
+
+struct QuantizedBert {
+    embeddings: Embeddings,
+    layers: Vec<Layer>,
+}
+
+impl QuantizedBert {
+    fn load(vb: VarBuilder, config: &BertConfig) -> Result<Self> {
+        if config.hidden_act != HiddenAct::Gelu {
+            bail!("Unsupported activation {:?}", config.hidden_act);
+        }
+        let layers = (0..config.num_hidden_layers)
+            .map(|i| Layer::load(vb.pp(format!("encoder.layer.{}", i)), config))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            embeddings: Embeddings::load(vb.pp("embeddings"), config)?,
+            layers,
+        })
+    }
+
+    fn forward(&self, ids: &Tensor, type_ids: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        // Masked positions get the lowest f32 before the softmax, as in candle's BertModel
+        let mask = attention_mask.unsqueeze(1)?.unsqueeze(1)?.to_dtype(DType::F32)?;
+        let mask = (mask.ones_like()? - &mask)?
+            .broadcast_mul(&Tensor::new(f32::MIN, mask.device())?)?;
+
+        let mut hidden = self.embeddings.forward(ids, type_ids)?;
+        for layer in &self.layers {
+            hidden = layer.forward(&hidden, &mask)?;
+        }
+        Ok(hidden)
+    }
+}
+
+pub struct QuantizedBackend {
+    model: QuantizedBert,
+    tokenizer: Tokenizer,
+    device: Device,
+    level: QuantizationLevel,
+}
+
+impl QuantizedBackend {
+    /// Load a GGUF file written by `quantize_checkpoint` and its `tokenizer.json`
+    pub fn load(gguf_path: &Path, tokenizer_path: &Path, device: &Device) -> Result<Self> {
+        let mut file = File::open(gguf_path)
+            .with_context(|| format!("Failed to open quantized model {}", gguf_path.display()))?;
+        let content = gguf_file::Content::read(&mut file)?;
+        let config = match content.metadata.get(CONFIG_KEY) {
+            Some(gguf_file::Value::String(json)) => serde_json::from_str::<BertConfig>(json)?,
+            _ => bail!("{} has no BERT configuration", gguf_path.display()),
+        };
+        let level = match content.metadata.get(LEVEL_KEY) {
+            Some(gguf_file::Value::String(level)) => level.parse()?,
+            _ => bail!("{} has no quantization level", gguf_path.display()),
+        };
+
+        let vb = VarBuilder::from_gguf(gguf_path, device)?;
+        Ok(Self {
+            model: QuantizedBert::load(vb, &config)?,
+            tokenizer: load_tokenizer(tokenizer_path)?,
+            device: device.clone(),
+            level,
+        })
+    }
+
+    pub fn level(&self) -> QuantizationLevel {
+        self.level
+    }
+}
+
+impl EncoderBackend for QuantizedBackend {
+    fn kind(&self) -> InferenceBackend {
+        InferenceBackend::Candle
+    }
+
+    fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    fn forward(&self, input: &EncodedInput) -> Result<Vec<Vec<f32>>> {
+        let ids = Tensor::new(input.ids.as_slice(), &self.device)?.unsqueeze(0)?;
+        let type_ids = Tensor::new(input.type_ids.as_slice(), &self.device)?.unsqueeze(0)?;
+        let mask = Tensor::new(input.attention_mask.as_slice(), &self.device)?.unsqueeze(0)?;
+        let hidden = self.model.forward(&ids, &type_ids, &mask)?;
+        Ok(hidden.squeeze(0)?.to_vec2::<f32>()?)
+    }
+
+    fn forward_batch(&self, inputs: &[EncodedInput]) -> Result<Vec<Vec<Vec<f32>>>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let batch = PaddedBatch::new(inputs);
+        let shape = (inputs.len(), batch.len);
+        let ids = Tensor::from_vec(batch.ids, shape, &self.device)?;
+        let type_ids = Tensor::from_vec(batch.type_ids, shape, &self.device)?;
+        let mask = Tensor::from_vec(batch.attention_mask, shape, &self.device)?;
+        let hidden = self.model.forward(&ids, &type_ids, &mask)?;
+        Ok(PaddedBatch::unpad(hidden.to_vec3::<f32>()?, inputs))
+    }
+}
+
+/// Encoder tensors with their matrices converted to `level`
+///
+/// Pretraining heads and the pooler are dropped, the `bert.` prefix is
+/// removed and old `gamma`/`beta` LayerNorm names are renamed. Tensors
+/// whose rows do not fit the block size stay F32.
+pub fn quantize_tensors(tensors: Vec<(String, Tensor)>, level: QuantizationLevel) -> Result<Vec<(String, QTensor)>> {
+    let dtype = ggml_dtype(level);
+    tensors
+        .into_iter()
+        .filter_map(|(name, tensor)| {
+            let name = name.strip_prefix("bert.").unwrap_or(&name);
+            let name = name
+                .replace("LayerNorm.gamma", "LayerNorm.weight")
+                .replace("LayerNorm.beta", "LayerNorm.bias");
+            (name.starts_with("embeddings.") || name.starts_with("encoder.")).then_some((name, tensor))
+        })
+        .map(|(name, tensor)| {
+            let tensor = tensor.to_dtype(DType::F32)?;
+            let fits_blocks = tensor.rank() == 2 && tensor.dim(1)? % dtype.block_size() == 0;
+            let target = if fits_blocks { dtype } else { GgmlDType::F32 };
+            Ok((name, QTensor::quantize(&tensor, target)?))
+        })
+        .collect()
+}
+
+/// Write converted tensors with the BERT configuration they belong to
+///
+/// The file is written next to `path` with a `.partial` suffix and renamed
+/// when complete, so an interrupted conversion never leaves a truncated file
+/// that later loads would pick up.
+pub fn write_quantized(
+    path: &Path,
+    config_json: &str,
+    level: QuantizationLevel,
+    tensors: &[(String, QTensor)],
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let config = gguf_file::Value::String(config_json.to_string());
+    let level = gguf_file::Value::String(level.as_str().to_string());
+    let metadata = [(CONFIG_KEY, &config), (LEVEL_KEY, &level)];
+    let tensors: Vec<(&str, &QTensor)> = tensors.iter().map(|(name, t)| (name.as_str(), t)).collect();
+
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    {
+        let mut file = File::create(&partial)
+            .with_context(|| format!("Failed to create {}", partial.display()))?;
+        gguf_file::write(&mut file, &metadata, &tensors)?;
+        file.sync_all()?;
+    }
+    fs::rename(&partial, path).with_context(|| format!("Failed to move {} into place", partial.display()))?;
+    Ok(())
+}
+
+/// Outcome of converting a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantizationSummary {
+    pub tensors: usize,
+    /// Tensors stored below F32
+    pub quantized: usize,
+    pub bytes: u64,
+}
+
+/// Convert a PyTorch or safetensors checkpoint into a GGUF file at `output`
+pub fn quantize_checkpoint(
+    config_path: &Path,
+    weights_path: &Path,
+    output: &Path,
+    level: QuantizationLevel,
+) -> Result<QuantizationSummary> {
+    let config_json = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    serde_json::from_str::<BertConfig>(&config_json).context("Invalid BERT configuration")?;
+
+    let tensors = read_tensors(weights_path)
+        .with_context(|| format!("Failed to read {}", weights_path.display()))?;
+    let quantized = quantize_tensors(tensors, level)?;
+    write_quantized(output, &config_json, level, &quantized)?;
+
+    Ok(QuantizationSummary {
+        tensors: quantized.len(),
+        quantized: quantized.iter().filter(|(_, t)| t.dtype() != GgmlDType::F32).count(),
+        bytes: fs::metadata(output)?.len(),
+    })
+}
@@ -0,0 +1,200 @@
+//! Encoder weight files
+//!
+//! Snapshots ship `model.safetensors`, `pytorch_model.bin` or both.
+//! Safetensors are preferred: they are memory-mapped rather than read into
+//! memory, and loading them cannot run code the way unpickling can. A
+//! snapshot with PyTorch weights only is converted once, into a file kept
+//! beside the snapshot in the model cache.
+
+use anyhow::{Context, Result};
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ai::ProgressCallback;
+use crate::inference::CancelToken;
+use crate::model_registry::WeightFormat;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConversionError {
+    #[error("Conversion of {0} was cancelled")]
+    Cancelled(String),
+}
+
+/// Formats present in a snapshot directory, preferred first
+pub fn detect_weight_formats(dir: &Path) -> Vec<WeightFormat> {
+    [WeightFormat::Safetensors, WeightFormat::PyTorch]
+        .into_iter()
+        .filter(|format| dir.join(format.file_name()).is_file())
+        .collect()
+}
+
+/// Weights file the encoder loads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightSource {
+    pub path: PathBuf,
+    pub format: WeightFormat,
+    /// Converted from the snapshot's PyTorch checkpoint
+    pub converted: bool,
+}
+
+/// Best weights available without converting: the snapshot's safetensors,
+/// an earlier conversion at `converted_path`, then the PyTorch checkpoint
+pub fn find_weights(snapshot_dir: &Path, converted_path: &Path) -> Option<WeightSource> {
+    let formats = detect_weight_formats(snapshot_dir);
+    if formats.contains(&WeightFormat::Safetensors) {
+        return Some(WeightSource {
+            path: snapshot_dir.join(WeightFormat::Safetensors.file_name()),
+            format: WeightFormat::Safetensors,
+            converted: false,
+        });
+    }
+    if converted_path.is_file() {
+        return Some(WeightSource {
+            path: converted_path.to_path_buf(),
+            format: WeightFormat::Safetensors,
+            converted: true,
+        });
+    }
+    formats.first().map(|format| WeightSource {
+        path: snapshot_dir.join(format.file_name()),
+        format: *format,
+        converted: false,
+    })
+}
+
+/// Weights to load, converting a PyTorch-only snapshot to safetensors first
+///
+/// A failed conversion falls back to the PyTorch checkpoint; a cancelled one
+/// fails with `ConversionError::Cancelled`.
+pub fn prepare_weights(
+    snapshot_dir: &Path,
+    converted_path: &Path,
+    cancel: &CancelToken,
+    progress: Option<&ProgressCallback>,
+) -> Result<WeightSource> {
+    let source = find_weights(snapshot_dir, converted_path)
+        .with_context(|| format!("No model weights in {}", snapshot_dir.display()))?;
+    if source.format != WeightFormat::PyTorch {
+        return Ok(source);
+    }
+
+    match convert_pth_to_safetensors(&source.path, converted_path, cancel, progress) {
+        Ok(()) => Ok(WeightSource {
+            path: converted_path.to_path_buf(),
+            format: WeightFormat::Safetensors,
+            converted: true,
+        }),
+        Err(e) if e.downcast_ref::<ConversionError>().is_some() => Err(e),
+        Err(e) => {
+            tracing::warn!("Could not convert {} to safetensors, loading it directly: {:#}", source.path.display(), e);
+            Ok(source)
+        }
+    }
+}
+
+/// Rewrite a PyTorch checkpoint as safetensors, keeping tensor names
+///
+/// Progress counts the tensors read; `cancel` is checked before each one.
+pub fn convert_pth_to_safetensors(
+    pth: &Path,
+    output: &Path,
+    cancel: &CancelToken,
+    progress: Option<&ProgressCallback>,
+) -> Result<()> {
+    let checkpoint = candle_core::pickle::PthTensors::new(pth, None)?;
+    let names: Vec<&String> = checkpoint.tensor_infos().keys().collect();
+    let mut tensors = HashMap::with_capacity(names.len());
+    for (index, name) in names.iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(ConversionError::Cancelled(pth.display().to_string()).into());
+        }
+        if let Some(report) = progress {
+            report(index as f32 / names.len() as f32, format!("Converting model weights ({}/{})...", index, names.len()));
+        }
+        if let Some(tensor) = checkpoint.get(name)? {
+            tensors.insert(name.to_string(), tensor);
+        }
+    }
+    if cancel.is_cancelled() {
+        return Err(ConversionError::Cancelled(pth.display().to_string()).into());
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Written aside and renamed, so an interrupted conversion is never loaded
+    let partial = output.with_extension("safetensors.partial");
+    candle_core::safetensors::save(&tensors, &partial)
+        .with_context(|| format!("Failed to write {}", partial.display()))?;
+    fs::rename(&partial, output)?;
+    if let Some(report) = progress {
+        report(1.0, "Model weights converted".to_string());
+    }
+    tracing::info!("Converted {} to {}", pth.display(), output.display());
+    Ok(())
+}
+
+/// All tensors of a weights file in either format
+pub fn read_tensors(path: &Path) -> Result<Vec<(String, Tensor)>> {
+    let tensors = match WeightFormat::from_path(path) {
+        Some(WeightFormat::Safetensors) => candle_core::safetensors::load(path, &Device::Cpu)?.into_iter().collect(),
+        _ => candle_core::pickle::read_all(path)?,
+    };
+    Ok(tensors)
+}
+
+/// Variables of a weights file; safetensors are memory-mapped
+pub fn var_builder(path: &Path, dtype: DType, device: &Device) -> Result<VarBuilder<'static>> {
+    let vb = match WeightFormat::from_path(path) {
+        // SAFETY: the cache files are only replaced by renaming, never
+        // modified in place, so the mapping stays valid
+        Some(WeightFormat::Safetensors) => unsafe { VarBuilder::from_mmaped_safetensors(&[path], dtype, device)? },
+        _ => VarBuilder::from_pth(path, dtype, device)?,
+    };
+    Ok(vb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safetensors_are_preferred() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let snapshot = dir.join("snapshot");
+        fs::create_dir_all(&snapshot).unwrap();
+        let converted = dir.join("converted").join("model.safetensors");
+        assert!(find_weights(&snapshot, &converted).is_none());
+
+        fs::write(snapshot.join("pytorch_model.bin"), b"pth").unwrap();
+        assert_eq!(detect_weight_formats(&snapshot), [WeightFormat::PyTorch]);
+        assert_eq!(find_weights(&snapshot, &converted).unwrap().format, WeightFormat::PyTorch);
+
+        fs::create_dir_all(converted.parent().unwrap()).unwrap();
+        fs::write(&converted, b"converted").unwrap();
+        let source = find_weights(&snapshot, &converted).unwrap();
+        assert_eq!((source.path.as_path(), source.converted), (converted.as_path(), true));
+
+        fs::write(snapshot.join("model.safetensors"), b"safetensors").unwrap();
+        assert_eq!(detect_weight_formats(&snapshot), [WeightFormat::Safetensors, WeightFormat::PyTorch]);
+        let source = find_weights(&snapshot, &converted).unwrap();
+        assert_eq!(source.path, snapshot.join("model.safetensors"));
+        assert!(!source.converted);
+    }
+
+    #[test]
+    fn test_failed_conversion_falls_back_to_pytorch() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("pytorch_model.bin"), b"not a checkpoint").unwrap();
+        let converted = dir.join("converted").join("model.safetensors");
+
+        let source = prepare_weights(dir, &converted, &CancelToken::new(), None).unwrap();
+        assert_eq!(source.format, WeightFormat::PyTorch);
+        assert!(!converted.exists());
+    }
+}
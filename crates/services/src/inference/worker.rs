@@ -0,0 +1,412 @@
+//! Inference worker
+//!
+//! Forward passes run on dedicated threads rather than on the async
+//! executor. Jobs wait in a priority queue, can be cancelled between
+//! batches, and report their queue position, batch progress and an ETA
+//! through the job's progress callback.
+
+use anyhow::Result;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+use super::{EncoderBackend, Pooling};
+use crate::ai::ProgressCallback;
+
+/// Texts encoded in one forward pass
+pub const DEFAULT_BATCH_SIZE: usize = 8;
+
+/// Order in which queued jobs are picked up; equal priorities run first come, first served
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JobPriority {
+    /// Benchmarks and bulk re-scoring
+    Low,
+    #[default]
+    Normal,
+    /// A student waiting on the result
+    High,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkerError {
+    #[error("Inference job {0} was cancelled")]
+    Cancelled(u64),
+    #[error("Inference worker stopped")]
+    Stopped,
+}
+
+/// Shared flag that stops a job before its next batch
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct JobOptions {
+    pub priority: JobPriority,
+    pub progress: Option<ProgressCallback>,
+    pub cancel: CancelToken,
+    pub pooling: Pooling,
+}
+
+/// A submitted job; dropping it does not cancel the job
+pub struct JobHandle {
+    id: u64,
+    cancel: CancelToken,
+    result: oneshot::Receiver<Result<Vec<Vec<f32>>>>,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Embeddings in the order the texts were submitted
+    pub async fn wait(self) -> Result<Vec<Vec<f32>>> {
+        self.result.await.map_err(|_| WorkerError::Stopped)?
+    }
+}
+
+struct Job {
+    id: u64,
+    texts: Vec<String>,
+    options: JobOptions,
+    reply: oneshot::Sender<Result<Vec<Vec<f32>>>>,
+}
+
+#[derive(Default)]
+struct Queue {
+    /// Highest priority first
+    jobs: Vec<Job>,
+    next_id: u64,
+    stopped: bool,
+}
+
+type Notice = (ProgressCallback, f32, String);
+
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+    /// Moving average of encoding time per text, in microseconds; 0 until measured
+    micros_per_text: AtomicU64,
+    threads: usize,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn eta(&self, texts: usize) -> Option<Duration> {
+        match self.micros_per_text.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros * texts as u64 / self.threads as u64)),
+        }
+    }
+
+    fn record(&self, elapsed: Duration, texts: usize) {
+        let sample = elapsed.as_micros() as u64 / texts.max(1) as u64;
+        let average = match self.micros_per_text.load(Ordering::Relaxed) {
+            0 => sample.max(1),
+            previous => (previous * 3 + sample) / 4,
+        };
+        self.micros_per_text.store(average.max(1), Ordering::Relaxed);
+    }
+
+    /// Queue positions of `jobs[from..]`, to be reported once the lock is released
+    fn position_notices(&self, jobs: &[Job], from: usize) -> Vec<Notice> {
+        let mut ahead: usize = jobs[..from].iter().map(|job| job.texts.len()).sum();
+        let mut notices = Vec::new();
+        for (position, job) in jobs.iter().enumerate().skip(from) {
+            ahead += job.texts.len();
+            if let Some(progress) = &job.options.progress {
+                let message = format!("Queued at position {}{}", position + 1, eta_suffix(self.eta(ahead)));
+                notices.push((progress.clone(), 0.0, message));
+            }
+        }
+        notices
+    }
+
+    fn process(&self, job: &Job, encoder: &dyn EncoderBackend, batch_size: usize) -> Result<Vec<Vec<f32>>> {
+        let report = |progress: f32, message: String| {
+            if let Some(callback) = &job.options.progress {
+                callback(progress, message);
+            }
+        };
+
+        let total = job.texts.len();
+        let mut embeddings = Vec::with_capacity(total);
+        report(0.0, format!("Encoding {} texts{}", total, eta_suffix(self.eta(total))));
+        for batch in job.texts.chunks(batch_size) {
+            if job.options.cancel.is_cancelled() {
+                return Err(WorkerError::Cancelled(job.id).into());
+            }
+            let start = Instant::now();
+            embeddings.extend(encoder.pool_batch(batch, job.options.pooling)?);
+            self.record(start.elapsed(), batch.len());
+
+            let done = embeddings.len();
+            let remaining = eta_suffix(self.eta(total - done).filter(|_| done < total));
+            report(done as f32 / total as f32, format!("Encoded {}/{} texts{}", done, total, remaining));
+        }
+        Ok(embeddings)
+    }
+}
+
+fn eta_suffix(eta: Option<Duration>) -> String {
+    eta.map(|eta| format!(", about {}s left", eta.as_secs_f64().ceil() as u64))
+        .unwrap_or_default()
+}
+
+fn notify(notices: Vec<Notice>) {
+    for (callback, progress, message) in notices {
+        callback(progress, message);
+    }
+}
+
+fn run(shared: Arc<Shared>, encoder: Arc<dyn EncoderBackend>, batch_size: usize) {
+    loop {
+        let (job, notices) = {
+            let mut queue = shared.lock();
+            while queue.jobs.is_empty() && !queue.stopped {
+                queue = shared.available.wait(queue).unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            if queue.stopped {
+                return;
+            }
+            let job = queue.jobs.remove(0);
+            let notices = shared.position_notices(&queue.jobs, 0);
+            (job, notices)
+        };
+        notify(notices);
+
+        let result = catch_unwind(AssertUnwindSafe(|| shared.process(&job, encoder.as_ref(), batch_size)))
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Inference job {} panicked", job.id)));
+        let _ = job.reply.send(result);
+    }
+}
+
+/// Blocking thread pool that owns an encoder and serves a job queue
+pub struct InferenceWorker {
+    shared: Arc<Shared>,
+}
+
+impl InferenceWorker {
+    pub fn new(encoder: Arc<dyn EncoderBackend>, threads: usize, batch_size: usize) -> Result<Self> {
+        let threads = threads.max(1);
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            available: Condvar::new(),
+            micros_per_text: AtomicU64::new(0),
+            threads,
+        });
+        for index in 0..threads {
+            let (shared, encoder) = (shared.clone(), encoder.clone());
+            std::thread::Builder::new()
+                .name(format!("inference-worker-{}", index))
+                .spawn(move || run(shared, encoder, batch_size.max(1)))?;
+        }
+        Ok(Self { shared })
+    }
+
+    /// Queue texts for encoding
+    pub fn submit(&self, texts: Vec<String>, options: JobOptions) -> JobHandle {
+        let (reply, result) = oneshot::channel();
+        let cancel = options.cancel.clone();
+        let (id, notices) = {
+            let mut queue = self.shared.lock();
+            let id = queue.next_id;
+            queue.next_id += 1;
+            let at = queue.jobs.partition_point(|job| job.options.priority >= options.priority);
+            queue.jobs.insert(at, Job { id, texts, options, reply });
+            (id, self.shared.position_notices(&queue.jobs, at))
+        };
+        self.shared.available.notify_one();
+        notify(notices);
+        JobHandle { id, cancel, result }
+    }
+
+    /// Jobs waiting for a thread
+    pub fn queued(&self) -> usize {
+        self.shared.lock().jobs.len()
+    }
+}
+
+impl Drop for InferenceWorker {
+    /// Threads finish their current job and exit; queued jobs fail with `WorkerError::Stopped`
+    fn drop(&mut self) {
+        let jobs = {
+            let mut queue = self.shared.lock();
+            queue.stopped = true;
+            std::mem::take(&mut queue.jobs)
+        };
+        self.shared.available.notify_all();
+        drop(jobs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_config::InferenceBackend;
+    use crate::inference::EncodedInput;
+    use std::sync::mpsc;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::Tokenizer;
+
+    /// Embeds each text as its length; blocks every batch until the gate opens
+    struct FakeEncoder {
+        tokenizer: Tokenizer,
+        gate: Arc<(Mutex<bool>, Condvar)>,
+        started: Mutex<mpsc::Sender<Vec<String>>>,
+    }
+
+    impl EncoderBackend for FakeEncoder {
+        fn kind(&self) -> InferenceBackend {
+            InferenceBackend::Candle
+        }
+
+        fn tokenizer(&self) -> &Tokenizer {
+            &self.tokenizer
+        }
+
+        fn forward(&self, _input: &EncodedInput) -> Result<Vec<Vec<f32>>> {
+            unreachable!("the fake encoder only embeds batches")
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.started.lock().unwrap().send(texts.to_vec()).unwrap();
+            let (open, opened) = &*self.gate;
+            let _open = opened.wait_while(open.lock().unwrap(), |open| !*open).unwrap();
+            Ok(texts.iter().map(|text| vec![text.len() as f32]).collect())
+        }
+    }
+
+    struct Fixture {
+        worker: InferenceWorker,
+        gate: Arc<(Mutex<bool>, Condvar)>,
+        started: mpsc::Receiver<Vec<String>>,
+    }
+
+    impl Fixture {
+        fn new(batch_size: usize) -> Self {
+            let gate = Arc::new((Mutex::new(false), Condvar::new()));
+            let (sender, started) = mpsc::channel();
+            let encoder = FakeEncoder {
+                tokenizer: Tokenizer::new(WordLevel::default()),
+                gate: gate.clone(),
+                started: Mutex::new(sender),
+            };
+            let worker = InferenceWorker::new(Arc::new(encoder), 1, batch_size).unwrap();
+            Self { worker, gate, started }
+        }
+
+        fn open(&self) {
+            let (open, opened) = &*self.gate;
+            *open.lock().unwrap() = true;
+            opened.notify_all();
+        }
+
+        fn batches(&self) -> Vec<Vec<String>> {
+            self.started.try_iter().collect()
+        }
+    }
+
+    fn texts(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_batches_keep_submission_order() {
+        let fixture = Fixture::new(2);
+        fixture.open();
+        let job = fixture.worker.submit(texts(&["a", "bb", "ccc", "dddd", "eeeee"]), JobOptions::default());
+        let embeddings = job.wait().await.unwrap();
+
+        assert_eq!(embeddings, vec![vec![1.0], vec![2.0], vec![3.0], vec![4.0], vec![5.0]]);
+        let sizes: Vec<usize> = fixture.batches().iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_jobs_run_first() {
+        let fixture = Fixture::new(8);
+        let running = fixture.worker.submit(texts(&["running"]), JobOptions::default());
+        fixture.started.recv().unwrap();
+
+        let submit = |text: &str, priority| {
+            fixture.worker.submit(texts(&[text]), JobOptions { priority, ..Default::default() })
+        };
+        let low = submit("low", JobPriority::Low);
+        let normal = submit("normal", JobPriority::Normal);
+        let high = submit("high", JobPriority::High);
+        assert_eq!(fixture.worker.queued(), 3);
+
+        fixture.open();
+        for job in [running, low, normal, high] {
+            job.wait().await.unwrap();
+        }
+        assert_eq!(fixture.batches(), vec![texts(&["high"]), texts(&["normal"]), texts(&["low"])]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_job_is_not_encoded() {
+        let fixture = Fixture::new(8);
+        let running = fixture.worker.submit(texts(&["running"]), JobOptions::default());
+        fixture.started.recv().unwrap();
+
+        let cancelled = fixture.worker.submit(texts(&["cancelled"]), JobOptions::default());
+        cancelled.cancel();
+        fixture.open();
+
+        assert!(running.wait().await.is_ok());
+        let error = cancelled.wait().await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<WorkerError>(), Some(WorkerError::Cancelled(1))));
+        assert!(fixture.batches().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_progress_reports_queue_position() {
+        let fixture = Fixture::new(1);
+        let running = fixture.worker.submit(texts(&["running"]), JobOptions::default());
+        fixture.started.recv().unwrap();
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let options = JobOptions {
+            progress: Some(Arc::new(move |progress, message| sink.lock().unwrap().push((progress, message)))),
+            ..Default::default()
+        };
+        let _ahead = fixture.worker.submit(texts(&["ahead"]), JobOptions { priority: JobPriority::High, ..Default::default() });
+        let job = fixture.worker.submit(texts(&["a", "b"]), options);
+
+        fixture.open();
+        running.wait().await.unwrap();
+        job.wait().await.unwrap();
+
+        let reports = reports.lock().unwrap();
+        let messages: Vec<&str> = reports.iter().map(|(_, message)| message.as_str()).collect();
+        assert_eq!(messages[0], "Queued at position 2");
+        assert!(messages.iter().any(|message| message.starts_with("Queued at position 1, about")));
+        assert!(messages.iter().any(|message| message.starts_with("Encoded 1/2 texts, about")));
+        assert_eq!(reports.last().unwrap(), &(1.0, "Encoded 2/2 texts".to_string()));
+    }
+}
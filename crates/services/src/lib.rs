@@ -2,11 +2,49 @@
 
 pub mod ai;
 pub mod ai_config;
+pub mod benchmark;
+pub mod download;
+pub mod drafts;
+pub mod embedding_cache;
 pub mod evaluation;
+pub mod explain;
+pub mod export;
+pub mod feedback;
+pub mod inference;
+pub mod model_install;
+pub mod model_registry;
+pub mod provenance;
+pub mod question_search;
+pub mod readability;
+pub mod review;
+pub mod revisions;
 pub mod rubrics;
+pub mod secrets;
+pub mod spelling;
+pub mod uncertainty;
+pub mod writing_analytics;
 
 pub use ai::*;
 pub use ai_config::*;
+pub use benchmark::*;
+pub use download::*;
+pub use drafts::*;
+pub use embedding_cache::*;
 pub use evaluation::*;
+pub use explain::*;
+pub use export::{export_essay, save_essay_export, ExportFormat};
+pub use feedback::*;
+pub use inference::*;
+pub use model_install::*;
+pub use model_registry::*;
+pub use provenance::*;
+pub use question_search::*;
+pub use readability::*;
+pub use review::*;
+pub use revisions::*;
 pub use rubrics::*;
+pub use secrets::{is_sealed, SecretKey};
+pub use spelling::*;
+pub use uncertainty::*;
+pub use writing_analytics::*;
 
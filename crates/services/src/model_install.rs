@@ -0,0 +1,315 @@
+//! Offline installation of encoder models
+//!
+//! A model package is a directory, `.tar`, `.tar.gz` or `.zip` holding
+//! `config.json`, `tokenizer.json`, the weights and a `manifest.json` with
+//! the SHA-256 of each file. Verified files are copied into the Hub cache
+//! layout, where `AIService::initialize` finds them without a network.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::model_registry::{is_valid_model_id, repo_dir_name};
+use crate::provenance::{sha256_file, sha256_hex};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Files every package must contain
+pub const REQUIRED_FILES: &[&str] = &["config.json", "tokenizer.json"];
+/// Weight formats the encoder loads, preferred first; a package needs one of them
+pub const WEIGHT_FILES: &[&str] = &["model.safetensors", "pytorch_model.bin"];
+
+/// Checksums of a model package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub model_id: String,
+    /// Hub commit the files were taken from, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    /// SHA-256 of each file, by file name
+    pub files: BTreeMap<String, String>,
+}
+
+impl ModelManifest {
+    /// Hash the files of `model_id` present in `dir`
+    pub fn for_directory(dir: &Path, model_id: &str, revision: Option<String>) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        for name in REQUIRED_FILES.iter().chain(WEIGHT_FILES) {
+            let path = dir.join(name);
+            if path.exists() {
+                files.insert(name.to_string(), sha256_file(&path)?);
+            }
+        }
+        let manifest = Self { model_id: model_id.to_string(), revision, files };
+        manifest.check_complete()?;
+        Ok(manifest)
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::write(dir.join(MANIFEST_FILE), serde_json::to_string_pretty(self)?)
+            .context("Failed to write manifest")
+    }
+
+    fn load(dir: &Path) -> Result<Self> {
+        let content = fs::read_to_string(dir.join(MANIFEST_FILE))
+            .with_context(|| format!("No {} in {}", MANIFEST_FILE, dir.display()))?;
+        serde_json::from_str(&content).context("Invalid manifest")
+    }
+
+    fn check_complete(&self) -> Result<()> {
+        if !is_valid_model_id(&self.model_id) {
+            bail!("Invalid model id {:?} in manifest", self.model_id);
+        }
+        for name in REQUIRED_FILES {
+            if !self.files.contains_key(*name) {
+                bail!("Model package is missing {}", name);
+            }
+        }
+        if !WEIGHT_FILES.iter().any(|name| self.files.contains_key(*name)) {
+            bail!("Model package has no weights ({})", WEIGHT_FILES.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Snapshot name: the Hub revision, or one derived from the checksums
+    fn snapshot_name(&self) -> Result<String> {
+        let name = match &self.revision {
+            Some(revision) => revision.clone(),
+            None => format!("local-{}", &sha256_hex(&serde_json::to_vec(&self.files)?)[..12]),
+        };
+        if !is_plain_name(&name) {
+            bail!("Invalid revision {:?}", name);
+        }
+        Ok(name)
+    }
+}
+
+/// Where a package was installed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledModel {
+    pub model_id: String,
+    pub revision: String,
+    pub snapshot_dir: PathBuf,
+    pub files: Vec<String>,
+}
+
+/// Verify a package and install it into the Hub cache at `cache_dir`
+///
+/// The model is the one the manifest names. Nothing is installed unless
+/// every file matches its checksum. The installed snapshot becomes the one
+/// `refs/main` points to, and `refs/<revision>` lets a configuration pinned
+/// to that revision find it.
+pub fn install_model(source: &Path, cache_dir: &Path) -> Result<InstalledModel> {
+    fs::create_dir_all(cache_dir)?;
+
+    if source.is_dir() {
+        return install_package(source, cache_dir);
+    }
+    let staging = cache_dir.join(format!(".staging-{}", Uuid::new_v4()));
+    let result = extract_archive(source, &staging)
+        .and_then(|()| package_root(&staging))
+        .and_then(|root| install_package(&root, cache_dir));
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn install_package(package: &Path, cache_dir: &Path) -> Result<InstalledModel> {
+    let manifest = ModelManifest::load(package)?;
+    manifest.check_complete()?;
+    let revision = manifest.snapshot_name()?;
+    let repo_dir = cache_dir.join(repo_dir_name(&manifest.model_id));
+
+    for (name, expected) in &manifest.files {
+        if !is_plain_name(name) {
+            bail!("Invalid file name in manifest: {}", name);
+        }
+        let path = package.join(name);
+        if !path.is_file() {
+            bail!("{} is listed in the manifest but missing", name);
+        }
+        let actual = sha256_file(&path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            bail!("Checksum mismatch for {}: expected {}, got {}", name, expected, actual);
+        }
+    }
+
+    // Copy next to the final snapshot, then swap it in
+    let snapshots = repo_dir.join("snapshots");
+    let snapshot_dir = snapshots.join(&revision);
+    let partial = snapshots.join(format!(".{}.partial", revision));
+    let _ = fs::remove_dir_all(&partial);
+    fs::create_dir_all(&partial)?;
+    for name in manifest.files.keys() {
+        fs::copy(package.join(name), partial.join(name))
+            .with_context(|| format!("Failed to copy {}", name))?;
+    }
+    if snapshot_dir.exists() {
+        fs::remove_dir_all(&snapshot_dir)?;
+    }
+    fs::rename(&partial, &snapshot_dir)?;
+
+    let refs = repo_dir.join("refs");
+    fs::create_dir_all(&refs)?;
+    fs::write(refs.join("main"), &revision)?;
+    if revision != "main" {
+        fs::write(refs.join(&revision), &revision)?;
+    }
+
+    Ok(InstalledModel {
+        model_id: manifest.model_id.clone(),
+        revision,
+        snapshot_dir,
+        files: manifest.files.keys().cloned().collect(),
+    })
+}
+
+/// A single path component that is not hidden
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let name = archive.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    fs::create_dir_all(dest)?;
+
+    if name.ends_with(".zip") {
+        zip::ZipArchive::new(file)?.extract(dest)?;
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        tar::Archive::new(flate2::read::GzDecoder::new(file)).unpack(dest)?;
+    } else if name.ends_with(".tar") {
+        tar::Archive::new(file).unpack(dest)?;
+    } else {
+        bail!("Unsupported package {}; use a directory, .zip, .tar or .tar.gz", archive.display());
+    }
+    Ok(())
+}
+
+/// The extracted directory holding the manifest, either the archive root
+/// or its single top-level folder
+fn package_root(extracted: &Path) -> Result<PathBuf> {
+    if extracted.join(MANIFEST_FILE).exists() {
+        return Ok(extracted.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = fs::read_dir(extracted)?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    match entries.as_slice() {
+        [dir] if dir.join(MANIFEST_FILE).exists() => Ok(dir.clone()),
+        _ => bail!("Archive has no {}", MANIFEST_FILE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::MODEL_ID;
+    use hf_hub::{Cache, Repo, RepoType};
+    use std::io::Write;
+
+    /// A package with stand-in file contents; only checksums are checked
+    fn package(dir: &Path) -> ModelManifest {
+        fs::write(dir.join("config.json"), r#"{"hidden_size": 768}"#).unwrap();
+        fs::write(dir.join("tokenizer.json"), r#"{"version": "1.0"}"#).unwrap();
+        fs::write(dir.join("pytorch_model.bin"), vec![7u8; 4096]).unwrap();
+        let manifest = ModelManifest::for_directory(dir, MODEL_ID, Some("abc123".to_string())).unwrap();
+        manifest.save(dir).unwrap();
+        manifest
+    }
+
+    fn cached(cache_dir: &Path, file: &str) -> Option<PathBuf> {
+        Cache::new(cache_dir.to_path_buf())
+            .repo(Repo::new(MODEL_ID.to_string(), RepoType::Model))
+            .get(file)
+    }
+
+    #[test]
+    fn test_install_from_directory() {
+        let (package_dir, cache_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let (source, cache) = (package_dir.path(), cache_dir.path());
+        let manifest = package(source);
+        assert_eq!(manifest.files.len(), 3);
+
+        let installed = install_model(source, cache).unwrap();
+        assert_eq!(installed.revision, "abc123");
+        for file in ["config.json", "tokenizer.json", "pytorch_model.bin"] {
+            let path = cached(cache, file).unwrap();
+            assert_eq!(path, installed.snapshot_dir.join(file));
+            assert_eq!(fs::read(path).unwrap(), fs::read(source.join(file)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_install_from_archives() {
+        let (package_dir, cache_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let (source, cache) = (package_dir.path(), cache_dir.path());
+        package(source);
+        let files = ["manifest.json", "config.json", "tokenizer.json", "pytorch_model.bin"];
+
+        let tar_path = source.join("bertimbau.tar.gz");
+        let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(&tar_path).unwrap(),
+            flate2::Compression::fast(),
+        ));
+        for file in files {
+            tar.append_path_with_name(source.join(file), format!("bertimbau/{}", file)).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let zip_path = source.join("bertimbau.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        for file in files {
+            zip.start_file(file, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(&fs::read(source.join(file)).unwrap()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        for archive in [&tar_path, &zip_path] {
+            let installed = install_model(archive, cache).unwrap();
+            assert_eq!(cached(cache, "pytorch_model.bin"), Some(installed.snapshot_dir.join("pytorch_model.bin")));
+        }
+        // Staging directories are cleaned up
+        let leftovers: Vec<_> = fs::read_dir(cache)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(".staging"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_corrupted_package_is_not_installed() {
+        let (package_dir, cache_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let (source, cache) = (package_dir.path(), cache_dir.path());
+        package(source);
+        fs::write(source.join("pytorch_model.bin"), vec![8u8; 4096]).unwrap();
+
+        let error = install_model(source, cache).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch for pytorch_model.bin"));
+        assert_eq!(cached(cache, "config.json"), None);
+    }
+
+    #[test]
+    fn test_incomplete_manifest_is_rejected() {
+        let (package_dir, cache_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let (source, cache) = (package_dir.path(), cache_dir.path());
+        let complete = package(source);
+
+        let mut manifest = complete.clone();
+        manifest.files.remove("pytorch_model.bin");
+        manifest.save(source).unwrap();
+        assert!(install_model(source, cache).unwrap_err().to_string().contains("no weights"));
+
+        let mut manifest = complete;
+        let hash = manifest.files["config.json"].clone();
+        manifest.files.insert("../config.json".to_string(), hash);
+        manifest.save(source).unwrap();
+        assert!(install_model(source, cache).unwrap_err().to_string().contains("Invalid file name"));
+    }
+}
@@ -0,0 +1,435 @@
+//! Registry of encoder models
+//!
+//! Built-in descriptors cover BERTimbau base and large and a multilingual
+//! BERT for essays that are not in Portuguese; more can be registered in
+//! `models.json` next to the AI configuration. Every model has its own Hub
+//! cache directory, so several can be installed side by side and switched
+//! without downloading again.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::ai::{ProgressCallback, MODEL_ID};
+use crate::ai_config::AIConfigManager;
+use crate::download::DownloadManager;
+use crate::inference::CancelToken;
+use crate::model_install::WEIGHT_FILES;
+
+pub const REGISTRY_FILE: &str = "models.json";
+const DEFAULT_REVISION: &str = "main";
+
+/// Format of the encoder weights in the repository
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightFormat {
+    /// `pytorch_model.bin`, a pickled checkpoint read fully into memory
+    #[default]
+    PyTorch,
+    /// `model.safetensors`, memory-mapped when loaded
+    Safetensors,
+}
+
+impl WeightFormat {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            WeightFormat::PyTorch => "pytorch_model.bin",
+            WeightFormat::Safetensors => "model.safetensors",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeightFormat::PyTorch => "pytorch",
+            WeightFormat::Safetensors => "safetensors",
+        }
+    }
+
+    /// Format of a weights file, by its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "safetensors" => Some(WeightFormat::Safetensors),
+            "bin" | "pth" | "pt" => Some(WeightFormat::PyTorch),
+            _ => None,
+        }
+    }
+}
+
+/// An encoder model the app can install and load
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelDescriptor {
+    /// Hugging Face repository, e.g. `neuralmind/bert-base-portuguese-cased`
+    pub id: String,
+    pub name: String,
+    /// Revision installed when the configuration pins none
+    #[serde(default = "default_revision")]
+    pub revision: String,
+    /// Files besides the weights
+    #[serde(default = "default_files")]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub weight_format: WeightFormat,
+    pub embedding_size: usize,
+    /// Language codes of the essays the model suits, e.g. `pt`
+    pub languages: Vec<String>,
+    pub license: String,
+}
+
+fn default_revision() -> String {
+    DEFAULT_REVISION.to_string()
+}
+
+fn default_files() -> Vec<String> {
+    vec!["config.json".to_string(), "tokenizer.json".to_string()]
+}
+
+impl ModelDescriptor {
+    /// Configuration, tokenizer and the weights in the published format
+    pub fn required_files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self.files.iter().map(String::as_str).collect();
+        files.push(self.weight_format.file_name());
+        files
+    }
+
+    /// Whether the model suits a locale such as `pt-BR` or `en`
+    pub fn supports_language(&self, locale: &str) -> bool {
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        self.languages.iter().any(|code| code.eq_ignore_ascii_case(language))
+    }
+}
+
+pub fn builtin_models() -> Vec<ModelDescriptor> {
+    let descriptor = |id: &str, name: &str, embedding_size, languages: &[&str], license: &str| ModelDescriptor {
+        id: id.to_string(),
+        name: name.to_string(),
+        revision: default_revision(),
+        files: default_files(),
+        weight_format: WeightFormat::PyTorch,
+        embedding_size,
+        languages: languages.iter().map(|code| code.to_string()).collect(),
+        license: license.to_string(),
+    };
+    vec![
+        descriptor(MODEL_ID, "BERTimbau Base", 768, &["pt"], "mit"),
+        descriptor("neuralmind/bert-large-portuguese-cased", "BERTimbau Large", 1024, &["pt"], "mit"),
+        ModelDescriptor {
+            weight_format: WeightFormat::Safetensors,
+            ..descriptor(
+                "google-bert/bert-base-multilingual-cased",
+                "Multilingual BERT",
+                768,
+                &["pt", "en", "es", "fr", "de", "it"],
+                "apache-2.0",
+            )
+        },
+    ]
+}
+
+/// Directory of a model in the Hub cache, e.g. `models--neuralmind--bert-base-portuguese-cased`
+pub fn repo_dir_name(model_id: &str) -> String {
+    format!("models--{}", model_id.replace('/', "--"))
+}
+
+/// Commit of a file in the Hub cache, read from its `snapshots/<commit>/` directory
+pub fn snapshot_commit(path: &Path) -> Option<String> {
+    let snapshot = path.parent()?;
+    if snapshot.parent()?.file_name()? != "snapshots" {
+        return None;
+    }
+    Some(snapshot.file_name()?.to_string_lossy().into_owned())
+}
+
+/// `owner/name` made of characters the Hub accepts
+pub fn is_valid_model_id(model_id: &str) -> bool {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && !part.starts_with('.')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    matches!(model_id.split_once('/'), Some((owner, name)) if valid_part(owner) && valid_part(name))
+}
+
+/// The model the app loads and the revision it is pinned to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveModel {
+    pub descriptor: ModelDescriptor,
+    pub revision: String,
+}
+
+/// A registry entry with what is installed of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredModel {
+    pub descriptor: ModelDescriptor,
+    /// Cached snapshots holding the configuration, tokenizer and weights in any format
+    pub installed_revisions: Vec<String>,
+    pub size_bytes: u64,
+    pub active: bool,
+}
+
+pub struct ModelRegistry {
+    config_manager: Arc<AIConfigManager>,
+    cache_dir: PathBuf,
+}
+
+impl ModelRegistry {
+    pub fn new(config_manager: Arc<AIConfigManager>, cache_dir: PathBuf) -> Self {
+        Self { config_manager, cache_dir }
+    }
+
+    /// Registry over the default configuration and Hub cache
+    pub fn open() -> Result<Self> {
+        Ok(Self::new(Arc::new(AIConfigManager::new()?), AIConfigManager::get_default_cache_dir()?))
+    }
+
+    fn registry_path(&self) -> PathBuf {
+        self.config_manager.config_path().with_file_name(REGISTRY_FILE)
+    }
+
+    fn custom_models(&self) -> Result<Vec<ModelDescriptor>> {
+        let path = self.registry_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).context("Failed to read model registry")?;
+        serde_json::from_str(&content).context("Failed to parse model registry")
+    }
+
+    /// Built-in models followed by registered ones; a registered model
+    /// replaces the built-in with the same id
+    pub fn descriptors(&self) -> Result<Vec<ModelDescriptor>> {
+        let custom = self.custom_models()?;
+        let mut models: Vec<ModelDescriptor> = builtin_models()
+            .into_iter()
+            .filter(|builtin| !custom.iter().any(|model| model.id == builtin.id))
+            .collect();
+        models.extend(custom);
+        Ok(models)
+    }
+
+    pub fn get(&self, model_id: &str) -> Result<ModelDescriptor> {
+        self.descriptors()?
+            .into_iter()
+            .find(|model| model.id == model_id)
+            .with_context(|| format!("Model {} is not registered", model_id))
+    }
+
+    /// Add or replace a model in `models.json`
+    pub fn register(&self, descriptor: ModelDescriptor) -> Result<()> {
+        if !is_valid_model_id(&descriptor.id) {
+            bail!("Invalid model id {:?}; expected owner/name", descriptor.id);
+        }
+        if descriptor.embedding_size == 0 {
+            bail!("Model {} needs an embedding size", descriptor.id);
+        }
+        let mut custom = self.custom_models()?;
+        custom.retain(|model| model.id != descriptor.id);
+        custom.push(descriptor);
+        fs::write(self.registry_path(), serde_json::to_string_pretty(&custom)?)
+            .context("Failed to write model registry")
+    }
+
+    /// The configured model, BERTimbau Base when none is set
+    pub fn active(&self) -> Result<ActiveModel> {
+        let config = self.config_manager.load()?;
+        let descriptor = self.get(config.active_model.as_deref().unwrap_or(MODEL_ID))?;
+        let revision = config
+            .pinned_revision()
+            .map(str::to_string)
+            .unwrap_or_else(|| descriptor.revision.clone());
+        Ok(ActiveModel { descriptor, revision })
+    }
+
+    /// Make `model_id` the model the app loads, pinned to `revision` if given
+    ///
+    /// The model does not need to be installed yet; it is downloaded the
+    /// next time the encoder initializes.
+    pub fn switch(&self, model_id: &str, revision: Option<&str>) -> Result<ActiveModel> {
+        let descriptor = self.get(model_id)?;
+        let mut config = self.config_manager.load()?;
+        config.active_model = Some(descriptor.id.clone());
+        config.model_version = revision.map(str::to_string);
+        self.config_manager.save(&config)?;
+        self.active()
+    }
+
+    pub fn list(&self) -> Result<Vec<RegisteredModel>> {
+        let active = self.active().ok().map(|active| active.descriptor.id);
+        self.descriptors()?
+            .into_iter()
+            .map(|descriptor| {
+                let repo_dir = self.repo_dir(&descriptor.id);
+                Ok(RegisteredModel {
+                    installed_revisions: installed_revisions(&repo_dir, &descriptor),
+                    size_bytes: if repo_dir.exists() { AIConfigManager::calculate_dir_size(&repo_dir)? } else { 0 },
+                    active: active.as_deref() == Some(descriptor.id.as_str()),
+                    descriptor,
+                })
+            })
+            .collect()
+    }
+
+    pub fn repo_dir(&self, model_id: &str) -> PathBuf {
+        self.cache_dir.join(repo_dir_name(model_id))
+    }
+
+    /// Download a model at `revision` (the descriptor's default when `None`)
+    /// into the Hub cache
+    pub fn install(
+        &self,
+        model_id: &str,
+        revision: Option<&str>,
+        cancel: &CancelToken,
+        progress: Option<&ProgressCallback>,
+    ) -> Result<PathBuf> {
+        let descriptor = self.get(model_id)?;
+        let revision = revision.unwrap_or(&descriptor.revision);
+        let preferences = self.config_manager.load()?.download_preferences;
+        let downloads = DownloadManager::new(&descriptor.id, preferences)
+            .with_revision(revision)
+            .with_token(self.config_manager.resolve_token()?);
+        downloads.download_snapshot(&self.repo_dir(&descriptor.id), &descriptor.required_files(), cancel, progress)
+    }
+
+    /// Delete every cached revision of a model, including its quantized weights
+    pub fn remove(&self, model_id: &str) -> Result<()> {
+        if self.active()?.descriptor.id == model_id {
+            bail!("{} is the active model; switch to another model before removing it", model_id);
+        }
+        let repo_dir = self.repo_dir(model_id);
+        if repo_dir.exists() {
+            fs::remove_dir_all(&repo_dir).with_context(|| format!("Failed to remove {}", repo_dir.display()))?;
+        }
+        Ok(())
+    }
+}
+
+fn installed_revisions(repo_dir: &Path, descriptor: &ModelDescriptor) -> Vec<String> {
+    let entries = match fs::read_dir(repo_dir.join("snapshots")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut revisions: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let dir = entry.path();
+            dir.is_dir()
+                && descriptor.files.iter().all(|file| dir.join(file).exists())
+                && WEIGHT_FILES.iter().any(|file| dir.join(file).exists())
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    revisions.sort();
+    revisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_config::QuantizationLevel;
+    use tempfile::TempDir;
+
+    fn temp_registry() -> (ModelRegistry, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = AIConfigManager::with_config_path(dir.path().join("ai_config.json")).unwrap();
+        (ModelRegistry::new(Arc::new(manager), dir.path().join("hub")), dir)
+    }
+
+    fn fake_snapshot(registry: &ModelRegistry, model_id: &str, revision: &str) {
+        let snapshot = registry.repo_dir(model_id).join("snapshots").join(revision);
+        fs::create_dir_all(&snapshot).unwrap();
+        for file in ["config.json", "tokenizer.json", "pytorch_model.bin"] {
+            fs::write(snapshot.join(file), file).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_default_model_is_bertimbau_base() {
+        let (registry, _dir) = temp_registry();
+        let active = registry.active().unwrap();
+        assert_eq!(active.descriptor.id, MODEL_ID);
+        assert_eq!(active.revision, "main");
+        assert_eq!(active.descriptor.required_files(), ["config.json", "tokenizer.json", "pytorch_model.bin"]);
+        assert!(active.descriptor.supports_language("pt-BR"));
+        assert!(!active.descriptor.supports_language("en"));
+
+        let multilingual = registry.get("google-bert/bert-base-multilingual-cased").unwrap();
+        assert!(multilingual.supports_language("en_US"));
+    }
+
+    #[test]
+    fn test_switch_pins_revision() {
+        let (registry, _dir) = temp_registry();
+        let active = registry.switch("neuralmind/bert-large-portuguese-cased", Some("abc123")).unwrap();
+        assert_eq!(active.descriptor.embedding_size, 1024);
+        assert_eq!(active.revision, "abc123");
+
+        let active = registry.switch(MODEL_ID, None).unwrap();
+        assert_eq!(active.revision, "main");
+        assert!(registry.switch("someone/unknown-model", None).is_err());
+    }
+
+    #[test]
+    fn test_register_list_and_remove() {
+        let (registry, _dir) = temp_registry();
+        let custom = ModelDescriptor {
+            id: "escola/bert-redacoes".to_string(),
+            name: "BERT Redações".to_string(),
+            revision: "v2".to_string(),
+            files: default_files(),
+            weight_format: WeightFormat::PyTorch,
+            embedding_size: 768,
+            languages: vec!["pt".to_string()],
+            license: "cc-by-4.0".to_string(),
+        };
+        registry.register(custom.clone()).unwrap();
+        assert!(registry.register(ModelDescriptor { id: "../etc".to_string(), ..custom.clone() }).is_err());
+        assert_eq!(registry.get("escola/bert-redacoes").unwrap(), custom);
+
+        fake_snapshot(&registry, "escola/bert-redacoes", "v2");
+        // Either weight format makes a snapshot complete
+        let converted = registry.repo_dir("escola/bert-redacoes").join("snapshots").join("v3");
+        fs::create_dir_all(&converted).unwrap();
+        for file in ["config.json", "tokenizer.json", "model.safetensors"] {
+            fs::write(converted.join(file), file).unwrap();
+        }
+        // An incomplete snapshot is not an installed revision
+        fs::create_dir_all(registry.repo_dir("escola/bert-redacoes").join("snapshots").join("v1")).unwrap();
+
+        let models = registry.list().unwrap();
+        assert_eq!(models.len(), builtin_models().len() + 1);
+        let entry = models.iter().find(|model| model.descriptor.id == custom.id).unwrap();
+        assert_eq!(entry.installed_revisions, ["v2", "v3"]);
+        assert!(entry.size_bytes > 0);
+        assert!(!entry.active);
+        assert!(models.iter().any(|model| model.active && model.descriptor.id == MODEL_ID));
+
+        registry.switch(&custom.id, None).unwrap();
+        assert!(registry.remove(&custom.id).is_err());
+        registry.switch(MODEL_ID, None).unwrap();
+        registry.remove(&custom.id).unwrap();
+        assert!(!registry.repo_dir(&custom.id).exists());
+    }
+
+    #[test]
+    fn test_model_ids() {
+        assert!(is_valid_model_id(MODEL_ID));
+        assert!(!is_valid_model_id("bert-base"));
+        assert!(!is_valid_model_id("a/b/c"));
+        assert!(!is_valid_model_id("../secrets"));
+        assert_eq!(repo_dir_name(MODEL_ID), "models--neuralmind--bert-base-portuguese-cased");
+    }
+
+    #[test]
+    fn test_quantized_weights_are_kept_per_commit() {
+        let snapshot = Path::new("/hub/models--a--b/snapshots/94d69c9/pytorch_model.bin");
+        assert_eq!(snapshot_commit(snapshot).as_deref(), Some("94d69c9"));
+        assert_eq!(snapshot_commit(Path::new("/modelos/pytorch_model.bin")), None);
+
+        let old = AIConfigManager::get_quantized_model_path(MODEL_ID, "94d69c9", QuantizationLevel::Q8).unwrap();
+        let new = AIConfigManager::get_quantized_model_path(MODEL_ID, "a1b2c3d", QuantizationLevel::Q8).unwrap();
+        assert_ne!(old, new);
+    }
+}
@@ -0,0 +1,168 @@
+//! Provenance of automatic evaluations
+//!
+//! Every evaluation is stored with the model, tokenizer, scorer and rubric
+//! versions it ran with and a hash of its input, so a contested or old
+//! score can be re-run and compared with what was originally given.
+
+use anyhow::Result;
+use domain::essay::ExamRubric;
+use domain::provenance::EvaluationRecord;
+use domain::revision::CriterionDelta;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::revisions::criterion_deltas;
+
+/// Version of the layer that turns embeddings into competency scores
+pub const SCORING_HEAD_VERSION: &str = "heuristic-1";
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+/// SHA-256 of a file, read in chunks
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Git blob id of some bytes: SHA-1 over a `blob <size>\0` header and the bytes
+pub fn git_blob_id(bytes: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", bytes.len()));
+    hasher.update(bytes);
+    hex(&hasher.finalize())
+}
+
+/// Git blob id of a file, read in chunks
+pub fn git_blob_id_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", file.metadata()?.len()));
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash of what the scorer sees: the theme (title) and the content
+pub fn input_hash(title: &str, content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(title.as_bytes());
+    hasher.update([0]);
+    hasher.update(content.as_bytes());
+    hex(&hasher.finalize())
+}
+
+/// Changes whenever a criterion, its maximum or its weight changes
+pub fn rubric_version(rubric: &ExamRubric) -> String {
+    let serialized = serde_json::to_vec(rubric).unwrap_or_default();
+    sha256_hex(&serialized)[..16].to_string()
+}
+
+/// What differs between an evaluation and a later run of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordComparison {
+    pub same_input: bool,
+    pub model_changed: bool,
+    /// Scoring head version or heuristic thresholds changed
+    pub scorer_changed: bool,
+    pub rubric_changed: bool,
+    pub criterion_deltas: Vec<CriterionDelta>,
+    pub total_delta: i32,
+}
+
+impl RecordComparison {
+    /// The same input got the same scores
+    pub fn is_reproduced(&self) -> bool {
+        self.same_input && self.total_delta == 0 && self.criterion_deltas.iter().all(|d| d.delta == 0)
+    }
+}
+
+pub fn compare_records(before: &EvaluationRecord, after: &EvaluationRecord) -> RecordComparison {
+    RecordComparison {
+        same_input: before.input_hash == after.input_hash,
+        model_changed: before.model != after.model,
+        scorer_changed: before.scoring_head_version != after.scoring_head_version
+            || before.heuristic_config != after.heuristic_config,
+        rubric_changed: before.rubric_version != after.rubric_version,
+        criterion_deltas: criterion_deltas(Some(&before.scores), Some(&after.scores)),
+        total_delta: after.total_score as i32 - before.total_score as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use domain::essay::ExamType;
+    use domain::provenance::{HeuristicConfig, ModelProvenance};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    use crate::rubrics::get_rubric;
+
+    fn record(scores: &[(&str, u16)]) -> EvaluationRecord {
+        let scores: HashMap<String, u16> = scores.iter().map(|(c, s)| (c.to_string(), *s)).collect();
+        EvaluationRecord {
+            id: Uuid::new_v4(),
+            essay_id: Uuid::nil(),
+            exam_type: ExamType::Enem,
+            model: Some(ModelProvenance {
+                model_id: "neuralmind/bert-base-portuguese-cased".to_string(),
+                revision: Some("abc123".to_string()),
+                tokenizer_hash: sha256_hex(b"tokenizer"),
+                weights: Some("candle/f32".to_string()),
+                weight_format: Some("safetensors".to_string()),
+            }),
+            scoring_head_version: SCORING_HEAD_VERSION.to_string(),
+            rubric_version: rubric_version(get_rubric(&ExamType::Enem).unwrap()),
+            heuristic_config: HeuristicConfig::default(),
+            input_hash: input_hash("Tema", "Texto."),
+            total_score: scores.values().sum(),
+            scores,
+            duration_ms: 12,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_hashes_are_stable() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Same id as `git hash-object`
+        assert_eq!(git_blob_id(b"hello\n"), "ce013625030ba8dba906f756967f9e9ca394464a");
+        assert_eq!(input_hash("Tema", "Texto."), input_hash("Tema", "Texto."));
+        assert_ne!(input_hash("Tema", "Texto."), input_hash("TemaTexto.", ""));
+
+        let enem = get_rubric(&ExamType::Enem).unwrap();
+        assert_eq!(rubric_version(enem), rubric_version(&enem.clone()));
+        let mut changed = enem.clone();
+        changed.criteria[0].max_score = 100;
+        assert_ne!(rubric_version(enem), rubric_version(&changed));
+    }
+
+    #[test]
+    fn test_compare_records() {
+        let before = record(&[("C1", 160), ("C2", 120)]);
+        let same = compare_records(&before, &record(&[("C1", 160), ("C2", 120)]));
+        assert!(same.is_reproduced());
+        assert!(!same.model_changed && !same.scorer_changed && !same.rubric_changed);
+
+        let mut after = record(&[("C1", 160), ("C2", 160)]);
+        after.model.as_mut().unwrap().revision = Some("def456".to_string());
+        after.heuristic_config.c1_min_words = 150;
+        let changed = compare_records(&before, &after);
+        assert!(!changed.is_reproduced());
+        assert!(changed.model_changed && changed.scorer_changed);
+        assert_eq!(changed.total_delta, 40);
+        assert_eq!(changed.criterion_deltas[1].delta, 40);
+    }
+}
@@ -0,0 +1,485 @@
+//! Semantic question search
+//!
+//! Questions are embedded by the local encoder (statement, alternatives and
+//! explanation, mean-pooled) into an in-memory vector index searched by brute
+//! force; a bank of a few thousand questions is scanned in well under a
+//! millisecond. The embeddings come from the embedding cache, so rebuilding
+//! the index at startup costs no forward passes. Query similarities go to the
+//! question repository, which blends them with its BM25 scores so there is a
+//! single ranking. The index remembers the weights it was built from and
+//! starts over after a reload or a switch to other weights.
+
+use anyhow::{bail, Context, Result};
+use domain::question::Question;
+use domain::question_query::SemanticScores;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::ai::{AIService, ModelEvent};
+use crate::ai_config::ModelStatus;
+use crate::inference::{JobOptions, JobPriority, Pooling};
+use crate::provenance::sha256_hex;
+
+/// Share of the hybrid score given to semantic similarity
+pub const SEMANTIC_WEIGHT: f32 = 0.7;
+
+/// Similarity below which a question without keyword matches is left out
+pub const MIN_SIMILARITY: f32 = 0.35;
+
+/// Text a question is embedded from
+pub fn question_text(question: &Question) -> String {
+    let mut text = question.statement.clone();
+    for alternative in &question.alternatives {
+        text.push('\n');
+        text.push_str(&alternative.text);
+    }
+    if !question.explanation.is_empty() {
+        text.push('\n');
+        text.push_str(&question.explanation);
+    }
+    text
+}
+
+fn normalized(mut embedding: Vec<f32>) -> Vec<f32> {
+    let norm = embedding.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        embedding.iter_mut().for_each(|value| *value /= norm);
+    }
+    embedding
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len(), "embeddings of different sizes");
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+struct IndexedQuestion {
+    /// Hash of the embedded text, to re-embed edited questions
+    text_hash: String,
+    /// Unit length, so a dot product is the cosine similarity
+    embedding: Vec<f32>,
+}
+
+/// Question embeddings searched by brute-force cosine similarity
+///
+/// All embeddings come from one model, named as `AIService::embedding_model`
+/// names it, and have the same size.
+#[derive(Default)]
+pub struct VectorIndex {
+    model: Option<String>,
+    entries: HashMap<Uuid, IndexedQuestion>,
+}
+
+impl VectorIndex {
+    pub fn new(model: Option<String>) -> Self {
+        Self { model, entries: HashMap::new() }
+    }
+
+    /// Weights the indexed embeddings come from
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Size of the indexed embeddings; `None` while empty
+    pub fn dimension(&self) -> Option<usize> {
+        self.entries.values().next().map(|entry| entry.embedding.len())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether `id` is indexed from the text with hash `text_hash`
+    pub fn is_current(&self, id: Uuid, text_hash: &str) -> bool {
+        self.entries.get(&id).is_some_and(|entry| entry.text_hash == text_hash)
+    }
+
+    pub fn insert(&mut self, id: Uuid, text_hash: String, embedding: Vec<f32>) -> Result<()> {
+        self.check_dimension(&embedding)?;
+        self.entries.insert(id, IndexedQuestion { text_hash, embedding: normalized(embedding) });
+        Ok(())
+    }
+
+    fn check_dimension(&self, embedding: &[f32]) -> Result<()> {
+        match self.dimension() {
+            Some(dimension) if dimension != embedding.len() => bail!(
+                "Embedding has {} dimensions, the index {}",
+                embedding.len(),
+                dimension
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        self.entries.remove(&id);
+    }
+
+    /// Cosine similarity of every indexed question to `query`
+    pub fn similarities(&self, query: &[f32]) -> Result<HashMap<Uuid, f32>> {
+        self.check_dimension(query)?;
+        let query = normalized(query.to_vec());
+        Ok(self.entries.iter().map(|(id, entry)| (*id, dot(&query, &entry.embedding))).collect())
+    }
+
+    /// The `k` questions closest to `query`, most similar first
+    pub fn nearest(&self, query: &[f32], k: usize, exclude: Option<Uuid>) -> Result<Vec<(Uuid, f32)>> {
+        let mut scored: Vec<(Uuid, f32)> =
+            self.similarities(query)?.into_iter().filter(|(id, _)| Some(*id) != exclude).collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored.truncate(k);
+        Ok(scored)
+    }
+
+    /// The `k` questions closest to the indexed question `id`
+    pub fn neighbours(&self, id: Uuid, k: usize) -> Vec<(Uuid, f32)> {
+        match self.entries.get(&id) {
+            // Indexed embeddings always match the index's size
+            Some(entry) => self.nearest(&entry.embedding, k, Some(id)).unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A question close to another one, with their cosine similarity
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestionHit {
+    pub id: Uuid,
+    pub similarity: f32,
+}
+
+/// Semantic index of the question bank
+pub struct QuestionSearch {
+    ai: Arc<AIService>,
+    index: RwLock<VectorIndex>,
+    /// Lifecycle events since the index was last checked against the model
+    events: Mutex<broadcast::Receiver<ModelEvent>>,
+}
+
+impl QuestionSearch {
+    pub fn new(ai: Arc<AIService>) -> Self {
+        let events = Mutex::new(ai.subscribe());
+        Self { ai, index: RwLock::new(VectorIndex::default()), events }
+    }
+
+    /// Empty the index if the model was reloaded or other weights are loaded
+    /// since it was built
+    ///
+    /// A reload may change the backend or precision behind the same model
+    /// name; missed events count as a reload.
+    async fn sync_model(&self) {
+        let reloaded = {
+            let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+            let mut reloaded = false;
+            loop {
+                match events.try_recv() {
+                    Ok(ModelEvent::Status(ModelStatus::Ready)) | Err(TryRecvError::Lagged(_)) => reloaded = true,
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+                }
+            }
+            reloaded
+        };
+
+        let model = self.ai.embedding_model().await;
+        let mut index = self.index.write().await;
+        if reloaded || index.model() != model.as_deref() {
+            if !index.is_empty() {
+                tracing::info!("Rebuilding the question index for {}", model.as_deref().unwrap_or("no model"));
+            }
+            *index = VectorIndex::new(model);
+        }
+    }
+
+    /// Embed the questions that are new or changed since they were indexed
+    ///
+    /// Does nothing until the model is loaded; call it again once it is.
+    /// Returns the number of questions embedded.
+    pub async fn index_questions(&self, questions: &[Question]) -> Result<usize> {
+        if !self.ai.is_initialized().await {
+            return Ok(0);
+        }
+        self.sync_model().await;
+
+        let pending: Vec<(Uuid, String, String)> = {
+            let index = self.index.read().await;
+            questions
+                .iter()
+                .map(|question| {
+                    let text = question_text(question);
+                    (question.id, sha256_hex(text.as_bytes()), text)
+                })
+                .filter(|(id, hash, _)| !index.is_current(*id, hash))
+                .collect()
+        };
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let texts = pending.iter().map(|(_, _, text)| text.clone()).collect();
+        let options = JobOptions { priority: JobPriority::Low, pooling: Pooling::Mean, ..Default::default() };
+        let embeddings = self.ai.embed_texts(texts, options).await?;
+
+        let embedded = pending.len();
+        let mut index = self.index.write().await;
+        for ((id, hash, _), embedding) in pending.into_iter().zip(embeddings) {
+            index.insert(id, hash, embedding)?;
+        }
+        tracing::debug!("Indexed {} questions; {} in the semantic index", embedded, index.len());
+        Ok(embedded)
+    }
+
+    /// Drop a deleted question from the index
+    pub async fn remove(&self, id: Uuid) {
+        self.index.write().await.remove(id);
+    }
+
+    /// Similarity of `query` to the indexed questions, for the repository
+    /// to blend with its BM25 scores before paginating
+    ///
+    /// `None` when the model is not loaded. Questions below
+    /// `MIN_SIMILARITY` are left out, so they only match by keywords.
+    pub async fn semantic_scores(&self, query: &str) -> Result<Option<SemanticScores>> {
+        if query.trim().is_empty() || !self.ai.is_initialized().await {
+            return Ok(None);
+        }
+        self.sync_model().await;
+        let options = JobOptions { priority: JobPriority::High, pooling: Pooling::Mean, ..Default::default() };
+        let query_embedding = self
+            .ai
+            .embed_texts(vec![query.to_string()], options)
+            .await?
+            .pop()
+            .context("No embedding returned for the query")?;
+        let mut similarities = self.index.read().await.similarities(&query_embedding)?;
+        similarities.retain(|_, similarity| *similarity >= MIN_SIMILARITY);
+        Ok(Some(SemanticScores { similarities, weight: SEMANTIC_WEIGHT }))
+    }
+
+    /// Questions most similar to `id`, from the index
+    ///
+    /// Empty until `id` has been indexed.
+    pub async fn similar(&self, id: Uuid, limit: usize) -> Vec<QuestionHit> {
+        self.sync_model().await;
+        self.index
+            .read()
+            .await
+            .neighbours(id, limit)
+            .into_iter()
+            .map(|(id, similarity)| QuestionHit { id, similarity })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_config::InferenceBackend;
+    use crate::inference::{EncodedInput, EncoderBackend};
+    use domain::provenance::ModelProvenance;
+    use domain::question::{Alternative, Difficulty, Subject};
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::Tokenizer;
+
+    /// Topics the test encoder tells apart; words of one topic share a dimension
+    const TOPICS: [&[&str]; 3] = [
+        &["fotossíntese", "clorofila", "plantas", "luz"],
+        &["revolução", "francesa", "burguesia", "monarquia"],
+        &["equação", "segundo", "grau", "raízes"],
+    ];
+
+    /// Counts topic words, followed by `padding` constant dimensions
+    struct TopicEncoder {
+        tokenizer: Tokenizer,
+        padding: usize,
+    }
+
+    fn encoder(padding: usize) -> Arc<TopicEncoder> {
+        Arc::new(TopicEncoder { tokenizer: Tokenizer::new(WordLevel::default()), padding })
+    }
+
+    fn provenance(model_id: &str) -> ModelProvenance {
+        ModelProvenance {
+            model_id: model_id.to_string(),
+            revision: None,
+            tokenizer_hash: String::new(),
+            weights: None,
+            weight_format: None,
+        }
+    }
+
+    impl EncoderBackend for TopicEncoder {
+        fn kind(&self) -> InferenceBackend {
+            InferenceBackend::Candle
+        }
+
+        fn tokenizer(&self) -> &Tokenizer {
+            &self.tokenizer
+        }
+
+        fn forward(&self, _input: &EncodedInput) -> Result<Vec<Vec<f32>>> {
+            unreachable!("the topic encoder only pools batches")
+        }
+
+        fn pool_batch(&self, texts: &[String], _pooling: Pooling) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let words: Vec<String> = text
+                        .split(|c: char| !c.is_alphanumeric())
+                        .map(str::to_lowercase)
+                        .collect();
+                    let mut embedding: Vec<f32> =
+                        TOPICS.iter().map(|topic| topic.iter().filter(|word| words.iter().any(|w| w == **word)).count() as f32).collect();
+                    embedding.extend(std::iter::repeat_n(0.1, self.padding));
+                    embedding
+                })
+                .collect())
+        }
+    }
+
+    fn question(statement: &str, explanation: &str, tags: &[&str]) -> Question {
+        Question {
+            id: Uuid::new_v4(),
+            subject: Subject::Biologia,
+            difficulty: Difficulty::Medio,
+            statement: statement.to_string(),
+            alternatives: vec![Alternative { id: 0, text: "Nenhuma das anteriores".to_string() }],
+            correct_answer: 0,
+            explanation: explanation.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            source: None,
+        }
+    }
+
+    fn bank() -> Vec<Question> {
+        vec![
+            question("Qual pigmento absorve a luz nas plantas?", "A clorofila.", &["botânica"]),
+            question("Como as plantas produzem glicose?", "Pela fotossíntese.", &["botânica"]),
+            question("Qual classe liderou a Revolução Francesa?", "A burguesia.", &["história"]),
+            question("Quantas raízes tem uma equação do segundo grau?", "Até duas.", &["álgebra"]),
+        ]
+    }
+
+    async fn search_service(loaded: bool) -> QuestionSearch {
+        let ai = AIService::with_encoder(encoder(1), provenance("topics")).unwrap().with_embedding_cache(None);
+        if !loaded {
+            ai.unload().await;
+        }
+        QuestionSearch::new(Arc::new(ai))
+    }
+
+    #[test]
+    fn test_question_text_covers_alternatives_and_explanation() {
+        let question = bank().remove(0);
+        let text = question_text(&question);
+        assert!(text.contains("pigmento") && text.contains("Nenhuma das anteriores") && text.contains("clorofila"));
+    }
+
+    #[test]
+    fn test_nearest_ranks_by_cosine() {
+        let mut index = VectorIndex::default();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        index.insert(a, "a".to_string(), vec![1.0, 0.0]).unwrap();
+        index.insert(b, "b".to_string(), vec![0.6, 0.8]).unwrap();
+        index.insert(c, "c".to_string(), vec![0.0, 5.0]).unwrap();
+
+        let nearest = index.nearest(&[2.0, 0.0], 2, None).unwrap();
+        assert_eq!(nearest.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![a, b]);
+        assert!((nearest[0].1 - 1.0).abs() < 1e-6);
+        assert_eq!(index.neighbours(c, 1)[0].0, b);
+        assert!(index.is_current(a, "a") && !index.is_current(a, "changed"));
+    }
+
+    #[test]
+    fn test_embeddings_of_another_size_are_rejected() {
+        let mut index = VectorIndex::default();
+        index.insert(Uuid::new_v4(), "a".to_string(), vec![1.0, 0.0]).unwrap();
+        assert!(index.insert(Uuid::new_v4(), "b".to_string(), vec![1.0, 0.0, 0.0]).is_err());
+        assert!(index.similarities(&[1.0]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_index_is_rebuilt_for_another_model() {
+        let search = search_service(true).await;
+        let questions = bank();
+        search.index_questions(&questions).await.unwrap();
+        assert_eq!(search.index.read().await.model(), Some("topics@local/unknown"));
+
+        search.ai.replace_encoder(encoder(3), provenance("other")).await.unwrap();
+        assert_eq!(search.index_questions(&questions).await.unwrap(), 4);
+        assert_eq!(search.index.read().await.model(), Some("other@local/unknown"));
+        assert_eq!(search.index.read().await.dimension(), Some(6));
+    }
+
+    #[tokio::test]
+    async fn test_index_is_rebuilt_after_a_reload() {
+        let search = search_service(true).await;
+        let questions = bank();
+        search.index_questions(&questions).await.unwrap();
+
+        // Same name, other embeddings: only the reload tells them apart
+        search.ai.replace_encoder(encoder(2), provenance("topics")).await.unwrap();
+        // Neighbours from the old embeddings are gone
+        assert!(search.similar(questions[0].id, 1).await.is_empty());
+        search.index_questions(&questions).await.unwrap();
+        assert_eq!(search.index.read().await.dimension(), Some(5));
+        assert_eq!(search.similar(questions[0].id, 1).await[0].id, questions[1].id);
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_finds_questions_without_shared_words() {
+        let search = search_service(true).await;
+        let questions = bank();
+        assert_eq!(search.index_questions(&questions).await.unwrap(), 4);
+        // Already indexed questions are not embedded again
+        assert_eq!(search.index_questions(&questions).await.unwrap(), 0);
+
+        // "fotossíntese" appears in one question only, but both plant questions match
+        let scores = search.semantic_scores("fotossíntese e clorofila").await.unwrap().unwrap();
+        let mut ids: Vec<Uuid> = scores.similarities.keys().copied().collect();
+        ids.sort();
+        let mut expected = vec![questions[0].id, questions[1].id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let similar = search.similar(questions[0].id, 1).await;
+        assert_eq!(similar[0].id, questions[1].id);
+    }
+
+    #[tokio::test]
+    async fn test_edited_questions_are_reindexed() {
+        let search = search_service(true).await;
+        let mut questions = bank();
+        search.index_questions(&questions).await.unwrap();
+
+        questions[3].statement = "Quem aboliu a monarquia na França?".to_string();
+        questions[3].explanation = "A revolução.".to_string();
+        assert_eq!(search.index_questions(&questions).await.unwrap(), 1);
+        assert_eq!(search.similar(questions[3].id, 1).await[0].id, questions[2].id);
+    }
+
+    #[tokio::test]
+    async fn test_semantic_scores_for_the_repository() {
+        let search = search_service(true).await;
+        let questions = bank();
+        search.index_questions(&questions).await.unwrap();
+
+        let scores = search.semantic_scores("clorofila").await.unwrap().unwrap();
+        assert_eq!(scores.weight, SEMANTIC_WEIGHT);
+        let mut ids: Vec<Uuid> = scores.similarities.keys().copied().collect();
+        ids.sort();
+        let mut expected = vec![questions[0].id, questions[1].id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        assert_eq!(search_service(false).await.semantic_scores("clorofila").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,301 @@
+//! Readability and lexical metrics for Portuguese texts
+//!
+//! Everything here is rule-based and approximate: syllables follow the usual
+//! hiatus/diphthong rules, subordination and passive voice are detected from
+//! connectives and `ser` + participle patterns.
+
+use domain::text_metrics::{RegisterMarkers, TextMetrics};
+use std::collections::HashSet;
+
+/// TTR below which an MTLD factor is closed (McCarthy & Jarvis, 2010)
+const MTLD_THRESHOLD: f32 = 0.72;
+
+const SUBORDINATORS: &[&str] = &[
+    "que", "porque", "embora", "quando", "caso", "enquanto", "conforme", "conquanto",
+    "onde", "cujo", "cuja", "cujos", "cujas", "porquanto",
+];
+
+const SER_FORMS: &[&str] = &[
+    "ser", "é", "são", "foi", "foram", "era", "eram", "será", "serão", "seria", "seriam",
+    "seja", "sejam", "fosse", "fossem", "sido", "sendo",
+];
+
+const IRREGULAR_PARTICIPLES: &[&str] = &[
+    "feito", "feita", "dito", "dita", "posto", "posta", "visto", "vista", "aberto", "aberta",
+    "escrito", "escrita", "eleito", "eleita", "aceito", "aceita", "preso", "presa", "pago",
+    "paga", "gasto", "gasta", "entregue", "morto", "morta",
+];
+
+/// Words ending like participles that are not verbs
+const FALSE_PARTICIPLES: &[&str] = &["nada", "cada", "vida", "lado", "medida", "saída", "ida"];
+
+const ORALITY: &[&str] = &["né", "aí", "daí", "pra", "pro", "pras", "pros", "tá", "tô", "tava", "vc", "você", "vocês"];
+const ORALITY_PHRASES: &[&str] = &["tipo assim", "a gente", "sei lá"];
+const SLANG: &[&str] = &[
+    "galera", "mano", "treta", "zoeira", "rolê", "vacilo", "bagulho", "maneiro", "firmeza",
+    "top", "pô", "véi", "mó",
+];
+const FIRST_PERSON: &[&str] = &["eu", "me", "mim", "comigo", "meu", "minha", "meus", "minhas"];
+
+fn is_vowel(c: char) -> bool {
+    "aeiouáéíóúâêôãõàü".contains(c)
+}
+
+fn is_strong_vowel(c: char) -> bool {
+    "aeoáéóâêôãõà".contains(c)
+}
+
+/// Whether `current` starts a new syllable right after the vowel `previous`
+fn is_hiatus(previous: char, current: char) -> bool {
+    match (previous, current) {
+        // Accented weak vowels break the diphthong: saúde, país, juízo
+        (_, 'í') | (_, 'ú') => true,
+        // Nasal diphthongs: mão, põe, mãe
+        ('ã', _) | ('õ', _) => false,
+        // Two strong vowels never share a syllable: poeta, área, voo
+        (p, c) => is_strong_vowel(p) && is_strong_vowel(c),
+    }
+}
+
+/// Approximate number of syllables of a Portuguese word
+pub fn count_syllables(word: &str) -> u32 {
+    let chars: Vec<char> = word
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect();
+    if chars.is_empty() {
+        return 0;
+    }
+
+    let mut count = 0;
+    for (idx, &c) in chars.iter().enumerate() {
+        if !is_vowel(c) {
+            continue;
+        }
+        match idx.checked_sub(1).map(|prev| chars[prev]) {
+            Some(prev) if is_vowel(prev) => {
+                if is_hiatus(prev, c) {
+                    count += 1;
+                }
+            }
+            _ => count += 1,
+        }
+    }
+    count.max(1)
+}
+
+/// Flesch reading ease adapted to Portuguese by Martins et al. (1996)
+pub fn flesch_martins(avg_sentence_length: f32, avg_syllables_per_word: f32) -> f32 {
+    (248.835 - 1.015 * avg_sentence_length - 84.6 * avg_syllables_per_word).clamp(0.0, 100.0)
+}
+
+fn mtld_pass<'a>(tokens: impl Iterator<Item = &'a String>) -> f32 {
+    let mut factors = 0.0;
+    let mut types = HashSet::new();
+    let mut count = 0usize;
+    let mut total = 0usize;
+
+    for token in tokens {
+        total += 1;
+        count += 1;
+        types.insert(token);
+        if (types.len() as f32 / count as f32) <= MTLD_THRESHOLD {
+            factors += 1.0;
+            types.clear();
+            count = 0;
+        }
+    }
+    if count > 0 {
+        let ttr = types.len() as f32 / count as f32;
+        factors += (1.0 - ttr) / (1.0 - MTLD_THRESHOLD);
+    }
+
+    if factors == 0.0 {
+        total as f32
+    } else {
+        total as f32 / factors
+    }
+}
+
+/// Measure of Textual Lexical Diversity, averaged over both reading directions
+pub fn mtld(tokens: &[String]) -> f32 {
+    if tokens.is_empty() {
+        return 0.0;
+    }
+    (mtld_pass(tokens.iter()) + mtld_pass(tokens.iter().rev())) / 2.0
+}
+
+fn is_participle(word: &str) -> bool {
+    if FALSE_PARTICIPLES.contains(&word) {
+        return false;
+    }
+    let singular = word.strip_suffix('s').unwrap_or(word);
+    ["ado", "ada", "ido", "ida"].iter().any(|end| singular.ends_with(end) && singular.len() > 4)
+        || IRREGULAR_PARTICIPLES.contains(&singular)
+}
+
+fn is_passive(sentence: &[String]) -> bool {
+    // Synthetic passive: vende-se, discutem-se
+    if sentence.iter().any(|w| w.ends_with("-se") && w.len() > 4) {
+        return true;
+    }
+    // Analytic passive: ser + (adverb) + participle
+    sentence.iter().enumerate().any(|(idx, word)| {
+        SER_FORMS.contains(&word.as_str())
+            && sentence[idx + 1..]
+                .iter()
+                .take(2)
+                .any(|next| is_participle(next))
+    })
+}
+
+fn register_markers(tokens: &[String]) -> RegisterMarkers {
+    fn record(found: &mut Vec<String>, marker: &str) {
+        if !found.iter().any(|m| m == marker) {
+            found.push(marker.to_string());
+        }
+    }
+
+    let mut markers = RegisterMarkers::default();
+
+    for (idx, token) in tokens.iter().enumerate() {
+        let word = token.as_str();
+        if let Some(next) = tokens.get(idx + 1) {
+            let phrase = format!("{} {}", word, next);
+            if ORALITY_PHRASES.contains(&phrase.as_str()) {
+                markers.orality += 1;
+                record(&mut markers.found, &phrase);
+                continue;
+            }
+        }
+        if ORALITY.contains(&word) {
+            markers.orality += 1;
+            record(&mut markers.found, word);
+        } else if SLANG.contains(&word) {
+            markers.slang += 1;
+            record(&mut markers.found, word);
+        } else if FIRST_PERSON.contains(&word) {
+            markers.first_person += 1;
+            record(&mut markers.found, word);
+        }
+    }
+    markers
+}
+
+/// Split a text into sentences of lowercase word tokens
+fn tokenize_sentences(content: &str) -> Vec<Vec<String>> {
+    fn flush_word(word: &mut String, current: &mut Vec<String>) {
+        let trimmed = word.trim_matches(|c| c == '-' || c == '\'');
+        if !trimmed.is_empty() {
+            current.push(trimmed.to_lowercase());
+        }
+        word.clear();
+    }
+
+    let mut sentences = Vec::new();
+    let mut current = Vec::new();
+    let mut word = String::new();
+
+    for c in content.chars() {
+        if c.is_alphanumeric() || c == '-' || c == '\'' {
+            word.push(c);
+            continue;
+        }
+        flush_word(&mut word, &mut current);
+        // Line breaks also close a sentence so titles and paragraphs don't merge
+        if matches!(c, '.' | '!' | '?' | '\n') && !current.is_empty() {
+            sentences.push(std::mem::take(&mut current));
+        }
+    }
+    flush_word(&mut word, &mut current);
+    if !current.is_empty() {
+        sentences.push(current);
+    }
+    sentences
+}
+
+/// Compute readability, lexical diversity, syntax and register metrics for a text
+pub fn compute_text_metrics(content: &str) -> TextMetrics {
+    let sentences = tokenize_sentences(content);
+    let tokens: Vec<String> = sentences.iter().flatten().cloned().collect();
+    if tokens.is_empty() {
+        return TextMetrics::default();
+    }
+
+    let word_count = tokens.len() as f32;
+    let sentence_count = sentences.len() as f32;
+    let syllables: u32 = tokens.iter().map(|w| count_syllables(w)).sum();
+    let avg_sentence_length = word_count / sentence_count;
+    let avg_syllables_per_word = syllables as f32 / word_count;
+
+    let types: HashSet<&String> = tokens.iter().collect();
+
+    let depths: Vec<u32> = sentences
+        .iter()
+        .map(|s| s.iter().filter(|w| SUBORDINATORS.contains(&w.as_str())).count() as u32)
+        .collect();
+    let passive = sentences.iter().filter(|s| is_passive(s)).count();
+
+    TextMetrics {
+        word_count: tokens.len() as u32,
+        sentence_count: sentences.len() as u32,
+        paragraph_count: content.lines().filter(|l| !l.trim().is_empty()).count() as u32,
+        avg_sentence_length,
+        avg_syllables_per_word,
+        flesch_martins: flesch_martins(avg_sentence_length, avg_syllables_per_word),
+        type_token_ratio: types.len() as f32 / word_count,
+        mtld: mtld(&tokens),
+        avg_subordination_depth: depths.iter().sum::<u32>() as f32 / sentence_count,
+        max_subordination_depth: depths.iter().copied().max().unwrap_or(0),
+        passive_voice_ratio: passive as f32 / sentence_count,
+        register: register_markers(&tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_syllables() {
+        assert_eq!(count_syllables("educação"), 4);
+        assert_eq!(count_syllables("saúde"), 3);
+        assert_eq!(count_syllables("poeta"), 3);
+        assert_eq!(count_syllables("brasileiro"), 4);
+        assert_eq!(count_syllables("que"), 1);
+        assert_eq!(count_syllables("país"), 2);
+    }
+
+    #[test]
+    fn test_compute_text_metrics() {
+        let formal = "A educação pública foi ampliada nas últimas décadas. \
+                      Entretanto, a evasão escolar persiste, porque muitos jovens precisam trabalhar.\n\n\
+                      Portanto, é necessário que o Estado amplie programas de permanência estudantil.";
+        let metrics = compute_text_metrics(formal);
+        assert_eq!(metrics.sentence_count, 3);
+        assert_eq!(metrics.paragraph_count, 2);
+        assert!(metrics.register.is_formal());
+        assert!(metrics.passive_voice_ratio > 0.0);
+        assert!(metrics.avg_subordination_depth > 0.0);
+        assert!((0.0..=100.0).contains(&metrics.flesch_martins));
+
+        let informal = "Eu acho que a galera tá certa, né. Tipo assim, pra mim a escola é chata.";
+        let register = compute_text_metrics(informal).register;
+        assert_eq!(register.first_person, 2);
+        assert_eq!(register.slang, 1);
+        assert_eq!(register.orality, 4);
+        assert!(register.found.contains(&"tipo assim".to_string()));
+    }
+
+    #[test]
+    fn test_mtld_rewards_diversity() {
+        let repetitive: Vec<String> = "a escola é boa e a escola é boa ".repeat(4).split_whitespace().map(String::from).collect();
+        let diverse: Vec<String> = "investir em professores reduz desigualdades históricas entre regiões brasileiras"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        assert!(mtld(&diverse) > mtld(&repetitive));
+        assert_eq!(compute_text_metrics("   "), TextMetrics::default());
+    }
+}
@@ -0,0 +1,167 @@
+//! Teacher review of automatic evaluations flagged as uncertain
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use domain::essay::Essay;
+use domain::review::{HumanReview, ReviewStatus};
+use domain::answer_sheet::AnswerSheet;
+use std::collections::HashMap;
+
+use crate::evaluation::screen_nota_zero;
+use crate::rubrics::get_rubric;
+
+/// Widest confidence interval of an essay, relative to the criterion maximum
+fn uncertainty(essay: &Essay) -> f32 {
+    let Some(confidence) = &essay.confidence else {
+        return 0.0;
+    };
+    let max_scores: HashMap<&str, u16> = get_rubric(&essay.exam_type)
+        .map(|r| r.criteria.iter().map(|c| (c.name.as_str(), c.max_score)).collect())
+        .unwrap_or_default();
+    confidence
+        .iter()
+        .map(|(name, interval)| {
+            let max = max_scores.get(name.as_str()).copied().unwrap_or(200).max(1);
+            interval.width() as f32 / max as f32
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Order pending evaluations with the most uncertain first, then the oldest
+pub fn review_queue(mut essays: Vec<Essay>) -> Vec<Essay> {
+    essays.retain(|e| e.review.as_ref().is_some_and(HumanReview::is_pending));
+    essays.sort_by(|a, b| {
+        uncertainty(b)
+            .total_cmp(&uncertainty(a))
+            .then(a.updated_at.cmp(&b.updated_at))
+    });
+    essays
+}
+
+fn finish_review(essay: &mut Essay, status: ReviewStatus, note: Option<String>) -> &mut HumanReview {
+    let review = essay.review.get_or_insert_with(|| HumanReview::pending(Vec::new()));
+    review.status = status;
+    review.note = note.filter(|n| !n.trim().is_empty());
+    review.reviewed_at = Some(Utc::now());
+    essay.updated_at = Utc::now();
+    review
+}
+
+/// Keep the automatic scores
+pub fn confirm_review(essay: &mut Essay, note: Option<String>) -> Result<()> {
+    if essay.rubric_scores.is_none() {
+        bail!("Essay has not been evaluated");
+    }
+    finish_review(essay, ReviewStatus::Confirmada, note);
+    Ok(())
+}
+
+/// Replace some competency scores, keeping the automatic ones for reference
+///
+/// An essay zeroed on the eliminatory criteria has no competency scores to
+/// override.
+pub fn override_scores(essay: &mut Essay, scores: HashMap<String, u16>, note: Option<String>) -> Result<()> {
+    let sheet = AnswerSheet::for_exam(&essay.exam_type);
+    if let Some(reason) = screen_nota_zero(&essay.content, sheet.estimate_lines(&essay.content), &sheet) {
+        bail!("Essay received zero, its scores cannot be overridden: {}", reason.description());
+    }
+    let rubric = get_rubric(&essay.exam_type).context("Rubric not found for exam type")?;
+    for (name, score) in &scores {
+        let criterion = rubric
+            .criteria
+            .iter()
+            .find(|c| &c.name == name)
+            .with_context(|| format!("Unknown criterion {}", name))?;
+        if !criterion.is_level(*score) {
+            bail!(
+                "{} score {} is not a level of the rubric (multiples of {} up to {})",
+                name,
+                score,
+                criterion.level_step(),
+                criterion.max_score
+            );
+        }
+    }
+
+    let rubric_scores = essay.rubric_scores.as_mut().context("Essay has not been evaluated")?;
+    let original = rubric_scores.scores.clone();
+    rubric_scores.scores.extend(scores);
+    essay.score = Some(rubric_scores.scores.values().sum());
+
+    let review = finish_review(essay, ReviewStatus::Alterada, note);
+    review.original_scores.get_or_insert(original);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::essay::{EssayStatus, ExamType, RubricScores};
+    use domain::review::{ReviewReason, ScoreInterval};
+    use uuid::Uuid;
+
+    fn flagged_essay(width: u16) -> Essay {
+        let scores = HashMap::from([("C1".to_string(), 120), ("C2".to_string(), 160)]);
+        let content = "A educação pública precisa de investimentos contínuos e planejamento. ".repeat(30);
+        let mut essay = Essay::new(Uuid::new_v4(), "Tema", &content, ExamType::Enem);
+        essay.status = EssayStatus::Corrigida;
+        essay.score = Some(280);
+        essay.rubric_scores = Some(RubricScores {
+            scores,
+            detailed_feedback: HashMap::new(),
+        });
+        essay.confidence = Some(HashMap::from([(
+            "C1".to_string(),
+            ScoreInterval { lower: 120, upper: 120 + width, agreement: 0.5 },
+        )]));
+        essay.review = Some(HumanReview::pending(vec![ReviewReason::IncertezaAlta {
+            criterion: "C1".to_string(),
+            lower: 120,
+            upper: 120 + width,
+        }]));
+        essay
+    }
+
+    #[test]
+    fn test_queue_puts_most_uncertain_first() {
+        let mut reviewed = flagged_essay(80);
+        confirm_review(&mut reviewed, None).unwrap();
+        let narrow = flagged_essay(40);
+        let wide = flagged_essay(80);
+
+        let queue = review_queue(vec![narrow.clone(), reviewed, wide.clone()]);
+        let ids: Vec<_> = queue.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![wide.id, narrow.id]);
+    }
+
+    #[test]
+    fn test_override_recomputes_total() {
+        let mut essay = flagged_essay(80);
+        override_scores(&mut essay, HashMap::from([("C1".to_string(), 160)]), Some("Bom domínio".to_string())).unwrap();
+
+        assert_eq!(essay.score, Some(320));
+        let review = essay.review.unwrap();
+        assert_eq!(review.status, ReviewStatus::Alterada);
+        assert_eq!(review.original_scores.unwrap()["C1"], 120);
+        assert_eq!(review.note.as_deref(), Some("Bom domínio"));
+    }
+
+    #[test]
+    fn test_override_rejects_invalid_scores() {
+        let mut essay = flagged_essay(80);
+        assert!(override_scores(&mut essay, HashMap::from([("C1".to_string(), 240)]), None).is_err());
+        assert!(override_scores(&mut essay, HashMap::from([("C1".to_string(), 150)]), None).is_err());
+        assert!(override_scores(&mut essay, HashMap::from([("C9".to_string(), 40)]), None).is_err());
+        assert!(essay.review.unwrap().is_pending());
+    }
+
+    #[test]
+    fn test_override_refuses_nota_zero_essays() {
+        let mut essay = flagged_essay(80);
+        essay.content = "Texto curto demais.".to_string();
+        let error = override_scores(&mut essay, HashMap::from([("C1".to_string(), 160)]), None).unwrap_err();
+        assert!(error.to_string().contains("zero"));
+        assert_eq!(essay.score, Some(280));
+        assert!(essay.review.unwrap().is_pending());
+    }
+}
@@ -0,0 +1,159 @@
+use domain::essay::RubricScores;
+use domain::revision::{CriterionDelta, DiffOp, DiffSegment, EssayRevision, RevisionComparison};
+use std::collections::{BTreeSet, HashMap};
+
+/// Compare two revisions of the same essay
+/// Produces the word-level diff and the per-criterion score deltas
+pub fn compare_revisions(from: &EssayRevision, to: &EssayRevision) -> RevisionComparison {
+    let diff = word_diff(&from.content, &to.content);
+    let count_words = |op: DiffOp| {
+        diff.iter()
+            .filter(|s| s.op == op)
+            .map(|s| s.text.split_whitespace().count())
+            .sum()
+    };
+
+    let total_delta = match (from.score, to.score) {
+        (Some(before), Some(after)) => Some(after as i32 - before as i32),
+        _ => None,
+    };
+
+    RevisionComparison {
+        from_revision: from.revision_number,
+        to_revision: to.revision_number,
+        words_added: count_words(DiffOp::Insert),
+        words_removed: count_words(DiffOp::Delete),
+        criterion_deltas: score_deltas(from.rubric_scores.as_ref(), to.rubric_scores.as_ref()),
+        total_delta,
+        diff,
+    }
+}
+
+/// Word-level diff between two texts based on the longest common subsequence
+pub fn word_diff(old: &str, new: &str) -> Vec<DiffSegment> {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    let (n, m) = (old_words.len(), new_words.len());
+
+    // lcs[i][j] = LCS length of old_words[i..] and new_words[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_words[i] == new_words[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    let mut push = |op: DiffOp, word: &str| match segments.last_mut() {
+        Some(last) if last.op == op => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => segments.push(DiffSegment { op, text: word.to_string() }),
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_words[i] == new_words[j] {
+            push(DiffOp::Equal, old_words[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push(DiffOp::Delete, old_words[i]);
+            i += 1;
+        } else {
+            push(DiffOp::Insert, new_words[j]);
+            j += 1;
+        }
+    }
+    for word in &old_words[i..] {
+        push(DiffOp::Delete, word);
+    }
+    for word in &new_words[j..] {
+        push(DiffOp::Insert, word);
+    }
+
+    segments
+}
+
+/// Score change for every criterion present in either revision, ordered by criterion name
+pub fn score_deltas(
+    before: Option<&RubricScores>,
+    after: Option<&RubricScores>,
+) -> Vec<CriterionDelta> {
+    criterion_deltas(before.map(|r| &r.scores), after.map(|r| &r.scores))
+}
+
+/// Score change for every criterion present in either score map, ordered by criterion name
+pub fn criterion_deltas(
+    before: Option<&HashMap<String, u16>>,
+    after: Option<&HashMap<String, u16>>,
+) -> Vec<CriterionDelta> {
+    let criteria: BTreeSet<&String> = before
+        .into_iter()
+        .chain(after)
+        .flat_map(|scores| scores.keys())
+        .collect();
+
+    criteria
+        .into_iter()
+        .map(|criterion| {
+            let before = before.and_then(|scores| scores.get(criterion).copied());
+            let after = after.and_then(|scores| scores.get(criterion).copied());
+            CriterionDelta {
+                criterion: criterion.clone(),
+                before,
+                after,
+                delta: after.unwrap_or(0) as i32 - before.unwrap_or(0) as i32,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rubric(scores: &[(&str, u16)]) -> RubricScores {
+        RubricScores {
+            scores: scores.iter().map(|(c, s)| (c.to_string(), *s)).collect(),
+            detailed_feedback: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let diff = word_diff("o governo precisa agir", "o Ministério da Educação precisa agir");
+        assert_eq!(diff, vec![
+            DiffSegment { op: DiffOp::Equal, text: "o".to_string() },
+            DiffSegment { op: DiffOp::Delete, text: "governo".to_string() },
+            DiffSegment { op: DiffOp::Insert, text: "Ministério da Educação".to_string() },
+            DiffSegment { op: DiffOp::Equal, text: "precisa agir".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_word_diff_identical_texts() {
+        let diff = word_diff("mesmo texto", "mesmo   texto");
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].op, DiffOp::Equal);
+    }
+
+    #[test]
+    fn test_score_deltas() {
+        let before = rubric(&[("C1", 120), ("C5", 80)]);
+        let after = rubric(&[("C1", 160), ("C5", 80), ("C4", 120)]);
+        let deltas = score_deltas(Some(&before), Some(&after));
+
+        assert_eq!(deltas.len(), 3);
+        assert_eq!(deltas[0].criterion, "C1");
+        assert_eq!(deltas[0].delta, 40);
+        assert_eq!(deltas[1].criterion, "C4");
+        assert_eq!(deltas[1].before, None);
+        assert_eq!(deltas[2].delta, 0);
+    }
+}
@@ -19,6 +19,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Domínio da modalidade escrita formal da língua portuguesa".to_string(),
                     weight: 0.2,
                     max_score: 200,
+                    levels: 5,
                     evaluation_points: vec![
                         "Gramática e ortografia".to_string(),
                         "Sintaxe e concordância".to_string(),
@@ -31,6 +32,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Compreensão da proposta de redação e aplicação de conhecimentos".to_string(),
                     weight: 0.2,
                     max_score: 200,
+                    levels: 5,
                     evaluation_points: vec![
                         "Adequação ao tema proposto".to_string(),
                         "Estrutura dissertativo-argumentativa".to_string(),
@@ -43,6 +45,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Seleção, relação, organização e interpretação de informações".to_string(),
                     weight: 0.2,
                     max_score: 200,
+                    levels: 5,
                     evaluation_points: vec![
                         "Coerência lógica".to_string(),
                         "Qualidade dos argumentos".to_string(),
@@ -55,6 +58,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Conhecimento dos mecanismos linguísticos de argumentação".to_string(),
                     weight: 0.2,
                     max_score: 200,
+                    levels: 5,
                     evaluation_points: vec![
                         "Coesão textual".to_string(),
                         "Uso de conectivos e operadores argumentativos".to_string(),
@@ -67,6 +71,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Proposta de intervenção respeitando direitos humanos".to_string(),
                     weight: 0.2,
                     max_score: 200,
+                    levels: 5,
                     evaluation_points: vec![
                         "Proposta clara e viável".to_string(),
                         "Detalhamento da ação".to_string(),
@@ -91,6 +96,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Organização textual e estrutura argumentativa".to_string(),
                     weight: 0.33,
                     max_score: 16,
+                    levels: 16,
                     evaluation_points: vec![
                         "Introdução, desenvolvimento e conclusão".to_string(),
                         "Progressão de ideias".to_string(),
@@ -101,6 +107,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Qualidade argumentativa e repertório".to_string(),
                     weight: 0.33,
                     max_score: 16,
+                    levels: 16,
                     evaluation_points: vec![
                         "Argumentos consistentes".to_string(),
                         "Conhecimento do tema".to_string(),
@@ -111,6 +118,7 @@ pub static EXAM_RUBRICS: Lazy<HashMap<ExamType, ExamRubric>> = Lazy::new(|| {
                     description: "Norma culta e coesão".to_string(),
                     weight: 0.34,
                     max_score: 16,
+                    levels: 16,
                     evaluation_points: vec![
                         "Gramática e ortografia".to_string(),
                         "Coesão e coerência".to_string(),
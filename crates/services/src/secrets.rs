@@ -0,0 +1,201 @@
+//! Authenticated encryption of stored secrets
+//!
+//! Values are sealed with XChaCha20-Poly1305 under a key read from a local
+//! key file, or derived from a passphrase with Argon2id. A sealed value is
+//! `v1:` followed by the base64 of the nonce and the ciphertext; the name
+//! of the field it belongs to is bound in as associated data, so a value
+//! copied into another field does not open either.
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Marks values sealed by `SecretKey::seal`, as opposed to legacy base64
+pub const SEALED_PREFIX: &str = "v1:";
+pub const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+pub struct SecretKey(Key);
+
+impl SecretKey {
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// Read an existing key file, failing if it is missing
+    ///
+    /// Used to open sealed values: a new key could never open them.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            bail!("Key file {} is missing; values sealed under it cannot be opened", path.display());
+        }
+        let bytes = fs::read(path)
+            .with_context(|| format!("Failed to read key file {}", path.display()))?;
+        if bytes.len() != KEY_LEN {
+            bail!("Key file {} is corrupted", path.display());
+        }
+        restrict_permissions(path)?;
+        Ok(Self(*Key::from_slice(&bytes)))
+    }
+
+    /// Read the key file, creating it readable by the owner only when missing
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if path.exists() {
+            return Self::load(path);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let key = Self::generate();
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(path)
+            .and_then(|mut file| file.write_all(key.0.as_slice()))
+            .with_context(|| format!("Failed to create key file {}", path.display()))?;
+        Ok(key)
+    }
+
+    /// Argon2id over the passphrase; the same salt gives the same key
+    pub fn from_passphrase(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        if passphrase.is_empty() {
+            bail!("Passphrase cannot be empty");
+        }
+        let mut key = Key::default();
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, key.as_mut_slice())
+            .map_err(|e| anyhow::anyhow!("Failed to derive key: {}", e))?;
+        Ok(Self(key))
+    }
+
+    pub fn seal(&self, plaintext: &str, context: &str) -> Result<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, Payload { msg: plaintext.as_bytes(), aad: context.as_bytes() })
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt secret"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", SEALED_PREFIX, general_purpose::STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt a sealed value, failing if it was altered or sealed under another key or context
+    pub fn open(&self, sealed: &str, context: &str) -> Result<String> {
+        let encoded = sealed.strip_prefix(SEALED_PREFIX).context("Value is not sealed")?;
+        let bytes = general_purpose::STANDARD
+            .decode(encoded)
+            .context("Sealed value is not valid base64")?;
+        if bytes.len() < NONCE_LEN {
+            bail!("Sealed value is truncated");
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = XChaCha20Poly1305::new(&self.0)
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: context.as_bytes() })
+            .map_err(|_| anyhow::anyhow!("Secret failed authentication; it was altered or the key changed"))?;
+        String::from_utf8(plaintext).context("Secret is not valid UTF-8")
+    }
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+pub fn generate_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode();
+    if mode & 0o077 != 0 {
+        tracing::warn!("Key file {} was readable by other users; restricting it", path.display());
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: &str = "huggingface_token";
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = SecretKey::generate();
+        let sealed = key.seal("hf_abcdefghijklmnop", CONTEXT).unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("hf_"));
+        assert_eq!(key.open(&sealed, CONTEXT).unwrap(), "hf_abcdefghijklmnop");
+
+        // A fresh nonce every time
+        assert_ne!(sealed, key.seal("hf_abcdefghijklmnop", CONTEXT).unwrap());
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let key = SecretKey::generate();
+        let sealed = key.seal("hf_abcdefghijklmnop", CONTEXT).unwrap();
+        let mut bytes = general_purpose::STANDARD.decode(&sealed[SEALED_PREFIX.len()..]).unwrap();
+
+        for index in [0, NONCE_LEN, bytes.len() - 1] {
+            bytes[index] ^= 0x01;
+            let tampered = format!("{}{}", SEALED_PREFIX, general_purpose::STANDARD.encode(&bytes));
+            assert!(key.open(&tampered, CONTEXT).is_err(), "flipped byte {}", index);
+            bytes[index] ^= 0x01;
+        }
+
+        assert!(key.open(&sealed[..sealed.len() - 4], CONTEXT).is_err());
+        assert!(key.open(&sealed, "other_field").is_err());
+        assert!(SecretKey::generate().open(&sealed, CONTEXT).is_err());
+    }
+
+    #[test]
+    fn test_passphrase_key() {
+        let salt = generate_salt();
+        let sealed = SecretKey::from_passphrase("correct horse", &salt).unwrap().seal("hf_token", CONTEXT).unwrap();
+
+        let same = SecretKey::from_passphrase("correct horse", &salt).unwrap();
+        assert_eq!(same.open(&sealed, CONTEXT).unwrap(), "hf_token");
+        let wrong = SecretKey::from_passphrase("wrong horse", &salt).unwrap();
+        assert!(wrong.open(&sealed, CONTEXT).is_err());
+        assert!(SecretKey::from_passphrase("", &salt).is_err());
+    }
+
+    #[test]
+    fn test_key_file_is_created_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token.key");
+
+        let sealed = SecretKey::load_or_create(&path).unwrap().seal("hf_token", CONTEXT).unwrap();
+        let reloaded = SecretKey::load_or_create(&path).unwrap();
+        assert_eq!(reloaded.open(&sealed, CONTEXT).unwrap(), "hf_token");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::write(&path, b"short").unwrap();
+        assert!(SecretKey::load_or_create(&path).is_err());
+    }
+}
@@ -0,0 +1,490 @@
+//! Hunspell `.aff`/`.dic` parsing and affix-aware lookup
+//!
+//! Words are checked by stripping candidate affixes and looking the stem up,
+//! like Hunspell itself, so the dictionary is never expanded in memory.
+//! Compounding and morphological fields are not supported.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::suggest::{fold_accents, phonetic_key};
+
+type Flag = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagMode {
+    /// One character per flag (default and `FLAG UTF-8`)
+    Char,
+    /// Two characters per flag (`FLAG long`)
+    Long,
+    /// Comma-separated numbers (`FLAG num`)
+    Num,
+}
+
+fn parse_flags(text: &str, mode: FlagMode) -> Vec<Flag> {
+    match mode {
+        FlagMode::Char => text.chars().map(|c| c as Flag).collect(),
+        FlagMode::Long => text
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| pair.iter().fold(0, |acc, c| (acc << 16) | *c as Flag))
+            .collect(),
+        FlagMode::Num => text
+            .split(',')
+            .filter_map(|n| n.trim().parse().ok())
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CondUnit {
+    Any,
+    Char(char),
+    Set(Vec<char>),
+    NotSet(Vec<char>),
+}
+
+impl CondUnit {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CondUnit::Any => true,
+            CondUnit::Char(expected) => *expected == c,
+            CondUnit::Set(chars) => chars.contains(&c),
+            CondUnit::NotSet(chars) => !chars.contains(&c),
+        }
+    }
+}
+
+/// Affix condition, a restricted regular expression such as `[^aeiou]o`
+#[derive(Debug, Clone, PartialEq)]
+struct Condition(Vec<CondUnit>);
+
+impl Condition {
+    fn parse(text: &str) -> Self {
+        if text == "." {
+            return Self(Vec::new());
+        }
+        let mut units = Vec::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => units.push(CondUnit::Any),
+                '[' => {
+                    let mut set: Vec<char> = chars.by_ref().take_while(|c| *c != ']').collect();
+                    if set.first() == Some(&'^') {
+                        set.remove(0);
+                        units.push(CondUnit::NotSet(set));
+                    } else {
+                        units.push(CondUnit::Set(set));
+                    }
+                }
+                c => units.push(CondUnit::Char(c)),
+            }
+        }
+        Self(units)
+    }
+
+    fn matches_end(&self, word: &str) -> bool {
+        let mut chars = word.chars().rev();
+        self.0.iter().rev().all(|unit| chars.next().is_some_and(|c| unit.matches(c)))
+    }
+
+    fn matches_start(&self, word: &str) -> bool {
+        let mut chars = word.chars();
+        self.0.iter().all(|unit| chars.next().is_some_and(|c| unit.matches(c)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AffixEntry {
+    flag: Flag,
+    strip: String,
+    affix: String,
+    condition: Condition,
+    cross_product: bool,
+}
+
+impl AffixEntry {
+    /// Undo this suffix, returning the stem it would have been applied to
+    fn remove_suffix(&self, word: &str) -> Option<String> {
+        let base = word.strip_suffix(self.affix.as_str())?;
+        if base.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{}", base, self.strip);
+        self.condition.matches_end(&stem).then_some(stem)
+    }
+
+    fn remove_prefix(&self, word: &str) -> Option<String> {
+        let base = word.strip_prefix(self.affix.as_str())?;
+        if base.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{}", self.strip, base);
+        self.condition.matches_start(&stem).then_some(stem)
+    }
+
+    fn apply_suffix(&self, stem: &str) -> Option<String> {
+        if !self.condition.matches_end(stem) {
+            return None;
+        }
+        let base = stem.strip_suffix(self.strip.as_str())?;
+        Some(format!("{}{}", base, self.affix))
+    }
+
+    fn apply_prefix(&self, stem: &str) -> Option<String> {
+        if !self.condition.matches_start(stem) {
+            return None;
+        }
+        let base = stem.strip_prefix(self.strip.as_str())?;
+        Some(format!("{}{}", self.affix, base))
+    }
+}
+
+/// Decode dictionary bytes according to the `SET` directive of the affix file
+fn decode(bytes: &[u8], latin1: bool) -> String {
+    if latin1 {
+        bytes.iter().map(|b| *b as char).collect()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Stems, affix rules and suggestion data loaded from a Hunspell dictionary
+#[derive(Debug, Clone, Default)]
+pub struct HunspellDictionary {
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    prefixes: Vec<AffixEntry>,
+    suffixes: Vec<AffixEntry>,
+    /// Affix entries grouped by affix text for lookup
+    prefix_index: HashMap<String, Vec<usize>>,
+    suffix_index: HashMap<String, Vec<usize>>,
+    need_affix: Option<Flag>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+    pub(crate) try_chars: Vec<char>,
+    pub(crate) replacements: Vec<(String, String)>,
+    /// Stems grouped by accent-free spelling and by phonetic key
+    pub(crate) folded_index: HashMap<String, Vec<String>>,
+    pub(crate) phonetic_index: HashMap<String, Vec<String>>,
+}
+
+impl HunspellDictionary {
+    /// Load a dictionary from its `.aff` and `.dic` files
+    pub fn load(aff_path: &Path, dic_path: &Path) -> Result<Self> {
+        let aff = fs::read(aff_path)
+            .with_context(|| format!("Failed to read {}", aff_path.display()))?;
+        let dic = fs::read(dic_path)
+            .with_context(|| format!("Failed to read {}", dic_path.display()))?;
+
+        let latin1 = String::from_utf8_lossy(&aff).lines().any(|line| {
+            line.starts_with("SET ") && line.contains("ISO8859")
+        });
+        Self::parse(&decode(&aff, latin1), &decode(&dic, latin1))
+    }
+
+    /// Parse dictionary contents already decoded to text
+    pub fn parse(aff: &str, dic: &str) -> Result<Self> {
+        let mut dictionary = Self::default();
+        let mut mode = FlagMode::Char;
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+        let mut alias_header_seen = false;
+        let mut cross_products: HashMap<(bool, Flag), bool> = HashMap::new();
+
+        for line in aff.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let Some(&directive) = parts.first() else {
+                continue;
+            };
+            let single_flag = |mode| parts.get(1).and_then(|f| parse_flags(f, mode).first().copied());
+            match directive {
+                "FLAG" => {
+                    mode = match parts.get(1).copied() {
+                        Some("long") => FlagMode::Long,
+                        Some("num") => FlagMode::Num,
+                        _ => FlagMode::Char,
+                    }
+                }
+                "TRY" => {
+                    if let Some(chars) = parts.get(1) {
+                        dictionary.try_chars = chars.chars().collect();
+                    }
+                }
+                "REP" if parts.len() >= 3 => {
+                    dictionary
+                        .replacements
+                        .push((parts[1].replace('_', " "), parts[2].replace('_', " ")));
+                }
+                "NEEDAFFIX" => dictionary.need_affix = single_flag(mode),
+                "FORBIDDENWORD" => dictionary.forbidden = single_flag(mode),
+                "NOSUGGEST" => dictionary.no_suggest = single_flag(mode),
+                "AF" if parts.len() >= 2 => {
+                    // The first AF line only carries the number of aliases
+                    if alias_header_seen {
+                        aliases.push(parse_flags(parts[1], mode));
+                    } else {
+                        alias_header_seen = true;
+                    }
+                }
+                "PFX" | "SFX" => {
+                    let is_prefix = directive == "PFX";
+                    let Some(flag) = single_flag(mode) else {
+                        continue;
+                    };
+                    // Header: PFX A Y 3
+                    if parts.len() == 4 && matches!(parts[2], "Y" | "N") {
+                        cross_products.insert((is_prefix, flag), parts[2] == "Y");
+                        continue;
+                    }
+                    if parts.len() < 4 {
+                        continue;
+                    }
+                    let strip = if parts[2] == "0" { "" } else { parts[2] };
+                    // Continuation classes after '/' are not supported
+                    let affix = parts[3].split('/').next().unwrap_or("");
+                    let affix = if affix == "0" { "" } else { affix };
+                    let entry = AffixEntry {
+                        flag,
+                        strip: strip.to_string(),
+                        affix: affix.to_string(),
+                        condition: Condition::parse(parts.get(4).copied().unwrap_or(".")),
+                        cross_product: cross_products.get(&(is_prefix, flag)).copied().unwrap_or(false),
+                    };
+                    let (entries, index) = if is_prefix {
+                        (&mut dictionary.prefixes, &mut dictionary.prefix_index)
+                    } else {
+                        (&mut dictionary.suffixes, &mut dictionary.suffix_index)
+                    };
+                    index.entry(entry.affix.clone()).or_default().push(entries.len());
+                    entries.push(entry);
+                }
+                _ => {}
+            }
+        }
+
+        if dictionary.try_chars.is_empty() {
+            dictionary.try_chars = "aeiosrnmtcdulpvgbfhqzjxçãáéêíóõôúâàü".chars().collect();
+        }
+
+        // First line of the .dic file is the approximate word count
+        for line in dic.lines().skip(1) {
+            let entry = line.split(['\t', ' ']).next().unwrap_or("").trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => {
+                    let flags = match flags.parse::<usize>() {
+                        Ok(idx) if !aliases.is_empty() => {
+                            aliases.get(idx.wrapping_sub(1)).cloned().unwrap_or_default()
+                        }
+                        _ => parse_flags(flags, mode),
+                    };
+                    (word, flags)
+                }
+                None => (entry, Vec::new()),
+            };
+            dictionary.insert(word, flags);
+        }
+
+        Ok(dictionary)
+    }
+
+    fn insert(&mut self, word: &str, flags: Vec<Flag>) {
+        let suggestible = self.no_suggest.is_none_or(|f| !flags.contains(&f))
+            && self.forbidden.is_none_or(|f| !flags.contains(&f));
+        if suggestible {
+            self.folded_index
+                .entry(fold_accents(&word.to_lowercase()))
+                .or_default()
+                .push(word.to_string());
+            self.phonetic_index
+                .entry(phonetic_key(word))
+                .or_default()
+                .push(word.to_string());
+        }
+        self.words.entry(word.to_string()).or_default().push(flags);
+    }
+
+    fn is_forbidden(&self, flags: &[Flag]) -> bool {
+        self.forbidden.is_some_and(|f| flags.contains(&f))
+    }
+
+    /// Whether `stem` is listed with every flag in `required`
+    fn stem_has_flags(&self, stem: &str, required: &[Flag]) -> bool {
+        self.words.get(stem).is_some_and(|homonyms| {
+            homonyms
+                .iter()
+                .any(|flags| !self.is_forbidden(flags) && required.iter().all(|f| flags.contains(f)))
+        })
+    }
+
+    fn entries_matching<'a>(
+        &'a self,
+        word: &'a str,
+        suffix: bool,
+    ) -> impl Iterator<Item = &'a AffixEntry> + 'a {
+        let (entries, index) = if suffix {
+            (&self.suffixes, &self.suffix_index)
+        } else {
+            (&self.prefixes, &self.prefix_index)
+        };
+        word.char_indices()
+            .map(|(idx, _)| idx)
+            .chain(std::iter::once(word.len()))
+            .filter_map(move |idx| {
+                let affix = if suffix { &word[idx..] } else { &word[..idx] };
+                index.get(affix)
+            })
+            .flatten()
+            .map(move |i| &entries[*i])
+    }
+
+    /// Exact, case-sensitive lookup with affix stripping
+    pub fn contains(&self, word: &str) -> bool {
+        if let Some(homonyms) = self.words.get(word) {
+            if homonyms.iter().any(|flags| self.is_forbidden(flags)) {
+                return false;
+            }
+            if homonyms
+                .iter()
+                .any(|flags| self.need_affix.is_none_or(|f| !flags.contains(&f)))
+            {
+                return true;
+            }
+        }
+
+        for suffix in self.entries_matching(word, true) {
+            let Some(stem) = suffix.remove_suffix(word) else {
+                continue;
+            };
+            if self.stem_has_flags(&stem, &[suffix.flag]) {
+                return true;
+            }
+            if suffix.cross_product {
+                for prefix in self.entries_matching(&stem, false) {
+                    if !prefix.cross_product {
+                        continue;
+                    }
+                    if let Some(root) = prefix.remove_prefix(&stem) {
+                        if self.stem_has_flags(&root, &[prefix.flag, suffix.flag]) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.entries_matching(word, false).any(|prefix| {
+            prefix
+                .remove_prefix(word)
+                .is_some_and(|stem| self.stem_has_flags(&stem, &[prefix.flag]))
+        })
+    }
+
+    /// Every form generated from a dictionary stem by its affix flags
+    pub fn expand(&self, stem: &str) -> Vec<String> {
+        let mut forms = Vec::new();
+        for flags in self.words.get(stem).into_iter().flatten() {
+            if self.is_forbidden(flags) {
+                continue;
+            }
+            if self.need_affix.is_none_or(|f| !flags.contains(&f)) {
+                forms.push(stem.to_string());
+            }
+            let suffixed: Vec<(String, bool)> = self
+                .suffixes
+                .iter()
+                .filter(|s| flags.contains(&s.flag))
+                .filter_map(|s| s.apply_suffix(stem).map(|form| (form, s.cross_product)))
+                .collect();
+            for prefix in self.prefixes.iter().filter(|p| flags.contains(&p.flag)) {
+                forms.extend(prefix.apply_prefix(stem));
+                if prefix.cross_product {
+                    forms.extend(
+                        suffixed
+                            .iter()
+                            .filter(|(_, cross)| *cross)
+                            .filter_map(|(form, _)| prefix.apply_prefix(form)),
+                    );
+                }
+            }
+            forms.extend(suffixed.into_iter().map(|(form, _)| form));
+        }
+        forms.sort();
+        forms.dedup();
+        forms
+    }
+
+    pub fn stem_count(&self) -> usize {
+        self.words.len()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const AFF: &str = "SET UTF-8
+TRY aeiosrnmtcdulpvgbfhqzjxçãáéêíóõôú
+REP 1
+REP ss ç
+
+SFX S Y 3
+SFX S 0 s [aeiou]
+SFX S ão ões ão
+SFX S l is l
+
+SFX M N 1
+SFX M 0 mente z
+
+PFX D Y 1
+PFX D 0 des .
+";
+
+    pub(crate) const DIC: &str = "6
+casa/S
+e
+educação/S
+feliz/M
+fazer/D
+igual/DS
+";
+
+    #[test]
+    fn test_condition_matching() {
+        let condition = Condition::parse("[^aeiou]o");
+        assert!(condition.matches_end("gato"));
+        assert!(!condition.matches_end("boio"));
+        assert!(Condition::parse(".").matches_end("x"));
+    }
+
+    #[test]
+    fn test_affix_lookup() {
+        let dictionary = HunspellDictionary::parse(AFF, DIC).unwrap();
+        assert_eq!(dictionary.stem_count(), 6);
+        assert!(dictionary.contains("casas"));
+        assert!(dictionary.contains("educações"));
+        assert!(dictionary.contains("felizmente"));
+        assert!(dictionary.contains("desfazer"));
+        // Prefix and suffix combined through cross product
+        assert!(dictionary.contains("desiguais"));
+        assert!(!dictionary.contains("iguals"));
+        assert!(!dictionary.contains("felizs"));
+        assert!(!dictionary.contains("Casa"));
+    }
+
+    #[test]
+    fn test_expand() {
+        let dictionary = HunspellDictionary::parse(AFF, DIC).unwrap();
+        assert_eq!(dictionary.expand("casa"), vec!["casa", "casas"]);
+        assert_eq!(dictionary.expand("fazer"), vec!["desfazer", "fazer"]);
+        assert_eq!(
+            dictionary.expand("igual"),
+            vec!["desiguais", "desigual", "iguais", "igual"]
+        );
+    }
+}
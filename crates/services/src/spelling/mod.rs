@@ -0,0 +1,318 @@
+//! Offline spell checking with Hunspell-format dictionaries
+//!
+//! The pt-BR dictionary (`pt_BR.aff` / `pt_BR.dic`) lives in the dictionary
+//! directory of the AI cache. Each user also gets a plain word list with the
+//! words they chose to accept.
+
+mod hunspell;
+mod suggest;
+
+pub use hunspell::HunspellDictionary;
+
+use anyhow::Result;
+use domain::essay::Correction;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use uuid::Uuid;
+
+use crate::ai_config::AIConfigManager;
+
+/// Base name of the bundled dictionary files
+pub const DICTIONARY_NAME: &str = "pt_BR";
+
+/// Suggestions offered per misspelled word
+const MAX_SUGGESTIONS: usize = 5;
+
+static SHARED_DICTIONARY: Lazy<Mutex<Option<Arc<HunspellDictionary>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Modification time and length of a user word list when it was read
+type FileStamp = Option<(SystemTime, u64)>;
+
+static USER_DICTIONARIES: Lazy<Mutex<HashMap<PathBuf, (FileStamp, UserDictionary)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Load the pt-BR dictionary from the cache once and share it
+///
+/// Returns `None` while the dictionary files are not installed, so a later
+/// call picks them up without restarting the app.
+pub fn shared_dictionary() -> Option<Arc<HunspellDictionary>> {
+    let mut cached = SHARED_DICTIONARY.lock().ok()?;
+    if cached.is_none() {
+        let dir = AIConfigManager::get_dictionary_dir().ok()?;
+        let aff = dir.join(format!("{}.aff", DICTIONARY_NAME));
+        let dic = dir.join(format!("{}.dic", DICTIONARY_NAME));
+        if !aff.exists() || !dic.exists() {
+            return None;
+        }
+        match HunspellDictionary::load(&aff, &dic) {
+            Ok(dictionary) => *cached = Some(Arc::new(dictionary)),
+            Err(e) => {
+                tracing::warn!("Failed to load spelling dictionary: {}", e);
+                return None;
+            }
+        }
+    }
+    cached.clone()
+}
+
+/// Words a user added to their personal dictionary, one per line on disk
+#[derive(Debug, Clone, Default)]
+pub struct UserDictionary {
+    path: Option<PathBuf>,
+    words: HashSet<String>,
+}
+
+impl UserDictionary {
+    /// Load the word list at `path`; a missing file is an empty dictionary
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let words = if path.exists() {
+            fs::read_to_string(&path)?
+                .lines()
+                .map(str::trim)
+                .filter(|w| !w.is_empty())
+                .map(String::from)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            words,
+        })
+    }
+
+    /// Like `load`, but only reads the file again once it has changed
+    ///
+    /// Words added through `add` append to the file, so the next call sees them.
+    pub fn load_cached(path: PathBuf) -> Result<Self> {
+        let stamp = file_stamp(&path);
+        let mut cache = USER_DICTIONARIES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_stamp, dictionary)) = cache.get(&path) {
+            if stamp.is_some() && *cached_stamp == stamp {
+                return Ok(dictionary.clone());
+            }
+        }
+        let dictionary = Self::load(path.clone())?;
+        cache.insert(path, (stamp, dictionary.clone()));
+        Ok(dictionary)
+    }
+
+    pub fn for_user(user_id: Uuid) -> Result<Self> {
+        let path = AIConfigManager::get_dictionary_dir()?.join(format!("user-{}.dic", user_id));
+        Self::load_cached(path)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(&word.to_lowercase())
+    }
+
+    /// Accept a word and persist it
+    pub fn add(&mut self, word: &str) -> Result<()> {
+        let word = word.trim();
+        if word.is_empty() || !self.words.insert(word.to_string()) {
+            return Ok(());
+        }
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", word)?;
+        }
+        Ok(())
+    }
+}
+
+/// Word not found in the dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    /// Char offset of the word in the checked text
+    pub position: usize,
+    pub word: String,
+}
+
+/// Spell checker combining the shared dictionary with a user's own words
+#[derive(Clone)]
+pub struct SpellChecker {
+    dictionary: Arc<HunspellDictionary>,
+    user: UserDictionary,
+}
+
+impl SpellChecker {
+    pub fn new(dictionary: Arc<HunspellDictionary>, user: UserDictionary) -> Self {
+        Self { dictionary, user }
+    }
+
+    /// Checker for a user with the shared dictionary, if it is installed
+    pub fn for_user(user_id: Uuid) -> Option<Self> {
+        let dictionary = shared_dictionary()?;
+        let user = UserDictionary::for_user(user_id).unwrap_or_default();
+        Some(Self::new(dictionary, user))
+    }
+
+    /// Accept any capitalization the dictionary form allows
+    pub fn check(&self, word: &str) -> bool {
+        if self.user.contains(word) || self.dictionary.contains(word) {
+            return true;
+        }
+        let lower = word.to_lowercase();
+        if lower != word && self.dictionary.contains(&lower) {
+            return true;
+        }
+        // ALL CAPS words may be capitalized in the dictionary (proper nouns)
+        let mut chars = lower.chars();
+        let capitalized: String = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+        capitalized != word && self.dictionary.contains(&capitalized)
+    }
+
+    /// Ranked suggestions, keeping the capitalization of the original word
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        suggest::suggest(&self.dictionary, &lower, |w| self.check(w), MAX_SUGGESTIONS)
+            .into_iter()
+            .map(|s| {
+                if capitalized {
+                    let mut chars = s.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or(s)
+                } else {
+                    s
+                }
+            })
+            .collect()
+    }
+
+    pub fn add_to_user_dictionary(&mut self, word: &str) -> Result<()> {
+        self.user.add(word)
+    }
+
+    /// Unknown words of a text, in order of appearance
+    ///
+    /// Acronyms, numbers and hyphenated words whose parts are all valid
+    /// ("guarda-chuva", "discute-se") are not reported.
+    pub fn check_text(&self, text: &str) -> Vec<Misspelling> {
+        let mut misspellings = Vec::new();
+        let mut word = String::new();
+        let mut start = 0;
+
+        for (idx, c) in text.chars().chain(std::iter::once(' ')).enumerate() {
+            if c.is_alphanumeric() || ((c == '-' || c == '\'') && !word.is_empty()) {
+                if word.is_empty() {
+                    start = idx;
+                }
+                word.push(c);
+                continue;
+            }
+            let token = word.trim_end_matches(['-', '\'']);
+            if !token.is_empty() && !self.is_exempt(token) && !self.check(token) {
+                misspellings.push(Misspelling {
+                    position: start,
+                    word: token.to_string(),
+                });
+            }
+            word.clear();
+        }
+        misspellings
+    }
+
+    fn is_exempt(&self, token: &str) -> bool {
+        let acronym = token.chars().count() > 1 && token.chars().all(|c| !c.is_lowercase());
+        let numeric = token.chars().any(|c| c.is_ascii_digit());
+        let hyphenated = token.contains('-') && token.split('-').all(|part| part.is_empty() || self.check(part));
+        acronym || numeric || hyphenated
+    }
+
+    /// Misspellings as C1 corrections with the best suggestion
+    pub fn corrections(&self, text: &str) -> Vec<Correction> {
+        self.check_text(text)
+            .into_iter()
+            .map(|m| {
+                let suggestions = self.suggest(&m.word);
+                let reason = if suggestions.len() > 1 {
+                    format!("Possível erro de ortografia. Outras opções: {}", suggestions[1..].join(", "))
+                } else {
+                    "Possível erro de ortografia".to_string()
+                };
+                Correction {
+                    position: m.position,
+                    suggested_text: suggestions.first().cloned().unwrap_or_default(),
+                    original_text: m.word,
+                    reason,
+                    rubric_criterion: "C1".to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hunspell::tests::{AFF, DIC};
+
+    fn checker() -> SpellChecker {
+        let dictionary = Arc::new(HunspellDictionary::parse(AFF, DIC).unwrap());
+        SpellChecker::new(dictionary, UserDictionary::default())
+    }
+
+    #[test]
+    fn test_check_text_positions() {
+        let checker = checker();
+        let misspellings = checker.check_text("Casas felizs, ENEM 2024 e educacao.");
+        let words: Vec<_> = misspellings.iter().map(|m| (m.position, m.word.as_str())).collect();
+        assert_eq!(words, vec![(6, "felizs"), (26, "educacao")]);
+    }
+
+    #[test]
+    fn test_corrections_use_best_suggestion() {
+        let corrections = checker().corrections("Educacao");
+        assert_eq!(corrections.len(), 1);
+        assert_eq!(corrections[0].suggested_text, "Educação");
+        assert_eq!(corrections[0].rubric_criterion, "C1");
+    }
+
+    #[test]
+    fn test_user_dictionary_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("user.dic");
+        let mut checker = SpellChecker::new(
+            Arc::new(HunspellDictionary::parse(AFF, DIC).unwrap()),
+            UserDictionary::load(path.clone()).unwrap(),
+        );
+        assert!(!checker.check("neuronexus"));
+        checker.add_to_user_dictionary("neuronexus").unwrap();
+        assert!(checker.check("NeuroNexus"));
+
+        let reloaded = UserDictionary::load(path.clone()).unwrap();
+        assert!(reloaded.contains("neuronexus"));
+    }
+
+    #[test]
+    fn test_cached_user_dictionary_sees_new_words() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("user.dic");
+        fs::write(&path, "enem\n").unwrap();
+
+        let mut dictionary = UserDictionary::load_cached(path.clone()).unwrap();
+        assert!(dictionary.contains("enem"));
+        dictionary.add("fuvest").unwrap();
+        assert!(UserDictionary::load_cached(path.clone()).unwrap().contains("fuvest"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Suggestion generation ranked by edit distance and pt-BR phonetics
+
+use std::collections::HashSet;
+
+use super::hunspell::HunspellDictionary;
+
+/// Remove Portuguese diacritics: "educação" becomes "educacao"
+pub(crate) fn fold_accents(word: &str) -> String {
+    word.chars().map(fold_char).collect()
+}
+
+fn fold_char(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' => 'a',
+        'é' | 'ê' => 'e',
+        'í' => 'i',
+        'ó' | 'ô' | 'õ' => 'o',
+        'ú' | 'ü' => 'u',
+        'ç' => 'c',
+        'Á' | 'À' | 'Â' | 'Ã' => 'A',
+        'É' | 'Ê' => 'E',
+        'Í' => 'I',
+        'Ó' | 'Ô' | 'Õ' => 'O',
+        'Ú' | 'Ü' => 'U',
+        'Ç' => 'C',
+        c => c,
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// Approximate pronunciation key for Brazilian Portuguese
+///
+/// Letters that sound alike collapse to one symbol, so "exceção" and
+/// "esseção" share a key while "casa" and "caça" do not.
+pub(crate) fn phonetic_key(word: &str) -> String {
+    let lower = word.to_lowercase();
+    let cedilla: Vec<bool> = lower.chars().map(|c| c == 'ç').collect();
+    let chars: Vec<char> = lower.chars().map(fold_char).collect();
+    let at = |i: usize| chars.get(i).copied();
+    let soft = |i: usize| matches!(at(i), Some('e' | 'i'));
+
+    let mut key = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let sound = match c {
+            'c' if cedilla[i] => 's',
+            'c' if at(i + 1) == Some('h') => {
+                i += 1;
+                'x'
+            }
+            'c' if soft(i + 1) => 's',
+            'c' | 'k' => 'k',
+            'q' => {
+                if at(i + 1) == Some('u') && soft(i + 2) {
+                    i += 1;
+                }
+                'k'
+            }
+            'g' if soft(i + 1) => 'j',
+            'g' => {
+                if at(i + 1) == Some('u') && soft(i + 2) {
+                    i += 1;
+                }
+                'g'
+            }
+            // "exc" and "sc" before e/i sound like a single s: exceção, nascer
+            'x' | 's' if at(i + 1) == Some('c') && soft(i + 2) => {
+                i += 1;
+                's'
+            }
+            's' if at(i + 1) == Some('h') => {
+                i += 1;
+                'x'
+            }
+            // Intervocalic s sounds like z: casa, mesa
+            's' if i > 0 && is_vowel(chars[i - 1]) && at(i + 1).is_some_and(is_vowel) => 'z',
+            'z' if i + 1 == chars.len() => 's',
+            'l' | 'n' if at(i + 1) == Some('h') => {
+                i += 1;
+                c
+            }
+            'h' => {
+                i += 1;
+                continue;
+            }
+            'w' => 'v',
+            'y' => 'i',
+            c => c,
+        };
+        if !key.ends_with(sound) {
+            key.push(sound);
+        }
+        i += 1;
+    }
+    key
+}
+
+/// Optimal string alignment distance where accent-only substitutions cost half
+///
+/// Costs are doubled to stay in integers: a full edit costs 2, swapping
+/// "a" for "ã" costs 1.
+pub(crate) fn weighted_distance(a: &str, b: &str) -> u32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i as u32 * 2;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j as u32 * 2;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = if a[i - 1] == b[j - 1] {
+                0
+            } else if fold_char(a[i - 1]) == fold_char(b[j - 1]) {
+                1
+            } else {
+                2
+            };
+            let mut best = (rows[i - 1][j] + 2)
+                .min(rows[i][j - 1] + 2)
+                .min(rows[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 2);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Words one edit away using the dictionary's TRY alphabet
+fn single_edits(word: &str, alphabet: &[char]) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut edits = Vec::new();
+    for i in 0..=chars.len() {
+        let (head, tail) = chars.split_at(i);
+        let head: String = head.iter().collect();
+        if !tail.is_empty() {
+            let rest: String = tail[1..].iter().collect();
+            edits.push(format!("{}{}", head, rest));
+            for c in alphabet {
+                edits.push(format!("{}{}{}", head, c, rest));
+            }
+            if tail.len() > 1 {
+                let swapped: String = std::iter::once(tail[1])
+                    .chain(std::iter::once(tail[0]))
+                    .chain(tail[2..].iter().copied())
+                    .collect();
+                edits.push(format!("{}{}", head, swapped));
+            }
+        }
+        let tail: String = tail.iter().collect();
+        for c in alphabet {
+            edits.push(format!("{}{}{}", head, c, tail));
+        }
+    }
+    edits
+}
+
+/// Rank candidate corrections for a lowercase misspelled word
+pub(crate) fn suggest(
+    dictionary: &HunspellDictionary,
+    word: &str,
+    is_valid: impl Fn(&str) -> bool,
+    limit: usize,
+) -> Vec<String> {
+    let mut candidates: HashSet<String> = HashSet::new();
+
+    for (from, to) in &dictionary.replacements {
+        for (idx, _) in word.match_indices(from.as_str()) {
+            let candidate = format!("{}{}{}", &word[..idx], to, &word[idx + from.len()..]);
+            if candidate.split(' ').all(&is_valid) {
+                candidates.insert(candidate);
+            }
+        }
+    }
+
+    candidates.extend(
+        single_edits(word, &dictionary.try_chars)
+            .into_iter()
+            .filter(|c| is_valid(c)),
+    );
+
+    let indexed = dictionary
+        .folded_index
+        .get(&fold_accents(word))
+        .into_iter()
+        .chain(dictionary.phonetic_index.get(&phonetic_key(word)))
+        .flatten();
+    candidates.extend(indexed.filter(|c| is_valid(c)).cloned());
+    candidates.remove(word);
+
+    let key = phonetic_key(word);
+    let mut ranked: Vec<(u32, String)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let distance = weighted_distance(word, &candidate);
+            let bonus = u32::from(phonetic_key(&candidate) == key);
+            (distance.saturating_sub(bonus), candidate)
+        })
+        .collect();
+    ranked.sort();
+    ranked.into_iter().take(limit).map(|(_, c)| c).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spelling::hunspell::tests::{AFF, DIC};
+
+    #[test]
+    fn test_phonetic_key() {
+        assert_eq!(phonetic_key("exceção"), phonetic_key("esseção"));
+        assert_eq!(phonetic_key("quero"), phonetic_key("kero"));
+        assert_eq!(phonetic_key("hoje"), phonetic_key("oje"));
+        assert_ne!(phonetic_key("casa"), phonetic_key("caça"));
+    }
+
+    #[test]
+    fn test_weighted_distance() {
+        assert_eq!(weighted_distance("educacao", "educação"), 2);
+        assert_eq!(weighted_distance("casa", "cas"), 2);
+        assert_eq!(weighted_distance("csaa", "casa"), 2);
+        assert_eq!(weighted_distance("igual", "igual"), 0);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = HunspellDictionary::parse(AFF, DIC).unwrap();
+        let valid = |w: &str| dictionary.contains(w);
+
+        // Missing accents are found through the folded index
+        assert_eq!(suggest(&dictionary, "educacao", valid, 3)[0], "educação");
+        // REP table
+        assert_eq!(suggest(&dictionary, "educassão", valid, 3)[0], "educação");
+        // Single edits, including affixed forms
+        assert_eq!(suggest(&dictionary, "csas", valid, 3)[0], "casas");
+        assert!(suggest(&dictionary, "xyzw", valid, 3).is_empty());
+    }
+}
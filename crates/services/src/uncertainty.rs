@@ -0,0 +1,197 @@
+//! Confidence intervals for competency scores
+//!
+//! Sentence bootstrap resampling of the heuristic scorer: the essay is
+//! rescored on many resampled copies, each leaving random sentences out, and
+//! the spread of those scores gives each competency an interval. The encoder
+//! is not involved. Sampling is seeded from a SHA-256 of the text, so an
+//! essay gets the same interval on every build and platform.
+
+use domain::essay::RubricCriterion;
+use domain::review::{ReviewReason, ScoreInterval};
+use sha2::{Digest, Sha256};
+
+use crate::explain::sentence_spans;
+
+/// Resampled copies of the essay rescored per evaluation
+pub const BOOTSTRAP_SAMPLES: usize = 32;
+
+/// Chance of leaving each sentence out of a resampled copy
+const SENTENCE_DROP_RATE: f64 = 0.15;
+
+/// Samples agreeing with the score below which it sits between two levels
+const MIN_AGREEMENT: f32 = 0.7;
+
+/// SplitMix64, enough for reproducible resampling
+struct SampleRng(u64);
+
+impl SampleRng {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Value below which `fraction` of the sorted samples fall
+fn quantile(sorted: &[u16], fraction: f64) -> u16 {
+    let idx = ((sorted.len() - 1) as f64 * fraction).round() as usize;
+    sorted[idx]
+}
+
+/// 90% interval of each score over sentence-resampled copies of the text
+///
+/// `scores` are the scores of the full text, in the order `scorer` returns them.
+pub fn bootstrap_intervals<F>(content: &str, scores: &[u16], scorer: F, samples: usize) -> Vec<ScoreInterval>
+where
+    F: Fn(&str) -> Vec<u16>,
+{
+    let spans = sentence_spans(content);
+    if spans.len() < 2 || samples == 0 {
+        return scores
+            .iter()
+            .map(|&s| ScoreInterval { lower: s, upper: s, agreement: 1.0 })
+            .collect();
+    }
+
+    let bytes: Vec<usize> = content
+        .char_indices()
+        .map(|(b, _)| b)
+        .chain(std::iter::once(content.len()))
+        .collect();
+    let digest = Sha256::digest(content.as_bytes());
+    let seed = digest[..8].iter().fold(0u64, |seed, &byte| seed << 8 | byte as u64);
+    let mut rng = SampleRng(seed);
+
+    let mut sampled: Vec<Vec<u16>> = vec![Vec::with_capacity(samples); scores.len()];
+    for _ in 0..samples {
+        let mut keep: Vec<bool> = spans.iter().map(|_| rng.next_f64() >= SENTENCE_DROP_RATE).collect();
+        if !keep.contains(&true) {
+            let idx = (rng.next_f64() * spans.len() as f64) as usize;
+            keep[idx.min(spans.len() - 1)] = true;
+        }
+
+        // Whitespace between sentences stays so paragraphs keep their breaks
+        let mut text = String::with_capacity(content.len());
+        let mut cursor = 0;
+        for (&(start, end), &kept) in spans.iter().zip(&keep) {
+            text.push_str(&content[bytes[cursor]..bytes[start]]);
+            if kept {
+                text.push_str(&content[bytes[start]..bytes[end]]);
+            }
+            cursor = end;
+        }
+        text.push_str(&content[bytes[cursor]..]);
+
+        for (idx, score) in scorer(&text).into_iter().enumerate().take(scores.len()) {
+            sampled[idx].push(score);
+        }
+    }
+
+    scores
+        .iter()
+        .zip(sampled.iter_mut())
+        .map(|(&score, values)| {
+            if values.is_empty() {
+                return ScoreInterval { lower: score, upper: score, agreement: 1.0 };
+            }
+            values.sort_unstable();
+            let agreeing = values.iter().filter(|&&v| v == score).count();
+            ScoreInterval {
+                lower: quantile(values, 0.05).min(score),
+                upper: quantile(values, 0.95).max(score),
+                agreement: agreeing as f32 / values.len() as f32,
+            }
+        })
+        .collect()
+}
+
+/// Why an evaluation should go to a teacher, if at all
+///
+/// An interval wider than one level is too uncertain; a score whose samples
+/// split between two neighbouring levels sits on the boundary between them.
+pub fn review_reasons(
+    criteria: &[RubricCriterion],
+    scores: &[u16],
+    intervals: &[ScoreInterval],
+) -> Vec<ReviewReason> {
+    criteria
+        .iter()
+        .zip(scores)
+        .zip(intervals)
+        .filter_map(|((criterion, &score), interval)| {
+            if interval.width() > criterion.level_step() {
+                Some(ReviewReason::IncertezaAlta {
+                    criterion: criterion.name.clone(),
+                    lower: interval.lower,
+                    upper: interval.upper,
+                })
+            } else if interval.width() > 0 && interval.agreement < MIN_AGREEMENT {
+                Some(ReviewReason::LimiteDeNivel {
+                    criterion: criterion.name.clone(),
+                    score,
+                    agreement: interval.agreement,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rubrics::get_rubric;
+    use domain::essay::ExamType;
+
+    /// C1 depends on length, C2 on a keyword in the first sentence only
+    fn scorer(text: &str) -> Vec<u16> {
+        let words = text.split_whitespace().count();
+        let c1 = if words >= 15 { 160 } else { 120 };
+        let c2 = if text.contains("tema") { 200 } else { 40 };
+        vec![c1, c2]
+    }
+
+    #[test]
+    fn test_intervals_are_reproducible() {
+        let content = "O tema é atual. Há muitos dados. Os dados mostram avanço. Falta investimento. Sem dúvida.";
+        let scores = scorer(content);
+        let first = bootstrap_intervals(content, &scores, scorer, BOOTSTRAP_SAMPLES);
+        let second = bootstrap_intervals(content, &scores, scorer, BOOTSTRAP_SAMPLES);
+        assert_eq!(first, second);
+
+        // Losing any sentence drops below the length threshold
+        assert_eq!((first[0].lower, first[0].upper), (120, 160));
+        assert!(first[0].agreement < MIN_AGREEMENT);
+        // The keyword lives in one sentence that is rarely dropped
+        assert_eq!(first[1].upper, 200);
+        assert!(first[1].agreement > 0.7);
+    }
+
+    #[test]
+    fn test_single_sentence_has_no_spread() {
+        let intervals = bootstrap_intervals("Uma frase só sobre o tema", &[120, 200], scorer, BOOTSTRAP_SAMPLES);
+        assert!(intervals.iter().all(|i| i.width() == 0 && i.agreement == 1.0));
+    }
+
+    #[test]
+    fn test_review_reasons() {
+        let criteria = &get_rubric(&ExamType::Enem).unwrap().criteria[..3];
+        let intervals = [
+            ScoreInterval { lower: 160, upper: 160, agreement: 1.0 },
+            ScoreInterval { lower: 80, upper: 200, agreement: 0.5 },
+            ScoreInterval { lower: 120, upper: 160, agreement: 0.55 },
+        ];
+        let reasons = review_reasons(criteria, &[160, 160, 120], &intervals);
+        assert_eq!(
+            reasons,
+            vec![
+                ReviewReason::IncertezaAlta { criterion: "C2".to_string(), lower: 80, upper: 200 },
+                ReviewReason::LimiteDeNivel { criterion: "C3".to_string(), score: 120, agreement: 0.55 },
+            ]
+        );
+    }
+}
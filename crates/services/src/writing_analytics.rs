@@ -0,0 +1,157 @@
+use domain::writing_process::WritingRecording;
+use serde::{Deserialize, Serialize};
+
+/// Gaps between edits longer than this count as a pause
+pub const PAUSE_THRESHOLD_MS: u64 = 10_000;
+
+/// Interval without edits while writing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WritingPause {
+    pub started_ms: u64,
+    pub duration_ms: u64,
+    /// Character offset where writing resumed
+    pub resumed_at: usize,
+}
+
+/// Summary of how an essay was written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WritingAnalytics {
+    pub total_duration_ms: u64,
+    /// Time until the first paragraph was finished with a line break
+    pub time_to_first_paragraph_ms: Option<u64>,
+    pub pauses: Vec<WritingPause>,
+    pub total_pause_ms: u64,
+    pub chars_inserted: usize,
+    pub chars_deleted: usize,
+    /// Share of the typed characters that were later deleted or replaced
+    pub revised_ratio: f32,
+    /// Number of edits that removed text, per paragraph of the text at edit time
+    pub paragraph_revisions: Vec<u32>,
+}
+
+/// Compute writing-process analytics from a recording
+pub fn analyze_writing(recording: &WritingRecording) -> WritingAnalytics {
+    let mut text = String::new();
+    let mut time_to_first_paragraph_ms = None;
+    let mut pauses = Vec::new();
+    let mut chars_inserted = 0;
+    let mut chars_deleted = 0;
+    let mut paragraph_revisions: Vec<u32> = Vec::new();
+    let mut previous_end: Option<u64> = None;
+
+    for event in &recording.events {
+        if let Some(end) = previous_end {
+            let gap = event.start_ms.saturating_sub(end);
+            if gap >= PAUSE_THRESHOLD_MS {
+                pauses.push(WritingPause {
+                    started_ms: end,
+                    duration_ms: gap,
+                    resumed_at: event.edit.at,
+                });
+            }
+        }
+        previous_end = Some(event.end_ms);
+
+        if event.edit.delete > 0 {
+            let paragraph = paragraph_index(&text, event.edit.at);
+            if paragraph_revisions.len() <= paragraph {
+                paragraph_revisions.resize(paragraph + 1, 0);
+            }
+            paragraph_revisions[paragraph] += 1;
+        }
+
+        chars_inserted += event.edit.insert.chars().count();
+        chars_deleted += event.edit.delete;
+        event.edit.apply(&mut text);
+
+        if time_to_first_paragraph_ms.is_none() && has_finished_paragraph(&text) {
+            time_to_first_paragraph_ms = Some(event.end_ms);
+        }
+    }
+
+    let total_pause_ms = pauses.iter().map(|p| p.duration_ms).sum();
+    let revised_ratio = if chars_inserted > 0 {
+        (chars_deleted as f32 / chars_inserted as f32).min(1.0)
+    } else {
+        0.0
+    };
+
+    WritingAnalytics {
+        total_duration_ms: recording.duration_ms(),
+        time_to_first_paragraph_ms,
+        pauses,
+        total_pause_ms,
+        chars_inserted,
+        chars_deleted,
+        revised_ratio,
+        paragraph_revisions,
+    }
+}
+
+/// Index of the paragraph containing the character offset `at`
+fn paragraph_index(text: &str, at: usize) -> usize {
+    let before: String = text.chars().take(at).collect();
+    before
+        .split('\n')
+        .filter(|p| !p.trim().is_empty())
+        .count()
+        .saturating_sub(1)
+}
+
+fn has_finished_paragraph(text: &str) -> bool {
+    text.find('\n')
+        .map(|idx| !text[..idx].trim().is_empty())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::writing_process::TextEdit;
+    use uuid::Uuid;
+
+    fn record(steps: &[(u64, &str)]) -> WritingRecording {
+        let mut recording = WritingRecording::new(Uuid::new_v4());
+        let mut current = String::new();
+        for (ms, text) in steps {
+            if let Some(edit) = TextEdit::between(&current, text) {
+                recording.push(*ms, edit);
+            }
+            current = text.to_string();
+        }
+        recording
+    }
+
+    #[test]
+    fn test_replay_reconstructs_text() {
+        let recording = record(&[
+            (0, "A"),
+            (100, "A escola"),
+            (20_000, "A escola pública"),
+            (21_000, "A escola"),
+        ]);
+
+        assert_eq!(recording.events.len(), 3);
+        assert_eq!(recording.text_at(500), "A escola");
+        assert_eq!(recording.text_at(20_500), "A escola pública");
+        assert_eq!(recording.final_text(), "A escola");
+    }
+
+    #[test]
+    fn test_analyze_writing() {
+        let recording = record(&[
+            (0, "Introdução."),
+            (2_000, "Introdução.\n"),
+            (30_000, "Introdução.\nDesenvolvimento ruim."),
+            (31_000, "Introdução.\nDesenvolvimento."),
+        ]);
+        let analytics = analyze_writing(&recording);
+
+        assert_eq!(analytics.time_to_first_paragraph_ms, Some(2_000));
+        assert_eq!(analytics.pauses.len(), 1);
+        assert_eq!(analytics.pauses[0].duration_ms, 28_000);
+        assert_eq!(analytics.chars_deleted, 5);
+        assert_eq!(analytics.paragraph_revisions, vec![0, 1]);
+        assert!(analytics.revised_ratio > 0.0 && analytics.revised_ratio < 0.2);
+    }
+}
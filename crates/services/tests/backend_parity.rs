@@ -0,0 +1,571 @@
+//! candle and ONNX backends must give the same embeddings
+//!
+//! The fixture is a two-layer BERT with random weights, built once for candle
+//! and once as the ONNX graph an exporter would produce for it. Quantized
+//! weights, and the same weights saved as a PyTorch checkpoint and converted
+//! to safetensors, are compared against the same F32 model.
+
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig};
+use prost::Message;
+use services::bench::embedding_drift;
+use services::{
+    prepare_weights, quantize_tensors, write_quantized, CancelToken, CandleBackend, ConversionError, EncoderBackend,
+    OnnxBackend, ProgressCallback, QuantizationLevel, QuantizedBackend, WeightFormat,
+};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokenizers::Tokenizer;
+use tract_onnx::pb::{
+    attribute_proto, tensor_proto, tensor_shape_proto, type_proto, AttributeProto, GraphProto, ModelProto,
+    NodeProto, OperatorSetIdProto, TensorProto, TensorShapeProto, TypeProto, ValueInfoProto,
+};
+
+const VOCAB: &[&str] = &[
+    "[PAD]", "[UNK]", "[CLS]", "[SEP]", "a", "educação", "é", "um", "direito", "de", "todos", "o", "estado",
+    "deve", "garantir", "acesso", "escola", "pública", "qualidade", "portanto", "<SEP>", ".", ",",
+];
+// Rows are multiples of 32 so every matrix fits whole Q8/Q4 blocks
+const HIDDEN: usize = 32;
+const HEADS: usize = 4;
+const INTERMEDIATE: usize = 64;
+const LAYERS: usize = 2;
+const MAX_POSITIONS: usize = 64;
+const EPS: f32 = 1e-12;
+
+const FIXTURES: &[&str] = &[
+    "A educação é um direito de todos.",
+    "O estado deve garantir acesso à escola pública de qualidade.",
+    "educação <SEP> Portanto, a escola deve garantir o direito de todos.",
+];
+
+fn tokenizer_json() -> String {
+    let vocab: HashMap<&str, usize> = VOCAB.iter().enumerate().map(|(i, t)| (*t, i)).collect();
+    serde_json::json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": {"type": "Lowercase"},
+        "pre_tokenizer": {"type": "BertPreTokenizer"},
+        "post_processor": {"type": "BertProcessing", "sep": ["[SEP]", 3], "cls": ["[CLS]", 2]},
+        "decoder": null,
+        "model": {"type": "WordLevel", "vocab": vocab, "unk_token": "[UNK]"}
+    })
+    .to_string()
+}
+
+fn config_json() -> serde_json::Value {
+    serde_json::json!({
+        "vocab_size": VOCAB.len(),
+        "hidden_size": HIDDEN,
+        "num_hidden_layers": LAYERS,
+        "num_attention_heads": HEADS,
+        "intermediate_size": INTERMEDIATE,
+        "hidden_act": "gelu",
+        "hidden_dropout_prob": 0.0,
+        "max_position_embeddings": MAX_POSITIONS,
+        "type_vocab_size": 2,
+        "initializer_range": 0.02,
+        "layer_norm_eps": EPS,
+        "pad_token_id": 0
+    })
+}
+
+fn config() -> BertConfig {
+    serde_json::from_value(config_json()).unwrap()
+}
+
+/// Parameters named as in the Hugging Face checkpoint, with deterministic values
+#[derive(Default)]
+struct Parameters {
+    seed: u64,
+    list: Vec<(String, Vec<usize>, Vec<f32>)>,
+}
+
+impl Parameters {
+    /// Values in `center ± scale`
+    fn add(&mut self, name: String, shape: Vec<usize>, center: f32, scale: f32) {
+        let values = (0..shape.iter().product())
+            .map(|_| {
+                self.seed = self.seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let unit = (self.seed >> 40) as f32 / (1u64 << 24) as f32;
+                center + (unit - 0.5) * 2.0 * scale
+            })
+            .collect();
+        self.list.push((name, shape, values));
+    }
+
+    fn layer_norm(&mut self, prefix: &str) {
+        self.add(format!("{}.LayerNorm.weight", prefix), vec![HIDDEN], 1.0, 0.1);
+        self.add(format!("{}.LayerNorm.bias", prefix), vec![HIDDEN], 0.0, 0.1);
+    }
+
+    fn linear(&mut self, prefix: &str, inputs: usize, outputs: usize) {
+        self.add(format!("{}.weight", prefix), vec![outputs, inputs], 0.0, 0.5);
+        self.add(format!("{}.bias", prefix), vec![outputs], 0.0, 0.1);
+    }
+}
+
+fn parameters() -> Vec<(String, Vec<usize>, Vec<f32>)> {
+    let mut p = Parameters { seed: 7, ..Default::default() };
+    p.add("embeddings.word_embeddings.weight".into(), vec![VOCAB.len(), HIDDEN], 0.0, 1.0);
+    p.add("embeddings.position_embeddings.weight".into(), vec![MAX_POSITIONS, HIDDEN], 0.0, 0.5);
+    p.add("embeddings.token_type_embeddings.weight".into(), vec![2, HIDDEN], 0.0, 0.5);
+    p.layer_norm("embeddings");
+    for layer in 0..LAYERS {
+        let prefix = format!("encoder.layer.{}", layer);
+        for name in ["query", "key", "value"] {
+            p.linear(&format!("{}.attention.self.{}", prefix, name), HIDDEN, HIDDEN);
+        }
+        p.linear(&format!("{}.attention.output.dense", prefix), HIDDEN, HIDDEN);
+        p.layer_norm(&format!("{}.attention.output", prefix));
+        p.linear(&format!("{}.intermediate.dense", prefix), HIDDEN, INTERMEDIATE);
+        p.linear(&format!("{}.output.dense", prefix), INTERMEDIATE, HIDDEN);
+        p.layer_norm(&format!("{}.output", prefix));
+    }
+    p.list
+}
+
+fn candle_backend(params: &[(String, Vec<usize>, Vec<f32>)], tokenizer_path: &Path) -> CandleBackend {
+    let device = Device::Cpu;
+    let tensors: HashMap<String, Tensor> = params
+        .iter()
+        .map(|(name, shape, values)| (name.clone(), Tensor::from_vec(values.clone(), shape.as_slice(), &device).unwrap()))
+        .collect();
+    let vb = VarBuilder::from_tensors(tensors, DType::F32, &device);
+    let model = BertModel::load(vb, &config()).unwrap();
+    CandleBackend::new(model, Tokenizer::from_file(tokenizer_path).unwrap(), device)
+}
+
+/// ONNX graph under construction
+#[derive(Default)]
+struct OnnxGraph {
+    nodes: Vec<NodeProto>,
+    initializers: Vec<TensorProto>,
+}
+
+impl OnnxGraph {
+    fn float(&mut self, name: &str, dims: &[usize], values: Vec<f32>) -> String {
+        self.initializers.push(TensorProto {
+            name: name.to_string(),
+            dims: dims.iter().map(|&d| d as i64).collect(),
+            data_type: tensor_proto::DataType::Float as i32,
+            float_data: values,
+            ..Default::default()
+        });
+        name.to_string()
+    }
+
+    fn ints(&mut self, name: &str, values: &[i64]) -> String {
+        self.initializers.push(TensorProto {
+            name: name.to_string(),
+            dims: vec![values.len() as i64],
+            data_type: tensor_proto::DataType::Int64 as i32,
+            int64_data: values.to_vec(),
+            ..Default::default()
+        });
+        name.to_string()
+    }
+
+    fn node(&mut self, op: &str, inputs: &[&str], attributes: Vec<AttributeProto>) -> String {
+        let output = format!("{}_{}", op.to_lowercase(), self.nodes.len());
+        self.nodes.push(NodeProto {
+            op_type: op.to_string(),
+            name: output.clone(),
+            input: inputs.iter().map(|i| i.to_string()).collect(),
+            output: vec![output.clone()],
+            attribute: attributes,
+            ..Default::default()
+        });
+        output
+    }
+
+    /// `x · Wᵀ + b` with the weight stored transposed, as exporters do
+    fn linear(&mut self, x: &str, params: &HashMap<String, (Vec<usize>, Vec<f32>)>, prefix: &str) -> String {
+        let (shape, values) = &params[&format!("{}.weight", prefix)];
+        let (rows, cols) = (shape[0], shape[1]);
+        let transposed: Vec<f32> = (0..cols)
+            .flat_map(|c| (0..rows).map(move |r| values[r * cols + c]))
+            .collect();
+        let weight = self.float(&format!("{}.weight_t", prefix), &[cols, rows], transposed);
+        let (_, bias) = &params[&format!("{}.bias", prefix)];
+        let bias = self.float(&format!("{}.bias", prefix), &[rows], bias.clone());
+        let product = self.node("MatMul", &[x, &weight], vec![]);
+        self.node("Add", &[&product, &bias], vec![])
+    }
+
+    fn layer_norm(&mut self, x: &str, params: &HashMap<String, (Vec<usize>, Vec<f32>)>, prefix: &str) -> String {
+        let mean = self.node("ReduceMean", &[x], vec![ints_attr("axes", &[2])]);
+        let centered = self.node("Sub", &[x, &mean], vec![]);
+        let squared = self.node("Mul", &[&centered, &centered], vec![]);
+        let variance = self.node("ReduceMean", &[&squared], vec![ints_attr("axes", &[2])]);
+        let eps = self.float(&format!("{}.eps", prefix), &[], vec![EPS]);
+        let shifted = self.node("Add", &[&variance, &eps], vec![]);
+        let std = self.node("Sqrt", &[&shifted], vec![]);
+        let normalized = self.node("Div", &[&centered, &std], vec![]);
+        let gamma = format!("{}.LayerNorm.weight", prefix);
+        let gamma = self.float(&gamma, &[HIDDEN], params[&gamma].1.clone());
+        let beta = format!("{}.LayerNorm.bias", prefix);
+        let beta = self.float(&beta, &[HIDDEN], params[&beta].1.clone());
+        let scaled = self.node("Mul", &[&normalized, &gamma], vec![]);
+        self.node("Add", &[&scaled, &beta], vec![])
+    }
+}
+
+fn ints_attr(name: &str, values: &[i64]) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: attribute_proto::AttributeType::Ints as i32,
+        ints: values.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn int_attr(name: &str, value: i64) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: attribute_proto::AttributeType::Int as i32,
+        i: value,
+        ..Default::default()
+    }
+}
+
+fn sequence_input(name: &str) -> ValueInfoProto {
+    let dim = |value| tensor_shape_proto::Dimension {
+        value: Some(value),
+        ..Default::default()
+    };
+    ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                elem_type: tensor_proto::DataType::Int64 as i32,
+                shape: Some(TensorShapeProto {
+                    dim: vec![
+                        dim(tensor_shape_proto::dimension::Value::DimValue(1)),
+                        dim(tensor_shape_proto::dimension::Value::DimParam("sequence".to_string())),
+                    ],
+                }),
+            })),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn onnx_model(params: &[(String, Vec<usize>, Vec<f32>)]) -> ModelProto {
+    let params: HashMap<String, (Vec<usize>, Vec<f32>)> = params
+        .iter()
+        .map(|(name, shape, values)| (name.clone(), (shape.clone(), values.clone())))
+        .collect();
+    let mut g = OnnxGraph::default();
+    let head_size = HIDDEN / HEADS;
+
+    // Embeddings: word + token type + the first `sequence` positions
+    let words = g.float("word_embeddings", &[VOCAB.len(), HIDDEN], params["embeddings.word_embeddings.weight"].1.clone());
+    let types = g.float("token_type_embeddings", &[2, HIDDEN], params["embeddings.token_type_embeddings.weight"].1.clone());
+    let positions = g.float(
+        "position_embeddings",
+        &[MAX_POSITIONS, HIDDEN],
+        params["embeddings.position_embeddings.weight"].1.clone(),
+    );
+    let word_emb = g.node("Gather", &[&words, "input_ids"], vec![]);
+    let type_emb = g.node("Gather", &[&types, "token_type_ids"], vec![]);
+    let shape = g.node("Shape", &["input_ids"], vec![]);
+    let one = g.ints("one", &[1]);
+    let two = g.ints("two", &[2]);
+    let zero = g.ints("zero", &[0]);
+    let seq_len = g.node("Slice", &[&shape, &one, &two, &zero], vec![]);
+    let pos_emb = g.node("Slice", &[&positions, &zero, &seq_len, &zero], vec![]);
+    let sum = g.node("Add", &[&word_emb, &type_emb], vec![]);
+    let sum = g.node("Add", &[&sum, &pos_emb], vec![]);
+    let mut hidden = g.layer_norm(&sum, &params, "embeddings");
+
+    let split_heads = g.ints("split_heads", &[1, -1, HEADS as i64, head_size as i64]);
+    let merge_heads = g.ints("merge_heads", &[1, -1, HIDDEN as i64]);
+    let scale = g.float("scale", &[], vec![(head_size as f32).sqrt()]);
+    let half = g.float("half", &[], vec![0.5]);
+    let unit = g.float("unit", &[], vec![1.0]);
+    let sqrt2 = g.float("sqrt2", &[], vec![std::f32::consts::SQRT_2]);
+
+    for layer in 0..LAYERS {
+        let prefix = format!("encoder.layer.{}", layer);
+        let heads = |g: &mut OnnxGraph, name: &str, perm: &[i64]| {
+            let projected = g.linear(&hidden, &params, &format!("{}.attention.self.{}", prefix, name));
+            let split = g.node("Reshape", &[&projected, &split_heads], vec![]);
+            g.node("Transpose", &[&split], vec![ints_attr("perm", perm)])
+        };
+        let query = heads(&mut g, "query", &[0, 2, 1, 3]);
+        let key = heads(&mut g, "key", &[0, 2, 3, 1]);
+        let value = heads(&mut g, "value", &[0, 2, 1, 3]);
+        let scores = g.node("MatMul", &[&query, &key], vec![]);
+        let scores = g.node("Div", &[&scores, &scale], vec![]);
+        let probs = g.node("Softmax", &[&scores], vec![int_attr("axis", 3)]);
+        let context = g.node("MatMul", &[&probs, &value], vec![]);
+        let context = g.node("Transpose", &[&context], vec![ints_attr("perm", &[0, 2, 1, 3])]);
+        let context = g.node("Reshape", &[&context, &merge_heads], vec![]);
+
+        let attention = g.linear(&context, &params, &format!("{}.attention.output.dense", prefix));
+        let attention = g.node("Add", &[&attention, &hidden], vec![]);
+        let attention = g.layer_norm(&attention, &params, &format!("{}.attention.output", prefix));
+
+        // GELU with erf, as candle's `gelu_erf`
+        let intermediate = g.linear(&attention, &params, &format!("{}.intermediate.dense", prefix));
+        let scaled = g.node("Div", &[&intermediate, &sqrt2], vec![]);
+        let erf = g.node("Erf", &[&scaled], vec![]);
+        let erf = g.node("Add", &[&erf, &unit], vec![]);
+        let gelu = g.node("Mul", &[&intermediate, &erf], vec![]);
+        let gelu = g.node("Mul", &[&gelu, &half], vec![]);
+
+        let output = g.linear(&gelu, &params, &format!("{}.output.dense", prefix));
+        let output = g.node("Add", &[&output, &attention], vec![]);
+        hidden = g.layer_norm(&output, &params, &format!("{}.output", prefix));
+    }
+
+    ModelProto {
+        ir_version: 8,
+        opset_import: vec![OperatorSetIdProto {
+            domain: String::new(),
+            version: 13,
+        }],
+        producer_name: "neuronexus-tests".to_string(),
+        graph: Some(GraphProto {
+            name: "bert".to_string(),
+            node: g.nodes,
+            initializer: g.initializers,
+            input: vec![sequence_input("input_ids"), sequence_input("token_type_ids")],
+            output: vec![ValueInfoProto {
+                name: hidden,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_candle_and_onnx_embeddings_match() {
+    let dir = tempfile::tempdir().unwrap();
+    let tokenizer_path = dir.path().join("tokenizer.json");
+    let onnx_path = dir.path().join("model.onnx");
+    std::fs::write(&tokenizer_path, tokenizer_json()).unwrap();
+
+    let params = parameters();
+    std::fs::write(&onnx_path, onnx_model(&params).encode_to_vec()).unwrap();
+    let candle = candle_backend(&params, &tokenizer_path);
+    let onnx = OnnxBackend::load(&onnx_path, &tokenizer_path).unwrap();
+
+    for text in FIXTURES {
+        let input = candle.encode(text).unwrap();
+        assert_eq!(input, onnx.encode(text).unwrap());
+        assert_eq!(input.ids.first(), Some(&2));
+
+        let expected = candle.forward(&input).unwrap();
+        let actual = onnx.forward(&input).unwrap();
+        assert_eq!(expected.len(), input.len());
+        assert_eq!(actual.len(), input.len());
+        for (token, (a, b)) in expected.iter().zip(&actual).enumerate() {
+            assert_eq!(a.len(), HIDDEN);
+            for (x, y) in a.iter().zip(b) {
+                assert!((x - y).abs() < 1e-4, "{:?}, token {}: {} vs {}", text, token, x, y);
+            }
+        }
+        assert_eq!(candle.embed(text).unwrap().len(), HIDDEN);
+    }
+}
+
+/// Write the fixture as the checkpoint converter would, and load it back
+fn quantized_backend(
+    params: &[(String, Vec<usize>, Vec<f32>)],
+    level: QuantizationLevel,
+    dir: &Path,
+    tokenizer_path: &Path,
+) -> (QuantizedBackend, u64) {
+    let device = Device::Cpu;
+    // Checkpoint names: `bert.` prefix, old LayerNorm names and a pretraining head
+    let mut tensors: Vec<(String, Tensor)> = params
+        .iter()
+        .map(|(name, shape, values)| {
+            let name = format!("bert.{}", name)
+                .replace("LayerNorm.weight", "LayerNorm.gamma")
+                .replace("LayerNorm.bias", "LayerNorm.beta");
+            (name, Tensor::from_vec(values.clone(), shape.as_slice(), &device).unwrap())
+        })
+        .collect();
+    tensors.push(("cls.predictions.bias".into(), Tensor::zeros(VOCAB.len(), DType::F32, &device).unwrap()));
+
+    let quantized = quantize_tensors(tensors, level).unwrap();
+    assert_eq!(quantized.len(), params.len());
+    let path = dir.join(format!("model-{}.gguf", level.as_str()));
+    write_quantized(&path, &config_json().to_string(), level, &quantized).unwrap();
+    assert!(!dir.join(format!("model-{}.gguf.partial", level.as_str())).exists());
+
+    let backend = QuantizedBackend::load(&path, tokenizer_path, &device).unwrap();
+    assert_eq!(backend.level(), level);
+    (backend, std::fs::metadata(&path).unwrap().len())
+}
+
+#[test]
+fn test_quantized_embeddings_stay_close_to_f32() {
+    let dir = tempfile::tempdir().unwrap();
+    let tokenizer_path = dir.path().join("tokenizer.json");
+    std::fs::write(&tokenizer_path, tokenizer_json()).unwrap();
+
+    let params = parameters();
+    let reference = candle_backend(&params, &tokenizer_path);
+    let expected: Vec<Vec<f32>> = FIXTURES.iter().map(|text| reference.embed(text).unwrap()).collect();
+
+    let mut sizes = Vec::new();
+    for (level, min_cosine) in [
+        (QuantizationLevel::F32, 0.99999),
+        (QuantizationLevel::F16, 0.999),
+        (QuantizationLevel::Q8, 0.99),
+        (QuantizationLevel::Q4, 0.9),
+    ] {
+        let (backend, size) = quantized_backend(&params, level, dir.path(), &tokenizer_path);
+        let actual: Vec<Vec<f32>> = FIXTURES.iter().map(|text| backend.embed(text).unwrap()).collect();
+        let drift = embedding_drift(&expected, &actual);
+        assert!(drift.min_cosine > min_cosine, "{}: {:?}", level.as_str(), drift);
+        if level == QuantizationLevel::F32 {
+            assert!(drift.max_abs_diff < 1e-4, "{:?}", drift);
+        }
+        sizes.push(size);
+    }
+    assert!(sizes.windows(2).all(|pair| pair[1] < pair[0]), "{:?}", sizes);
+}
+
+#[test]
+fn test_padded_batches_match_single_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let tokenizer_path = dir.path().join("tokenizer.json");
+    std::fs::write(&tokenizer_path, tokenizer_json()).unwrap();
+
+    let params = parameters();
+    let candle = candle_backend(&params, &tokenizer_path);
+    let (quantized, _) = quantized_backend(&params, QuantizationLevel::Q8, dir.path(), &tokenizer_path);
+    let texts: Vec<String> = FIXTURES.iter().map(|text| text.to_string()).collect();
+
+    for backend in [&candle as &dyn EncoderBackend, &quantized] {
+        let inputs: Vec<_> = texts.iter().map(|text| backend.encode(text).unwrap()).collect();
+        let batched = backend.forward_batch(&inputs).unwrap();
+        for (input, hidden) in inputs.iter().zip(&batched) {
+            let single = backend.forward(input).unwrap();
+            assert_eq!(hidden.len(), single.len());
+            for (a, b) in hidden.iter().flatten().zip(single.iter().flatten()) {
+                assert!((a - b).abs() < 1e-4, "{} vs {}", a, b);
+            }
+        }
+        let singles: Vec<Vec<f32>> = texts.iter().map(|text| backend.embed(text).unwrap()).collect();
+        assert!(embedding_drift(&singles, &backend.embed_batch(&texts).unwrap()).max_abs_diff < 1e-4);
+    }
+}
+
+/// Save parameters the way `torch.save(model.state_dict())` does: a zip
+/// with the pickled dictionary and one raw little-endian storage per tensor
+fn write_pth(path: &Path, params: &[(String, Vec<usize>, Vec<f32>)]) {
+    fn unicode(pickle: &mut Vec<u8>, text: &str) {
+        pickle.push(b'X');
+        pickle.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        pickle.extend_from_slice(text.as_bytes());
+    }
+    fn int(pickle: &mut Vec<u8>, value: usize) {
+        pickle.push(b'J');
+        pickle.extend_from_slice(&(value as i32).to_le_bytes());
+    }
+    fn ints(pickle: &mut Vec<u8>, values: impl IntoIterator<Item = usize>) {
+        pickle.push(b'(');
+        for value in values {
+            int(pickle, value);
+        }
+        pickle.push(b't');
+    }
+
+    // PROTO 2, then an empty dict filled by one SETITEMS
+    let mut pickle = vec![0x80, 2, b'}', b'('];
+    for (key, (name, shape, values)) in params.iter().enumerate() {
+        unicode(&mut pickle, name);
+        pickle.extend_from_slice(b"ctorch._utils\n_rebuild_tensor_v2\n(");
+        // Persistent id of the storage: ("storage", FloatStorage, key, "cpu", numel)
+        pickle.push(b'(');
+        unicode(&mut pickle, "storage");
+        pickle.extend_from_slice(b"ctorch\nFloatStorage\n");
+        unicode(&mut pickle, &key.to_string());
+        unicode(&mut pickle, "cpu");
+        int(&mut pickle, values.len());
+        pickle.extend_from_slice(b"tQ");
+        int(&mut pickle, 0);
+        ints(&mut pickle, shape.iter().copied());
+        let strides: Vec<usize> = (0..shape.len()).map(|dim| shape[dim + 1..].iter().product()).collect();
+        ints(&mut pickle, strides);
+        // requires_grad, then an empty OrderedDict of backward hooks
+        pickle.extend_from_slice(b"\x89ccollections\nOrderedDict\n)RtR");
+    }
+    pickle.extend_from_slice(b"u.");
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("archive/data.pkl", options).unwrap();
+    zip.write_all(&pickle).unwrap();
+    for (key, (_, _, values)) in params.iter().enumerate() {
+        zip.start_file(format!("archive/data/{}", key), options).unwrap();
+        for value in values {
+            zip.write_all(&value.to_le_bytes()).unwrap();
+        }
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_pytorch_checkpoint_converts_to_safetensors() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    let snapshot = dir.join("snapshots").join("abc123");
+    std::fs::create_dir_all(&snapshot).unwrap();
+    let tokenizer_path = snapshot.join("tokenizer.json");
+    std::fs::write(&tokenizer_path, tokenizer_json()).unwrap();
+    let config_path = snapshot.join("config.json");
+    std::fs::write(&config_path, config_json().to_string()).unwrap();
+
+    let params = parameters();
+    let pth_path = snapshot.join("pytorch_model.bin");
+    write_pth(&pth_path, &params);
+    let reference = candle_backend(&params, &tokenizer_path);
+    let expected: Vec<Vec<f32>> = FIXTURES.iter().map(|text| reference.embed(text).unwrap()).collect();
+
+    let converted_path = dir.join("converted").join("abc123").join("model.safetensors");
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+    let error = prepare_weights(&snapshot, &converted_path, &cancelled, None).unwrap_err();
+    assert!(matches!(error.downcast_ref::<ConversionError>(), Some(ConversionError::Cancelled(_))));
+    assert!(!converted_path.exists());
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let progress: ProgressCallback = {
+        let reported = reported.clone();
+        Arc::new(move |fraction: f32, _message: String| reported.lock().unwrap().push(fraction))
+    };
+    let source = prepare_weights(&snapshot, &converted_path, &CancelToken::new(), Some(&progress)).unwrap();
+    assert_eq!(source.format, WeightFormat::Safetensors);
+    assert!(source.converted);
+    assert_eq!(source.path, converted_path);
+    let reported = reported.lock().unwrap();
+    assert_eq!(reported.len(), params.len() + 1);
+    assert_eq!(reported.last(), Some(&1.0));
+
+    // The conversion happens once
+    let modified = std::fs::metadata(&converted_path).unwrap().modified().unwrap();
+    assert_eq!(prepare_weights(&snapshot, &converted_path, &CancelToken::new(), None).unwrap(), source);
+    assert_eq!(std::fs::metadata(&converted_path).unwrap().modified().unwrap(), modified);
+
+    let device = Device::Cpu;
+    for weights in [&pth_path, &converted_path] {
+        let backend = CandleBackend::load(&config_path, &tokenizer_path, weights, &device).unwrap();
+        let actual: Vec<Vec<f32>> = FIXTURES.iter().map(|text| backend.embed(text).unwrap()).collect();
+        assert_eq!(embedding_drift(&expected, &actual).max_abs_diff, 0.0, "{}", weights.display());
+    }
+}
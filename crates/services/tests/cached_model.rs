@@ -0,0 +1,121 @@
+//! A cached model loads without the token
+//!
+//! The token is only needed to download, so a sealed token that cannot be
+//! opened (here its key file is gone) must not stop an offline load. The test
+//! points `HOME` at a temporary directory, so it is the only test in this
+//! binary.
+
+use candle_core::{DType, Device, Tensor};
+use services::{AIConfigManager, AIService, ModelStatus, MODEL_ID};
+use std::collections::HashMap;
+use std::path::Path;
+
+const VOCAB: &[&str] = &["[PAD]", "[UNK]", "[CLS]", "[SEP]", "a", "educação", "é", "um", "direito", "."];
+const HIDDEN: usize = 8;
+const INTERMEDIATE: usize = 16;
+const MAX_POSITIONS: usize = 32;
+const COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+
+fn tokenizer_json() -> String {
+    let vocab: HashMap<&str, usize> = VOCAB.iter().enumerate().map(|(i, t)| (*t, i)).collect();
+    serde_json::json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": {"type": "Lowercase"},
+        "pre_tokenizer": {"type": "BertPreTokenizer"},
+        "post_processor": {"type": "BertProcessing", "sep": ["[SEP]", 3], "cls": ["[CLS]", 2]},
+        "decoder": null,
+        "model": {"type": "WordLevel", "vocab": vocab, "unk_token": "[UNK]"}
+    })
+    .to_string()
+}
+
+fn config_json() -> String {
+    serde_json::json!({
+        "vocab_size": VOCAB.len(),
+        "hidden_size": HIDDEN,
+        "num_hidden_layers": 1,
+        "num_attention_heads": 2,
+        "intermediate_size": INTERMEDIATE,
+        "hidden_act": "gelu",
+        "hidden_dropout_prob": 0.0,
+        "max_position_embeddings": MAX_POSITIONS,
+        "type_vocab_size": 2,
+        "initializer_range": 0.02,
+        "layer_norm_eps": 1e-12,
+        "pad_token_id": 0
+    })
+    .to_string()
+}
+
+/// A one-layer BERT checkpoint with every parameter set to a small constant
+fn write_weights(path: &Path) {
+    let shapes: Vec<(String, Vec<usize>)> = {
+        let mut shapes = vec![
+            ("embeddings.word_embeddings.weight".to_string(), vec![VOCAB.len(), HIDDEN]),
+            ("embeddings.position_embeddings.weight".to_string(), vec![MAX_POSITIONS, HIDDEN]),
+            ("embeddings.token_type_embeddings.weight".to_string(), vec![2, HIDDEN]),
+        ];
+        let mut layer_norm = |prefix: &str| {
+            shapes.push((format!("{}.LayerNorm.weight", prefix), vec![HIDDEN]));
+            shapes.push((format!("{}.LayerNorm.bias", prefix), vec![HIDDEN]));
+        };
+        layer_norm("embeddings");
+        layer_norm("encoder.layer.0.attention.output");
+        layer_norm("encoder.layer.0.output");
+        let linears = [
+            ("encoder.layer.0.attention.self.query", HIDDEN, HIDDEN),
+            ("encoder.layer.0.attention.self.key", HIDDEN, HIDDEN),
+            ("encoder.layer.0.attention.self.value", HIDDEN, HIDDEN),
+            ("encoder.layer.0.attention.output.dense", HIDDEN, HIDDEN),
+            ("encoder.layer.0.intermediate.dense", HIDDEN, INTERMEDIATE),
+            ("encoder.layer.0.output.dense", INTERMEDIATE, HIDDEN),
+        ];
+        for (prefix, inputs, outputs) in linears {
+            shapes.push((format!("{}.weight", prefix), vec![outputs, inputs]));
+            shapes.push((format!("{}.bias", prefix), vec![outputs]));
+        }
+        shapes
+    };
+    let tensors: HashMap<String, Tensor> = shapes
+        .into_iter()
+        .map(|(name, shape)| {
+            let tensor = (Tensor::ones(shape.as_slice(), DType::F32, &Device::Cpu).unwrap() * 0.01).unwrap();
+            (name, tensor)
+        })
+        .collect();
+    candle_core::safetensors::save(&tensors, path).unwrap();
+}
+
+#[tokio::test]
+async fn test_cached_model_loads_when_the_token_cannot_be_opened() {
+    let home = tempfile::tempdir().unwrap();
+    std::env::set_var("HOME", home.path());
+    std::env::remove_var("XDG_CONFIG_HOME");
+    std::env::remove_var("HF_TOKEN");
+
+    // A sealed token whose key file is missing
+    let config = AIConfigManager::new().unwrap();
+    config.set_token("hf_abcdefghijklmnop").unwrap();
+    let key_path = home.path().join(".config").join("neuronexus").join("token.key");
+    std::fs::remove_file(&key_path).unwrap();
+    assert!(config.resolve_token().is_err());
+
+    // The model snapshot as the hub cache lays it out
+    let repo_dir = AIConfigManager::get_model_cache_dir(MODEL_ID).unwrap();
+    let snapshot = repo_dir.join("snapshots").join(COMMIT);
+    std::fs::create_dir_all(&snapshot).unwrap();
+    std::fs::create_dir_all(repo_dir.join("refs")).unwrap();
+    std::fs::write(repo_dir.join("refs").join("main"), COMMIT).unwrap();
+    std::fs::write(snapshot.join("config.json"), config_json()).unwrap();
+    std::fs::write(snapshot.join("tokenizer.json"), tokenizer_json()).unwrap();
+    write_weights(&snapshot.join("model.safetensors"));
+
+    let service = AIService::new().unwrap();
+    service.initialize().await.unwrap();
+    assert_eq!(service.status(), ModelStatus::Ready);
+    let provenance = service.provenance().await.unwrap();
+    assert_eq!(provenance.revision.as_deref(), Some(COMMIT));
+}
@@ -0,0 +1,293 @@
+//! Download manager against a local stand-in for the Hub
+//!
+//! The server speaks just enough HTTP/1.1 for the metadata endpoint and
+//! ranged file downloads, and can cut a response short to simulate a
+//! dropped connection.
+
+use hf_hub::{Cache, Repo, RepoType};
+use services::{git_blob_id, sha256_hex, CancelToken, DownloadError, DownloadManager, DownloadPreferences, ProgressCallback};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+const MODEL: &str = "neuralmind/bert-base-portuguese-cased";
+const COMMIT: &str = "94d69c95f98f7d5b2a8700c420230ae10def0baa";
+
+#[derive(Default)]
+struct ServerState {
+    files: HashMap<String, Vec<u8>>,
+    /// Checksums published in the metadata, by file name
+    sha256: HashMap<String, String>,
+    blob_ids: HashMap<String, String>,
+    /// Sizes published in place of the real ones, by file name
+    sizes: HashMap<String, usize>,
+    /// Bytes of body to send before dropping the next file response
+    cut_after: Option<usize>,
+    /// `(path, Range header)` of every file request
+    requests: Vec<(String, Option<String>)>,
+}
+
+struct StandIn {
+    endpoint: String,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl StandIn {
+    fn start(files: &[(&str, Vec<u8>)]) -> Self {
+        let state = ServerState {
+            sha256: files.iter().map(|(name, body)| (name.to_string(), sha256_hex(body))).collect(),
+            blob_ids: files.iter().map(|(name, body)| (name.to_string(), git_blob_id(body))).collect(),
+            files: files.iter().map(|(name, body)| (name.to_string(), body.clone())).collect(),
+            ..Default::default()
+        };
+        let state = Arc::new(Mutex::new(state));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+        let shared = state.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = serve(stream, &shared);
+            }
+        });
+        Self { endpoint, state }
+    }
+
+    fn manager(&self, preferences: DownloadPreferences) -> DownloadManager {
+        DownloadManager::new(MODEL, preferences).with_endpoint(&self.endpoint)
+    }
+
+    fn ranges(&self) -> Vec<Option<String>> {
+        let state = self.state.lock().unwrap();
+        state.requests.iter().map(|(_, range)| range.clone()).collect()
+    }
+}
+
+fn serve(stream: TcpStream, state: &Mutex<ServerState>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let mut range = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("range") {
+                range = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let mut stream = stream;
+    let mut state = state.lock().unwrap();
+    if path.starts_with(&format!("/api/models/{}/revision/main", MODEL)) {
+        let siblings: Vec<serde_json::Value> = state
+            .files
+            .iter()
+            .map(|(name, body)| (name, state.sizes.get(name).copied().unwrap_or(body.len())))
+            .map(|(name, size)| match name.as_str() {
+                // Small files are plain git blobs; only LFS files carry a
+                // SHA-256, and their blob id is the pointer's
+                "pytorch_model.bin" => serde_json::json!({
+                    "rfilename": name,
+                    "blobId": git_blob_id(b"version https://git-lfs.github.com/spec/v1\n"),
+                    "size": size,
+                    "lfs": {"sha256": state.sha256[name], "size": size, "pointerSize": 134}
+                }),
+                _ => serde_json::json!({"rfilename": name, "blobId": state.blob_ids[name], "size": size}),
+            })
+            .collect();
+        let body = serde_json::json!({"sha": COMMIT, "siblings": siblings}).to_string();
+        return write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+    }
+
+    // Files are only served by commit, never by branch
+    let prefix = format!("/{}/resolve/{}/", MODEL, COMMIT);
+    let name = path.strip_prefix(&prefix).unwrap_or_default().to_string();
+    state.requests.push((name.clone(), range.clone()));
+    let body = match state.files.get(&name) {
+        Some(body) => body.clone(),
+        None => return write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    };
+
+    let start = range
+        .as_deref()
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok())
+        .unwrap_or(0);
+    if start >= body.len() && start > 0 {
+        return write!(stream, "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    }
+    let rest = &body[start..];
+    let status = if start > 0 {
+        format!("206 Partial Content\r\nContent-Range: bytes {}-{}/{}", start, body.len() - 1, body.len())
+    } else {
+        "200 OK".to_string()
+    };
+    write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, rest.len())?;
+    let sent = state.cut_after.take().map_or(rest.len(), |cut| cut.min(rest.len()));
+    stream.write_all(&rest[..sent])?;
+    stream.flush()
+}
+
+fn weights() -> Vec<u8> {
+    (0..300_000u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8).collect()
+}
+
+/// Cache directory and the repository folder inside it
+fn temp_repo() -> (TempDir, PathBuf) {
+    let cache = tempfile::tempdir().unwrap();
+    let repo_dir = cache.path().join("models--neuralmind--bert-base-portuguese-cased");
+    (cache, repo_dir)
+}
+
+fn part(dir: &Path, name: &str) -> PathBuf {
+    dir.join("snapshots").join(COMMIT).join(format!("{}.part", name))
+}
+
+#[test]
+fn test_snapshot_is_downloaded_into_cache_layout() {
+    let server = StandIn::start(&[
+        ("config.json", br#"{"hidden_size": 768}"#.to_vec()),
+        ("tokenizer.json", br#"{"version": "1.0"}"#.to_vec()),
+        ("pytorch_model.bin", weights()),
+    ]);
+    let (_cache, repo_dir) = temp_repo();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    let progress: ProgressCallback = Arc::new(move |fraction, _message| sink.lock().unwrap().push(fraction));
+
+    let manager = server.manager(DownloadPreferences::default());
+    let names = ["config.json", "tokenizer.json", "pytorch_model.bin"];
+    let snapshot = manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), Some(&progress)).unwrap();
+    assert_eq!(snapshot, repo_dir.join("snapshots").join(COMMIT));
+
+    let cache = Cache::new(repo_dir.parent().unwrap().to_path_buf());
+    let cached = cache.repo(Repo::new(MODEL.to_string(), RepoType::Model));
+    assert_eq!(std::fs::read(cached.get("pytorch_model.bin").unwrap()).unwrap(), weights());
+    assert!(cached.get("config.json").is_some());
+
+    let reports = reports.lock().unwrap();
+    assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(reports.last(), Some(&1.0));
+
+    // Files already in the snapshot are not fetched again
+    let requests = server.ranges().len();
+    manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap();
+    assert_eq!(server.ranges().len(), requests);
+}
+
+#[test]
+fn test_interrupted_download_resumes_with_range() {
+    let server = StandIn::start(&[("pytorch_model.bin", weights())]);
+    let (_cache, repo_dir) = temp_repo();
+    server.state.lock().unwrap().cut_after = Some(100_000);
+
+    let manager = server.manager(DownloadPreferences::default());
+    let names = ["pytorch_model.bin"];
+    assert!(manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).is_err());
+    assert_eq!(std::fs::metadata(part(&repo_dir, "pytorch_model.bin")).unwrap().len(), 100_000);
+
+    let snapshot = manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap();
+    assert_eq!(std::fs::read(snapshot.join("pytorch_model.bin")).unwrap(), weights());
+    assert!(!part(&repo_dir, "pytorch_model.bin").exists());
+    assert_eq!(server.ranges(), vec![None, Some("bytes=100000-".to_string())]);
+}
+
+#[test]
+fn test_resume_can_be_disabled() {
+    let server = StandIn::start(&[("pytorch_model.bin", weights())]);
+    let (_cache, repo_dir) = temp_repo();
+    server.state.lock().unwrap().cut_after = Some(100_000);
+
+    let preferences = DownloadPreferences { resume_on_interrupt: false, ..Default::default() };
+    let manager = server.manager(preferences);
+    let names = ["pytorch_model.bin"];
+    assert!(manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).is_err());
+    manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap();
+    assert_eq!(server.ranges(), vec![None, None]);
+}
+
+#[test]
+fn test_checksum_mismatch_is_rejected() {
+    let server = StandIn::start(&[("pytorch_model.bin", weights())]);
+    let (_cache, repo_dir) = temp_repo();
+    server.state.lock().unwrap().sha256.insert("pytorch_model.bin".to_string(), sha256_hex(b"other weights"));
+
+    let names = ["pytorch_model.bin"];
+    let error = server
+        .manager(DownloadPreferences::default())
+        .download_snapshot(&repo_dir, &names, &CancelToken::new(), None)
+        .unwrap_err();
+    assert!(matches!(error.downcast_ref::<DownloadError>(), Some(DownloadError::ChecksumMismatch { .. })));
+    assert!(!part(&repo_dir, "pytorch_model.bin").exists());
+    assert!(!repo_dir.join("snapshots").join(COMMIT).join("pytorch_model.bin").exists());
+
+    // Without verification the size check alone passes
+    let preferences = DownloadPreferences { verify_integrity: false, ..Default::default() };
+    server.manager(preferences).download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap();
+}
+
+#[test]
+fn test_size_mismatch_discards_part_file() {
+    let server = StandIn::start(&[("pytorch_model.bin", weights())]);
+    let (_cache, repo_dir) = temp_repo();
+    server.state.lock().unwrap().sizes.insert("pytorch_model.bin".to_string(), weights().len() + 10);
+
+    let manager = server.manager(DownloadPreferences::default());
+    let names = ["pytorch_model.bin"];
+    let error = manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap_err();
+    assert!(matches!(error.downcast_ref::<DownloadError>(), Some(DownloadError::SizeMismatch { .. })));
+    assert!(!part(&repo_dir, "pytorch_model.bin").exists());
+
+    // The next attempt starts over instead of appending to the bad bytes
+    server.state.lock().unwrap().sizes.clear();
+    let snapshot = manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap();
+    assert_eq!(std::fs::read(snapshot.join("pytorch_model.bin")).unwrap(), weights());
+    assert_eq!(server.ranges(), vec![None, None]);
+}
+
+#[test]
+fn test_non_lfs_file_is_checked_against_its_blob_id() {
+    let server = StandIn::start(&[("config.json", br#"{"hidden_size": 768}"#.to_vec())]);
+    let (_cache, repo_dir) = temp_repo();
+    server.state.lock().unwrap().blob_ids.insert("config.json".to_string(), git_blob_id(b"{}"));
+
+    let names = ["config.json"];
+    let error = server
+        .manager(DownloadPreferences::default())
+        .download_snapshot(&repo_dir, &names, &CancelToken::new(), None)
+        .unwrap_err();
+    assert!(matches!(error.downcast_ref::<DownloadError>(), Some(DownloadError::ChecksumMismatch { .. })));
+    assert!(!repo_dir.join("snapshots").join(COMMIT).join("config.json").exists());
+}
+
+#[test]
+fn test_cancelled_download_keeps_part_file() {
+    let server = StandIn::start(&[("pytorch_model.bin", weights())]);
+    let (_cache, repo_dir) = temp_repo();
+    let cancel = CancelToken::new();
+    let trigger = cancel.clone();
+    let progress: ProgressCallback = Arc::new(move |_, _| trigger.cancel());
+
+    let manager = server.manager(DownloadPreferences::default());
+    let names = ["pytorch_model.bin"];
+    let error = manager.download_snapshot(&repo_dir, &names, &cancel, Some(&progress)).unwrap_err();
+    assert!(matches!(error.downcast_ref::<DownloadError>(), Some(DownloadError::Cancelled(_))));
+    assert!(part(&repo_dir, "pytorch_model.bin").exists());
+
+    let snapshot = manager.download_snapshot(&repo_dir, &names, &CancelToken::new(), None).unwrap();
+    assert_eq!(std::fs::read(snapshot.join("pytorch_model.bin")).unwrap(), weights());
+}
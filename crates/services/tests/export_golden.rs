@@ -0,0 +1,107 @@
+//! Golden-file tests for essay export
+//!
+//! Run with `UPDATE_GOLDEN=1` to regenerate the files in `tests/golden`.
+
+use chrono::{TimeZone, Utc};
+use domain::essay::{Correction, Essay, EssayStatus, ExamType, RubricScores};
+use services::{export_essay, ExportFormat};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+fn corrected_essay() -> Essay {
+    let mut scores = HashMap::new();
+    let mut detailed_feedback = HashMap::new();
+    for (criterion, score, feedback) in [
+        ("C1", 160, "Bom domínio da norma culta, com poucos desvios gramaticais."),
+        ("C2", 160, "Boa compreensão do tema com desenvolvimento adequado."),
+        ("C3", 120, "Organização razoável dos argumentos."),
+        ("C4", 160, "Boa articulação com uso adequado de conectivos."),
+        ("C5", 80, "Proposta insuficiente ou pouco detalhada."),
+    ] {
+        scores.insert(criterion.to_string(), score);
+        detailed_feedback.insert(criterion.to_string(), feedback.to_string());
+    }
+
+    let mut essay = Essay::new(
+        Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap(),
+        "Caminhos para combater a evasão escolar",
+        "A evasão escolar é um problema grave que afeta milhares de estudantes brasileiros.
+
+\
+         Para combater a evasão, o governo precisa fazer alguma coisa. Além disso, é importante investir em infraestrutura escolar e na formação de professores.
+
+\
+         Dessa forma, é possível reduzir a evasão e garantir educação de qualidade (para todos).",
+        ExamType::Enem,
+    );
+    essay.id = Uuid::parse_str("a0000000-0000-0000-0000-000000000002").unwrap();
+    essay.status = EssayStatus::Corrigida;
+    essay.score = Some(680);
+    essay.feedback = Some("Pontuação total: 680/1000
+
+Desempenho geral: bom
+
+Detalhe melhor a proposta de intervenção.".to_string());
+    essay.corrections = Some(vec![
+        Correction {
+            position: 0,
+            original_text: "[Início do texto]".to_string(),
+            suggested_text: "Apresente a tese já na introdução.".to_string(),
+            reason: "A introdução não explicita o ponto de vista.".to_string(),
+            rubric_criterion: "C2".to_string(),
+        },
+        Correction {
+            position: 90,
+            original_text: "o governo precisa fazer alguma coisa".to_string(),
+            suggested_text: "o Ministério da Educação deve ampliar programas de busca ativa".to_string(),
+            reason: "Agente e ação vagos.".to_string(),
+            rubric_criterion: "C5".to_string(),
+        },
+    ]);
+    essay.rubric_scores = Some(RubricScores {
+        scores,
+        detailed_feedback,
+    });
+    essay.estimated_lines = Some(9);
+    essay.created_at = Utc.with_ymd_and_hms(2025, 3, 10, 14, 0, 0).unwrap();
+    essay.updated_at = Utc.with_ymd_and_hms(2025, 3, 12, 9, 30, 0).unwrap();
+    essay.submitted_at = Some(essay.updated_at);
+    essay
+}
+
+fn assert_golden(name: &str, actual: &[u8]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read(&path)
+        .unwrap_or_else(|_| panic!("missing golden file {}, run with UPDATE_GOLDEN=1", path.display()));
+    assert!(expected == actual, "{} differs from golden file", name);
+}
+
+#[test]
+fn test_pdf_matches_golden() {
+    let pdf = export_essay(&corrected_essay(), ExportFormat::Pdf).unwrap();
+    assert!(pdf.starts_with(b"%PDF-1.4"));
+    assert_golden("corrected_essay.pdf", &pdf);
+}
+
+#[test]
+fn test_docx_matches_golden() {
+    let docx = export_essay(&corrected_essay(), ExportFormat::Docx).unwrap();
+    assert!(docx.starts_with(b"PK\x03\x04"));
+    assert_golden("corrected_essay.docx", &docx);
+}
+
+#[test]
+fn test_export_is_deterministic() {
+    let essay = corrected_essay();
+    for format in [ExportFormat::Pdf, ExportFormat::Docx] {
+        assert_eq!(
+            export_essay(&essay, format).unwrap(),
+            export_essay(&essay, format).unwrap()
+        );
+    }
+}
@@ -72,13 +72,13 @@ impl LocaleDetector {
 
     /// Check if a locale is in the supported languages list
     fn is_supported(locale: &str) -> bool {
-        SUPPORTED_LANGUAGES.iter().any(|l| l.code == locale)
+        get_supported_languages().iter().any(|l| l.code == locale)
     }
 
     /// Find default variant for a language (e.g., "en" -> "en-US")
     fn find_default_variant(lang: &str) -> Option<String> {
-        SUPPORTED_LANGUAGES
-            .iter()
+        get_supported_languages()
+            .into_iter()
             .find(|l| l.code.starts_with(&format!("{}-", lang)))
             .map(|l| l.code.clone())
     }
@@ -8,5 +8,7 @@ pub enum Error {
     InvalidInput(String),
     #[error("Internal error: {0}")]
     Internal(String),
+    #[error("Stale cursor: the search index changed, restart from the first page")]
+    StaleCursor,
 }
 
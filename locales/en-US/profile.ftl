@@ -59,6 +59,11 @@ profile-ai-token-placeholder = Paste your token here (starts with hf_)
 profile-ai-token-save = Save Token
 profile-ai-token-validate = Validate Token
 profile-ai-token-clear = Clear Token
+profile-ai-feedback-model-label = Feedback model (GGUF, optional)
+profile-ai-feedback-model-placeholder = Path to a quantized .gguf file with tokenizer.json next to it
+profile-ai-feedback-model-save = Save Model Path
+profile-ai-feedback-model-saved = Personalized feedback enabled
+profile-ai-feedback-model-missing = Model or tokenizer.json not found
 
 # Status Messages
 profile-ai-status-not-configured = Not Configured
@@ -77,6 +82,7 @@ profile-ai-cancel-download = Cancel Download
 profile-ai-test-model = Test Model
 profile-ai-clear-cache = Clear Model Cache
 profile-ai-reload-model = Reload Model
+profile-ai-unload-model = Unload Model
 
 # Guide
 profile-ai-guide-title = How to Get HuggingFace Token
@@ -118,8 +124,18 @@ profile-ai-error-unknown = An unknown error occurred. Please try again
 
 # Info Messages
 profile-ai-info-cache-location = Cache location:
+profile-ai-info-embedding-cache = Cached embeddings:
 profile-ai-info-model-size = Model size: ~420MB
 profile-ai-info-first-download = First download may take 5-10 minutes depending on your connection
 profile-ai-info-offline-ready = After download, AI features work 100% offline
 profile-ai-info-requires-token = Token required only for downloading. Cached models work without token
 profile-ai-info-token-permissions = Required permissions: Read access to models and repositories
+
+# Profile Page - Writing Metrics
+profile-metrics-title = Writing Evolution
+profile-metrics-empty = Corrected essays will show your readability and vocabulary trends here
+profile-metrics-readability = Readability (Flesch–Martins)
+profile-metrics-diversity = Lexical diversity (MTLD)
+profile-metrics-ttr = Type-token ratio
+profile-metrics-passive = Passive voice
+profile-metrics-informal = Informal markers
@@ -59,6 +59,11 @@ profile-ai-token-placeholder = Cole seu token aqui (começa com hf_)
 profile-ai-token-save = Salvar Token
 profile-ai-token-validate = Validar Token
 profile-ai-token-clear = Limpar Token
+profile-ai-feedback-model-label = Modelo de feedback (GGUF, opcional)
+profile-ai-feedback-model-placeholder = Caminho de um arquivo .gguf quantizado com o tokenizer.json ao lado
+profile-ai-feedback-model-save = Salvar Caminho
+profile-ai-feedback-model-saved = Feedback personalizado ativado
+profile-ai-feedback-model-missing = Modelo ou tokenizer.json não encontrado
 
 # Status Messages
 profile-ai-status-not-configured = Não Configurado
@@ -77,6 +82,7 @@ profile-ai-cancel-download = Cancelar Download
 profile-ai-test-model = Testar Modelo
 profile-ai-clear-cache = Limpar Cache do Modelo
 profile-ai-reload-model = Recarregar Modelo
+profile-ai-unload-model = Descarregar Modelo
 
 # Guide
 profile-ai-guide-title = Como Obter o Token do HuggingFace
@@ -118,8 +124,18 @@ profile-ai-error-unknown = Ocorreu um erro desconhecido. Tente novamente
 
 # Info Messages
 profile-ai-info-cache-location = Local do cache:
+profile-ai-info-embedding-cache = Embeddings em cache:
 profile-ai-info-model-size = Tamanho do modelo: ~420MB
 profile-ai-info-first-download = O primeiro download pode levar 5-10 minutos dependendo da sua conexão
 profile-ai-info-offline-ready = Após o download, os recursos de IA funcionam 100% offline
 profile-ai-info-requires-token = Token necessário apenas para download. Modelos em cache funcionam sem token
 profile-ai-info-token-permissions = Permissões necessárias: Acesso de leitura a modelos e repositórios
+
+# Profile Page - Writing Metrics
+profile-metrics-title = Evolução da Escrita
+profile-metrics-empty = Suas redações corrigidas mostrarão aqui a evolução da legibilidade e do vocabulário
+profile-metrics-readability = Legibilidade (Flesch–Martins)
+profile-metrics-diversity = Diversidade lexical (MTLD)
+profile-metrics-ttr = Razão tipo/ocorrência
+profile-metrics-passive = Voz passiva
+profile-metrics-informal = Marcas de informalidade
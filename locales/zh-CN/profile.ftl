@@ -59,6 +59,11 @@ profile-ai-token-placeholder = 在此粘贴您的令牌(以hf_开头)
 profile-ai-token-save = 保存令牌
 profile-ai-token-validate = 验证令牌
 profile-ai-token-clear = 清除令牌
+profile-ai-feedback-model-label = 反馈模型（GGUF，可选）
+profile-ai-feedback-model-placeholder = 量化 .gguf 文件路径，同目录需有 tokenizer.json
+profile-ai-feedback-model-save = 保存模型路径
+profile-ai-feedback-model-saved = 已启用个性化反馈
+profile-ai-feedback-model-missing = 未找到模型或 tokenizer.json
 
 # Status Messages
 profile-ai-status-not-configured = 未配置
@@ -77,6 +82,7 @@ profile-ai-cancel-download = 取消下载
 profile-ai-test-model = 测试模型
 profile-ai-clear-cache = 清除模型缓存
 profile-ai-reload-model = 重新加载模型
+profile-ai-unload-model = 卸载模型
 
 # Guide
 profile-ai-guide-title = 如何获取HuggingFace令牌
@@ -118,8 +124,18 @@ profile-ai-error-unknown = 发生未知错误。请重试
 
 # Info Messages
 profile-ai-info-cache-location = 缓存位置:
+profile-ai-info-embedding-cache = 已缓存的嵌入:
 profile-ai-info-model-size = 模型大小:~420MB
 profile-ai-info-first-download = 首次下载可能需要5-10分钟,具体取决于您的连接
 profile-ai-info-offline-ready = 下载后,AI功能可100%离线工作
 profile-ai-info-requires-token = 仅下载时需要令牌。缓存的模型无需令牌即可工作
 profile-ai-info-token-permissions = 所需权限:对模型和存储库的读取访问权限
+
+# Profile Page - Writing Metrics
+profile-metrics-title = 写作进展
+profile-metrics-empty = 已批改的作文将在此显示可读性和词汇的变化趋势
+profile-metrics-readability = 可读性(Flesch–Martins)
+profile-metrics-diversity = 词汇多样性(MTLD)
+profile-metrics-ttr = 类符/形符比
+profile-metrics-passive = 被动语态
+profile-metrics-informal = 非正式标记